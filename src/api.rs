@@ -0,0 +1,623 @@
+//! Trait abstraction over the PeerCat API
+
+use std::future::Future;
+use std::ops::Range;
+use std::sync::Arc;
+use std::time::Duration;
+
+use futures_core::future::BoxFuture;
+use ipnet::IpNet;
+
+use crate::client::PeerCat;
+use crate::error::Result;
+use crate::types::*;
+
+/// The PeerCat API operations, implemented by [`PeerCat`]
+///
+/// Write application code against this trait to swap in a test double such
+/// as `peercat::mock::MockPeerCat` (behind the `mock` feature) without
+/// standing up an HTTP server.
+///
+/// # Example
+///
+/// ```no_run
+/// use peercat::{PeerCat, PeerCatApi};
+///
+/// async fn can_afford<A: PeerCatApi>(api: &A, cost: f64) -> peercat::Result<bool> {
+///     Ok(api.get_balance().await?.credits >= cost)
+/// }
+///
+/// # async fn example() -> peercat::Result<()> {
+/// let client = PeerCat::new("pcat_live_xxx")?;
+/// let ok = can_afford(&client, 0.28).await?;
+/// # Ok(())
+/// # }
+/// ```
+pub trait PeerCatApi: Send + Sync {
+    /// Generate an image from a text prompt
+    fn generate(
+        &self,
+        params: GenerateParams,
+    ) -> impl Future<Output = Result<GenerateResult>> + Send;
+
+    /// Upload an image for use as a generation input
+    fn upload_image(&self, data: Vec<u8>) -> impl Future<Output = Result<UploadedImage>> + Send;
+
+    /// Get the IPFS pin status of a generation, by generation ID or IPFS hash
+    fn get_ipfs_status(&self, id_or_hash: &str) -> impl Future<Output = Result<IpfsStatus>> + Send;
+
+    /// Get the seed, dimensions, timings, and safety decision of a generation
+    fn get_generation_metadata(
+        &self,
+        id: &str,
+    ) -> impl Future<Output = Result<GenerationMetadata>> + Send;
+
+    /// Create a signed URL for a private image, valid for `ttl`
+    fn create_signed_url(
+        &self,
+        generation_id: &str,
+        ttl: Duration,
+    ) -> impl Future<Output = Result<SignedUrl>> + Send;
+
+    /// Create a public link to a generation, e.g. for posting in chat
+    fn create_share_link(
+        &self,
+        generation_id: &str,
+        options: ShareOptions,
+    ) -> impl Future<Output = Result<ShareLink>> + Send;
+
+    /// Add tags to a generation, e.g. to attribute usage to a campaign
+    fn tag_generation(
+        &self,
+        id: &str,
+        tags: Vec<String>,
+    ) -> impl Future<Output = Result<()>> + Send;
+
+    /// Delete a generation, removing its image from the CDN and optionally unpinning it from IPFS
+    fn delete_generation(
+        &self,
+        id: &str,
+        unpin_ipfs: bool,
+    ) -> impl Future<Output = Result<GenerationDeletion>> + Send;
+
+    /// Create a collection for organizing generations
+    fn create_collection(
+        &self,
+        params: CreateCollectionParams,
+    ) -> impl Future<Output = Result<Collection>> + Send;
+
+    /// Add a generation to a collection
+    fn add_to_collection(
+        &self,
+        collection_id: &str,
+        generation_id: &str,
+    ) -> impl Future<Output = Result<CollectionItem>> + Send;
+
+    /// List all collections
+    fn list_collections(&self) -> impl Future<Output = Result<CollectionsResponse>> + Send;
+
+    /// List the generations in a collection
+    fn list_collection_items(
+        &self,
+        collection_id: &str,
+    ) -> impl Future<Output = Result<CollectionItemsResponse>> + Send;
+
+    /// Save generation settings on the server under a name
+    fn create_preset(
+        &self,
+        name: &str,
+        params: GenerateParams,
+    ) -> impl Future<Output = Result<Preset>> + Send;
+
+    /// List saved presets
+    fn list_presets(&self) -> impl Future<Output = Result<PresetsResponse>> + Send;
+
+    /// Delete a saved preset
+    fn delete_preset(&self, preset_id: &str) -> impl Future<Output = Result<()>> + Send;
+
+    /// Generate an image from a saved preset
+    fn generate_from_preset(
+        &self,
+        preset_id: &str,
+        overrides: GenerateParams,
+    ) -> impl Future<Output = Result<GenerateResult>> + Send;
+
+    /// List available image generation models
+    fn get_models(&self) -> impl Future<Output = Result<Vec<Model>>> + Send;
+
+    /// Get a single model by ID
+    fn get_model(&self, model_id: &str) -> impl Future<Output = Result<Model>> + Send;
+
+    /// List the style adapters (LoRAs) available for a model
+    fn get_available_loras(&self, model_id: &str)
+        -> impl Future<Output = Result<Vec<Lora>>> + Send;
+
+    /// Get current pricing for all models
+    fn get_prices(&self) -> impl Future<Output = Result<PriceResponse>> + Send;
+
+    /// Get historical SOL/USD and model prices over a time range
+    fn get_price_history(
+        &self,
+        range: Range<Timestamp>,
+        granularity: PriceGranularity,
+    ) -> impl Future<Output = Result<PriceHistory>> + Send;
+
+    /// Get current credit balance
+    fn get_balance(&self) -> impl Future<Output = Result<Balance>> + Send;
+
+    /// Create a deposit to top up credits with SOL
+    fn create_deposit(
+        &self,
+        amount_usd: f64,
+    ) -> impl Future<Output = Result<DepositInstructions>> + Send;
+
+    /// Get status of a deposit by transaction signature
+    fn get_deposit_status(
+        &self,
+        tx_signature: &str,
+    ) -> impl Future<Output = Result<DepositStatus>> + Send;
+
+    /// Withdraw credits to a wallet (requires wallet signature)
+    fn withdraw(
+        &self,
+        params: WithdrawParams,
+    ) -> impl Future<Output = Result<WithdrawalResult>> + Send;
+
+    /// Get status of a withdrawal
+    fn get_withdrawal(
+        &self,
+        withdrawal_id: &str,
+    ) -> impl Future<Output = Result<WithdrawalResult>> + Send;
+
+    /// Get usage history
+    fn get_history(
+        &self,
+        params: HistoryParams,
+    ) -> impl Future<Output = Result<HistoryResponse>> + Send;
+
+    /// Get a single usage history record by ID
+    fn get_history_item(&self, id: &str) -> impl Future<Output = Result<HistoryItem>> + Send;
+
+    /// Get aggregated credit usage over a date range
+    fn get_usage_summary(
+        &self,
+        params: UsageSummaryParams,
+    ) -> impl Future<Output = Result<UsageSummary>> + Send;
+
+    /// Create a new API key (requires wallet signature)
+    fn create_key(
+        &self,
+        params: CreateKeyParams,
+    ) -> impl Future<Output = Result<CreateKeyResult>> + Send;
+
+    /// List all API keys for the authenticated wallet
+    fn list_keys(&self) -> impl Future<Output = Result<KeysResponse>> + Send;
+
+    /// Get a single API key by ID
+    fn get_key(&self, key_id: &str) -> impl Future<Output = Result<ApiKey>> + Send;
+
+    /// Revoke an API key
+    fn revoke_key(&self, key_id: &str) -> impl Future<Output = Result<()>> + Send;
+
+    /// Update API key name
+    fn update_key_name(&self, key_id: &str, name: &str) -> impl Future<Output = Result<()>> + Send;
+
+    /// Replace the IP allowlist of an API key
+    fn update_key_allowlist(
+        &self,
+        key_id: &str,
+        networks: Vec<IpNet>,
+    ) -> impl Future<Output = Result<()>> + Send;
+
+    /// Submit a prompt for on-chain payment
+    fn submit_prompt(
+        &self,
+        params: SubmitPromptParams,
+    ) -> impl Future<Output = Result<PromptSubmission>> + Send;
+
+    /// List prompt submissions
+    fn list_submissions(
+        &self,
+        filter: SubmissionFilter,
+    ) -> impl Future<Output = Result<SubmissionsResponse>> + Send;
+
+    /// Get a prompt submission by ID
+    fn get_submission(
+        &self,
+        submission_id: &str,
+    ) -> impl Future<Output = Result<PromptSubmission>> + Send;
+
+    /// Link a payment transaction to a submission
+    fn link_transaction(
+        &self,
+        submission_id: &str,
+        tx_signature: &str,
+    ) -> impl Future<Output = Result<PromptSubmission>> + Send;
+
+    /// Get status of an on-chain generation by transaction signature
+    fn get_onchain_status(
+        &self,
+        tx_signature: &str,
+    ) -> impl Future<Output = Result<OnChainGenerationStatus>> + Send;
+
+    /// Get the event timeline of an on-chain generation
+    fn get_onchain_events(
+        &self,
+        tx_signature: &str,
+    ) -> impl Future<Output = Result<OnChainEvents>> + Send;
+}
+
+impl PeerCatApi for PeerCat {
+    fn generate(
+        &self,
+        params: GenerateParams,
+    ) -> impl Future<Output = Result<GenerateResult>> + Send {
+        PeerCat::generate(self, params)
+    }
+
+    fn upload_image(&self, data: Vec<u8>) -> impl Future<Output = Result<UploadedImage>> + Send {
+        PeerCat::upload_image(self, data)
+    }
+
+    fn get_ipfs_status(&self, id_or_hash: &str) -> impl Future<Output = Result<IpfsStatus>> + Send {
+        PeerCat::get_ipfs_status(self, id_or_hash)
+    }
+
+    fn get_generation_metadata(
+        &self,
+        id: &str,
+    ) -> impl Future<Output = Result<GenerationMetadata>> + Send {
+        PeerCat::get_generation_metadata(self, id)
+    }
+
+    fn create_signed_url(
+        &self,
+        generation_id: &str,
+        ttl: Duration,
+    ) -> impl Future<Output = Result<SignedUrl>> + Send {
+        PeerCat::create_signed_url(self, generation_id, ttl)
+    }
+
+    fn create_share_link(
+        &self,
+        generation_id: &str,
+        options: ShareOptions,
+    ) -> impl Future<Output = Result<ShareLink>> + Send {
+        PeerCat::create_share_link(self, generation_id, options)
+    }
+
+    fn tag_generation(
+        &self,
+        id: &str,
+        tags: Vec<String>,
+    ) -> impl Future<Output = Result<()>> + Send {
+        PeerCat::tag_generation(self, id, tags)
+    }
+
+    fn delete_generation(
+        &self,
+        id: &str,
+        unpin_ipfs: bool,
+    ) -> impl Future<Output = Result<GenerationDeletion>> + Send {
+        PeerCat::delete_generation(self, id, unpin_ipfs)
+    }
+
+    fn create_collection(
+        &self,
+        params: CreateCollectionParams,
+    ) -> impl Future<Output = Result<Collection>> + Send {
+        PeerCat::create_collection(self, params)
+    }
+
+    fn add_to_collection(
+        &self,
+        collection_id: &str,
+        generation_id: &str,
+    ) -> impl Future<Output = Result<CollectionItem>> + Send {
+        PeerCat::add_to_collection(self, collection_id, generation_id)
+    }
+
+    fn list_collections(&self) -> impl Future<Output = Result<CollectionsResponse>> + Send {
+        PeerCat::list_collections(self)
+    }
+
+    fn list_collection_items(
+        &self,
+        collection_id: &str,
+    ) -> impl Future<Output = Result<CollectionItemsResponse>> + Send {
+        PeerCat::list_collection_items(self, collection_id)
+    }
+
+    fn create_preset(
+        &self,
+        name: &str,
+        params: GenerateParams,
+    ) -> impl Future<Output = Result<Preset>> + Send {
+        PeerCat::create_preset(self, name, params)
+    }
+
+    fn list_presets(&self) -> impl Future<Output = Result<PresetsResponse>> + Send {
+        PeerCat::list_presets(self)
+    }
+
+    fn delete_preset(&self, preset_id: &str) -> impl Future<Output = Result<()>> + Send {
+        PeerCat::delete_preset(self, preset_id)
+    }
+
+    fn generate_from_preset(
+        &self,
+        preset_id: &str,
+        overrides: GenerateParams,
+    ) -> impl Future<Output = Result<GenerateResult>> + Send {
+        PeerCat::generate_from_preset(self, preset_id, overrides)
+    }
+
+    fn get_models(&self) -> impl Future<Output = Result<Vec<Model>>> + Send {
+        PeerCat::get_models(self)
+    }
+
+    fn get_model(&self, model_id: &str) -> impl Future<Output = Result<Model>> + Send {
+        PeerCat::get_model(self, model_id)
+    }
+
+    fn get_available_loras(
+        &self,
+        model_id: &str,
+    ) -> impl Future<Output = Result<Vec<Lora>>> + Send {
+        PeerCat::get_available_loras(self, model_id)
+    }
+
+    fn get_prices(&self) -> impl Future<Output = Result<PriceResponse>> + Send {
+        PeerCat::get_prices(self)
+    }
+
+    fn get_price_history(
+        &self,
+        range: Range<Timestamp>,
+        granularity: PriceGranularity,
+    ) -> impl Future<Output = Result<PriceHistory>> + Send {
+        PeerCat::get_price_history(self, range, granularity)
+    }
+
+    fn get_balance(&self) -> impl Future<Output = Result<Balance>> + Send {
+        PeerCat::get_balance(self)
+    }
+
+    fn create_deposit(
+        &self,
+        amount_usd: f64,
+    ) -> impl Future<Output = Result<DepositInstructions>> + Send {
+        PeerCat::create_deposit(self, amount_usd)
+    }
+
+    fn get_deposit_status(
+        &self,
+        tx_signature: &str,
+    ) -> impl Future<Output = Result<DepositStatus>> + Send {
+        PeerCat::get_deposit_status(self, tx_signature)
+    }
+
+    fn withdraw(
+        &self,
+        params: WithdrawParams,
+    ) -> impl Future<Output = Result<WithdrawalResult>> + Send {
+        PeerCat::withdraw(self, params)
+    }
+
+    fn get_withdrawal(
+        &self,
+        withdrawal_id: &str,
+    ) -> impl Future<Output = Result<WithdrawalResult>> + Send {
+        PeerCat::get_withdrawal(self, withdrawal_id)
+    }
+
+    fn get_history(
+        &self,
+        params: HistoryParams,
+    ) -> impl Future<Output = Result<HistoryResponse>> + Send {
+        PeerCat::get_history(self, params)
+    }
+
+    fn get_history_item(&self, id: &str) -> impl Future<Output = Result<HistoryItem>> + Send {
+        PeerCat::get_history_item(self, id)
+    }
+
+    fn get_usage_summary(
+        &self,
+        params: UsageSummaryParams,
+    ) -> impl Future<Output = Result<UsageSummary>> + Send {
+        PeerCat::get_usage_summary(self, params)
+    }
+
+    fn create_key(
+        &self,
+        params: CreateKeyParams,
+    ) -> impl Future<Output = Result<CreateKeyResult>> + Send {
+        PeerCat::create_key(self, params)
+    }
+
+    fn list_keys(&self) -> impl Future<Output = Result<KeysResponse>> + Send {
+        PeerCat::list_keys(self)
+    }
+
+    fn get_key(&self, key_id: &str) -> impl Future<Output = Result<ApiKey>> + Send {
+        PeerCat::get_key(self, key_id)
+    }
+
+    fn revoke_key(&self, key_id: &str) -> impl Future<Output = Result<()>> + Send {
+        PeerCat::revoke_key(self, key_id)
+    }
+
+    fn update_key_name(&self, key_id: &str, name: &str) -> impl Future<Output = Result<()>> + Send {
+        PeerCat::update_key_name(self, key_id, name)
+    }
+
+    fn update_key_allowlist(
+        &self,
+        key_id: &str,
+        networks: Vec<IpNet>,
+    ) -> impl Future<Output = Result<()>> + Send {
+        PeerCat::update_key_allowlist(self, key_id, networks)
+    }
+
+    fn submit_prompt(
+        &self,
+        params: SubmitPromptParams,
+    ) -> impl Future<Output = Result<PromptSubmission>> + Send {
+        PeerCat::submit_prompt(self, params)
+    }
+
+    fn list_submissions(
+        &self,
+        filter: SubmissionFilter,
+    ) -> impl Future<Output = Result<SubmissionsResponse>> + Send {
+        PeerCat::list_submissions(self, filter)
+    }
+
+    fn get_submission(
+        &self,
+        submission_id: &str,
+    ) -> impl Future<Output = Result<PromptSubmission>> + Send {
+        PeerCat::get_submission(self, submission_id)
+    }
+
+    fn link_transaction(
+        &self,
+        submission_id: &str,
+        tx_signature: &str,
+    ) -> impl Future<Output = Result<PromptSubmission>> + Send {
+        PeerCat::link_transaction(self, submission_id, tx_signature)
+    }
+
+    fn get_onchain_status(
+        &self,
+        tx_signature: &str,
+    ) -> impl Future<Output = Result<OnChainGenerationStatus>> + Send {
+        PeerCat::get_onchain_status(self, tx_signature)
+    }
+
+    fn get_onchain_events(
+        &self,
+        tx_signature: &str,
+    ) -> impl Future<Output = Result<OnChainEvents>> + Send {
+        PeerCat::get_onchain_events(self, tx_signature)
+    }
+}
+
+/// Declare [`DynPeerCatApi`], its blanket impl for every [`PeerCatApi`], and
+/// the [`PeerCatApi`] impl for [`DynPeerCat`]
+///
+/// `&str` arguments borrow for the `'a` of the boxed future in
+/// [`DynPeerCatApi`], and are elided everywhere else.
+macro_rules! dyn_api {
+    (
+        $(#[$meta:meta])*
+        $(fn $name:ident($($arg:ident: $(&$str:ident)? $($ty:ident $(<$param:ident>)?)?),*) -> $ret:ty;)*
+    ) => {
+        $(#[$meta])*
+        pub trait DynPeerCatApi: Send + Sync {
+            $(
+                #[doc = concat!("See [`PeerCatApi::", stringify!($name), "`]")]
+                fn $name<'a>(
+                    &'a self,
+                    $($arg: $(&'a $str)? $($ty $(<$param>)?)?),*
+                ) -> BoxFuture<'a, Result<$ret>>;
+            )*
+        }
+
+        impl<T: PeerCatApi> DynPeerCatApi for T {
+            $(
+                fn $name<'a>(
+                    &'a self,
+                    $($arg: $(&'a $str)? $($ty $(<$param>)?)?),*
+                ) -> BoxFuture<'a, Result<$ret>> {
+                    Box::pin(PeerCatApi::$name(self, $($arg),*))
+                }
+            )*
+        }
+
+        impl PeerCatApi for DynPeerCat {
+            $(
+                async fn $name(&self, $($arg: $(&$str)? $($ty $(<$param>)?)?),*) -> Result<$ret> {
+                    DynPeerCatApi::$name(&**self, $($arg),*).await
+                }
+            )*
+        }
+    };
+}
+
+dyn_api! {
+    /// Object-safe form of [`PeerCatApi`], for holding a client without generics
+    ///
+    /// Every [`PeerCatApi`] implementation implements this trait too, with each
+    /// call's future boxed. Hold clients as [`DynPeerCat`], which implements
+    /// [`PeerCatApi`] again, so code written against that trait accepts it
+    /// unchanged.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use std::sync::Arc;
+    ///
+    /// use peercat::{DynPeerCat, PeerCat, PeerCatApi};
+    ///
+    /// struct AppState {
+    ///     peercat: DynPeerCat,
+    /// }
+    ///
+    /// # async fn example() -> peercat::Result<()> {
+    /// let state = AppState {
+    ///     peercat: Arc::new(PeerCat::new("pcat_live_xxx")?),
+    /// };
+    /// let balance = state.peercat.get_balance().await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    fn generate(params: GenerateParams) -> GenerateResult;
+    fn upload_image(data: Vec<u8>) -> UploadedImage;
+    fn get_ipfs_status(id_or_hash: &str) -> IpfsStatus;
+    fn get_generation_metadata(id: &str) -> GenerationMetadata;
+    fn create_signed_url(generation_id: &str, ttl: Duration) -> SignedUrl;
+    fn create_share_link(generation_id: &str, options: ShareOptions) -> ShareLink;
+    fn tag_generation(id: &str, tags: Vec<String>) -> ();
+    fn delete_generation(id: &str, unpin_ipfs: bool) -> GenerationDeletion;
+    fn create_collection(params: CreateCollectionParams) -> Collection;
+    fn add_to_collection(collection_id: &str, generation_id: &str) -> CollectionItem;
+    fn list_collections() -> CollectionsResponse;
+    fn list_collection_items(collection_id: &str) -> CollectionItemsResponse;
+    fn create_preset(name: &str, params: GenerateParams) -> Preset;
+    fn list_presets() -> PresetsResponse;
+    fn delete_preset(preset_id: &str) -> ();
+    fn generate_from_preset(preset_id: &str, overrides: GenerateParams) -> GenerateResult;
+    fn get_models() -> Vec<Model>;
+    fn get_model(model_id: &str) -> Model;
+    fn get_available_loras(model_id: &str) -> Vec<Lora>;
+    fn get_prices() -> PriceResponse;
+    fn get_price_history(range: Range<Timestamp>, granularity: PriceGranularity) -> PriceHistory;
+    fn get_balance() -> Balance;
+    fn create_deposit(amount_usd: f64) -> DepositInstructions;
+    fn get_deposit_status(tx_signature: &str) -> DepositStatus;
+    fn withdraw(params: WithdrawParams) -> WithdrawalResult;
+    fn get_withdrawal(withdrawal_id: &str) -> WithdrawalResult;
+    fn get_history(params: HistoryParams) -> HistoryResponse;
+    fn get_history_item(id: &str) -> HistoryItem;
+    fn get_usage_summary(params: UsageSummaryParams) -> UsageSummary;
+    fn create_key(params: CreateKeyParams) -> CreateKeyResult;
+    fn list_keys() -> KeysResponse;
+    fn get_key(key_id: &str) -> ApiKey;
+    fn revoke_key(key_id: &str) -> ();
+    fn update_key_name(key_id: &str, name: &str) -> ();
+    fn update_key_allowlist(key_id: &str, networks: Vec<IpNet>) -> ();
+    fn submit_prompt(params: SubmitPromptParams) -> PromptSubmission;
+    fn list_submissions(filter: SubmissionFilter) -> SubmissionsResponse;
+    fn get_submission(submission_id: &str) -> PromptSubmission;
+    fn link_transaction(submission_id: &str, tx_signature: &str) -> PromptSubmission;
+    fn get_onchain_status(tx_signature: &str) -> OnChainGenerationStatus;
+    fn get_onchain_events(tx_signature: &str) -> OnChainEvents;
+}
+
+/// A type-erased, shareable [`PeerCatApi`] client
+///
+/// Any [`PeerCatApi`] implementation converts with `Arc::new`, including
+/// [`PeerCat`] and test doubles.
+pub type DynPeerCat = Arc<dyn DynPeerCatApi>;
@@ -0,0 +1,501 @@
+//! `proptest` strategies for SDK params and responses
+//!
+//! Requires the `proptest` feature. Generated values cover every enum
+//! variant and both states of every optional field, within the ranges the
+//! API actually returns. Nested request structures (weighted prompts, LoRA
+//! adapters, ControlNet guidance) are left empty.
+
+use std::collections::HashMap;
+
+use proptest::collection::{hash_map, vec};
+use proptest::option;
+use proptest::prelude::*;
+
+use crate::types::{
+    ApiKey, Balance, DepositState, DepositStatus, GenerateParams, GenerateResult, GenerateUsage,
+    GenerationMode, HistoryItem, HistoryParams, HistoryStatus, KeyEnvironment, KeyScope,
+    OnChainEvent, OnChainEventKind, OnChainGenerationStatus, OnChainStatus, SafetyLevel,
+    SubmissionStatus, Timestamp, WithdrawalResult, WithdrawalStatus,
+};
+
+/// RFC 3339 timestamps between 2020 and 2029, parsed into [`Timestamp`]
+fn timestamp() -> impl Strategy<Value = Timestamp> {
+    (
+        2020u32..2030,
+        1u32..=12,
+        1u32..=28,
+        0u32..24,
+        0u32..60,
+        0u32..60,
+    )
+        .prop_map(|(year, month, day, hour, minute, second)| {
+            format!(
+                "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z",
+                year, month, day, hour, minute, second
+            )
+            .parse()
+            .expect("generated timestamp is valid")
+        })
+}
+
+/// Opaque IDs like `gen_3f9a0c1b`
+fn id(prefix: &'static str) -> impl Strategy<Value = String> {
+    "[a-z0-9]{8,24}".prop_map(move |suffix| format!("{}_{}", prefix, suffix))
+}
+
+fn model() -> impl Strategy<Value = String> {
+    "[a-z0-9][a-z0-9-]{0,31}"
+}
+
+fn prompt() -> impl Strategy<Value = String> {
+    "\\PC{1,200}"
+}
+
+/// Credit amounts in USD
+fn credits() -> impl Strategy<Value = f64> {
+    0.0..10_000.0
+}
+
+fn url() -> impl Strategy<Value = String> {
+    "[a-z0-9_]{1,32}".prop_map(|name| format!("https://cdn.peerc.at/images/{}.png", name))
+}
+
+fn ipfs_hash() -> impl Strategy<Value = String> {
+    "Qm[1-9A-HJ-NP-Za-km-z]{44}"
+}
+
+/// Base58 Solana addresses and signatures
+fn base58(len: std::ops::RangeInclusive<usize>) -> impl Strategy<Value = String> {
+    proptest::string::string_regex(&format!(
+        "[1-9A-HJ-NP-Za-km-z]{{{},{}}}",
+        len.start(),
+        len.end()
+    ))
+    .expect("base58 pattern is valid")
+}
+
+fn options() -> impl Strategy<Value = HashMap<String, serde_json::Value>> {
+    hash_map(
+        "[a-z_]{1,16}",
+        prop_oneof![
+            any::<bool>().prop_map(serde_json::Value::from),
+            any::<u32>().prop_map(serde_json::Value::from),
+            "\\PC{0,32}".prop_map(serde_json::Value::from),
+        ],
+        0..4,
+    )
+}
+
+macro_rules! arbitrary_enum {
+    ($($ty:ident => [$($variant:ident),+ $(,)?];)+) => {
+        $(
+            impl Arbitrary for $ty {
+                type Parameters = ();
+                type Strategy = BoxedStrategy<Self>;
+
+                fn arbitrary_with(_: ()) -> Self::Strategy {
+                    prop_oneof![$(Just($ty::$variant)),+].boxed()
+                }
+            }
+        )+
+    };
+}
+
+arbitrary_enum! {
+    GenerationMode => [Production, Demo];
+    SafetyLevel => [Strict, Standard, Relaxed];
+    HistoryStatus => [Pending, Completed, Refunded];
+    DepositState => [Pending, Confirmed, Failed];
+    WithdrawalStatus => [Pending, Processing, Completed, Failed];
+    KeyScope => [Generate, Read, Admin];
+    KeyEnvironment => [Live, Test];
+    SubmissionStatus => [Open, Paid, Expired];
+    OnChainStatus => [Pending, Processing, Completed, Failed, Refunded];
+    OnChainEventKind => [
+        PaymentDetected,
+        AmountVerified,
+        Queued,
+        GenerationStarted,
+        UploadedToIpfs,
+        Completed,
+        Failed,
+        RefundIssued,
+    ];
+}
+
+impl Arbitrary for GenerateParams {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Self>;
+
+    fn arbitrary_with(_: ()) -> Self::Strategy {
+        (
+            prompt(),
+            option::of(model()),
+            option::of(any::<GenerationMode>()),
+            option::of(options()),
+            option::of(any::<SafetyLevel>()),
+            option::of(any::<u64>()),
+            option::of(1u8..=100),
+            option::of(any::<bool>()),
+            option::of("[a-z]{2}"),
+        )
+            .prop_map(
+                |(
+                    prompt,
+                    model,
+                    mode,
+                    options,
+                    safety_level,
+                    seed,
+                    quality,
+                    auto_translate,
+                    source_language,
+                )| {
+                    let mut params = GenerateParams::new(prompt);
+                    params.model = model;
+                    params.mode = mode;
+                    params.options = options;
+                    params.safety_level = safety_level;
+                    params.seed = seed;
+                    params.quality = quality;
+                    params.auto_translate = auto_translate;
+                    params.source_language = source_language;
+                    params
+                },
+            )
+            .boxed()
+    }
+}
+
+impl Arbitrary for HistoryParams {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Self>;
+
+    fn arbitrary_with(_: ()) -> Self::Strategy {
+        (
+            option::of(1u32..=100),
+            option::of(0u32..10_000),
+            option::of(any::<HistoryStatus>()),
+            option::of(model()),
+            option::of(vec("[a-z0-9-]{1,16}", 1..3)),
+        )
+            .prop_map(|(limit, offset, status, model, tags)| HistoryParams {
+                limit,
+                offset,
+                status,
+                model,
+                tags,
+                ..HistoryParams::default()
+            })
+            .boxed()
+    }
+}
+
+impl Arbitrary for GenerateResult {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Self>;
+
+    fn arbitrary_with(_: ()) -> Self::Strategy {
+        (
+            (id("gen"), url(), option::of(ipfs_hash()), model()),
+            (any::<GenerationMode>(), credits(), credits(), any::<bool>()),
+            (
+                option::of(any::<SafetyLevel>()),
+                option::of(any::<u64>()),
+                option::of(prompt()),
+                option::of(options()),
+                option::of(1u64..50_000_000),
+                option::of(prompt()),
+            ),
+        )
+            .prop_map(
+                |(
+                    (id, image_url, ipfs_hash, model),
+                    (mode, credits_used, balance_remaining, private),
+                    (safety_level, seed, prompt, options, expected_file_size, translated_prompt),
+                )| GenerateResult {
+                    id,
+                    image_url,
+                    ipfs_hash,
+                    model,
+                    mode,
+                    usage: GenerateUsage {
+                        credits_used,
+                        balance_remaining,
+                    },
+                    private,
+                    safety_level,
+                    seed,
+                    prompt,
+                    options,
+                    prompts: Vec::new(),
+                    quality: None,
+                    adapters: Vec::new(),
+                    controlnet: None,
+                    auto_translate: None,
+                    source_language: None,
+                    expected_file_size,
+                    translated_prompt,
+                },
+            )
+            .boxed()
+    }
+}
+
+impl Arbitrary for Balance {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Self>;
+
+    fn arbitrary_with(_: ()) -> Self::Strategy {
+        (credits(), credits(), credits(), credits(), any::<u64>())
+            .prop_map(
+                |(credits, total_deposited, total_spent, total_withdrawn, total_generated)| {
+                    Balance {
+                        credits,
+                        total_deposited,
+                        total_spent,
+                        total_withdrawn,
+                        total_generated,
+                    }
+                },
+            )
+            .boxed()
+    }
+}
+
+impl Arbitrary for HistoryItem {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Self>;
+
+    fn arbitrary_with(_: ()) -> Self::Strategy {
+        (
+            id("usage"),
+            "/v1/[a-z/]{1,24}",
+            option::of(model()),
+            credits(),
+            option::of(id("gen")),
+            any::<HistoryStatus>(),
+            timestamp(),
+            option::of(timestamp()),
+            option::of(url()),
+            vec("[a-z0-9-]{1,16}", 0..3),
+        )
+            .prop_map(
+                |(
+                    id,
+                    endpoint,
+                    model,
+                    credits_used,
+                    request_id,
+                    status,
+                    created_at,
+                    completed_at,
+                    image_url,
+                    tags,
+                )| HistoryItem {
+                    id,
+                    endpoint,
+                    model,
+                    credits_used,
+                    request_id,
+                    status,
+                    created_at,
+                    completed_at,
+                    image_url,
+                    tags,
+                },
+            )
+            .boxed()
+    }
+}
+
+impl Arbitrary for DepositStatus {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Self>;
+
+    fn arbitrary_with(_: ()) -> Self::Strategy {
+        (
+            base58(64..=88),
+            any::<DepositState>(),
+            option::of(0.0..100.0),
+            option::of(credits()),
+            option::of(timestamp()),
+            option::of(timestamp()),
+            option::of("\\PC{1,64}"),
+        )
+            .prop_map(
+                |(
+                    tx_signature,
+                    status,
+                    amount_sol,
+                    credits_added,
+                    created_at,
+                    confirmed_at,
+                    error,
+                )| {
+                    DepositStatus {
+                        tx_signature,
+                        status,
+                        amount_sol,
+                        credits_added,
+                        created_at,
+                        confirmed_at,
+                        error,
+                    }
+                },
+            )
+            .boxed()
+    }
+}
+
+impl Arbitrary for WithdrawalResult {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Self>;
+
+    fn arbitrary_with(_: ()) -> Self::Strategy {
+        (
+            id("wd"),
+            any::<WithdrawalStatus>(),
+            credits(),
+            option::of(0.0..100.0),
+            base58(32..=44),
+            option::of(base58(64..=88)),
+            timestamp(),
+            option::of(timestamp()),
+            option::of("\\PC{1,64}"),
+        )
+            .prop_map(
+                |(
+                    withdrawal_id,
+                    status,
+                    amount_usd,
+                    amount_sol,
+                    destination_wallet,
+                    tx_signature,
+                    created_at,
+                    completed_at,
+                    error,
+                )| WithdrawalResult {
+                    withdrawal_id,
+                    status,
+                    amount_usd,
+                    amount_sol,
+                    destination_wallet,
+                    tx_signature,
+                    created_at,
+                    completed_at,
+                    error,
+                },
+            )
+            .boxed()
+    }
+}
+
+impl Arbitrary for ApiKey {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Self>;
+
+    fn arbitrary_with(_: ()) -> Self::Strategy {
+        (
+            id("key"),
+            option::of("\\PC{1,32}"),
+            "pcat_(live|test)_[a-z0-9]{3}",
+            any::<KeyEnvironment>(),
+            "(standard|pro|enterprise)",
+            timestamp(),
+            option::of(timestamp()),
+            any::<bool>(),
+            vec(any::<KeyScope>(), 0..3),
+            option::of(timestamp()),
+        )
+            .prop_map(
+                |(
+                    id,
+                    name,
+                    key_prefix,
+                    environment,
+                    rate_limit_tier,
+                    created_at,
+                    last_used_at,
+                    revoked,
+                    scopes,
+                    expires_at,
+                )| ApiKey {
+                    id,
+                    name,
+                    key_prefix,
+                    environment,
+                    rate_limit_tier,
+                    created_at,
+                    last_used_at,
+                    revoked,
+                    scopes,
+                    expires_at,
+                    ip_allowlist: Vec::new(),
+                },
+            )
+            .boxed()
+    }
+}
+
+impl Arbitrary for OnChainGenerationStatus {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Self>;
+
+    fn arbitrary_with(_: ()) -> Self::Strategy {
+        (
+            base58(64..=88),
+            any::<OnChainStatus>(),
+            option::of(model()),
+            option::of(timestamp()),
+            option::of(url()),
+            option::of(ipfs_hash()),
+            option::of(timestamp()),
+            option::of("\\PC{1,64}"),
+            option::of("\\PC{1,64}"),
+        )
+            .prop_map(
+                |(
+                    tx_signature,
+                    status,
+                    model,
+                    created_at,
+                    image_url,
+                    ipfs_hash,
+                    completed_at,
+                    error,
+                    message,
+                )| OnChainGenerationStatus {
+                    tx_signature,
+                    status,
+                    model,
+                    created_at,
+                    image_url,
+                    ipfs_hash,
+                    completed_at,
+                    error,
+                    message,
+                },
+            )
+            .boxed()
+    }
+}
+
+impl Arbitrary for OnChainEvent {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Self>;
+
+    fn arbitrary_with(_: ()) -> Self::Strategy {
+        (
+            any::<OnChainEventKind>(),
+            timestamp(),
+            option::of("\\PC{1,64}"),
+        )
+            .prop_map(|(kind, timestamp, message)| OnChainEvent {
+                kind,
+                timestamp,
+                message,
+            })
+            .boxed()
+    }
+}
@@ -0,0 +1,161 @@
+//! Audit log of SDK calls
+//!
+//! Requires the `audit` feature. Every API call the client makes is
+//! described by an [`AuditRecord`] and passed to an [`AuditSink`]: what was
+//! called, a hash of the parameters sent, and what came back.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use peercat::audit::AuditWriter;
+//! use peercat::{PeerCat, PeerCatConfig};
+//!
+//! # fn main() -> Result<(), Box<dyn std::error::Error>> {
+//! let file = std::fs::File::options()
+//!     .create(true)
+//!     .append(true)
+//!     .open("peercat-audit.jsonl")?;
+//! let client = PeerCat::with_config(
+//!     PeerCatConfig::new("pcat_live_xxx").with_audit_log(AuditWriter::new(file)),
+//! )?;
+//! # Ok(())
+//! # }
+//! ```
+
+use std::fmt;
+use std::io::Write;
+use std::sync::Mutex;
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+/// One API call, as recorded in the audit log
+///
+/// GETs coalesced into an identical request already in flight are recorded
+/// once, for the caller that sent the request.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AuditRecord {
+    /// When the call started, in milliseconds since the Unix epoch
+    pub timestamp_ms: u64,
+    pub method: String,
+    /// Request path without its query string
+    pub endpoint: String,
+    /// SHA-256 of the JSON body sent, as hex; `None` for calls without a body
+    pub params_hash: Option<String>,
+    /// The JSON body sent, with any fields set by
+    /// [`with_redacted_field`](crate::PeerCatConfig::with_redacted_field) redacted
+    pub params: Option<serde_json::Value>,
+    /// HTTP status of the successful response; `None` if the call failed
+    pub status: Option<u16>,
+    /// The `X-Request-Id` of the successful response, if the API sent one
+    pub request_id: Option<String>,
+    /// The `id` of the returned object (e.g. the generation ID), if any
+    pub result_id: Option<String>,
+    /// Credits the response reported using, if any
+    pub credits: Option<f64>,
+    /// Time taken by the call, including retries
+    pub duration_ms: u64,
+    /// The error the call failed with, if any, with redacted values removed
+    pub error: Option<String>,
+}
+
+/// Hex SHA-256 of a request body
+pub(crate) fn params_hash(body: &serde_json::Value) -> String {
+    hex::encode(Sha256::digest(body.to_string()))
+}
+
+/// The top-level `id` of a JSON response body
+pub(crate) fn result_id(body: &[u8]) -> Option<String> {
+    #[derive(Deserialize)]
+    struct Body {
+        id: String,
+    }
+
+    serde_json::from_slice::<Body>(body)
+        .ok()
+        .map(|body| body.id)
+}
+
+/// Receives an [`AuditRecord`] for every call
+///
+/// `record` is called inline after each call completes, so it should be
+/// quick; records can't be rejected, so a sink that fails to store one
+/// should report that itself.
+pub trait AuditSink: Send + Sync + fmt::Debug {
+    /// Store one record
+    fn record(&self, record: &AuditRecord);
+}
+
+/// Writes each record as a line of JSON
+///
+/// Lines are flushed as they're written. Write errors are ignored.
+pub struct AuditWriter<W> {
+    writer: Mutex<W>,
+}
+
+impl<W: Write + Send> AuditWriter<W> {
+    /// Write records to `writer`
+    pub fn new(writer: W) -> Self {
+        Self {
+            writer: Mutex::new(writer),
+        }
+    }
+
+    /// Get the writer back
+    pub fn into_inner(self) -> W {
+        self.writer.into_inner().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+impl<W: Write + Send> AuditSink for AuditWriter<W> {
+    fn record(&self, record: &AuditRecord) {
+        let Ok(mut line) = serde_json::to_vec(record) else {
+            return;
+        };
+        line.push(b'\n');
+        let mut writer = self.writer.lock().unwrap_or_else(|e| e.into_inner());
+        let _ = writer.write_all(&line).and_then(|_| writer.flush());
+    }
+}
+
+impl<W> fmt::Debug for AuditWriter<W> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AuditWriter").finish_non_exhaustive()
+    }
+}
+
+/// Passes each record to a function
+///
+/// # Example
+///
+/// ```no_run
+/// use peercat::audit::AuditCallback;
+/// use peercat::{PeerCat, PeerCatConfig};
+///
+/// let client = PeerCat::with_config(
+///     PeerCatConfig::new("pcat_live_xxx").with_audit_log(AuditCallback::new(|record| {
+///         println!("{} {} -> {:?}", record.method, record.endpoint, record.result_id);
+///     })),
+/// )?;
+/// # Ok::<(), peercat::PeerCatError>(())
+/// ```
+pub struct AuditCallback<F>(F);
+
+impl<F: Fn(&AuditRecord) + Send + Sync> AuditCallback<F> {
+    /// Call `f` with every record
+    pub fn new(f: F) -> Self {
+        Self(f)
+    }
+}
+
+impl<F: Fn(&AuditRecord) + Send + Sync> AuditSink for AuditCallback<F> {
+    fn record(&self, record: &AuditRecord) {
+        (self.0)(record)
+    }
+}
+
+impl<F> fmt::Debug for AuditCallback<F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AuditCallback").finish_non_exhaustive()
+    }
+}
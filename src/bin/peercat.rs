@@ -0,0 +1,226 @@
+//! Command-line interface for the PeerCat API
+//!
+//! Built with `cargo install peercat --features cli`. Reads the API key from
+//! `--api-key` or the `PEERCAT_API_KEY` environment variable.
+
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+use clap::{Parser, Subcommand};
+use peercat::{GenerateParams, HistoryParams, PeerCat, PeerCatConfig, PeerCatError};
+use serde::Serialize;
+
+#[derive(Debug, Parser)]
+#[command(name = "peercat", version, about = "PeerCat AI image generation API")]
+struct Cli {
+    /// API key
+    #[arg(long, env = "PEERCAT_API_KEY", hide_env_values = true)]
+    api_key: String,
+
+    /// Override the API base URL
+    #[arg(long, env = "PEERCAT_BASE_URL")]
+    base_url: Option<String>,
+
+    /// Print responses as JSON
+    #[arg(long, global = true)]
+    json: bool,
+
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Debug, Subcommand)]
+enum Command {
+    /// Generate an image from a text prompt
+    Generate {
+        /// Text prompt
+        prompt: String,
+        /// Model to use
+        #[arg(long)]
+        model: Option<String>,
+        /// Use demo mode (free, returns a placeholder)
+        #[arg(long)]
+        demo: bool,
+        /// Download the image to this file
+        #[arg(long, short)]
+        output: Option<PathBuf>,
+    },
+    /// List available models
+    Models,
+    /// Show credit balance
+    Balance,
+    /// Show usage history
+    History {
+        /// Number of records
+        #[arg(long, default_value_t = 20)]
+        limit: u32,
+        /// Records to skip
+        #[arg(long, default_value_t = 0)]
+        offset: u32,
+    },
+    /// Manage API keys
+    Keys {
+        #[command(subcommand)]
+        command: Option<KeysCommand>,
+    },
+    /// On-chain payment commands
+    Onchain {
+        #[command(subcommand)]
+        command: OnchainCommand,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+enum KeysCommand {
+    /// List API keys (default)
+    List,
+    /// Revoke an API key
+    Revoke {
+        /// Key ID
+        key_id: String,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+enum OnchainCommand {
+    /// Show the status of an on-chain generation
+    Status {
+        /// Payment transaction signature
+        tx_signature: String,
+    },
+}
+
+#[tokio::main]
+async fn main() -> ExitCode {
+    let cli = Cli::parse();
+
+    match run(cli).await {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("error: {}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+async fn run(cli: Cli) -> Result<(), PeerCatError> {
+    let mut config = PeerCatConfig::new(cli.api_key);
+    if let Some(base_url) = cli.base_url {
+        config = config.with_base_url(base_url);
+    }
+    let client = PeerCat::with_config(config)?;
+    let json = cli.json;
+
+    match cli.command {
+        Command::Generate {
+            prompt,
+            model,
+            demo,
+            output,
+        } => {
+            let mut params = GenerateParams::new(prompt);
+            if let Some(model) = model {
+                params = params.with_model(model);
+            }
+            if demo {
+                params = params.with_demo_mode();
+            }
+
+            let result = client.generate(params).await?;
+            if let Some(path) = &output {
+                let mut file = tokio::fs::File::create(path).await?;
+                client
+                    .download_image_stream(&result)
+                    .await?
+                    .copy_to(&mut file)
+                    .await?;
+            }
+
+            print(json, &result, || {
+                println!("Image: {}", result.image_url);
+                if let Some(path) = &output {
+                    println!("Saved: {}", path.display());
+                }
+                println!("Credits used: {}", result.usage.credits_used);
+            });
+        }
+        Command::Models => {
+            let models = client.get_models().await?;
+            print(json, &models, || {
+                for model in &models {
+                    println!("{:<24} ${:<8} {}", model.id, model.price_usd, model.name);
+                }
+            });
+        }
+        Command::Balance => {
+            let balance = client.get_balance().await?;
+            print(json, &balance, || println!("Credits: ${}", balance.credits));
+        }
+        Command::History { limit, offset } => {
+            let history = client
+                .get_history(HistoryParams::new().with_limit(limit).with_offset(offset))
+                .await?;
+            print(json, &history, || {
+                for item in &history.items {
+                    println!(
+                        "{}  {:<12} {:<24} {} credits",
+                        item.created_at,
+                        format!("{:?}", item.status).to_lowercase(),
+                        item.endpoint,
+                        item.credits_used
+                    );
+                }
+            });
+        }
+        Command::Keys { command } => match command.unwrap_or(KeysCommand::List) {
+            KeysCommand::List => {
+                let keys = client.list_keys().await?;
+                print(json, &keys, || {
+                    for key in &keys.keys {
+                        println!(
+                            "{}  {}...  {}{}",
+                            key.id,
+                            key.key_prefix,
+                            key.name.as_deref().unwrap_or("-"),
+                            if key.revoked { "  (revoked)" } else { "" }
+                        );
+                    }
+                });
+            }
+            KeysCommand::Revoke { key_id } => {
+                client.revoke_key(&key_id).await?;
+                if !json {
+                    println!("Revoked {}", key_id);
+                }
+            }
+        },
+        Command::Onchain {
+            command: OnchainCommand::Status { tx_signature },
+        } => {
+            let status = client.get_onchain_status(&tx_signature).await?;
+            print(json, &status, || {
+                println!("Status: {:?}", status.status);
+                if let Some(url) = &status.image_url {
+                    println!("Image: {}", url);
+                }
+                if let Some(error) = &status.error {
+                    println!("Error: {}", error);
+                }
+            });
+        }
+    }
+
+    Ok(())
+}
+
+/// Print `value` as JSON, or run `human` to print it for people
+fn print<T: Serialize>(json: bool, value: &T, human: impl FnOnce()) {
+    if json {
+        match serde_json::to_string_pretty(value) {
+            Ok(out) => println!("{}", out),
+            Err(e) => eprintln!("error: {}", e),
+        }
+    } else {
+        human();
+    }
+}
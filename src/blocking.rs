@@ -0,0 +1,419 @@
+//! Blocking (synchronous) client
+//!
+//! Mirrors the async [`PeerCat`](crate::PeerCat) API with blocking calls,
+//! for build scripts, simple CLIs, and code that doesn't use async.
+//!
+//! Each client owns a private single-threaded Tokio runtime. Don't call it
+//! from within an async context; use the async client there instead.
+//!
+//! Downloads return the whole image at once. Use the async client's
+//! [`download_image_stream`](crate::PeerCat::download_image_stream) to
+//! stream large images instead.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use peercat::blocking::PeerCat;
+//! use peercat::GenerateParams;
+//!
+//! let client = PeerCat::new("pcat_live_xxx")?;
+//! let result = client.generate(GenerateParams::new("A beautiful sunset"))?;
+//!
+//! println!("Image URL: {}", result.image_url);
+//! # Ok::<(), peercat::PeerCatError>(())
+//! ```
+
+use std::future::Future;
+use std::ops::Range;
+use std::sync::Arc;
+use std::time::Duration;
+
+use bytes::Bytes;
+use ipnet::IpNet;
+use tokio::runtime::Runtime;
+
+use crate::error::Result;
+use crate::poll::PollOptions;
+use crate::spend::SessionSpend;
+use crate::types::*;
+
+/// Blocking PeerCat API client
+#[derive(Debug, Clone)]
+pub struct PeerCat {
+    inner: crate::PeerCat,
+    runtime: Arc<Runtime>,
+}
+
+impl PeerCat {
+    /// Create a new blocking client with the given API key
+    ///
+    /// # Errors
+    ///
+    /// Returns `PeerCatError::EmptyApiKey` if the API key is empty.
+    pub fn new(api_key: impl Into<String>) -> Result<Self> {
+        Self::with_config(PeerCatConfig::new(api_key))
+    }
+
+    /// Create a blocking client for the test environment with CI guardrails
+    ///
+    /// See [`crate::PeerCat::test`].
+    pub fn test(api_key: impl Into<String>) -> Result<Self> {
+        Self::with_config(PeerCatConfig::test(api_key))
+    }
+
+    /// Create a blocking client that generates placeholders locally and never uses the network
+    ///
+    /// See [`crate::PeerCat::offline_demo`].
+    pub fn offline_demo() -> Self {
+        Self::with_config(PeerCatConfig::offline_demo())
+            .expect("offline demo configuration is valid")
+    }
+
+    /// Create a new blocking client with custom configuration
+    pub fn with_config(config: PeerCatConfig) -> Result<Self> {
+        let inner = crate::PeerCat::with_config(config)?;
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()?;
+
+        Ok(Self {
+            inner,
+            runtime: Arc::new(runtime),
+        })
+    }
+
+    /// The underlying async client
+    pub fn as_async(&self) -> &crate::PeerCat {
+        &self.inner
+    }
+
+    fn block_on<F: Future>(&self, future: F) -> F::Output {
+        self.runtime.block_on(future)
+    }
+
+    /// Open a connection to the API ahead of the first real request
+    pub fn warmup(&self, options: WarmupOptions) -> Result<()> {
+        self.block_on(self.inner.warmup(options))
+    }
+
+    /// The environment of the client's API keys
+    pub fn environment(&self) -> Option<KeyEnvironment> {
+        self.inner.environment()
+    }
+
+    /// Credits spent on generations by this client and its clones
+    pub fn session_spend(&self) -> SessionSpend {
+        self.inner.session_spend()
+    }
+
+    /// Start counting session spend from zero, returning the previous total
+    pub fn reset_session_spend(&self) -> SessionSpend {
+        self.inner.reset_session_spend()
+    }
+
+    // ============ Generation ============
+
+    /// Generate an image from a text prompt
+    pub fn generate(&self, params: GenerateParams) -> Result<GenerateResult> {
+        self.block_on(self.inner.generate(params))
+    }
+
+    /// Upload an image for use as a generation input
+    pub fn upload_image(&self, data: Vec<u8>) -> Result<UploadedImage> {
+        self.block_on(self.inner.upload_image(data))
+    }
+
+    /// Get the IPFS pin status of a generation, by generation ID or IPFS hash
+    pub fn get_ipfs_status(&self, id_or_hash: &str) -> Result<IpfsStatus> {
+        self.block_on(self.inner.get_ipfs_status(id_or_hash))
+    }
+
+    /// Get the seed, dimensions, timings, and safety decision of a generation
+    pub fn get_generation_metadata(&self, id: &str) -> Result<GenerationMetadata> {
+        self.block_on(self.inner.get_generation_metadata(id))
+    }
+
+    /// Create a signed URL for a private image, valid for `ttl`
+    pub fn create_signed_url(&self, generation_id: &str, ttl: Duration) -> Result<SignedUrl> {
+        self.block_on(self.inner.create_signed_url(generation_id, ttl))
+    }
+
+    /// Create a public link to a generation, e.g. for posting in chat
+    pub fn create_share_link(
+        &self,
+        generation_id: &str,
+        options: ShareOptions,
+    ) -> Result<ShareLink> {
+        self.block_on(self.inner.create_share_link(generation_id, options))
+    }
+
+    /// Add tags to a generation, e.g. to attribute usage to a campaign
+    pub fn tag_generation(&self, id: &str, tags: Vec<String>) -> Result<()> {
+        self.block_on(self.inner.tag_generation(id, tags))
+    }
+
+    /// Delete a generation, removing its image from the CDN and optionally unpinning it from IPFS
+    pub fn delete_generation(&self, id: &str, unpin_ipfs: bool) -> Result<GenerationDeletion> {
+        self.block_on(self.inner.delete_generation(id, unpin_ipfs))
+    }
+
+    // ============ Downloads ============
+
+    /// Download a thumbnail of a generated image
+    pub fn download_thumbnail(&self, result: &GenerateResult, size: ThumbSize) -> Result<Bytes> {
+        self.block_on(self.inner.download_thumbnail(result, size))
+    }
+
+    /// Download a generated image through the configured IPFS gateway
+    ///
+    /// Falls back to the CDN URL if the image wasn't pinned to IPFS or the
+    /// gateway request fails.
+    pub fn download_from_ipfs(&self, result: &GenerateResult) -> Result<Bytes> {
+        self.block_on(async {
+            self.inner
+                .download_from_ipfs(result)
+                .await?
+                .read_all()
+                .await
+        })
+    }
+
+    // ============ Collections ============
+
+    /// Create a collection for organizing generations
+    pub fn create_collection(&self, params: CreateCollectionParams) -> Result<Collection> {
+        self.block_on(self.inner.create_collection(params))
+    }
+
+    /// Add a generation to a collection
+    pub fn add_to_collection(
+        &self,
+        collection_id: &str,
+        generation_id: &str,
+    ) -> Result<CollectionItem> {
+        self.block_on(self.inner.add_to_collection(collection_id, generation_id))
+    }
+
+    /// List all collections
+    pub fn list_collections(&self) -> Result<CollectionsResponse> {
+        self.block_on(self.inner.list_collections())
+    }
+
+    /// List the generations in a collection
+    pub fn list_collection_items(&self, collection_id: &str) -> Result<CollectionItemsResponse> {
+        self.block_on(self.inner.list_collection_items(collection_id))
+    }
+
+    // ============ Presets ============
+
+    /// Save generation settings on the server under a name
+    pub fn create_preset(&self, name: &str, params: GenerateParams) -> Result<Preset> {
+        self.block_on(self.inner.create_preset(name, params))
+    }
+
+    /// List saved presets
+    pub fn list_presets(&self) -> Result<PresetsResponse> {
+        self.block_on(self.inner.list_presets())
+    }
+
+    /// Delete a saved preset
+    pub fn delete_preset(&self, preset_id: &str) -> Result<()> {
+        self.block_on(self.inner.delete_preset(preset_id))
+    }
+
+    /// Generate an image from a saved preset
+    pub fn generate_from_preset(
+        &self,
+        preset_id: &str,
+        overrides: GenerateParams,
+    ) -> Result<GenerateResult> {
+        self.block_on(self.inner.generate_from_preset(preset_id, overrides))
+    }
+
+    // ============ Models & Pricing ============
+
+    /// List available image generation models
+    pub fn get_models(&self) -> Result<Vec<Model>> {
+        self.block_on(self.inner.get_models())
+    }
+
+    /// Get a single model by ID
+    pub fn get_model(&self, model_id: &str) -> Result<Model> {
+        self.block_on(self.inner.get_model(model_id))
+    }
+
+    /// List the style adapters (LoRAs) available for a model
+    pub fn get_available_loras(&self, model_id: &str) -> Result<Vec<Lora>> {
+        self.block_on(self.inner.get_available_loras(model_id))
+    }
+
+    /// Get current pricing for all models
+    pub fn get_prices(&self) -> Result<PriceResponse> {
+        self.block_on(self.inner.get_prices())
+    }
+
+    /// Get historical SOL/USD and model prices over a time range
+    pub fn get_price_history(
+        &self,
+        range: Range<Timestamp>,
+        granularity: PriceGranularity,
+    ) -> Result<PriceHistory> {
+        self.block_on(self.inner.get_price_history(range, granularity))
+    }
+
+    // ============ Account ============
+
+    /// Get current credit balance
+    pub fn get_balance(&self) -> Result<Balance> {
+        self.block_on(self.inner.get_balance())
+    }
+
+    /// Create a deposit to top up credits with SOL
+    pub fn create_deposit(&self, amount_usd: f64) -> Result<DepositInstructions> {
+        self.block_on(self.inner.create_deposit(amount_usd))
+    }
+
+    /// Get status of a deposit by transaction signature
+    pub fn get_deposit_status(&self, tx_signature: &str) -> Result<DepositStatus> {
+        self.block_on(self.inner.get_deposit_status(tx_signature))
+    }
+
+    /// Withdraw credits to a wallet (requires wallet signature)
+    pub fn withdraw(&self, params: WithdrawParams) -> Result<WithdrawalResult> {
+        self.block_on(self.inner.withdraw(params))
+    }
+
+    /// Get status of a withdrawal
+    pub fn get_withdrawal(&self, withdrawal_id: &str) -> Result<WithdrawalResult> {
+        self.block_on(self.inner.get_withdrawal(withdrawal_id))
+    }
+
+    /// Get usage history
+    pub fn get_history(&self, params: HistoryParams) -> Result<HistoryResponse> {
+        self.block_on(self.inner.get_history(params))
+    }
+
+    /// Get a single usage history record by ID
+    pub fn get_history_item(&self, id: &str) -> Result<HistoryItem> {
+        self.block_on(self.inner.get_history_item(id))
+    }
+
+    /// Wait until a generation's usage record is no longer pending
+    pub fn wait_for_generation(&self, usage_id: &str, options: PollOptions) -> Result<HistoryItem> {
+        self.block_on(self.inner.wait_for_generation(usage_id, options))
+    }
+
+    /// Get aggregated credit usage over a date range
+    pub fn get_usage_summary(&self, params: UsageSummaryParams) -> Result<UsageSummary> {
+        self.block_on(self.inner.get_usage_summary(params))
+    }
+
+    /// Export all usage history matching `params` to `writer`
+    ///
+    /// `params.limit` sets the page size rather than capping the export.
+    #[cfg(feature = "export")]
+    pub fn export_history<W: std::io::Write>(
+        &self,
+        params: HistoryParams,
+        format: crate::export::Format,
+        mut writer: W,
+    ) -> Result<usize> {
+        let mut export = crate::export::HistoryExport::new(params, format);
+        while let Some(chunk) = self.block_on(export.next_chunk(&self.inner))? {
+            writer.write_all(&chunk)?;
+        }
+        writer.flush()?;
+        Ok(export.written)
+    }
+
+    /// Download every completed generation in the history matching `params` to `dest_dir`
+    #[cfg(feature = "export")]
+    pub fn export_images(
+        &self,
+        params: HistoryParams,
+        dest_dir: impl AsRef<std::path::Path>,
+        options: crate::export::ExportOptions,
+    ) -> Result<crate::export::ImageManifest> {
+        self.block_on(self.inner.export_images(params, dest_dir, options))
+    }
+
+    // ============ API Keys ============
+
+    /// Create a new API key (requires wallet signature)
+    pub fn create_key(&self, params: CreateKeyParams) -> Result<CreateKeyResult> {
+        self.block_on(self.inner.create_key(params))
+    }
+
+    /// List all API keys for the authenticated wallet
+    pub fn list_keys(&self) -> Result<KeysResponse> {
+        self.block_on(self.inner.list_keys())
+    }
+
+    /// Get a single API key by ID
+    pub fn get_key(&self, key_id: &str) -> Result<ApiKey> {
+        self.block_on(self.inner.get_key(key_id))
+    }
+
+    /// Revoke an API key
+    pub fn revoke_key(&self, key_id: &str) -> Result<()> {
+        self.block_on(self.inner.revoke_key(key_id))
+    }
+
+    /// Update API key name
+    pub fn update_key_name(&self, key_id: &str, name: &str) -> Result<()> {
+        self.block_on(self.inner.update_key_name(key_id, name))
+    }
+
+    /// Replace the IP allowlist of an API key
+    pub fn update_key_allowlist(&self, key_id: &str, networks: Vec<IpNet>) -> Result<()> {
+        self.block_on(self.inner.update_key_allowlist(key_id, networks))
+    }
+
+    // ============ On-Chain Payments ============
+
+    /// Submit a prompt for on-chain payment
+    pub fn submit_prompt(&self, params: SubmitPromptParams) -> Result<PromptSubmission> {
+        self.block_on(self.inner.submit_prompt(params))
+    }
+
+    /// List prompt submissions
+    pub fn list_submissions(&self, filter: SubmissionFilter) -> Result<SubmissionsResponse> {
+        self.block_on(self.inner.list_submissions(filter))
+    }
+
+    /// Get a prompt submission by ID
+    pub fn get_submission(&self, submission_id: &str) -> Result<PromptSubmission> {
+        self.block_on(self.inner.get_submission(submission_id))
+    }
+
+    /// Link a payment transaction to a submission
+    pub fn link_transaction(
+        &self,
+        submission_id: &str,
+        tx_signature: &str,
+    ) -> Result<PromptSubmission> {
+        self.block_on(self.inner.link_transaction(submission_id, tx_signature))
+    }
+
+    /// Get status of an on-chain generation by transaction signature
+    pub fn get_onchain_status(&self, tx_signature: &str) -> Result<OnChainGenerationStatus> {
+        self.block_on(self.inner.get_onchain_status(tx_signature))
+    }
+
+    /// Get the event timeline of an on-chain generation
+    pub fn get_onchain_events(&self, tx_signature: &str) -> Result<OnChainEvents> {
+        self.block_on(self.inner.get_onchain_events(tx_signature))
+    }
+
+    /// Wait until an on-chain generation completes, fails, or is refunded
+    pub fn wait_for_onchain_completion(
+        &self,
+        tx_signature: &str,
+        options: PollOptions,
+    ) -> Result<OnChainGenerationStatus> {
+        self.block_on(
+            self.inner
+                .wait_for_onchain_completion(tx_signature, options),
+        )
+    }
+}
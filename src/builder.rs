@@ -0,0 +1,136 @@
+//! Client builder that validates its configuration up front
+
+use std::time::Duration;
+
+use crate::client::PeerCat;
+use crate::error::{ConfigError, ConfigProblem};
+use crate::retry_budget::RetryBudget;
+use crate::types::{GenerationMode, PeerCatConfig};
+
+/// Builder for a [`PeerCat`] client
+///
+/// Created with [`PeerCat::builder`]. Settings without a method of their own
+/// can be changed on the underlying [`PeerCatConfig`] with
+/// [`configure`](Self::configure). Nothing is checked until
+/// [`build`](Self::build), which reports every problem at once.
+///
+/// # Example
+///
+/// ```
+/// use std::time::Duration;
+/// use peercat::PeerCat;
+///
+/// let client = PeerCat::builder("pcat_live_xxx")
+///     .base_url("https://api.peerc.at")
+///     .timeout(Duration::from_secs(30))
+///     .max_retries(5)
+///     .build()?;
+/// # Ok::<(), peercat::ConfigError>(())
+/// ```
+#[derive(Debug, Clone)]
+pub struct PeerCatBuilder {
+    config: PeerCatConfig,
+}
+
+impl PeerCatBuilder {
+    /// Start a builder for a client using `api_key`
+    pub fn new(api_key: impl Into<String>) -> Self {
+        Self::from_config(PeerCatConfig::new(api_key))
+    }
+
+    /// Start a builder from an existing configuration
+    ///
+    /// Use this for presets like [`PeerCatConfig::test`] or
+    /// [`PeerCatConfig::from_key_pool`].
+    pub fn from_config(config: PeerCatConfig) -> Self {
+        Self { config }
+    }
+
+    /// Set the API base URL
+    ///
+    /// Must be an `http` or `https` URL without a query or fragment.
+    pub fn base_url(mut self, url: impl Into<String>) -> Self {
+        self.config.base_url = Some(url.into());
+        self
+    }
+
+    /// Set the timeout for each attempt of a request, up to one hour
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.config.attempt_timeout = Some(timeout);
+        self
+    }
+
+    /// Bound a request's total time, across all attempts and backoff
+    pub fn overall_deadline(mut self, deadline: Duration) -> Self {
+        self.config.overall_deadline = Some(deadline);
+        self
+    }
+
+    /// Set the number of retries for failed requests, up to 10
+    pub fn max_retries(mut self, retries: u32) -> Self {
+        self.config.max_retries = Some(retries);
+        self
+    }
+
+    /// Limit retries across every request the client makes
+    pub fn retry_budget(mut self, budget: RetryBudget) -> Self {
+        self.config.retry_budget = Some(budget);
+        self
+    }
+
+    /// Send a duplicate GET request when the first takes longer than `after`
+    pub fn hedge_after(mut self, after: Duration) -> Self {
+        self.config.hedge_after = Some(after);
+        self
+    }
+
+    /// Set the mode used by `generate` when the params don't specify one
+    pub fn default_mode(mut self, mode: GenerationMode) -> Self {
+        self.config.default_mode = Some(mode);
+        self
+    }
+
+    /// Set the IPFS gateway used to resolve IPFS hashes
+    pub fn ipfs_gateway(mut self, url: impl Into<String>) -> Self {
+        self.config.ipfs_gateway = Some(url.into());
+        self
+    }
+
+    /// Change any other setting of the underlying configuration
+    ///
+    /// ```
+    /// use peercat::PeerCat;
+    ///
+    /// let client = PeerCat::builder("pcat_live_xxx")
+    ///     .configure(|config| config.with_conditional_requests())
+    ///     .build()?;
+    /// # Ok::<(), peercat::ConfigError>(())
+    /// ```
+    pub fn configure(mut self, f: impl FnOnce(PeerCatConfig) -> PeerCatConfig) -> Self {
+        self.config = f(self.config);
+        self
+    }
+
+    /// Validate the configuration and create the client
+    ///
+    /// # Errors
+    ///
+    /// Returns a `ConfigError` listing every problem with the configuration,
+    /// or the reason the client couldn't be created from a valid one, e.g.
+    /// a VCR cassette that can't be read.
+    pub fn build(self) -> Result<PeerCat, ConfigError> {
+        self.config.validate()?;
+        PeerCat::from_validated(self.config).map_err(|e| {
+            ConfigError::new(vec![ConfigProblem {
+                field: "client",
+                message: e.to_string(),
+            }])
+        })
+    }
+}
+
+impl From<PeerCatConfig> for PeerCatBuilder {
+    fn from(config: PeerCatConfig) -> Self {
+        Self::from_config(config)
+    }
+}
@@ -0,0 +1,235 @@
+//! Concurrent bulk generation
+
+use std::collections::VecDeque;
+use std::fmt;
+use std::sync::Arc;
+
+use tokio::sync::watch;
+use tokio::task::JoinSet;
+
+use crate::api::PeerCatApi;
+use crate::client::PeerCat;
+use crate::error::PeerCatError;
+use crate::types::{GenerateParams, GenerateResult};
+
+const DEFAULT_CONCURRENCY: usize = 4;
+
+/// Progress of a [`BulkGenerator`] run, reported after each generation
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BulkProgress {
+    /// Generations that succeeded
+    pub completed: usize,
+    /// Generations that failed
+    pub failed: usize,
+    /// Generations not yet finished (queued or in flight)
+    pub remaining: usize,
+    /// Credits spent by successful generations so far
+    pub credits_spent: f64,
+}
+
+/// Outcome of a [`BulkGenerator`] run
+///
+/// Items are identified by their position in the input.
+#[derive(Debug)]
+pub struct BulkReport {
+    /// Successful generations
+    pub completed: Vec<(usize, GenerateResult)>,
+    /// Failed generations
+    pub failed: Vec<(usize, PeerCatError)>,
+    /// Params that were never started because the run was stopped
+    pub remaining: Vec<(usize, GenerateParams)>,
+    /// Credits spent by successful generations
+    pub credits_spent: f64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum RunState {
+    Running,
+    Paused,
+    Stopped,
+}
+
+/// Pauses, resumes, or stops a [`BulkGenerator`] run from another task
+///
+/// Pausing and stopping only affect generations that haven't started;
+/// in-flight requests are allowed to finish.
+#[derive(Debug, Clone)]
+pub struct BulkController {
+    state: Arc<watch::Sender<RunState>>,
+}
+
+impl BulkController {
+    /// Stop starting new generations until [`resume`](Self::resume) is called
+    pub fn pause(&self) {
+        self.set(RunState::Paused);
+    }
+
+    /// Continue a paused run
+    pub fn resume(&self) {
+        self.set(RunState::Running);
+    }
+
+    /// End the run once in-flight generations finish
+    ///
+    /// Params that weren't started are returned in [`BulkReport::remaining`]
+    /// so the run can be continued later.
+    pub fn stop(&self) {
+        self.set(RunState::Stopped);
+    }
+
+    /// Returns true if the run is paused
+    pub fn is_paused(&self) -> bool {
+        *self.state.borrow() == RunState::Paused
+    }
+
+    fn set(&self, state: RunState) {
+        self.state.send_if_modified(|current| {
+            // A stopped run can't be resumed
+            let changed = *current != state && *current != RunState::Stopped;
+            if changed {
+                *current = state;
+            }
+            changed
+        });
+    }
+}
+
+type ProgressCallback = Box<dyn FnMut(&BulkProgress) + Send>;
+
+/// Runs many generations with bounded concurrency
+///
+/// Failed generations are recorded in the report rather than ending the run.
+///
+/// # Example
+///
+/// ```no_run
+/// use peercat::{BulkGenerator, GenerateParams, PeerCat};
+///
+/// # async fn example() -> peercat::Result<()> {
+/// let client = PeerCat::new("pcat_live_xxx")?;
+/// let prompts = ["A red fox", "A snowy owl", "A river otter"];
+///
+/// let generator = BulkGenerator::new(client, prompts.map(GenerateParams::new))
+///     .with_concurrency(8)
+///     .on_progress(|progress| {
+///         println!(
+///             "{} done, {} failed, {} left, ${:.2} spent",
+///             progress.completed, progress.failed, progress.remaining, progress.credits_spent
+///         );
+///     });
+///
+/// let report = generator.run().await;
+/// println!("{} images generated", report.completed.len());
+/// # Ok(())
+/// # }
+/// ```
+pub struct BulkGenerator<A = PeerCat> {
+    api: A,
+    queue: VecDeque<(usize, GenerateParams)>,
+    concurrency: usize,
+    on_progress: Option<ProgressCallback>,
+    state: Arc<watch::Sender<RunState>>,
+}
+
+impl<A> BulkGenerator<A>
+where
+    A: PeerCatApi + Clone + Send + Sync + 'static,
+{
+    /// Create a generator for a set of params
+    pub fn new(api: A, params: impl IntoIterator<Item = GenerateParams>) -> Self {
+        Self {
+            api,
+            queue: params.into_iter().enumerate().collect(),
+            concurrency: DEFAULT_CONCURRENCY,
+            on_progress: None,
+            state: Arc::new(watch::Sender::new(RunState::Running)),
+        }
+    }
+
+    /// Set the maximum number of generations in flight (default: 4)
+    pub fn with_concurrency(mut self, concurrency: usize) -> Self {
+        self.concurrency = concurrency.max(1);
+        self
+    }
+
+    /// Call `callback` after each generation finishes
+    pub fn on_progress(mut self, callback: impl FnMut(&BulkProgress) + Send + 'static) -> Self {
+        self.on_progress = Some(Box::new(callback));
+        self
+    }
+
+    /// A handle to pause, resume, or stop the run
+    pub fn controller(&self) -> BulkController {
+        BulkController {
+            state: self.state.clone(),
+        }
+    }
+
+    /// Run all generations and report the results
+    ///
+    /// Must be called from within a Tokio runtime.
+    pub async fn run(mut self) -> BulkReport {
+        let mut state = self.state.subscribe();
+        let mut tasks = JoinSet::new();
+        let mut report = BulkReport {
+            completed: Vec::new(),
+            failed: Vec::new(),
+            remaining: Vec::new(),
+            credits_spent: 0.0,
+        };
+
+        loop {
+            let current = *state.borrow_and_update();
+            if current == RunState::Running {
+                while tasks.len() < self.concurrency {
+                    let Some((index, params)) = self.queue.pop_front() else {
+                        break;
+                    };
+                    let api = self.api.clone();
+                    tasks.spawn(async move { (index, api.generate(params).await) });
+                }
+            }
+
+            let Some(joined) = tasks.join_next().await else {
+                if current == RunState::Stopped || self.queue.is_empty() {
+                    break;
+                }
+                // Paused with nothing in flight
+                let _ = state.changed().await;
+                continue;
+            };
+
+            match joined {
+                Ok((index, Ok(result))) => {
+                    report.credits_spent += result.usage.credits_used;
+                    report.completed.push((index, result));
+                }
+                Ok((index, Err(error))) => report.failed.push((index, error)),
+                Err(error) => std::panic::resume_unwind(error.into_panic()),
+            }
+
+            if let Some(callback) = &mut self.on_progress {
+                callback(&BulkProgress {
+                    completed: report.completed.len(),
+                    failed: report.failed.len(),
+                    remaining: self.queue.len() + tasks.len(),
+                    credits_spent: report.credits_spent,
+                });
+            }
+        }
+
+        report.remaining = self.queue.into_iter().collect();
+        report
+    }
+}
+
+impl<A: fmt::Debug> fmt::Debug for BulkGenerator<A> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("BulkGenerator")
+            .field("api", &self.api)
+            .field("queued", &self.queue.len())
+            .field("concurrency", &self.concurrency)
+            .field("state", &*self.state.borrow())
+            .finish()
+    }
+}
@@ -0,0 +1,79 @@
+//! Deterministic JSON for snapshot tests
+
+use serde::Serialize;
+use serde_json::{Map, Value};
+
+use crate::error::Result;
+
+/// Serialize SDK values to JSON that doesn't change between runs or releases
+///
+/// Implemented for every `Serialize` type, so it works on any response or
+/// params type, and on your own types embedding them.
+///
+/// The output is pretty-printed with two-space indentation, and the keys of
+/// every object are sorted, at every depth. Struct field order and the
+/// iteration order of map fields such as [`GenerateResult::options`] play no
+/// part, so reordering fields in a release or enabling serde_json's
+/// `preserve_order` feature doesn't change it. Field names use the API's
+/// camelCase spelling, timestamps are RFC 3339 and floats use their shortest
+/// round-tripping representation.
+///
+/// [`GenerateResult::options`]: crate::GenerateResult::options
+///
+/// # Example
+///
+/// ```
+/// use peercat::{CanonicalJson, GenerateParams};
+///
+/// # fn example() -> peercat::Result<()> {
+/// let params = GenerateParams::new("A sunset")
+///     .with_option("steps", 30.into())
+///     .with_option("cfg_scale", 7.into());
+///
+/// assert_eq!(
+///     params.to_canonical_json()?,
+///     r#"{
+///   "options": {
+///     "cfg_scale": 7,
+///     "steps": 30
+///   },
+///   "prompt": "A sunset"
+/// }"#
+/// );
+/// # Ok(())
+/// # }
+/// ```
+pub trait CanonicalJson {
+    /// Serialize to canonical JSON
+    ///
+    /// # Errors
+    ///
+    /// Returns `PeerCatError::Json` if the value can't be represented as
+    /// JSON, e.g. a map with non-string keys.
+    fn to_canonical_json(&self) -> Result<String>;
+}
+
+impl<T: Serialize + ?Sized> CanonicalJson for T {
+    fn to_canonical_json(&self) -> Result<String> {
+        let value = sort_keys(serde_json::to_value(self)?);
+        Ok(serde_json::to_string_pretty(&value)?)
+    }
+}
+
+/// Rebuild every object in `value` with its keys in sorted order
+fn sort_keys(value: Value) -> Value {
+    match value {
+        Value::Object(fields) => {
+            let mut fields: Vec<(String, Value)> = fields.into_iter().collect();
+            fields.sort_by(|(a, _), (b, _)| a.cmp(b));
+            Value::Object(
+                fields
+                    .into_iter()
+                    .map(|(key, value)| (key, sort_keys(value)))
+                    .collect::<Map<String, Value>>(),
+            )
+        }
+        Value::Array(items) => Value::Array(items.into_iter().map(sort_keys).collect()),
+        other => other,
+    }
+}
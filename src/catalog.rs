@@ -0,0 +1,98 @@
+//! Cached model catalog
+
+use std::sync::Arc;
+
+use tokio::sync::Mutex;
+
+use crate::client::PeerCat;
+use crate::error::Result;
+use crate::types::Model;
+
+/// Shared cache slot for the model list
+pub(crate) type ModelCache = Arc<Mutex<Option<Arc<[Model]>>>>;
+
+/// A lazily loaded, cached view of the model list
+///
+/// The first lookup fetches `/v1/models`; later lookups are served from
+/// memory until [`refresh`](Self::refresh) is called. All catalogs obtained
+/// from the same client (or its clones) share one cache.
+///
+/// # Example
+///
+/// ```no_run
+/// use peercat::PeerCat;
+///
+/// # async fn example() -> peercat::Result<()> {
+/// let client = PeerCat::new("pcat_live_xxx")?;
+/// let catalog = client.models_catalog();
+///
+/// if let Some(model) = catalog.find("stable-diffusion-xl").await? {
+///     println!("{}: ${}", model.name, model.price_usd);
+/// }
+///
+/// let cheapest = catalog.cheapest().await?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct ModelCatalog {
+    client: PeerCat,
+    cache: ModelCache,
+}
+
+impl ModelCatalog {
+    pub(crate) fn new(client: PeerCat, cache: ModelCache) -> Self {
+        Self { client, cache }
+    }
+
+    /// All models, loading them if they haven't been fetched yet
+    pub async fn models(&self) -> Result<Arc<[Model]>> {
+        let mut cache = self.cache.lock().await;
+        if let Some(models) = cache.as_ref() {
+            return Ok(models.clone());
+        }
+
+        let models: Arc<[Model]> = self.client.get_models().await?.into();
+        *cache = Some(models.clone());
+        Ok(models)
+    }
+
+    /// Re-fetch the model list, replacing the cached copy
+    pub async fn refresh(&self) -> Result<Arc<[Model]>> {
+        let mut cache = self.cache.lock().await;
+        let models: Arc<[Model]> = self.client.get_models().await?.into();
+        *cache = Some(models.clone());
+        Ok(models)
+    }
+
+    /// Drop the cached copy so the next lookup fetches it again
+    pub async fn invalidate(&self) {
+        *self.cache.lock().await = None;
+    }
+
+    /// Find a model by ID
+    pub async fn find(&self, id: &str) -> Result<Option<Model>> {
+        Ok(self.models().await?.iter().find(|m| m.id == id).cloned())
+    }
+
+    /// The model with the lowest USD price
+    pub async fn cheapest(&self) -> Result<Option<Model>> {
+        Ok(self
+            .models()
+            .await?
+            .iter()
+            .min_by(|a, b| a.price_usd.total_cmp(&b.price_usd))
+            .cloned())
+    }
+
+    /// All models from the given provider (case-insensitive)
+    pub async fn by_provider(&self, provider: &str) -> Result<Vec<Model>> {
+        Ok(self
+            .models()
+            .await?
+            .iter()
+            .filter(|m| m.provider.eq_ignore_ascii_case(provider))
+            .cloned()
+            .collect())
+    }
+}
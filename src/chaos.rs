@@ -0,0 +1,257 @@
+//! Fault injection around a [`PeerCatApi`] client
+//!
+//! Requires the `chaos` feature.
+
+use std::future::Future;
+use std::ops::Range;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+
+use ipnet::IpNet;
+use web_time::{SystemTime, UNIX_EPOCH};
+
+use crate::api::PeerCatApi;
+use crate::error::{PeerCatError, RateLimitInfo, Result};
+use crate::rt;
+use crate::types::*;
+
+/// Truncated body that injected malformed responses fail to parse
+const MALFORMED_BODY: &str = r#"{"id": "gen_"#;
+
+/// A [`PeerCatApi`] wrapper that randomly delays and fails calls
+///
+/// Each call first sleeps with the configured latency probability, then
+/// draws at most one fault:
+///
+/// - a rate limit error, returned without calling the wrapped client
+/// - a timeout, returned after the wrapped call completes, as if the
+///   response was lost in transit
+/// - a malformed response, a JSON error returned after the wrapped call
+///   completes, as if the body was cut off
+///
+/// Probabilities are between 0 and 1. If they add up to more than 1, faults
+/// later in the list happen less often than configured. Set a seed to
+/// replay the same sequence of faults.
+///
+/// # Example
+///
+/// ```no_run
+/// use std::time::Duration;
+///
+/// use peercat::chaos::ChaosPeerCat;
+/// use peercat::{PeerCat, PeerCatApi};
+///
+/// # async fn example() -> peercat::Result<()> {
+/// let api = ChaosPeerCat::new(PeerCat::new("pcat_test_xxx")?)
+///     .with_latency(0.2, Duration::from_millis(50)..Duration::from_millis(500))
+///     .with_rate_limits(0.05)
+///     .with_timeouts(0.02)
+///     .with_malformed_responses(0.01);
+///
+/// // Code written against `PeerCatApi` now sees occasional failures
+/// let balance = api.get_balance().await;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct ChaosPeerCat<T> {
+    inner: T,
+    latency: f64,
+    latency_range: Range<Duration>,
+    rate_limits: f64,
+    timeouts: f64,
+    malformed_responses: f64,
+    rng: AtomicU64,
+}
+
+/// A fault drawn for a single call
+enum Fault {
+    RateLimit,
+    Timeout,
+    Malformed,
+}
+
+impl<T> ChaosPeerCat<T> {
+    /// Wrap a client, injecting no faults until some are configured
+    pub fn new(inner: T) -> Self {
+        let seed = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |elapsed| elapsed.as_nanos() as u64);
+        Self {
+            inner,
+            latency: 0.0,
+            latency_range: Duration::ZERO..Duration::ZERO,
+            rate_limits: 0.0,
+            timeouts: 0.0,
+            malformed_responses: 0.0,
+            rng: AtomicU64::new(seed),
+        }
+    }
+
+    /// Delay calls with the given probability, by a duration picked from `range`
+    pub fn with_latency(mut self, probability: f64, range: Range<Duration>) -> Self {
+        self.latency = probability.clamp(0.0, 1.0);
+        self.latency_range = range;
+        self
+    }
+
+    /// Fail calls with a rate limit error with the given probability
+    pub fn with_rate_limits(mut self, probability: f64) -> Self {
+        self.rate_limits = probability.clamp(0.0, 1.0);
+        self
+    }
+
+    /// Fail calls with a timeout with the given probability
+    pub fn with_timeouts(mut self, probability: f64) -> Self {
+        self.timeouts = probability.clamp(0.0, 1.0);
+        self
+    }
+
+    /// Fail calls with a response that doesn't parse with the given probability
+    pub fn with_malformed_responses(mut self, probability: f64) -> Self {
+        self.malformed_responses = probability.clamp(0.0, 1.0);
+        self
+    }
+
+    /// Seed the random faults, so a test sees the same ones every run
+    pub fn with_seed(self, seed: u64) -> Self {
+        self.rng.store(seed, Ordering::Relaxed);
+        self
+    }
+
+    /// The wrapped client
+    pub fn inner(&self) -> &T {
+        &self.inner
+    }
+
+    /// Unwrap the client
+    pub fn into_inner(self) -> T {
+        self.inner
+    }
+
+    /// A uniformly distributed value in `[0, 1)`, from SplitMix64
+    fn random(&self) -> f64 {
+        let mut z = self
+            .rng
+            .fetch_add(0x9e37_79b9_7f4a_7c15, Ordering::Relaxed)
+            .wrapping_add(0x9e37_79b9_7f4a_7c15);
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^= z >> 31;
+        (z >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    fn delay(&self) -> Option<Duration> {
+        if self.random() >= self.latency {
+            return None;
+        }
+        let Range { start, end } = self.latency_range;
+        Some(start + end.saturating_sub(start).mul_f64(self.random()))
+    }
+
+    fn fault(&self) -> Option<Fault> {
+        let roll = self.random();
+        let mut threshold = self.rate_limits;
+        if roll < threshold {
+            return Some(Fault::RateLimit);
+        }
+        threshold += self.timeouts;
+        if roll < threshold {
+            return Some(Fault::Timeout);
+        }
+        threshold += self.malformed_responses;
+        if roll < threshold {
+            return Some(Fault::Malformed);
+        }
+        None
+    }
+
+    /// Run a call of the wrapped client with faults injected
+    ///
+    /// The call is only started if no fault replaces it, since some clients
+    /// do their work before the returned future is polled.
+    async fn call<R, F>(&self, call: impl FnOnce() -> F) -> Result<R>
+    where
+        F: Future<Output = Result<R>>,
+    {
+        if let Some(delay) = self.delay() {
+            rt::sleep(delay).await;
+        }
+        let fault = self.fault();
+        if let Some(Fault::RateLimit) = fault {
+            return Err(PeerCatError::RateLimit {
+                message: "Rate limit exceeded (injected)".to_string(),
+                code: "rate_limit_exceeded".to_string(),
+                rate_limit_info: Some(RateLimitInfo {
+                    retry_after: Some(1),
+                    ..RateLimitInfo::default()
+                }),
+            });
+        }
+        let result = call().await;
+        match fault {
+            Some(Fault::Timeout) => Err(PeerCatError::Timeout),
+            Some(Fault::Malformed) => Err(PeerCatError::Json(
+                serde_json::from_str::<serde_json::Value>(MALFORMED_BODY)
+                    .expect_err("malformed body doesn't parse"),
+            )),
+            _ => result,
+        }
+    }
+}
+
+macro_rules! chaos_api {
+    ($(fn $name:ident($($arg:ident: $ty:ty),*) -> $ret:ty;)*) => {
+        impl<T: PeerCatApi> PeerCatApi for ChaosPeerCat<T> {
+            $(
+                fn $name(&self, $($arg: $ty),*) -> impl Future<Output = Result<$ret>> + Send {
+                    self.call(move || self.inner.$name($($arg),*))
+                }
+            )*
+        }
+    };
+}
+
+chaos_api! {
+    fn generate(params: GenerateParams) -> GenerateResult;
+    fn upload_image(data: Vec<u8>) -> UploadedImage;
+    fn get_ipfs_status(id_or_hash: &str) -> IpfsStatus;
+    fn get_generation_metadata(id: &str) -> GenerationMetadata;
+    fn create_signed_url(generation_id: &str, ttl: Duration) -> SignedUrl;
+    fn create_share_link(generation_id: &str, options: ShareOptions) -> ShareLink;
+    fn tag_generation(id: &str, tags: Vec<String>) -> ();
+    fn delete_generation(id: &str, unpin_ipfs: bool) -> GenerationDeletion;
+    fn create_collection(params: CreateCollectionParams) -> Collection;
+    fn add_to_collection(collection_id: &str, generation_id: &str) -> CollectionItem;
+    fn list_collections() -> CollectionsResponse;
+    fn list_collection_items(collection_id: &str) -> CollectionItemsResponse;
+    fn create_preset(name: &str, params: GenerateParams) -> Preset;
+    fn list_presets() -> PresetsResponse;
+    fn delete_preset(preset_id: &str) -> ();
+    fn generate_from_preset(preset_id: &str, overrides: GenerateParams) -> GenerateResult;
+    fn get_models() -> Vec<Model>;
+    fn get_model(model_id: &str) -> Model;
+    fn get_available_loras(model_id: &str) -> Vec<Lora>;
+    fn get_prices() -> PriceResponse;
+    fn get_price_history(range: Range<Timestamp>, granularity: PriceGranularity) -> PriceHistory;
+    fn get_balance() -> Balance;
+    fn create_deposit(amount_usd: f64) -> DepositInstructions;
+    fn get_deposit_status(tx_signature: &str) -> DepositStatus;
+    fn withdraw(params: WithdrawParams) -> WithdrawalResult;
+    fn get_withdrawal(withdrawal_id: &str) -> WithdrawalResult;
+    fn get_history(params: HistoryParams) -> HistoryResponse;
+    fn get_history_item(id: &str) -> HistoryItem;
+    fn get_usage_summary(params: UsageSummaryParams) -> UsageSummary;
+    fn create_key(params: CreateKeyParams) -> CreateKeyResult;
+    fn list_keys() -> KeysResponse;
+    fn get_key(key_id: &str) -> ApiKey;
+    fn revoke_key(key_id: &str) -> ();
+    fn update_key_name(key_id: &str, name: &str) -> ();
+    fn update_key_allowlist(key_id: &str, networks: Vec<IpNet>) -> ();
+    fn submit_prompt(params: SubmitPromptParams) -> PromptSubmission;
+    fn list_submissions(filter: SubmissionFilter) -> SubmissionsResponse;
+    fn get_submission(submission_id: &str) -> PromptSubmission;
+    fn link_transaction(submission_id: &str, tx_signature: &str) -> PromptSubmission;
+    fn get_onchain_status(tx_signature: &str) -> OnChainGenerationStatus;
+    fn get_onchain_events(tx_signature: &str) -> OnChainEvents;
+}
@@ -1,15 +1,48 @@
 //! PeerCat API client
 
+use base64::prelude::*;
+use ipnet::IpNet;
+use reqwest::header::HeaderMap;
 use reqwest::{Client, StatusCode};
+use std::future::{poll_fn, Future};
+use std::ops::Range;
+use std::pin::{pin, Pin};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::task::Poll;
 use std::time::Duration;
+use web_time::Instant;
 
+use crate::builder::PeerCatBuilder;
+use crate::catalog::{ModelCache, ModelCatalog};
+use crate::conditional::ConditionalCache;
 use crate::error::{PeerCatError, RateLimitInfo, Result};
+use crate::events::{ClientEvent, ClientEvents, EventBus};
+use crate::key_pool::{KeyPool, SelectedKey};
+use crate::metrics::{MetricEvent, PeerCatMetrics};
+use crate::offline;
+use crate::pacer::RateLimitPacer;
+#[cfg(any(feature = "audit", feature = "vcr"))]
+use crate::redact::Redactor;
+use crate::report::{ErrorContext, ErrorReporter};
+use crate::retry_budget::{RetryBudget, RetryDecision};
+use crate::rt;
+use crate::secret::SecretString;
+#[cfg(all(feature = "tower", not(target_arch = "wasm32")))]
+use crate::service::Transport;
+use crate::single_flight::SingleFlight;
+use crate::spans::RequestSpan;
+use crate::spend::{SessionSpend, SpendTracker};
 use crate::types::*;
+#[cfg(feature = "vcr")]
+use crate::vcr::Vcr;
 
 const DEFAULT_BASE_URL: &str = "https://api.peerc.at";
 const DEFAULT_TIMEOUT: u64 = 60;
 const DEFAULT_MAX_RETRIES: u32 = 3;
-const USER_AGENT: &str = concat!("peercat-rust/", env!("CARGO_PKG_VERSION"));
+/// Host labels that mark a base URL as a test environment
+const TEST_HOST_LABELS: &[&str] = &["test", "sandbox", "staging"];
+pub(crate) const USER_AGENT: &str = concat!("peercat-rust/", env!("CARGO_PKG_VERSION"));
 
 /// PeerCat API client
 ///
@@ -33,10 +66,39 @@ const USER_AGENT: &str = concat!("peercat-rust/", env!("CARGO_PKG_VERSION"));
 /// ```
 #[derive(Debug, Clone)]
 pub struct PeerCat {
-    api_key: String,
+    keys: KeyPool,
+    environment: Option<KeyEnvironment>,
+    environment_mismatch: Option<&'static str>,
+    mismatch_reported: Arc<AtomicBool>,
     base_url: String,
     client: Client,
+    timeout: Duration,
+    overall_deadline: Option<Duration>,
     max_retries: u32,
+    default_mode: Option<GenerationMode>,
+    block_production_operations: bool,
+    offline_demo: bool,
+    budget: Option<f64>,
+    pacer: Option<Arc<RateLimitPacer>>,
+    retry_budget: Option<RetryBudget>,
+    hedge_after: Option<Duration>,
+    slow_request_threshold: Option<Duration>,
+    conditional: Option<Arc<ConditionalCache>>,
+    single_flight: Option<Arc<SingleFlight>>,
+    metrics: Option<Arc<dyn PeerCatMetrics>>,
+    error_reporter: Option<Arc<dyn ErrorReporter>>,
+    #[cfg(feature = "audit")]
+    audit: Option<Arc<dyn crate::audit::AuditSink>>,
+    #[cfg(feature = "audit")]
+    redactor: Redactor,
+    events: EventBus,
+    ipfs_gateway: String,
+    models_cache: ModelCache,
+    spend: Arc<SpendTracker>,
+    #[cfg(all(feature = "tower", not(target_arch = "wasm32")))]
+    transport: Option<Transport>,
+    #[cfg(feature = "vcr")]
+    vcr: Option<Vcr>,
 }
 
 impl PeerCat {
@@ -58,11 +120,70 @@ impl PeerCat {
         Self::with_config(PeerCatConfig::new(api_key))
     }
 
+    /// Create a client for the test environment with CI guardrails
+    ///
+    /// See [`PeerCatConfig::test`] for the guardrails applied.
+    ///
+    /// # Errors
+    ///
+    /// Returns `PeerCatError::EmptyApiKey` if the API key is empty, or
+    /// `PeerCatError::InvalidConfig` if it isn't a `pcat_test_` key.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use peercat::{PeerCat, GenerateParams};
+    ///
+    /// # async fn example() -> peercat::Result<()> {
+    /// let client = PeerCat::test("pcat_test_xxx")?;
+    ///
+    /// // Runs in demo mode unless the params say otherwise
+    /// let result = client.generate(GenerateParams::new("Test prompt")).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn test(api_key: impl Into<String>) -> Result<Self> {
+        Self::with_config(PeerCatConfig::test(api_key))
+    }
+
+    /// Create a client that generates placeholders locally and never uses the network
+    ///
+    /// For unit tests and demos without network access; see
+    /// [`PeerCatConfig::offline_demo`] for what it does.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use peercat::{GenerateParams, PeerCat};
+    ///
+    /// # async fn example() -> peercat::Result<()> {
+    /// let client = PeerCat::offline_demo();
+    ///
+    /// let result = client.generate(GenerateParams::new("A sunset")).await?;
+    /// assert_eq!(result.usage.credits_used, 0.0);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn offline_demo() -> Self {
+        Self::with_config(PeerCatConfig::offline_demo())
+            .expect("offline demo configuration is valid")
+    }
+
+    /// Start building a client, with every setting checked at `build` time
+    ///
+    /// See [`PeerCatBuilder`] for an example.
+    pub fn builder(api_key: impl Into<String>) -> PeerCatBuilder {
+        PeerCatBuilder::new(api_key)
+    }
+
     /// Create a new PeerCat client with custom configuration
     ///
     /// # Errors
     ///
-    /// Returns `PeerCatError::EmptyApiKey` if the API key is empty.
+    /// Returns `PeerCatError::EmptyApiKey` if the API key is empty, or
+    /// `PeerCatError::InvalidConfig` if a key starts with `pcat_` but isn't a
+    /// `pcat_live_` or `pcat_test_` key, or a key pool mixes environments.
+    /// Other settings are used as given; [`PeerCat::builder`] checks them too.
     ///
     /// # Example
     ///
@@ -77,31 +198,261 @@ impl PeerCat {
     /// # Ok::<(), peercat::PeerCatError>(())
     /// ```
     pub fn with_config(config: PeerCatConfig) -> Result<Self> {
-        if config.api_key.is_empty() {
+        let keys: Vec<_> = match &config.key_pool {
+            Some(pool) => pool.keys().iter().map(|key| key.expose_secret()).collect(),
+            None => vec![config.api_key.expose_secret()],
+        };
+        if keys.is_empty() || keys.iter().any(|key| key.is_empty()) {
             return Err(PeerCatError::EmptyApiKey);
         }
+        if let Some(problem) = config.key_problems().into_iter().next() {
+            return Err(PeerCatError::InvalidConfig(problem.to_string()));
+        }
+        Self::from_validated(config)
+    }
 
-        let timeout = config.timeout.unwrap_or(DEFAULT_TIMEOUT);
+    /// Create a client from a configuration whose keys have been checked
+    pub(crate) fn from_validated(config: PeerCatConfig) -> Result<Self> {
+        let keys = config
+            .key_pool
+            .unwrap_or_else(|| KeyPool::new([config.api_key.expose_secret()]));
+        // Key checks ensure every key in the pool is from the same environment
+        let environment = keys
+            .keys()
+            .first()
+            .and_then(|key| KeyEnvironment::from_key(key.expose_secret()));
+
+        let timeout = config
+            .attempt_timeout
+            .unwrap_or_else(|| Duration::from_secs(config.timeout.unwrap_or(DEFAULT_TIMEOUT)));
         let base_url = config
             .base_url
             .unwrap_or_else(|| DEFAULT_BASE_URL.to_string())
             .trim_end_matches('/')
             .to_string();
 
-        let client = Client::builder()
-            .timeout(Duration::from_secs(timeout))
-            .user_agent(USER_AGENT)
-            .build()
-            .expect("Failed to create HTTP client");
+        // The timeout is applied per request since wasm32 clients don't support
+        // a client-wide timeout
+        #[cfg_attr(target_arch = "wasm32", allow(unused_mut))]
+        let mut builder = Client::builder().user_agent(USER_AGENT);
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            if let Some(max) = config.pool_max_idle_per_host {
+                builder = builder.pool_max_idle_per_host(max);
+            }
+            if let Some(timeout) = config.pool_idle_timeout {
+                builder = builder.pool_idle_timeout(timeout);
+            }
+            builder = builder.tcp_keepalive(config.tcp_keepalive);
+            builder = match config.http_version {
+                HttpVersion::Auto => builder,
+                HttpVersion::Http1Only => builder.http1_only(),
+                HttpVersion::Http2PriorKnowledge => builder.http2_prior_knowledge(),
+            };
+            builder = builder.http2_adaptive_window(config.http2_adaptive_window);
+            for (host, addr) in &config.resolve {
+                builder = builder.resolve(host, *addr);
+            }
+        }
+        let client = builder.build().map_err(|e| {
+            PeerCatError::InvalidConfig(format!("failed to create HTTP client: {}", e))
+        })?;
+
+        let environment_mismatch =
+            environment_mismatch(environment, &base_url, config.default_mode);
+        #[cfg(feature = "tracing")]
+        if let Some(reason) = environment_mismatch {
+            tracing::warn!(reason, "PeerCat client environment mismatch");
+        }
+
+        #[cfg(any(feature = "audit", feature = "vcr"))]
+        let redactor = Redactor::new(&config.redacted_fields);
+        #[cfg(feature = "vcr")]
+        let vcr = config
+            .vcr
+            .map(|mode| Vcr::new(mode, keys.keys(), redactor.clone()))
+            .transpose()?;
 
         Ok(Self {
-            api_key: config.api_key,
+            keys,
+            environment,
+            environment_mismatch,
+            mismatch_reported: Arc::new(AtomicBool::new(false)),
             base_url,
             client,
+            timeout,
+            overall_deadline: config.overall_deadline,
             max_retries: config.max_retries.unwrap_or(DEFAULT_MAX_RETRIES),
+            default_mode: config.default_mode,
+            block_production_operations: config.block_production_operations,
+            offline_demo: config.offline_demo,
+            budget: config.budget,
+            pacer: config
+                .pace_rate_limits
+                .then(|| Arc::new(RateLimitPacer::default())),
+            retry_budget: config.retry_budget,
+            hedge_after: config.hedge_after,
+            slow_request_threshold: config.slow_request_threshold,
+            conditional: config
+                .conditional_requests
+                .then(|| Arc::new(ConditionalCache::default())),
+            single_flight: config
+                .coalesce_requests
+                .then(|| Arc::new(SingleFlight::default())),
+            metrics: config.metrics,
+            error_reporter: config.error_reporter,
+            #[cfg(feature = "audit")]
+            audit: config.audit_log,
+            #[cfg(feature = "audit")]
+            redactor,
+            events: EventBus::default(),
+            ipfs_gateway: config
+                .ipfs_gateway
+                .unwrap_or_else(|| DEFAULT_IPFS_GATEWAY.to_string()),
+            models_cache: ModelCache::default(),
+            spend: Arc::new(SpendTracker::default()),
+            #[cfg(all(feature = "tower", not(target_arch = "wasm32")))]
+            transport: config.transport,
+            #[cfg(feature = "vcr")]
+            vcr,
         })
     }
 
+    /// Open a connection to the API ahead of the first real request
+    ///
+    /// Resolves DNS and completes the TLS handshake so the connection is
+    /// pooled, e.g. during a serverless cold start. `options` can also load
+    /// the model catalog and prices. Without them, a `HEAD` request is sent
+    /// to the base URL and its status ignored.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use peercat::{PeerCat, WarmupOptions};
+    ///
+    /// # async fn example() -> peercat::Result<()> {
+    /// let client = PeerCat::new("pcat_live_xxx")?;
+    /// client.warmup(WarmupOptions::new().with_models()).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn warmup(&self, options: WarmupOptions) -> Result<()> {
+        self.ensure_online()?;
+        if options.models {
+            self.models_catalog().refresh().await?;
+        }
+        if options.prices {
+            self.get_prices().await?;
+        }
+        if !options.models && !options.prices {
+            let selected = self.keys.select();
+            self.send(
+                &reqwest::Method::HEAD,
+                &self.base_url,
+                self.timeout,
+                None,
+                &HeaderMap::new(),
+                &selected.key,
+            )
+            .await?;
+        }
+        Ok(())
+    }
+
+    /// Subscribe to the client's lifecycle events
+    ///
+    /// Every call, retry, rate limit, retry budget exhaustion and credit
+    /// charge by this client and its clones is emitted to every subscriber.
+    /// Events are only built while someone is subscribed.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use peercat::{ClientEvent, PeerCat};
+    ///
+    /// # async fn example() -> peercat::Result<()> {
+    /// let client = PeerCat::new("pcat_live_xxx")?;
+    ///
+    /// let mut events = client.events();
+    /// tokio::spawn(async move {
+    ///     while let Some(event) = events.next().await {
+    ///         if let ClientEvent::CreditsSpent { endpoint, credits } = event {
+    ///             println!("{} cost {} credits", endpoint, credits);
+    ///         }
+    ///     }
+    /// });
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn events(&self) -> ClientEvents {
+        self.events.subscribe()
+    }
+
+    /// Write the interactions recorded so far to the cassette file
+    ///
+    /// In [`VcrMode::Record`](crate::vcr::VcrMode::Record) mode interactions
+    /// are kept in memory and written when the last clone of the client is
+    /// dropped. Call this to write them sooner, or to find out if writing
+    /// fails. Does nothing without a recording cassette.
+    #[cfg(feature = "vcr")]
+    pub fn save_cassette(&self) -> Result<()> {
+        match &self.vcr {
+            Some(vcr) => vcr.save(),
+            None => Ok(()),
+        }
+    }
+
+    /// The environment of the client's API keys
+    ///
+    /// Returns `None` for keys without a `pcat_live_` or `pcat_test_`
+    /// prefix, e.g. placeholder keys used against a mock server. Such keys
+    /// are accepted, but reported as an
+    /// [`EnvironmentMismatch`](crate::ClientEvent::EnvironmentMismatch).
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use peercat::{KeyEnvironment, PeerCat};
+    ///
+    /// let client = PeerCat::new("pcat_test_xxx")?;
+    /// assert_eq!(client.environment(), Some(KeyEnvironment::Test));
+    /// # Ok::<(), peercat::PeerCatError>(())
+    /// ```
+    pub fn environment(&self) -> Option<KeyEnvironment> {
+        self.environment
+    }
+
+    /// Credits spent on generations by this client and its clones
+    ///
+    /// Totals the `usage.credits_used` of every successful generation since
+    /// the client was created or [`reset_session_spend`](Self::reset_session_spend)
+    /// was called, so batch jobs can report their cost without querying history.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use peercat::{GenerateParams, PeerCat};
+    ///
+    /// # async fn example() -> peercat::Result<()> {
+    /// let client = PeerCat::new("pcat_live_xxx")?;
+    ///
+    /// for prompt in ["A red fox", "A blue whale"] {
+    ///     client.generate(GenerateParams::new(prompt)).await?;
+    /// }
+    /// let spend = client.session_spend();
+    /// println!("{} generations, {} credits", spend.generations, spend.credits);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn session_spend(&self) -> SessionSpend {
+        self.spend.total()
+    }
+
+    /// Start counting session spend from zero, returning the previous total
+    pub fn reset_session_spend(&self) -> SessionSpend {
+        self.spend.reset()
+    }
+
     // ============ Image Generation ============
 
     /// Generate an image from a text prompt
@@ -123,7 +474,449 @@ impl PeerCat {
     /// # }
     /// ```
     pub async fn generate(&self, params: GenerateParams) -> Result<GenerateResult> {
-        self.post("/v1/generate", &params).await
+        if self.offline_demo {
+            return Ok(self.offline_result(params));
+        }
+        let aspect_ratio = params.aspect_ratio;
+        let mut params = self.prepare_params(params).await?;
+        self.ensure_within_budget(params.mode)?;
+        if aspect_ratio.is_some() || params.has_ranged_options() {
+            params = self.prepare_for_model(params, aspect_ratio).await?;
+        }
+        let mut result: GenerateResult = self.post("/v1/generate", &params).await?;
+        self.spend.add(result.usage.credits_used);
+
+        // Keep what was asked for, so the result can be reproduced
+        result.keep_params(params);
+        Ok(result)
+    }
+
+    /// Upload an image for use as a generation input, such as a ControlNet control image
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use peercat::PeerCat;
+    ///
+    /// # async fn example() -> peercat::Result<()> {
+    /// let client = PeerCat::new("pcat_live_xxx")?;
+    ///
+    /// let pose = std::fs::read("pose.png")?;
+    /// let upload = client.upload_image(pose).await?;
+    /// println!("Uploaded to {}", upload.url);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn upload_image(&self, data: Vec<u8>) -> Result<UploadedImage> {
+        #[derive(serde::Serialize)]
+        #[serde(rename_all = "camelCase")]
+        struct UploadParams {
+            data: String,
+            content_type: &'static str,
+        }
+
+        let params = UploadParams {
+            content_type: image_content_type(&data),
+            data: BASE64_STANDARD.encode(&data),
+        };
+        self.post("/v1/uploads", &params).await
+    }
+
+    /// URL of a generated image on the configured IPFS gateway
+    ///
+    /// Falls back to the CDN URL when the image wasn't pinned to IPFS.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use peercat::{GenerateParams, PeerCat, PeerCatConfig};
+    ///
+    /// # async fn example() -> peercat::Result<()> {
+    /// let client = PeerCat::with_config(
+    ///     PeerCatConfig::new("pcat_live_xxx").with_ipfs_gateway("https://gateway.pinata.cloud"),
+    /// )?;
+    /// let result = client.generate(GenerateParams::new("A desert at night")).await?;
+    ///
+    /// println!("{}", client.ipfs_url(&result));
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn ipfs_url(&self, result: &GenerateResult) -> String {
+        result
+            .ipfs_url_via(&self.ipfs_gateway)
+            .unwrap_or_else(|| result.image_url.clone())
+    }
+
+    /// Get the IPFS pin status of a generation, by generation ID or IPFS hash
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use peercat::{GenerateParams, PeerCat};
+    ///
+    /// # async fn example() -> peercat::Result<()> {
+    /// let client = PeerCat::new("pcat_live_xxx")?;
+    /// let result = client.generate(GenerateParams::new("A genesis NFT")).await?;
+    ///
+    /// let status = client.get_ipfs_status(&result.id).await?;
+    /// if status.pinned {
+    ///     println!("ipfs://{} is held by {} providers", status.hash, status.providers);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn get_ipfs_status(&self, id_or_hash: &str) -> Result<IpfsStatus> {
+        self.get(&format!("/v1/ipfs/{}/status", id_or_hash)).await
+    }
+
+    /// Get the seed, dimensions, timings, and safety decision of a generation
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use peercat::{GenerateParams, PeerCat};
+    ///
+    /// # async fn example() -> peercat::Result<()> {
+    /// let client = PeerCat::new("pcat_live_xxx")?;
+    /// let result = client.generate(GenerateParams::new("A koi pond")).await?;
+    ///
+    /// let metadata = client.get_generation_metadata(&result.id).await?;
+    /// println!(
+    ///     "seed {} at {}x{} in {:?}",
+    ///     metadata.seed,
+    ///     metadata.width,
+    ///     metadata.height,
+    ///     metadata.inference_time()
+    /// );
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn get_generation_metadata(&self, id: &str) -> Result<GenerationMetadata> {
+        self.get(&format!("/v1/generations/{}/metadata", id)).await
+    }
+
+    /// Create a signed URL for a private image, valid for `ttl`
+    ///
+    /// The lifetime is rounded down to whole seconds.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use std::time::Duration;
+    ///
+    /// use peercat::{GenerateParams, PeerCat};
+    ///
+    /// # async fn example() -> peercat::Result<()> {
+    /// let client = PeerCat::new("pcat_live_xxx")?;
+    /// let result = client.generate(GenerateParams::new("A private portrait")).await?;
+    ///
+    /// if result.private {
+    ///     let signed = client
+    ///         .create_signed_url(&result.id, Duration::from_secs(15 * 60))
+    ///         .await?;
+    ///     println!("{} (expires {:?})", signed.url, signed.expires_at);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn create_signed_url(&self, generation_id: &str, ttl: Duration) -> Result<SignedUrl> {
+        #[derive(serde::Serialize)]
+        #[serde(rename_all = "camelCase")]
+        struct SignParams {
+            ttl_seconds: u64,
+        }
+
+        self.post(
+            &format!("/v1/generations/{}/signed-url", generation_id),
+            &SignParams {
+                ttl_seconds: ttl.as_secs(),
+            },
+        )
+        .await
+    }
+
+    /// Create a public link to a generation, e.g. for posting in chat
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use std::time::Duration;
+    ///
+    /// use peercat::{PeerCat, ShareOptions};
+    ///
+    /// # async fn example() -> peercat::Result<()> {
+    /// let client = PeerCat::new("pcat_live_xxx")?;
+    ///
+    /// let link = client
+    ///     .create_share_link(
+    ///         "gen_123",
+    ///         ShareOptions::new().with_expires_in(Duration::from_secs(24 * 60 * 60)),
+    ///     )
+    ///     .await?;
+    /// println!("{}", link.url);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn create_share_link(
+        &self,
+        generation_id: &str,
+        options: ShareOptions,
+    ) -> Result<ShareLink> {
+        #[derive(serde::Serialize)]
+        #[serde(rename_all = "camelCase")]
+        struct ShareParams {
+            #[serde(skip_serializing_if = "Option::is_none")]
+            expires_in_seconds: Option<u64>,
+            allow_download: bool,
+        }
+
+        self.post(
+            &format!("/v1/generations/{}/share", generation_id),
+            &ShareParams {
+                expires_in_seconds: options.expires_in.map(|ttl| ttl.as_secs()),
+                allow_download: options.allow_download,
+            },
+        )
+        .await
+    }
+
+    /// Add tags to a generation, e.g. to attribute usage to a campaign
+    ///
+    /// Tags show up on [`HistoryItem::tags`] and can be used to filter
+    /// history with [`HistoryParams::with_tag`].
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use peercat::{GenerateParams, HistoryParams, PeerCat};
+    ///
+    /// # async fn example() -> peercat::Result<()> {
+    /// let client = PeerCat::new("pcat_live_xxx")?;
+    /// let result = client.generate(GenerateParams::new("A spring banner")).await?;
+    ///
+    /// client
+    ///     .tag_generation(&result.id, vec!["campaign:spring-sale".to_string()])
+    ///     .await?;
+    ///
+    /// let campaign = client
+    ///     .get_history(HistoryParams::new().with_tag("campaign:spring-sale"))
+    ///     .await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn tag_generation(&self, id: &str, tags: Vec<String>) -> Result<()> {
+        #[derive(serde::Serialize)]
+        struct TagParams {
+            tags: Vec<String>,
+        }
+
+        let _: SuccessResponse = self
+            .post(&format!("/v1/generations/{}/tags", id), &TagParams { tags })
+            .await?;
+        Ok(())
+    }
+
+    /// Delete a generation, removing its image from the CDN
+    ///
+    /// With `unpin_ipfs`, the image is also unpinned from IPFS. Deletion can't
+    /// be undone.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use peercat::PeerCat;
+    ///
+    /// # async fn example() -> peercat::Result<()> {
+    /// let client = PeerCat::new("pcat_live_xxx")?;
+    ///
+    /// let deletion = client.delete_generation("gen_123", true).await?;
+    /// assert!(deletion.cdn_removed && deletion.ipfs_unpinned);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn delete_generation(
+        &self,
+        id: &str,
+        unpin_ipfs: bool,
+    ) -> Result<GenerationDeletion> {
+        #[derive(serde::Serialize)]
+        #[serde(rename_all = "camelCase")]
+        struct DeleteQuery {
+            unpin_ipfs: bool,
+        }
+
+        self.ensure_production_operation("delete_generation")?;
+        let path = with_query(
+            &format!("/v1/generations/{}", id),
+            &DeleteQuery { unpin_ipfs },
+        )?;
+        self.delete(&path).await
+    }
+
+    // ============ Collections ============
+
+    /// Create a collection for organizing generations
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use peercat::{CreateCollectionParams, GenerateParams, PeerCat};
+    ///
+    /// # async fn example() -> peercat::Result<()> {
+    /// let client = PeerCat::new("pcat_live_xxx")?;
+    ///
+    /// let album = client
+    ///     .create_collection(CreateCollectionParams::new("Landscapes").with_description("Best of"))
+    ///     .await?;
+    ///
+    /// let result = client.generate(GenerateParams::new("A fjord at dawn")).await?;
+    /// client.add_to_collection(&album.id, &result.id).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn create_collection(&self, params: CreateCollectionParams) -> Result<Collection> {
+        self.post("/v1/collections", &params).await
+    }
+
+    /// Add a generation to a collection
+    pub async fn add_to_collection(
+        &self,
+        collection_id: &str,
+        generation_id: &str,
+    ) -> Result<CollectionItem> {
+        #[derive(serde::Serialize)]
+        #[serde(rename_all = "camelCase")]
+        struct AddParams<'a> {
+            generation_id: &'a str,
+        }
+
+        self.post(
+            &format!("/v1/collections/{}/items", collection_id),
+            &AddParams { generation_id },
+        )
+        .await
+    }
+
+    /// List all collections
+    pub async fn list_collections(&self) -> Result<CollectionsResponse> {
+        self.get("/v1/collections").await
+    }
+
+    /// List the generations in a collection
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use peercat::PeerCat;
+    ///
+    /// # async fn example() -> peercat::Result<()> {
+    /// let client = PeerCat::new("pcat_live_xxx")?;
+    ///
+    /// for collection in client.list_collections().await?.collections {
+    ///     let items = client.list_collection_items(&collection.id).await?;
+    ///     println!("{}: {} images", collection.name, items.items.len());
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn list_collection_items(
+        &self,
+        collection_id: &str,
+    ) -> Result<CollectionItemsResponse> {
+        self.get(&format!("/v1/collections/{}/items", collection_id))
+            .await
+    }
+
+    // ============ Presets ============
+
+    /// Save generation settings on the server under a name, to share across services
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use peercat::{GenerateParams, PeerCat};
+    ///
+    /// # async fn example() -> peercat::Result<()> {
+    /// let client = PeerCat::new("pcat_live_xxx")?;
+    ///
+    /// let preset = client
+    ///     .create_preset(
+    ///         "product-shot",
+    ///         GenerateParams::new("A product photo on a white background")
+    ///             .with_model("stable-diffusion-xl")
+    ///             .with_steps(40),
+    ///     )
+    ///     .await?;
+    /// println!("Saved preset {}", preset.id);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn create_preset(&self, name: &str, params: GenerateParams) -> Result<Preset> {
+        #[derive(serde::Serialize)]
+        struct CreatePresetParams<'a> {
+            name: &'a str,
+            params: GenerateParams,
+        }
+
+        let params = self.prepare_params(params).await?;
+        self.post("/v1/presets", &CreatePresetParams { name, params })
+            .await
+    }
+
+    /// List saved presets
+    pub async fn list_presets(&self) -> Result<PresetsResponse> {
+        self.get("/v1/presets").await
+    }
+
+    /// Delete a saved preset
+    pub async fn delete_preset(&self, preset_id: &str) -> Result<()> {
+        self.ensure_production_operation("delete_preset")?;
+        let _: SuccessResponse = self.delete(&format!("/v1/presets/{}", preset_id)).await?;
+        Ok(())
+    }
+
+    /// Generate an image from a saved preset
+    ///
+    /// Fields set in `overrides` replace the preset's; an empty prompt keeps
+    /// the preset's prompt. Overrides aren't validated against the preset's
+    /// model, and aspect ratios aren't resolved, so set a `resolution` option instead.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use peercat::{GenerateParams, PeerCat};
+    ///
+    /// # async fn example() -> peercat::Result<()> {
+    /// let client = PeerCat::new("pcat_live_xxx")?;
+    ///
+    /// let result = client
+    ///     .generate_from_preset("preset_123", GenerateParams::new("A red sneaker").with_seed(7))
+    ///     .await?;
+    /// println!("Image URL: {}", result.image_url);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn generate_from_preset(
+        &self,
+        preset_id: &str,
+        overrides: GenerateParams,
+    ) -> Result<GenerateResult> {
+        if self.offline_demo {
+            return Ok(self.offline_result(overrides));
+        }
+        let overrides = self.prepare_params(overrides).await?;
+        self.ensure_within_budget(overrides.mode)?;
+        let mut body = serde_json::to_value(&overrides)?;
+        if overrides.prompt.is_empty() {
+            if let Some(body) = body.as_object_mut() {
+                body.remove("prompt");
+            }
+        }
+        let result: GenerateResult = self
+            .post(&format!("/v1/presets/{}/generate", preset_id), &body)
+            .await?;
+        self.spend.add(result.usage.credits_used);
+        Ok(result)
     }
 
     // ============ Models & Pricing ============
@@ -150,6 +943,75 @@ impl PeerCat {
         Ok(response.models)
     }
 
+    /// List the style adapters (LoRAs) available for a model
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use peercat::{GenerateParams, PeerCat};
+    ///
+    /// # async fn example() -> peercat::Result<()> {
+    /// let client = PeerCat::new("pcat_live_xxx")?;
+    ///
+    /// let loras = client.get_available_loras("stable-diffusion-xl").await?;
+    /// if let Some(lora) = loras.first() {
+    ///     let params = GenerateParams::new(format!("A castle, {}", lora.trigger_words.join(", ")))
+    ///         .with_lora(&lora.id, lora.default_weight);
+    ///     client.generate(params).await?;
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn get_available_loras(&self, model_id: &str) -> Result<Vec<Lora>> {
+        let response: LorasResponse = self.get(&format!("/v1/models/{}/loras", model_id)).await?;
+        Ok(response.loras)
+    }
+
+    /// Get a cached, lazily loaded view of the model list
+    ///
+    /// All catalogs from this client and its clones share one cache. See
+    /// [`ModelCatalog`] for details.
+    pub fn models_catalog(&self) -> ModelCatalog {
+        ModelCatalog::new(self.clone(), self.models_cache.clone())
+    }
+
+    /// Get a single model by ID
+    ///
+    /// # Errors
+    ///
+    /// Returns `PeerCatError::NotFound` with `param` set to `"model"` if no
+    /// model has the given ID.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use peercat::PeerCat;
+    ///
+    /// # async fn example() -> peercat::Result<()> {
+    /// let client = PeerCat::new("pcat_live_xxx")?;
+    /// let model = client.get_model("stable-diffusion-xl").await?;
+    ///
+    /// println!("{}: max {} chars", model.name, model.max_prompt_length);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn get_model(&self, model_id: &str) -> Result<Model> {
+        self.get(&format!("/v1/models/{}", model_id))
+            .await
+            .map_err(|e| match e {
+                PeerCatError::NotFound {
+                    message,
+                    code,
+                    param: None,
+                } => PeerCatError::NotFound {
+                    message,
+                    code,
+                    param: Some("model".to_string()),
+                },
+                e => e,
+            })
+    }
+
     /// Get current pricing for all models
     ///
     /// # Example
@@ -169,6 +1031,46 @@ impl PeerCat {
         self.get("/v1/price").await
     }
 
+    /// Get historical SOL/USD and model prices over a time range
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use peercat::{PeerCat, PriceGranularity};
+    ///
+    /// # async fn example() -> peercat::Result<()> {
+    /// let client = PeerCat::new("pcat_live_xxx")?;
+    ///
+    /// let from = "2024-01-01T00:00:00Z".parse().unwrap();
+    /// let to = "2024-02-01T00:00:00Z".parse().unwrap();
+    /// let history = client.get_price_history(from..to, PriceGranularity::Day).await?;
+    ///
+    /// for point in &history.points {
+    ///     println!("{}: ${}", point.timestamp, point.sol_price);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn get_price_history(
+        &self,
+        range: Range<Timestamp>,
+        granularity: PriceGranularity,
+    ) -> Result<PriceHistory> {
+        #[derive(serde::Serialize)]
+        struct HistoryQuery {
+            from: Timestamp,
+            to: Timestamp,
+            granularity: PriceGranularity,
+        }
+
+        let query = HistoryQuery {
+            from: range.start,
+            to: range.end,
+            granularity,
+        };
+        self.get(&with_query("/v1/price/history", &query)?).await
+    }
+
     // ============ Account ============
 
     /// Get current credit balance
@@ -190,42 +1092,154 @@ impl PeerCat {
         self.get("/v1/balance").await
     }
 
+    /// Create a deposit to top up credits with SOL
+    ///
+    /// Returns the treasury address, amount, and memo to send. Credits are
+    /// added once the transaction is confirmed; use
+    /// [`get_deposit_status`](Self::get_deposit_status) to track it.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use peercat::PeerCat;
+    ///
+    /// # async fn example() -> peercat::Result<()> {
+    /// let client = PeerCat::new("pcat_live_xxx")?;
+    ///
+    /// let deposit = client.create_deposit(25.0).await?;
+    ///
+    /// println!("Send {} SOL to {}", deposit.required_amount.sol, deposit.payment_address);
+    /// println!("Include memo: {}", deposit.memo);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn create_deposit(&self, amount_usd: f64) -> Result<DepositInstructions> {
+        #[derive(serde::Serialize)]
+        #[serde(rename_all = "camelCase")]
+        struct CreateDepositParams {
+            amount_usd: f64,
+        }
+
+        self.post("/v1/deposits", &CreateDepositParams { amount_usd })
+            .await
+    }
+
+    /// Get status of a deposit by transaction signature
+    pub async fn get_deposit_status(&self, tx_signature: &str) -> Result<DepositStatus> {
+        self.get(&format!("/v1/deposits/{}", tx_signature)).await
+    }
+
+    /// Withdraw credits to a wallet (requires wallet signature)
+    ///
+    /// The payout is processed asynchronously; use
+    /// [`get_withdrawal`](Self::get_withdrawal) to poll its status.
+    ///
+    /// Withdrawals are never retried, whatever the client's `max_retries`: a
+    /// request that reached the server may already be paying out. After a
+    /// network error or timeout, check the balance or usage history before
+    /// sending it again, and confirm a known withdrawal with `get_withdrawal`.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use peercat::{PeerCat, WithdrawParams};
+    ///
+    /// # async fn example() -> peercat::Result<()> {
+    /// let client = PeerCat::new("pcat_live_xxx")?;
+    ///
+    /// let withdrawal = client.withdraw(WithdrawParams {
+    ///     amount: 10.0,
+    ///     destination_wallet: "walletAddress...".to_string(),
+    ///     message: "Withdraw 10 USD from PeerCat".to_string(),
+    ///     signature: "base58signature...".to_string(),
+    ///     public_key: "walletPublicKey...".to_string(),
+    /// }).await?;
+    ///
+    /// println!("Withdrawal {}: {:?}", withdrawal.withdrawal_id, withdrawal.status);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn withdraw(&self, params: WithdrawParams) -> Result<WithdrawalResult> {
+        self.ensure_production_operation("withdraw")?;
+        let client = PeerCat {
+            max_retries: 0,
+            ..self.clone()
+        };
+        client.post("/v1/withdrawals", &params).await
+    }
+
+    /// Get status of a withdrawal
+    pub async fn get_withdrawal(&self, withdrawal_id: &str) -> Result<WithdrawalResult> {
+        self.get(&format!("/v1/withdrawals/{}", withdrawal_id))
+            .await
+    }
+
     /// Get usage history
     ///
     /// # Example
     ///
     /// ```no_run
-    /// use peercat::{PeerCat, HistoryParams};
+    /// use peercat::{PeerCat, HistoryParams};
+    ///
+    /// # async fn example() -> peercat::Result<()> {
+    /// let client = PeerCat::new("pcat_live_xxx")?;
+    ///
+    /// let history = client.get_history(
+    ///     HistoryParams::new().with_limit(10)
+    /// ).await?;
+    ///
+    /// for item in history.items {
+    ///     println!("{}: {} credits", item.endpoint, item.credits_used);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn get_history(&self, params: HistoryParams) -> Result<HistoryResponse> {
+        self.get(&with_query("/v1/history", &params)?).await
+    }
+
+    /// Get a single usage history record by ID
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use peercat::PeerCat;
+    ///
+    /// # async fn example() -> peercat::Result<()> {
+    /// let client = PeerCat::new("pcat_live_xxx")?;
+    ///
+    /// let item = client.get_history_item("use_123").await?;
+    /// println!("{:?}: {} credits", item.status, item.credits_used);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn get_history_item(&self, id: &str) -> Result<HistoryItem> {
+        self.get(&format!("/v1/history/{}", id)).await
+    }
+
+    /// Get aggregated credit usage over a date range
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use peercat::{PeerCat, UsageGroupBy, UsageSummaryParams};
     ///
     /// # async fn example() -> peercat::Result<()> {
     /// let client = PeerCat::new("pcat_live_xxx")?;
     ///
-    /// let history = client.get_history(
-    ///     HistoryParams::new().with_limit(10)
+    /// let summary = client.get_usage_summary(
+    ///     UsageSummaryParams::new(UsageGroupBy::Model)
+    ///         .with_date_range("2024-01-01T00:00:00Z", "2024-02-01T00:00:00Z")
     /// ).await?;
     ///
-    /// for item in history.items {
-    ///     println!("{}: {} credits", item.endpoint, item.credits_used);
+    /// for bucket in summary.buckets {
+    ///     println!("{}: {} credits", bucket.key, bucket.credits_used);
     /// }
     /// # Ok(())
     /// # }
     /// ```
-    pub async fn get_history(&self, params: HistoryParams) -> Result<HistoryResponse> {
-        let mut path = "/v1/history".to_string();
-        let mut query_parts = Vec::new();
-
-        if let Some(limit) = params.limit {
-            query_parts.push(format!("limit={}", limit));
-        }
-        if let Some(offset) = params.offset {
-            query_parts.push(format!("offset={}", offset));
-        }
-
-        if !query_parts.is_empty() {
-            path = format!("{}?{}", path, query_parts.join("&"));
-        }
-
-        self.get(&path).await
+    pub async fn get_usage_summary(&self, params: UsageSummaryParams) -> Result<UsageSummary> {
+        self.get(&with_query("/v1/analytics/usage", &params)?).await
     }
 
     // ============ API Keys ============
@@ -235,17 +1249,20 @@ impl PeerCat {
     /// # Example
     ///
     /// ```no_run
-    /// use peercat::{PeerCat, CreateKeyParams};
+    /// use peercat::{PeerCat, CreateKeyParams, KeyScope};
     ///
     /// # async fn example() -> peercat::Result<()> {
     /// let client = PeerCat::new("pcat_live_xxx")?;
     ///
-    /// let new_key = client.create_key(CreateKeyParams {
-    ///     name: Some("Production App".to_string()),
-    ///     message: "Create API key for PeerCat".to_string(),
-    ///     signature: "base58signature...".to_string(),
-    ///     public_key: "walletPublicKey...".to_string(),
-    /// }).await?;
+    /// let new_key = client.create_key(
+    ///     CreateKeyParams::new(
+    ///         "Create API key for PeerCat",
+    ///         "base58signature...",
+    ///         "walletPublicKey...",
+    ///     )
+    ///     .with_name("Production App")
+    ///     .with_scopes([KeyScope::Generate])
+    /// ).await?;
     ///
     /// // Warning: Full key is only shown once!
     /// println!("API Key: {}", new_key.key);
@@ -261,8 +1278,28 @@ impl PeerCat {
         self.get("/v1/keys").await
     }
 
+    /// Get a single API key by ID
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use peercat::PeerCat;
+    ///
+    /// # async fn example() -> peercat::Result<()> {
+    /// let client = PeerCat::new("pcat_live_xxx")?;
+    ///
+    /// let key = client.get_key("key_123").await?;
+    /// println!("{} ({}): last used {:?}", key.key_prefix, key.rate_limit_tier, key.last_used_at);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn get_key(&self, key_id: &str) -> Result<ApiKey> {
+        self.get(&format!("/v1/keys/{}", key_id)).await
+    }
+
     /// Revoke an API key
     pub async fn revoke_key(&self, key_id: &str) -> Result<()> {
+        self.ensure_production_operation("revoke_key")?;
         let _: SuccessResponse = self.delete(&format!("/v1/keys/{}", key_id)).await?;
         Ok(())
     }
@@ -280,6 +1317,42 @@ impl PeerCat {
         Ok(())
     }
 
+    /// Replace the IP allowlist of an API key
+    ///
+    /// Pass an empty list to remove the restriction.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use peercat::PeerCat;
+    ///
+    /// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+    /// let client = PeerCat::new("pcat_live_xxx")?;
+    ///
+    /// client
+    ///     .update_key_allowlist("key_123", vec!["203.0.113.0/24".parse()?])
+    ///     .await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn update_key_allowlist(&self, key_id: &str, networks: Vec<IpNet>) -> Result<()> {
+        #[derive(serde::Serialize)]
+        #[serde(rename_all = "camelCase")]
+        struct UpdateParams {
+            ip_allowlist: Vec<IpNet>,
+        }
+
+        let _: SuccessResponse = self
+            .patch(
+                &format!("/v1/keys/{}", key_id),
+                &UpdateParams {
+                    ip_allowlist: networks,
+                },
+            )
+            .await?;
+        Ok(())
+    }
+
     // ============ On-Chain Payments ============
 
     /// Submit a prompt for on-chain payment
@@ -303,9 +1376,103 @@ impl PeerCat {
     /// # }
     /// ```
     pub async fn submit_prompt(&self, params: SubmitPromptParams) -> Result<PromptSubmission> {
+        if let Some(tolerance) = params.slippage_tolerance {
+            if !(0.0..=SubmitPromptParams::MAX_SLIPPAGE_TOLERANCE).contains(&tolerance) {
+                return Err(PeerCatError::Validation {
+                    field: "slippage_tolerance".to_string(),
+                    message: format!(
+                        "must be between 0 and {}, got {}",
+                        SubmitPromptParams::MAX_SLIPPAGE_TOLERANCE,
+                        tolerance
+                    ),
+                });
+            }
+        }
+
         self.post("/v1/prompts", &params).await
     }
 
+    /// List prompt submissions, e.g. to pick up unpaid ones after a restart
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use peercat::{PeerCat, SubmissionFilter, SubmissionStatus};
+    ///
+    /// # async fn example() -> peercat::Result<()> {
+    /// let client = PeerCat::new("pcat_live_xxx")?;
+    ///
+    /// let open = client
+    ///     .list_submissions(SubmissionFilter::new().with_status(SubmissionStatus::Open))
+    ///     .await?;
+    /// for submission in open.submissions {
+    ///     println!("{}: send {} SOL", submission.submission_id, submission.required_amount.sol);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn list_submissions(&self, filter: SubmissionFilter) -> Result<SubmissionsResponse> {
+        self.get(&with_query("/v1/prompts", &filter)?).await
+    }
+
+    /// Get a prompt submission by ID, with its payment details and status
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use peercat::{PeerCat, SubmissionStatus};
+    ///
+    /// # async fn example() -> peercat::Result<()> {
+    /// let client = PeerCat::new("pcat_live_xxx")?;
+    ///
+    /// let submission = client.get_submission("sub_123").await?;
+    /// if submission.status == SubmissionStatus::Open {
+    ///     println!("Send {} SOL with memo {}", submission.required_amount.sol, submission.memo);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn get_submission(&self, submission_id: &str) -> Result<PromptSubmission> {
+        self.get(&format!("/v1/prompts/{}", submission_id)).await
+    }
+
+    /// Link a payment transaction to a submission
+    ///
+    /// For payments whose memo was omitted or mangled by the wallet, so the
+    /// server can't match them to a submission on its own. Returns the updated
+    /// submission.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use peercat::PeerCat;
+    ///
+    /// # async fn example() -> peercat::Result<()> {
+    /// let client = PeerCat::new("pcat_live_xxx")?;
+    ///
+    /// let submission = client.link_transaction("sub_123", "5xyz...").await?;
+    /// println!("Submission is now {:?}", submission.status);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn link_transaction(
+        &self,
+        submission_id: &str,
+        tx_signature: &str,
+    ) -> Result<PromptSubmission> {
+        #[derive(serde::Serialize)]
+        #[serde(rename_all = "camelCase")]
+        struct LinkParams<'a> {
+            tx_signature: &'a str,
+        }
+
+        self.post(
+            &format!("/v1/prompts/{}/transactions", submission_id),
+            &LinkParams { tx_signature },
+        )
+        .await
+    }
+
     /// Get status of an on-chain generation by transaction signature
     ///
     /// # Example
@@ -334,10 +1501,138 @@ impl PeerCat {
         self.get(&format!("/v1/generate/{}", tx_signature)).await
     }
 
+    /// Get the event timeline of an on-chain generation
+    ///
+    /// Lists each step (payment detected, amount verified, queued, generation
+    /// started, uploaded to IPFS, completed or refunded) with its timestamp.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use peercat::PeerCat;
+    ///
+    /// # async fn example() -> peercat::Result<()> {
+    /// let client = PeerCat::new("pcat_live_xxx")?;
+    ///
+    /// let timeline = client.get_onchain_events("txSignature...").await?;
+    /// for event in timeline.events {
+    ///     println!("{:?} {:?}: {}", event.timestamp, event.kind, event.message.unwrap_or_default());
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn get_onchain_events(&self, tx_signature: &str) -> Result<OnChainEvents> {
+        self.get(&format!("/v1/generate/{}/events", tx_signature))
+            .await
+    }
+
     // ============ Internal Methods ============
 
+    /// Apply the default mode, resolve the sampler, and upload control image bytes
+    ///
+    /// The aspect ratio is dropped; `generate` resolves it separately.
+    async fn prepare_params(&self, mut params: GenerateParams) -> Result<GenerateParams> {
+        if params.mode.is_none() {
+            params.mode = self.default_mode;
+        }
+        params.aspect_ratio = None;
+        params.resolve_sampler();
+        if let Some(controlnet) = &mut params.controlnet {
+            if let ControlImage::Bytes(data) = &mut controlnet.image {
+                let upload = self.upload_image(std::mem::take(data)).await?;
+                controlnet.image = ControlImage::Url(upload.url);
+            }
+        }
+        Ok(params)
+    }
+
+    /// Validate params against their model and resolve the aspect ratio to a resolution
+    ///
+    /// Uses the cached model list. Params for an unknown model are left for
+    /// the API to reject.
+    async fn prepare_for_model(
+        &self,
+        params: GenerateParams,
+        aspect_ratio: Option<AspectRatio>,
+    ) -> Result<GenerateParams> {
+        let model_id = params
+            .model
+            .as_deref()
+            .unwrap_or(KnownModel::StableDiffusionXl.as_str());
+        let Some(model) = self.models_catalog().find(model_id).await? else {
+            return Ok(params);
+        };
+        params.validate_for(&model)?;
+
+        let has_resolution = params
+            .options
+            .as_ref()
+            .is_some_and(|options| options.contains_key("resolution"));
+        let resolution = aspect_ratio
+            .filter(|_| !has_resolution)
+            .and_then(|aspect_ratio| model.resolution_for(aspect_ratio));
+
+        Ok(match resolution {
+            Some(resolution) => params.with_option("resolution", resolution.to_string().into()),
+            None => params,
+        })
+    }
+
+    pub(crate) fn http_client(&self) -> &Client {
+        &self.client
+    }
+
+    /// A placeholder generation for an offline demo client
+    fn offline_result(&self, params: GenerateParams) -> GenerateResult {
+        let result = offline::demo_result(params);
+        self.spend.add(result.usage.credits_used);
+        result
+    }
+
+    /// Refuse network access on an offline demo client
+    pub(crate) fn ensure_online(&self) -> Result<()> {
+        if self.offline_demo {
+            return Err(PeerCatError::OperationNotAllowed {
+                operation: "network access".to_string(),
+            });
+        }
+        Ok(())
+    }
+
+    fn ensure_production_operation(&self, operation: &str) -> Result<()> {
+        if self.block_production_operations {
+            return Err(PeerCatError::OperationNotAllowed {
+                operation: operation.to_string(),
+            });
+        }
+        Ok(())
+    }
+
+    /// Refuse a paid generation once the session spend has reached the budget
+    fn ensure_within_budget(&self, mode: Option<GenerationMode>) -> Result<()> {
+        let Some(limit) = self.budget else {
+            return Ok(());
+        };
+        let spent = self.spend.total().credits;
+        if mode != Some(GenerationMode::Demo) && spent >= limit {
+            return Err(PeerCatError::BudgetExceeded { spent, limit });
+        }
+        Ok(())
+    }
+
     async fn get<T: serde::de::DeserializeOwned>(&self, path: &str) -> Result<T> {
-        self.request(reqwest::Method::GET, path, None::<&()>).await
+        let Some(single_flight) = &self.single_flight else {
+            return self.request(reqwest::Method::GET, path, None::<&()>).await;
+        };
+        let method = reqwest::Method::GET;
+        let span = RequestSpan::call(&method, path);
+        // Only callers starting on the same key share a response
+        let selected = self.keys.select();
+        let call = single_flight.run(selected.index, path, || {
+            self.request_body(method.clone(), path, None::<&()>, Some(selected), &span)
+        });
+        let body = self.observe(&method, path, &span, call).await?;
+        Ok(serde_json::from_slice(&body)?)
     }
 
     async fn post<T: serde::de::DeserializeOwned, B: serde::Serialize>(
@@ -361,52 +1656,332 @@ impl PeerCat {
             .await
     }
 
+    /// Send a single attempt and read the whole response
+    async fn send(
+        &self,
+        method: &reqwest::Method,
+        url: &str,
+        timeout: Duration,
+        body: Option<&serde_json::Value>,
+        headers: &HeaderMap,
+        key: &SecretString,
+    ) -> Result<RawResponse> {
+        #[cfg(feature = "vcr")]
+        if let Some(vcr) = &self.vcr {
+            if vcr.is_replaying() {
+                return vcr.replay(method, &url[self.base_url.len()..], body);
+            }
+        }
+
+        #[cfg(all(feature = "tower", not(target_arch = "wasm32")))]
+        let response = match &self.transport {
+            Some(transport) => {
+                transport
+                    .send(method, url, timeout, body, headers, key)
+                    .await?
+            }
+            None => {
+                self.dispatch(method, url, timeout, body, headers, key)
+                    .await?
+            }
+        };
+        #[cfg(not(all(feature = "tower", not(target_arch = "wasm32"))))]
+        let response = self
+            .dispatch(method, url, timeout, body, headers, key)
+            .await?;
+
+        #[cfg(feature = "vcr")]
+        if let Some(vcr) = &self.vcr {
+            vcr.record(method, &url[self.base_url.len()..], body, &response);
+        }
+
+        Ok(response)
+    }
+
+    /// Send a GET, plus a duplicate if it hasn't responded within `after`
+    ///
+    /// Returns the first successful (2xx or 304) response. If both fail, the
+    /// last failure is returned. The duplicate is an extra attempt, so it
+    /// spends the retry budget and waits for the pacer like a retry would;
+    /// if the budget is spent, only the first request is awaited.
+    async fn send_hedged(
+        &self,
+        url: &str,
+        timeout: Duration,
+        headers: &HeaderMap,
+        selected: &SelectedKey,
+        after: Duration,
+    ) -> Result<RawResponse> {
+        let method = reqwest::Method::GET;
+        let mut primary = pin!(self.send(&method, url, timeout, None, headers, &selected.key));
+        let mut timer = pin!(rt::sleep(after));
+        let first = poll_fn(|cx| match primary.as_mut().poll(cx) {
+            Poll::Ready(result) => Poll::Ready(Some(result)),
+            Poll::Pending => timer.as_mut().poll(cx).map(|()| None),
+        })
+        .await;
+        if let Some(result) = first {
+            return result;
+        }
+
+        if let Some(budget) = &self.retry_budget {
+            if let RetryDecision::Refused { opened } = budget.try_retry() {
+                if opened {
+                    self.events.publish(|| ClientEvent::CircuitOpened);
+                }
+                return primary.await;
+            }
+        }
+        let delay = self.pacer.as_ref().and_then(|p| p.reserve(selected.index));
+        let mut hedge = pin!(async {
+            if let Some(delay) = delay {
+                rt::sleep(delay).await;
+            }
+            self.send(&method, url, timeout, None, headers, &selected.key)
+                .await
+        });
+        let mut finished = 0;
+        let mut primary_done = false;
+        let mut hedge_done = false;
+        poll_fn(|cx| {
+            let futures: [(Pin<&mut dyn Future<Output = Result<RawResponse>>>, _); 2] = [
+                (primary.as_mut(), &mut primary_done),
+                (hedge.as_mut(), &mut hedge_done),
+            ];
+            for (future, done) in futures {
+                if *done {
+                    continue;
+                }
+                if let Poll::Ready(result) = future.poll(cx) {
+                    *done = true;
+                    finished += 1;
+                    let succeeded = matches!(
+                        &result,
+                        Ok(r) if r.status.is_success() || r.status == StatusCode::NOT_MODIFIED
+                    );
+                    if succeeded || finished == 2 {
+                        return Poll::Ready(result);
+                    }
+                }
+            }
+            Poll::Pending
+        })
+        .await
+    }
+
+    /// Send a request with the built-in HTTP client
+    async fn dispatch(
+        &self,
+        method: &reqwest::Method,
+        url: &str,
+        timeout: Duration,
+        body: Option<&serde_json::Value>,
+        headers: &HeaderMap,
+        key: &SecretString,
+    ) -> Result<RawResponse> {
+        let mut request = self
+            .client
+            .request(method.clone(), url)
+            .timeout(timeout)
+            .header(reqwest::header::AUTHORIZATION, key.bearer_header()?)
+            .header("Content-Type", "application/json")
+            .headers(headers.clone());
+
+        if let Some(b) = body {
+            request = request.json(b);
+        }
+
+        let response = request.send().await.map_err(transport_error)?;
+        Ok(RawResponse {
+            status: response.status(),
+            headers: response.headers().clone(),
+            body: response.bytes().await.map_err(transport_error)?.to_vec(),
+        })
+    }
+
     async fn request<T: serde::de::DeserializeOwned, B: serde::Serialize>(
         &self,
         method: reqwest::Method,
         path: &str,
         body: Option<&B>,
     ) -> Result<T> {
-        let url = format!("{}{}", self.base_url, path);
-        let mut last_error: Option<PeerCatError> = None;
-
-        for attempt in 0..=self.max_retries {
-            let mut request = self
-                .client
-                .request(method.clone(), &url)
-                .header("Authorization", format!("Bearer {}", self.api_key))
-                .header("Content-Type", "application/json");
+        let span = RequestSpan::call(&method, path);
+        let call = self.request_body(method.clone(), path, body, None, &span);
+        let body = self.observe(&method, path, &span, call).await?;
+        Ok(serde_json::from_slice(&body)?)
+    }
 
-            if let Some(b) = body {
-                request = request.json(b);
+    /// Run a call inside its span, recording its outcome
+    async fn observe<T>(
+        &self,
+        method: &reqwest::Method,
+        path: &str,
+        span: &RequestSpan,
+        call: impl Future<Output = Result<T>>,
+    ) -> Result<T> {
+        if let Some(reason) = self.environment_mismatch {
+            // Warn once, the first time anyone is listening
+            if self.events.is_active() && !self.mismatch_reported.swap(true, Ordering::Relaxed) {
+                self.events.publish(|| ClientEvent::EnvironmentMismatch {
+                    reason: reason.to_string(),
+                });
             }
+        }
+        self.events.publish(|| ClientEvent::RequestStarted {
+            method: method.to_string(),
+            endpoint: endpoint(path).to_string(),
+        });
+        let started = Instant::now();
+        let body = span.instrument(call).await;
+        span.record_result(&body);
+        let duration = started.elapsed();
+        self.notify(MetricEvent::Request {
+            method: method.as_str(),
+            endpoint: endpoint(path),
+            duration,
+            error: body.as_ref().err(),
+        });
+        if let (Err(error), Some(reporter)) = (&body, &self.error_reporter) {
+            reporter.report(
+                error,
+                &ErrorContext {
+                    method: method.as_str(),
+                    endpoint: endpoint(path),
+                    duration,
+                },
+            );
+        }
+        body
+    }
+
+    /// Send a request with retries and failover, returning the successful response body
+    ///
+    /// The first attempt uses `key` when given; later ones choose from the pool.
+    async fn request_body<B: serde::Serialize>(
+        &self,
+        method: reqwest::Method,
+        path: &str,
+        body: Option<&B>,
+        key: Option<SelectedKey>,
+        span: &RequestSpan,
+    ) -> Result<Vec<u8>> {
+        let body = body.map(serde_json::to_value).transpose()?;
+        #[cfg(feature = "audit")]
+        let started = Instant::now();
+        let response = self
+            .send_with_retries(&method, path, body.as_ref(), key, span)
+            .await;
+        #[cfg(feature = "audit")]
+        self.audit(&method, path, body.as_ref(), started, &response);
+        response.map(|response| response.body)
+    }
+
+    /// Send a request with retries and failover, returning the final successful response
+    async fn send_with_retries(
+        &self,
+        method: &reqwest::Method,
+        path: &str,
+        body: Option<&serde_json::Value>,
+        mut key: Option<SelectedKey>,
+        span: &RequestSpan,
+    ) -> Result<RawResponse> {
+        self.ensure_online()?;
+        let url = format!("{}{}", self.base_url, path);
+        let mut attempt = 0;
+        let mut failovers = 0;
+        let mut sent = 0;
+        let started = Instant::now();
+        if let Some(budget) = &self.retry_budget {
+            budget.record_request();
+        }
 
-            let result = request.send().await;
+        loop {
+            let selected = key.take().unwrap_or_else(|| self.keys.select());
+            if let Some(delay) = self.pacer.as_ref().and_then(|p| p.reserve(selected.index)) {
+                rt::sleep(delay).await;
+            }
+            let timeout = match self.overall_deadline {
+                Some(deadline) => match deadline.checked_sub(started.elapsed()) {
+                    Some(remaining) if !remaining.is_zero() => self.timeout.min(remaining),
+                    _ => return Err(PeerCatError::Timeout),
+                },
+                None => self.timeout,
+            };
+            let conditional = self
+                .conditional
+                .as_ref()
+                .filter(|_| *method == reqwest::Method::GET);
+            let (headers, cached) = conditional
+                .and_then(|cache| cache.lookup(selected.index, &url))
+                .unzip();
+            let headers = headers.unwrap_or_default();
+            let attempt_span = span.attempt(sent);
+            let attempt_started = Instant::now();
+            sent += 1;
+            let result = attempt_span
+                .instrument(async {
+                    match self.hedge_after {
+                        Some(after) if *method == reqwest::Method::GET => {
+                            self.send_hedged(&url, timeout, &headers, &selected, after)
+                                .await
+                        }
+                        _ => {
+                            self.send(method, &url, timeout, body, &headers, &selected.key)
+                                .await
+                        }
+                    }
+                })
+                .await;
+            attempt_span.record_result(&result);
+            let duration = attempt_started.elapsed();
+            if self
+                .slow_request_threshold
+                .is_some_and(|threshold| duration > threshold)
+            {
+                attempt_span.warn_slow(sent - 1, duration);
+                self.events.publish(|| ClientEvent::SlowRequest {
+                    method: method.to_string(),
+                    endpoint: endpoint(path).to_string(),
+                    attempt: sent,
+                    duration,
+                });
+            }
 
-            match result {
+            let error = match result {
                 Ok(response) => {
-                    let status = response.status();
+                    let status = response.status;
+                    attempt_span.record_status(status);
+                    span.record_status(status);
 
                     // Parse rate limit headers
-                    let rate_limit_info = RateLimitInfo::from_headers(response.headers());
+                    let rate_limit_info = RateLimitInfo::from_headers(&response.headers);
+                    if let Some(pacer) = &self.pacer {
+                        pacer.update(
+                            selected.index,
+                            rate_limit_info.as_ref(),
+                            status == StatusCode::TOO_MANY_REQUESTS,
+                        );
+                    }
 
+                    if status == StatusCode::NOT_MODIFIED {
+                        if let Some(cached) = cached {
+                            return Ok(RawResponse {
+                                body: cached.to_vec(),
+                                ..response
+                            });
+                        }
+                    }
                     if status.is_success() {
-                        return response.json().await.map_err(|e| {
-                            // Detect decode errors and convert to Json variant instead of Network
-                            // reqwest::Error::is_decode() returns true for JSON deserialization failures
-                            if e.is_decode() {
-                                PeerCatError::Json(serde_json::Error::io(
-                                    std::io::Error::new(std::io::ErrorKind::InvalidData, e.to_string()),
-                                ))
-                            } else {
-                                PeerCatError::Network(e)
-                            }
-                        });
+                        if let Some(cache) = conditional {
+                            cache.store(selected.index, &url, &response.headers, &response.body);
+                        }
+                        self.record_credits(path, span, &response.body);
+                        return Ok(response);
                     }
 
                     // Parse error response
                     let error_response: std::result::Result<ApiErrorResponse, _> =
-                        response.json().await;
+                        serde_json::from_slice(&response.body);
 
                     let error = match error_response {
                         Ok(err) => PeerCatError::from_api_error(
@@ -426,37 +2001,271 @@ impl PeerCat {
                         },
                     };
 
+                    // Rotate to another pooled key when this one is rejected or
+                    // rate limited. Failovers don't count against max_retries.
+                    let failover = match status {
+                        StatusCode::UNAUTHORIZED => {
+                            self.keys.mark_revoked(selected.index);
+                            true
+                        }
+                        StatusCode::TOO_MANY_REQUESTS => {
+                            self.notify(MetricEvent::RateLimited {
+                                method: method.as_str(),
+                                endpoint: endpoint(path),
+                                retry_after: error.retry_after(),
+                            });
+                            self.keys
+                                .mark_rate_limited(selected.index, error.retry_after());
+                            true
+                        }
+                        _ => false,
+                    };
+                    if failover
+                        && failovers < self.keys.len()
+                        && self.keys.has_alternative(selected.index)
+                    {
+                        failovers += 1;
+                        continue;
+                    }
+
                     // Don't retry client errors (4xx) except rate limits
                     if status.is_client_error() && status != StatusCode::TOO_MANY_REQUESTS {
                         return Err(error);
                     }
 
-                    last_error = Some(error);
+                    error
                 }
-                Err(e) => {
-                    if e.is_timeout() {
-                        last_error = Some(PeerCatError::Timeout);
-                    } else {
-                        last_error = Some(PeerCatError::Network(e));
+                Err(e) => e,
+            };
+
+            if attempt >= self.max_retries {
+                return Err(error);
+            }
+            // Don't start a retry that couldn't finish before the deadline
+            let delay = backoff_delay(attempt, error.retry_after());
+            if self
+                .overall_deadline
+                .is_some_and(|deadline| started.elapsed() + delay >= deadline)
+            {
+                return Err(error);
+            }
+            if let Some(budget) = &self.retry_budget {
+                if let RetryDecision::Refused { opened } = budget.try_retry() {
+                    if opened {
+                        self.events.publish(|| ClientEvent::CircuitOpened);
                     }
+                    return Err(error);
                 }
             }
 
-            // Exponential backoff before retry (use Retry-After for rate limits)
-            if attempt < self.max_retries {
-                let mut delay = std::cmp::min(1000 * 2u64.pow(attempt), 10000);
+            self.notify(MetricEvent::Retry {
+                method: method.as_str(),
+                endpoint: endpoint(path),
+                attempt: attempt + 1,
+                delay,
+            });
+            rt::sleep(delay).await;
+            attempt += 1;
+        }
+    }
 
-                // Use Retry-After header if available for rate limit errors
-                if let Some(ref error) = last_error {
-                    if let Some(retry_after) = error.retry_after() {
-                        delay = retry_after * 1000; // Convert seconds to milliseconds
-                    }
-                }
+    /// Report the credits a successful response says it cost, if anyone is listening
+    fn record_credits(&self, path: &str, span: &RequestSpan, body: &[u8]) {
+        if self.metrics.is_none() && !self.events.is_active() && !span.is_recording() {
+            return;
+        }
+        let Some(credits) = credits_used(body) else {
+            return;
+        };
+        span.record_credits(credits);
+        self.notify(MetricEvent::CreditsSpent {
+            endpoint: endpoint(path),
+            credits,
+        });
+    }
 
-                tokio::time::sleep(Duration::from_millis(delay)).await;
+    /// Pass a finished call to the audit log, if there is one
+    #[cfg(feature = "audit")]
+    fn audit(
+        &self,
+        method: &reqwest::Method,
+        path: &str,
+        body: Option<&serde_json::Value>,
+        started: Instant,
+        response: &Result<RawResponse>,
+    ) {
+        let Some(audit) = &self.audit else {
+            return;
+        };
+        let duration = started.elapsed();
+        let timestamp = web_time::SystemTime::now()
+            .checked_sub(duration)
+            .and_then(|t| t.duration_since(web_time::UNIX_EPOCH).ok())
+            .unwrap_or_default();
+        let mut record = crate::audit::AuditRecord {
+            timestamp_ms: timestamp.as_millis() as u64,
+            method: method.to_string(),
+            endpoint: endpoint(path).to_string(),
+            params_hash: body.map(crate::audit::params_hash),
+            params: body.cloned().map(|mut body| {
+                self.redactor.redact(&mut body);
+                body
+            }),
+            status: None,
+            request_id: None,
+            result_id: None,
+            credits: None,
+            duration_ms: duration.as_millis() as u64,
+            error: None,
+        };
+        match response {
+            Ok(response) => {
+                record.status = Some(response.status.as_u16());
+                record.request_id = response
+                    .headers
+                    .get("x-request-id")
+                    .and_then(|v| v.to_str().ok())
+                    .map(str::to_string);
+                record.result_id = crate::audit::result_id(&response.body);
+                record.credits = credits_used(&response.body);
+            }
+            Err(e) => {
+                record.error = Some(match body {
+                    Some(body) if !self.redactor.is_empty() => {
+                        self.redactor.scrub(body, &e.to_string())
+                    }
+                    _ => e.to_string(),
+                })
             }
         }
+        audit.record(&record);
+    }
+
+    /// Pass an event to the metrics sink and event subscribers
+    fn notify(&self, event: MetricEvent<'_>) {
+        if let Some(metrics) = &self.metrics {
+            metrics.record(event);
+        }
+        self.events.publish_metric(&event);
+    }
+}
+
+/// Exponential backoff before a retry, or the Retry-After value for rate limits
+pub(crate) fn backoff_delay(attempt: u32, retry_after: Option<u64>) -> Duration {
+    match retry_after {
+        Some(secs) => retry_after_delay(secs),
+        None => Duration::from_millis(std::cmp::min(1000 * 2u64.pow(attempt), 10000)),
+    }
+}
+
+/// Longest wait taken from a server's `Retry-After`, so a huge value can't
+/// overflow a deadline or bench a key indefinitely
+pub(crate) const MAX_RETRY_AFTER: Duration = Duration::from_secs(3600);
+
+/// Time to wait for a `Retry-After` value, capped at an hour
+pub(crate) fn retry_after_delay(secs: u64) -> Duration {
+    Duration::from_secs(secs).min(MAX_RETRY_AFTER)
+}
+
+/// The request path without its query string
+pub(crate) fn endpoint(path: &str) -> &str {
+    path.split_once('?').map_or(path, |(path, _)| path)
+}
+
+/// Why the API key is probably being used by mistake, if it is
+fn environment_mismatch(
+    environment: Option<KeyEnvironment>,
+    base_url: &str,
+    default_mode: Option<GenerationMode>,
+) -> Option<&'static str> {
+    match environment {
+        // Key checks reject other `pcat_` keys, so this one has no PeerCat prefix
+        None => return Some("API key doesn't start with pcat_live_ or pcat_test_"),
+        Some(KeyEnvironment::Test) => return None,
+        Some(KeyEnvironment::Live) => {}
+    }
+    if default_mode == Some(GenerationMode::Demo) {
+        Some("live API key used with demo mode as the default")
+    } else if is_test_url(base_url) {
+        Some("live API key used with a test base URL")
+    } else {
+        None
+    }
+}
+
+/// Whether a base URL's host names a test environment, like
+/// `sandbox.api.peerc.at` or `api-staging.example.com`
+fn is_test_url(base_url: &str) -> bool {
+    let authority = base_url
+        .split_once("://")
+        .map_or(base_url, |(_, rest)| rest)
+        .split(['/', '?', '#'])
+        .next()
+        .unwrap_or_default();
+    let host = authority.rsplit('@').next().unwrap_or_default();
+    let host = host.split(':').next().unwrap_or_default();
+    host.split(['.', '-'])
+        .any(|label| TEST_HOST_LABELS.contains(&label.to_ascii_lowercase().as_str()))
+}
+
+/// Credits charged according to a response's `usage.creditsUsed`
+fn credits_used(body: &[u8]) -> Option<f64> {
+    #[derive(serde::Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    struct Usage {
+        credits_used: f64,
+    }
+    #[derive(serde::Deserialize)]
+    struct Body {
+        usage: Usage,
+    }
+
+    serde_json::from_slice::<Body>(body)
+        .ok()
+        .map(|body| body.usage.credits_used)
+}
+
+/// A response read into memory, from the network or a replayed cassette
+pub(crate) struct RawResponse {
+    pub status: StatusCode,
+    pub headers: HeaderMap,
+    pub body: Vec<u8>,
+}
+
+/// Map a transport error, distinguishing timeouts
+pub(crate) fn transport_error(e: reqwest::Error) -> PeerCatError {
+    if e.is_timeout() {
+        PeerCatError::Timeout
+    } else {
+        PeerCatError::from(e)
+    }
+}
+
+/// Guess an image's content type from its magic bytes
+fn image_content_type(data: &[u8]) -> &'static str {
+    if data.starts_with(b"\x89PNG\r\n\x1a\n") {
+        "image/png"
+    } else if data.starts_with(&[0xff, 0xd8, 0xff]) {
+        "image/jpeg"
+    } else if data.len() >= 12 && &data[..4] == b"RIFF" && &data[8..12] == b"WEBP" {
+        "image/webp"
+    } else {
+        "application/octet-stream"
+    }
+}
+
+/// Append URL-encoded query parameters to a path, omitting the `?` when empty
+fn with_query<Q: serde::Serialize>(path: &str, query: &Q) -> Result<String> {
+    let query = serde_urlencoded::to_string(query).map_err(|e| {
+        PeerCatError::Json(serde_json::Error::io(std::io::Error::new(
+            std::io::ErrorKind::InvalidData,
+            e.to_string(),
+        )))
+    })?;
 
-        Err(last_error.unwrap_or(PeerCatError::Timeout))
+    if query.is_empty() {
+        Ok(path.to_string())
+    } else {
+        Ok(format!("{}?{}", path, query))
     }
 }
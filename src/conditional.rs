@@ -0,0 +1,69 @@
+//! Conditional GET requests using ETag and Last-Modified validators
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use reqwest::header::{HeaderMap, ETAG, IF_MODIFIED_SINCE, IF_NONE_MATCH, LAST_MODIFIED};
+
+/// Most responses kept for revalidation; beyond this an arbitrary one is evicted
+const MAX_ENTRIES: usize = 256;
+
+/// A response body and the validators it was served with
+#[derive(Debug)]
+struct Entry {
+    validators: HeaderMap,
+    body: Arc<Vec<u8>>,
+}
+
+/// Cached GET responses, revalidated with `If-None-Match`/`If-Modified-Since`
+///
+/// Shared by all clones of a client. Only responses carrying an `ETag` or
+/// `Last-Modified` header are kept, per key and URL, so accounts in a key
+/// pool never see each other's data.
+#[derive(Debug, Default)]
+pub(crate) struct ConditionalCache {
+    entries: Mutex<HashMap<(usize, String), Entry>>,
+}
+
+impl ConditionalCache {
+    /// The conditional headers and cached body for a URL, if it was cached
+    pub fn lookup(&self, key: usize, url: &str) -> Option<(HeaderMap, Arc<Vec<u8>>)> {
+        let entries = self.lock();
+        let entry = entries.get(&(key, url.to_string()))?;
+        Some((entry.validators.clone(), entry.body.clone()))
+    }
+
+    /// Keep a successful response for revalidation if it has validators
+    pub fn store(&self, key: usize, url: &str, headers: &HeaderMap, body: &[u8]) {
+        let mut validators = HeaderMap::new();
+        if let Some(etag) = headers.get(ETAG) {
+            validators.insert(IF_NONE_MATCH, etag.clone());
+        }
+        if let Some(last_modified) = headers.get(LAST_MODIFIED) {
+            validators.insert(IF_MODIFIED_SINCE, last_modified.clone());
+        }
+
+        let mut entries = self.lock();
+        let id = (key, url.to_string());
+        if validators.is_empty() {
+            entries.remove(&id);
+            return;
+        }
+        if entries.len() >= MAX_ENTRIES && !entries.contains_key(&id) {
+            if let Some(evicted) = entries.keys().next().cloned() {
+                entries.remove(&evicted);
+            }
+        }
+        entries.insert(
+            id,
+            Entry {
+                validators,
+                body: Arc::new(body.to_vec()),
+            },
+        );
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, HashMap<(usize, String), Entry>> {
+        self.entries.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
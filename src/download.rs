@@ -0,0 +1,272 @@
+//! Streaming image downloads
+
+use std::fmt;
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use bytes::Bytes;
+use futures_core::Stream;
+use tokio_util::sync::{CancellationToken, WaitForCancellationFutureOwned};
+
+use crate::client::{transport_error, PeerCat};
+use crate::error::{PeerCatError, Result};
+use crate::types::{GenerateResult, ThumbSize};
+
+type ChunkFuture = Pin<Box<dyn Future<Output = (reqwest::Response, Result<Option<Bytes>>)> + Send>>;
+
+/// An image being downloaded, yielded in chunks as they arrive
+///
+/// Implements [`Stream`], so large images never have to be held in memory
+/// all at once.
+pub struct ImageStream {
+    response: Option<reqwest::Response>,
+    pending: Option<ChunkFuture>,
+    content_length: Option<u64>,
+    cancelled: Option<Pin<Box<WaitForCancellationFutureOwned>>>,
+}
+
+impl ImageStream {
+    /// Size of the image in bytes, if the server reported it
+    pub fn content_length(&self) -> Option<u64> {
+        self.content_length
+    }
+
+    /// Stop the download once `token` is cancelled
+    ///
+    /// The next chunk is then [`PeerCatError::Cancelled`] and the connection
+    /// is released; the stream ends after that.
+    pub fn with_cancellation(mut self, token: CancellationToken) -> Self {
+        self.cancelled = Some(Box::pin(token.cancelled_owned()));
+        self
+    }
+
+    /// Wait for the next chunk of the image
+    pub async fn next(&mut self) -> Option<Result<Bytes>> {
+        std::future::poll_fn(|cx| Pin::new(&mut *self).poll_next(cx)).await
+    }
+
+    /// Read the rest of the image into memory
+    pub(crate) async fn read_all(mut self) -> Result<Bytes> {
+        let mut image = Vec::new();
+        while let Some(chunk) = self.next().await {
+            image.extend_from_slice(&chunk?);
+        }
+        Ok(image.into())
+    }
+
+    /// Write the rest of the image to `writer`, returning the number of bytes written
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use peercat::{GenerateParams, PeerCat};
+    ///
+    /// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+    /// let client = PeerCat::new("pcat_live_xxx")?;
+    /// let result = client.generate(GenerateParams::new("A mountain lake")).await?;
+    ///
+    /// let mut file = tokio::fs::File::create("lake.png").await?;
+    /// client.download_image_stream(&result).await?.copy_to(&mut file).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg(feature = "runtime-tokio")]
+    pub async fn copy_to<W>(mut self, writer: &mut W) -> Result<u64>
+    where
+        W: tokio::io::AsyncWrite + Unpin + ?Sized,
+    {
+        use tokio::io::AsyncWriteExt;
+
+        let mut written = 0;
+        while let Some(chunk) = self.next().await {
+            let chunk = chunk?;
+            writer.write_all(&chunk).await?;
+            written += chunk.len() as u64;
+        }
+        writer.flush().await?;
+        Ok(written)
+    }
+}
+
+impl Stream for ImageStream {
+    type Item = Result<Bytes>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        if let Some(cancelled) = self.cancelled.as_mut() {
+            if cancelled.as_mut().poll(cx).is_ready() {
+                self.cancelled = None;
+                let active = self.response.take().is_some() || self.pending.take().is_some();
+                if active {
+                    return Poll::Ready(Some(Err(PeerCatError::Cancelled)));
+                }
+            }
+        }
+
+        if self.pending.is_none() {
+            let Some(mut response) = self.response.take() else {
+                return Poll::Ready(None);
+            };
+            self.pending = Some(Box::pin(async move {
+                let chunk = response.chunk().await.map_err(transport_error);
+                (response, chunk)
+            }));
+        }
+
+        let pending = self.pending.as_mut().expect("chunk future was just set");
+        let (response, chunk) = match pending.as_mut().poll(cx) {
+            Poll::Ready(output) => output,
+            Poll::Pending => return Poll::Pending,
+        };
+        self.pending = None;
+
+        match chunk {
+            Ok(Some(chunk)) => {
+                self.response = Some(response);
+                Poll::Ready(Some(Ok(chunk)))
+            }
+            Ok(None) => Poll::Ready(None),
+            Err(e) => Poll::Ready(Some(Err(e))),
+        }
+    }
+}
+
+impl fmt::Debug for ImageStream {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ImageStream")
+            .field("content_length", &self.content_length)
+            .field(
+                "finished",
+                &(self.response.is_none() && self.pending.is_none()),
+            )
+            .finish()
+    }
+}
+
+impl PeerCat {
+    /// Download a generated image as a stream of chunks
+    ///
+    /// The image is fetched from the CDN without sending the API key.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use peercat::{GenerateParams, PeerCat};
+    ///
+    /// # async fn example() -> peercat::Result<()> {
+    /// let client = PeerCat::new("pcat_live_xxx")?;
+    /// let result = client.generate(GenerateParams::new("A 4K landscape")).await?;
+    ///
+    /// let mut image = client.download_image_stream(&result).await?;
+    /// while let Some(chunk) = image.next().await {
+    ///     println!("received {} bytes", chunk?.len());
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn download_image_stream(&self, result: &GenerateResult) -> Result<ImageStream> {
+        self.download_stream(&result.image_url).await
+    }
+
+    /// Download a thumbnail of a generated image
+    ///
+    /// Thumbnails are small, so the whole image is returned at once.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use peercat::{GenerateParams, PeerCat, ThumbSize};
+    ///
+    /// # async fn example() -> peercat::Result<()> {
+    /// let client = PeerCat::new("pcat_live_xxx")?;
+    /// let result = client.generate(GenerateParams::new("A lighthouse")).await?;
+    ///
+    /// let preview = client.download_thumbnail(&result, ThumbSize::Medium).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn download_thumbnail(
+        &self,
+        result: &GenerateResult,
+        size: ThumbSize,
+    ) -> Result<Bytes> {
+        self.download_stream(&result.thumbnail_url(size))
+            .await?
+            .read_all()
+            .await
+    }
+
+    /// Download a generated image through the configured IPFS gateway
+    ///
+    /// Falls back to the CDN URL if the image wasn't pinned to IPFS or the
+    /// gateway request fails.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use peercat::{GenerateParams, PeerCat, PeerCatConfig};
+    ///
+    /// # async fn example() -> peercat::Result<()> {
+    /// let client = PeerCat::with_config(
+    ///     PeerCatConfig::new("pcat_live_xxx").with_ipfs_gateway("https://gateway.pinata.cloud"),
+    /// )?;
+    /// let result = client.generate(GenerateParams::new("A desert at night")).await?;
+    ///
+    /// let mut image = client.download_from_ipfs(&result).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn download_from_ipfs(&self, result: &GenerateResult) -> Result<ImageStream> {
+        let url = self.ipfs_url(result);
+        if url != result.image_url {
+            if let Ok(stream) = self.download_stream(&url).await {
+                return Ok(stream);
+            }
+        }
+        self.download_stream(&result.image_url).await
+    }
+
+    /// Start downloading a file from a URL
+    pub(crate) async fn download_stream(&self, url: &str) -> Result<ImageStream> {
+        self.ensure_online()?;
+        let response = self
+            .http_client()
+            .get(url)
+            .send()
+            .await
+            .map_err(transport_error)?;
+
+        let status = response.status();
+        if !status.is_success() {
+            let message = format!("Failed to download {}", url);
+            return Err(if status == reqwest::StatusCode::NOT_FOUND {
+                PeerCatError::NotFound {
+                    message,
+                    code: "download_not_found".to_string(),
+                    param: None,
+                }
+            } else if status.is_server_error() {
+                PeerCatError::Server {
+                    message,
+                    code: "download_failed".to_string(),
+                    status: status.as_u16(),
+                }
+            } else {
+                PeerCatError::Unknown {
+                    status: status.as_u16(),
+                    error_type: "download_error".to_string(),
+                    code: "download_failed".to_string(),
+                    message,
+                    param: None,
+                }
+            });
+        }
+
+        Ok(ImageStream {
+            content_length: response.content_length(),
+            response: Some(response),
+            pending: None,
+            cancelled: None,
+        })
+    }
+}
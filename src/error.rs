@@ -1,6 +1,9 @@
 //! PeerCat SDK error types
 
+use std::sync::Arc;
+use std::time::Duration;
 use thiserror::Error;
+use web_time::{SystemTime, UNIX_EPOCH};
 
 /// Rate limit information from response headers
 #[derive(Debug, Clone, Default)]
@@ -11,7 +14,10 @@ pub struct RateLimitInfo {
     pub remaining: Option<u32>,
     /// Unix timestamp when the rate limit resets
     pub reset: Option<i64>,
-    /// Seconds to wait before retrying (from Retry-After header)
+    /// Seconds to wait before retrying (from Retry-After header).
+    ///
+    /// Both delay-seconds and HTTP-date forms are accepted; dates are
+    /// converted to seconds from the time the response was received.
     pub retry_after: Option<u64>,
 }
 
@@ -36,7 +42,7 @@ impl RateLimitInfo {
         let retry_after = headers
             .get("Retry-After")
             .and_then(|v| v.to_str().ok())
-            .and_then(|s| s.parse().ok());
+            .and_then(parse_retry_after);
 
         if limit.is_some() || remaining.is_some() || reset.is_some() || retry_after.is_some() {
             Some(Self {
@@ -51,6 +57,81 @@ impl RateLimitInfo {
     }
 }
 
+/// Parse a Retry-After value, which is either delay-seconds or an HTTP-date.
+///
+/// HTTP-dates are normalized to seconds from now; dates in the past yield 0.
+fn parse_retry_after(value: &str) -> Option<u64> {
+    let value = value.trim();
+    if let Ok(secs) = value.parse() {
+        return Some(secs);
+    }
+
+    // Compare as offsets from the epoch: `httpdate` returns a std `SystemTime`,
+    // but `std::time::SystemTime::now` is unavailable on wasm32
+    let date = httpdate::parse_http_date(value)
+        .ok()?
+        .duration_since(std::time::UNIX_EPOCH)
+        .ok()?;
+    let now = SystemTime::now().duration_since(UNIX_EPOCH).ok()?;
+    Some(date.saturating_sub(now).as_secs())
+}
+
+/// One problem found while validating a client configuration
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConfigProblem {
+    /// Configuration field the problem is with, e.g. `base_url`
+    pub field: &'static str,
+    /// What is wrong with it
+    pub message: String,
+}
+
+impl std::fmt::Display for ConfigProblem {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {}", self.field, self.message)
+    }
+}
+
+/// Every problem found while validating a client configuration
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub struct ConfigError {
+    problems: Vec<ConfigProblem>,
+}
+
+impl ConfigError {
+    pub(crate) fn new(problems: Vec<ConfigProblem>) -> Self {
+        Self { problems }
+    }
+
+    /// The problems found, in the order the fields were checked
+    pub fn problems(&self) -> &[ConfigProblem] {
+        &self.problems
+    }
+}
+
+impl std::fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (i, problem) in self.problems.iter().enumerate() {
+            if i > 0 {
+                f.write_str("; ")?;
+            }
+            write!(f, "{}", problem)?;
+        }
+        Ok(())
+    }
+}
+
+impl From<reqwest::Error> for PeerCatError {
+    fn from(e: reqwest::Error) -> Self {
+        PeerCatError::Network(Arc::new(e))
+    }
+}
+
+impl From<ConfigError> for PeerCatError {
+    fn from(e: ConfigError) -> Self {
+        Self::InvalidConfig(e.to_string())
+    }
+}
+
 /// All possible errors from the PeerCat SDK
 #[derive(Error, Debug)]
 pub enum PeerCatError {
@@ -58,6 +139,22 @@ pub enum PeerCatError {
     #[error("API key is required")]
     EmptyApiKey,
 
+    /// Invalid client configuration
+    #[error("Invalid configuration: {0}")]
+    InvalidConfig(String),
+
+    /// Operation refused by a client guardrail (e.g., withdrawals on a test client)
+    #[error("Operation not allowed: {operation} is disabled for this client")]
+    OperationNotAllowed { operation: String },
+
+    /// Paid generation refused because the session spend reached the client's budget
+    #[error("Budget exceeded: {spent} of {limit} credits spent this session")]
+    BudgetExceeded { spent: f64, limit: f64 },
+
+    /// Request parameter rejected by the SDK before sending (e.g., out of the model's range)
+    #[error("Invalid {field}: {message}")]
+    Validation { field: String, message: String },
+
     /// Authentication error (invalid or missing API key)
     #[error("Authentication error: {message}")]
     Authentication {
@@ -103,17 +200,47 @@ pub enum PeerCatError {
     },
 
     /// Network error
+    ///
+    /// Shared, so every caller of a coalesced request gets the same error.
     #[error("Network error: {0}")]
-    Network(#[from] reqwest::Error),
+    Network(#[source] Arc<reqwest::Error>),
 
     /// JSON serialization/deserialization error
     #[error("JSON error: {0}")]
     Json(#[from] serde_json::Error),
 
+    /// I/O error (e.g., while writing an export)
+    #[error("I/O error: {0}")]
+    Io(#[from] std::io::Error),
+
+    /// Wallet signing error (invalid keypair encoding)
+    #[error("Signer error: {0}")]
+    Signer(String),
+
+    /// Solana RPC or transaction error (e.g., a payment rejected or failed on-chain)
+    #[error("Solana error: {0}")]
+    Solana(String),
+
+    /// Webhook signature is missing, malformed, or doesn't match the payload
+    #[error("Invalid webhook signature")]
+    InvalidSignature,
+
+    /// Error from a custom transport service (e.g., a tower layer)
+    #[error("Transport error: {0}")]
+    Transport(Box<dyn std::error::Error + Send + Sync>),
+
     /// Request timeout
     #[error("Request timed out")]
     Timeout,
 
+    /// The caller cancelled the request through a [`CancellationToken`](crate::CancellationToken)
+    #[error("Request cancelled")]
+    Cancelled,
+
+    /// A wait helper gave up before the work finished
+    #[error("Deadline exceeded after {polls} polls ({elapsed:?})")]
+    DeadlineExceeded { elapsed: Duration, polls: u32 },
+
     /// Unknown API error
     #[error("API error ({status}): {message}")]
     Unknown {
@@ -195,12 +322,111 @@ impl PeerCatError {
         matches!(
             self,
             PeerCatError::Network(_)
+                | PeerCatError::Transport(_)
                 | PeerCatError::Timeout
                 | PeerCatError::Server { .. }
                 | PeerCatError::RateLimit { .. }
         )
     }
 
+    /// Copy the error for another caller of a shared request
+    ///
+    /// Transport, JSON and I/O errors can't be cloned, so the copy keeps
+    /// their variant, message and retryability but not their source.
+    pub(crate) fn duplicate(&self) -> Self {
+        use PeerCatError::*;
+        match self {
+            EmptyApiKey => EmptyApiKey,
+            InvalidConfig(message) => InvalidConfig(message.clone()),
+            OperationNotAllowed { operation } => OperationNotAllowed {
+                operation: operation.clone(),
+            },
+            BudgetExceeded { spent, limit } => BudgetExceeded {
+                spent: *spent,
+                limit: *limit,
+            },
+            Validation { field, message } => Validation {
+                field: field.clone(),
+                message: message.clone(),
+            },
+            Authentication {
+                message,
+                code,
+                param,
+            } => Authentication {
+                message: message.clone(),
+                code: code.clone(),
+                param: param.clone(),
+            },
+            InvalidRequest {
+                message,
+                code,
+                param,
+            } => InvalidRequest {
+                message: message.clone(),
+                code: code.clone(),
+                param: param.clone(),
+            },
+            InsufficientCredits { message, code } => InsufficientCredits {
+                message: message.clone(),
+                code: code.clone(),
+            },
+            RateLimit {
+                message,
+                code,
+                rate_limit_info,
+            } => RateLimit {
+                message: message.clone(),
+                code: code.clone(),
+                rate_limit_info: rate_limit_info.clone(),
+            },
+            NotFound {
+                message,
+                code,
+                param,
+            } => NotFound {
+                message: message.clone(),
+                code: code.clone(),
+                param: param.clone(),
+            },
+            Server {
+                message,
+                code,
+                status,
+            } => Server {
+                message: message.clone(),
+                code: code.clone(),
+                status: *status,
+            },
+            Network(e) => Network(Arc::clone(e)),
+            Transport(e) => Transport(e.to_string().into()),
+            Json(e) => Json(serde::de::Error::custom(e)),
+            Io(e) => Io(std::io::Error::new(e.kind(), e.to_string())),
+            Signer(message) => Signer(message.clone()),
+            Solana(message) => Solana(message.clone()),
+            InvalidSignature => InvalidSignature,
+            Timeout => Timeout,
+            Cancelled => Cancelled,
+            DeadlineExceeded { elapsed, polls } => DeadlineExceeded {
+                elapsed: *elapsed,
+                polls: *polls,
+            },
+            Unknown {
+                status,
+                error_type,
+                code,
+                message,
+                param,
+            } => Unknown {
+                status: *status,
+                error_type: error_type.clone(),
+                code: code.clone(),
+                message: message.clone(),
+                param: param.clone(),
+            },
+        }
+    }
+
     /// Returns the error code if available
     pub fn code(&self) -> Option<&str> {
         match self {
@@ -222,6 +448,7 @@ impl PeerCatError {
             PeerCatError::InvalidRequest { param, .. } => param.as_deref(),
             PeerCatError::NotFound { param, .. } => param.as_deref(),
             PeerCatError::Unknown { param, .. } => param.as_deref(),
+            PeerCatError::Validation { field, .. } => Some(field),
             _ => None,
         }
     }
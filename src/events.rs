@@ -0,0 +1,188 @@
+//! Broadcast stream of client lifecycle events
+
+use std::fmt;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use std::time::Duration;
+
+use futures_core::Stream;
+use tokio::sync::broadcast::{self, error::RecvError};
+use tokio_util::sync::ReusableBoxFuture;
+
+use crate::metrics::MetricEvent;
+
+/// Events buffered per subscriber before the oldest are dropped
+const CAPACITY: usize = 256;
+
+/// Something the client did, as seen by [`PeerCat::events`](crate::PeerCat::events)
+///
+/// `endpoint` is the request path without its query string.
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub enum ClientEvent {
+    /// A call is about to send its first attempt
+    RequestStarted { method: String, endpoint: String },
+    /// An attempt failed and is about to be retried
+    Retried {
+        method: String,
+        endpoint: String,
+        /// Number of the retry about to be sent, starting at 1
+        attempt: u32,
+        /// Backoff before the retry is sent
+        delay: Duration,
+    },
+    /// An attempt was rejected with HTTP 429
+    RateLimited {
+        method: String,
+        endpoint: String,
+        retry_after: Option<u64>,
+    },
+    /// An attempt took longer than the configured slow request threshold
+    SlowRequest {
+        method: String,
+        endpoint: String,
+        /// Which attempt of the call was slow, starting at 1
+        attempt: u32,
+        duration: Duration,
+    },
+    /// The retry budget ran out, so failures are returned without retrying
+    /// until it refills
+    CircuitOpened,
+    /// A successful response reported the credits it cost
+    CreditsSpent { endpoint: String, credits: f64 },
+    /// The client's configuration looks like a mistake, e.g. a live key
+    /// with a test base URL, or a key without a PeerCat prefix
+    ///
+    /// Sent once per client and its clones, before the first call made
+    /// while someone is subscribed.
+    EnvironmentMismatch { reason: String },
+    /// This subscriber fell behind and missed `skipped` events
+    Lagged { skipped: u64 },
+}
+
+impl ClientEvent {
+    /// The event matching a metric event, if there is one
+    fn from_metric(event: &MetricEvent<'_>) -> Option<Self> {
+        match *event {
+            MetricEvent::Retry {
+                method,
+                endpoint,
+                attempt,
+                delay,
+            } => Some(Self::Retried {
+                method: method.to_string(),
+                endpoint: endpoint.to_string(),
+                attempt,
+                delay,
+            }),
+            MetricEvent::RateLimited {
+                method,
+                endpoint,
+                retry_after,
+            } => Some(Self::RateLimited {
+                method: method.to_string(),
+                endpoint: endpoint.to_string(),
+                retry_after,
+            }),
+            MetricEvent::CreditsSpent { endpoint, credits } => Some(Self::CreditsSpent {
+                endpoint: endpoint.to_string(),
+                credits,
+            }),
+            MetricEvent::Request { .. } => None,
+        }
+    }
+}
+
+/// Sends events to every [`ClientEvents`] stream of a client and its clones
+#[derive(Debug, Clone)]
+pub(crate) struct EventBus {
+    sender: broadcast::Sender<ClientEvent>,
+}
+
+impl Default for EventBus {
+    fn default() -> Self {
+        Self {
+            sender: broadcast::channel(CAPACITY).0,
+        }
+    }
+}
+
+impl EventBus {
+    /// Returns true if anyone is subscribed
+    pub fn is_active(&self) -> bool {
+        self.sender.receiver_count() > 0
+    }
+
+    /// Send an event, only building it if anyone is subscribed
+    pub fn publish(&self, event: impl FnOnce() -> ClientEvent) {
+        if self.is_active() {
+            let _ = self.sender.send(event());
+        }
+    }
+
+    /// Send the event matching a metric event, if there is one
+    pub fn publish_metric(&self, event: &MetricEvent<'_>) {
+        if self.is_active() {
+            if let Some(event) = ClientEvent::from_metric(event) {
+                let _ = self.sender.send(event);
+            }
+        }
+    }
+
+    pub fn subscribe(&self) -> ClientEvents {
+        ClientEvents {
+            recv: ReusableBoxFuture::new(recv(self.sender.subscribe())),
+        }
+    }
+}
+
+type Received = (
+    Result<ClientEvent, RecvError>,
+    broadcast::Receiver<ClientEvent>,
+);
+
+async fn recv(mut rx: broadcast::Receiver<ClientEvent>) -> Received {
+    let result = rx.recv().await;
+    (result, rx)
+}
+
+/// A stream of [`ClientEvent`]s from [`PeerCat::events`](crate::PeerCat::events)
+///
+/// Only events that happen after the stream is created are emitted. If the
+/// stream isn't read quickly enough, older events are dropped and a
+/// [`ClientEvent::Lagged`] is emitted in their place. The stream ends when
+/// the client and all its clones are dropped.
+pub struct ClientEvents {
+    recv: ReusableBoxFuture<'static, Received>,
+}
+
+impl ClientEvents {
+    /// Wait for the next event
+    pub async fn next(&mut self) -> Option<ClientEvent> {
+        std::future::poll_fn(|cx| Pin::new(&mut *self).poll_next(cx)).await
+    }
+}
+
+impl Stream for ClientEvents {
+    type Item = ClientEvent;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let (result, rx) = match self.recv.poll(cx) {
+            Poll::Ready(output) => output,
+            Poll::Pending => return Poll::Pending,
+        };
+        self.recv.set(recv(rx));
+
+        match result {
+            Ok(event) => Poll::Ready(Some(event)),
+            Err(RecvError::Lagged(skipped)) => Poll::Ready(Some(ClientEvent::Lagged { skipped })),
+            Err(RecvError::Closed) => Poll::Ready(None),
+        }
+    }
+}
+
+impl fmt::Debug for ClientEvents {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ClientEvents").finish_non_exhaustive()
+    }
+}
@@ -0,0 +1,439 @@
+//! History export to CSV and JSON Lines, and bulk image export
+//!
+//! Enabled with the `export` feature.
+//!
+//! ```no_run
+//! use peercat::{PeerCat, HistoryParams};
+//! use peercat::export::Format;
+//!
+//! # async fn example() -> peercat::Result<()> {
+//! let client = PeerCat::new("pcat_live_xxx")?;
+//!
+//! let file = tokio::fs::File::create("usage.csv").await?;
+//! let rows = client
+//!     .export_history(HistoryParams::new(), Format::Csv, file)
+//!     .await?;
+//!
+//! println!("Exported {} records", rows);
+//! # Ok(())
+//! # }
+//! ```
+
+use std::io::Write;
+
+use crate::client::PeerCat;
+use crate::error::Result;
+use crate::types::{format_timestamp, HistoryItem, HistoryParams, HistoryStatus};
+
+#[cfg(all(feature = "runtime-tokio", not(target_arch = "wasm32")))]
+pub use images::{ExportOptions, ImageManifest};
+
+/// Page size used when paging through history for export
+const EXPORT_PAGE_SIZE: u32 = 100;
+
+/// Column order for CSV exports. This order is stable across releases.
+const CSV_COLUMNS: [&str; 8] = [
+    "id",
+    "endpoint",
+    "model",
+    "credits_used",
+    "request_id",
+    "status",
+    "created_at",
+    "completed_at",
+];
+
+/// Output format for history exports
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    /// Comma-separated values with a header row
+    Csv,
+    /// One JSON object per line, using the API's field names
+    Jsonl,
+}
+
+impl PeerCat {
+    /// Export all usage history matching `params` to `writer`
+    ///
+    /// Pages through the history endpoint starting at `params.offset` until
+    /// no more records remain, writing each page as it arrives. `params.limit`
+    /// sets the page size rather than capping the export, so every matching
+    /// record is written. Returns the number of records written.
+    pub async fn export_history<W>(
+        &self,
+        params: HistoryParams,
+        format: Format,
+        mut writer: W,
+    ) -> Result<usize>
+    where
+        W: tokio::io::AsyncWrite + Unpin,
+    {
+        use tokio::io::AsyncWriteExt;
+
+        let mut export = HistoryExport::new(params, format);
+        while let Some(chunk) = export.next_chunk(self).await? {
+            writer.write_all(&chunk).await?;
+        }
+        writer.flush().await?;
+        Ok(export.written)
+    }
+}
+
+/// Progress through a history export, formatting a page at a time
+pub(crate) struct HistoryExport {
+    params: HistoryParams,
+    format: Format,
+    offset: u32,
+    header_written: bool,
+    done: bool,
+    /// Number of records formatted so far
+    pub(crate) written: usize,
+}
+
+impl HistoryExport {
+    pub(crate) fn new(mut params: HistoryParams, format: Format) -> Self {
+        if params.limit.is_none() {
+            params.limit = Some(EXPORT_PAGE_SIZE);
+        }
+        Self {
+            offset: params.offset.unwrap_or(0),
+            params,
+            format,
+            header_written: false,
+            done: false,
+            written: 0,
+        }
+    }
+
+    /// Fetch and format the next page, or `None` once every record is written
+    pub(crate) async fn next_chunk(&mut self, client: &PeerCat) -> Result<Option<Vec<u8>>> {
+        if self.done {
+            return Ok(None);
+        }
+
+        let mut chunk = Vec::new();
+        if self.format == Format::Csv && !self.header_written {
+            writeln!(chunk, "{}", CSV_COLUMNS.join(","))?;
+        }
+        self.header_written = true;
+
+        let page = client
+            .get_history(self.params.clone().with_offset(self.offset))
+            .await?;
+        for item in &page.items {
+            match self.format {
+                Format::Csv => write_csv_row(&mut chunk, item)?,
+                Format::Jsonl => {
+                    serde_json::to_writer(&mut chunk, item)?;
+                    writeln!(chunk)?;
+                }
+            }
+        }
+
+        self.written += page.items.len();
+        self.offset += page.items.len() as u32;
+        self.done = !page.pagination.has_more || page.items.is_empty();
+        Ok(Some(chunk))
+    }
+}
+
+fn write_csv_row<W: Write>(writer: &mut W, item: &HistoryItem) -> std::io::Result<()> {
+    let status = match item.status {
+        HistoryStatus::Pending => "pending",
+        HistoryStatus::Completed => "completed",
+        HistoryStatus::Refunded => "refunded",
+    };
+
+    let fields = [
+        csv_escape(&item.id),
+        csv_escape(&item.endpoint),
+        csv_escape(item.model.as_deref().unwrap_or("")),
+        item.credits_used.to_string(),
+        csv_escape(item.request_id.as_deref().unwrap_or("")),
+        status.to_string(),
+        csv_escape(&format_timestamp(&item.created_at)),
+        csv_escape(
+            &item
+                .completed_at
+                .as_ref()
+                .map(format_timestamp)
+                .unwrap_or_default(),
+        ),
+    ];
+
+    writeln!(writer, "{}", fields.join(","))
+}
+
+/// Quote a CSV field if it contains a delimiter, quote, or newline
+fn csv_escape(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+#[cfg(all(feature = "runtime-tokio", not(target_arch = "wasm32")))]
+mod images {
+    use std::collections::{BTreeMap, HashMap};
+    use std::path::{Path, PathBuf};
+
+    use serde::{Deserialize, Serialize};
+    use tokio::task::JoinSet;
+
+    use super::EXPORT_PAGE_SIZE;
+    use crate::client::{backoff_delay, PeerCat};
+    use crate::error::{PeerCatError, Result};
+    use crate::rt;
+    use crate::types::{HistoryParams, HistoryStatus};
+
+    /// Name of the manifest written to the export directory
+    const MANIFEST_FILE: &str = "manifest.json";
+
+    /// Options for [`PeerCat::export_images`]
+    #[derive(Debug, Clone)]
+    pub struct ExportOptions {
+        concurrency: usize,
+        max_retries: u32,
+        overwrite: bool,
+    }
+
+    impl ExportOptions {
+        /// Create options with the defaults
+        pub fn new() -> Self {
+            Self {
+                concurrency: 4,
+                max_retries: 3,
+                overwrite: false,
+            }
+        }
+
+        /// Set the maximum number of downloads in flight (default: 4)
+        pub fn with_concurrency(mut self, concurrency: usize) -> Self {
+            self.concurrency = concurrency.max(1);
+            self
+        }
+
+        /// Set how many times a failed download is retried (default: 3)
+        pub fn with_max_retries(mut self, max_retries: u32) -> Self {
+            self.max_retries = max_retries;
+            self
+        }
+
+        /// Download images again even if their file already exists
+        ///
+        /// By default existing files are kept, so an interrupted export can be
+        /// resumed by running it again.
+        pub fn with_overwrite(mut self) -> Self {
+            self.overwrite = true;
+            self
+        }
+    }
+
+    impl Default for ExportOptions {
+        fn default() -> Self {
+            Self::new()
+        }
+    }
+
+    /// Result of an image export, also written to `manifest.json`
+    #[derive(Debug, Clone, Default, Serialize, Deserialize)]
+    pub struct ImageManifest {
+        /// Exported images by generation ID, as paths relative to the export directory
+        pub images: BTreeMap<String, String>,
+        /// Generations that couldn't be downloaded, by generation ID, with the error
+        pub failed: BTreeMap<String, String>,
+    }
+
+    impl PeerCat {
+        /// Download every completed generation in the history matching `params` to `dest_dir`
+        ///
+        /// Pages through the history endpoint, downloads images concurrently
+        /// (retrying transient failures), and writes a `manifest.json` mapping
+        /// generation IDs to files. A failed download is recorded in the
+        /// manifest rather than ending the export; only history and file
+        /// system errors are returned.
+        ///
+        /// # Example
+        ///
+        /// ```no_run
+        /// use peercat::export::ExportOptions;
+        /// use peercat::{HistoryParams, PeerCat};
+        ///
+        /// # async fn example() -> peercat::Result<()> {
+        /// let client = PeerCat::new("pcat_live_xxx")?;
+        ///
+        /// let manifest = client
+        ///     .export_images(HistoryParams::new(), "backup", ExportOptions::new())
+        ///     .await?;
+        ///
+        /// println!("{} exported, {} failed", manifest.images.len(), manifest.failed.len());
+        /// # Ok(())
+        /// # }
+        /// ```
+        pub async fn export_images(
+            &self,
+            params: HistoryParams,
+            dest_dir: impl AsRef<Path>,
+            options: ExportOptions,
+        ) -> Result<ImageManifest> {
+            let dest_dir = dest_dir.as_ref();
+            tokio::fs::create_dir_all(dest_dir).await?;
+
+            let mut params = params;
+            if params.limit.is_none() {
+                params.limit = Some(EXPORT_PAGE_SIZE);
+            }
+            let mut offset = params.offset.unwrap_or(0);
+            let mut manifest = ImageManifest::default();
+            let mut pending = Vec::new();
+            // URL each file name was claimed for, so no two images share a file
+            let mut claimed = HashMap::from([(MANIFEST_FILE.to_string(), String::new())]);
+
+            loop {
+                let page = self.get_history(params.clone().with_offset(offset)).await?;
+                for item in &page.items {
+                    if item.status != HistoryStatus::Completed {
+                        continue;
+                    }
+                    if let Some(url) = &item.image_url {
+                        let id = item.request_id.clone().unwrap_or_else(|| item.id.clone());
+                        let file = file_name(&id, url);
+                        match claimed.get(&file) {
+                            // The same image listed twice
+                            Some(claimed_url) if claimed_url == url => {}
+                            Some(_) => {
+                                let error = format!("{} is already used by another image", file);
+                                manifest.failed.insert(id, error);
+                            }
+                            None => {
+                                claimed.insert(file.clone(), url.clone());
+                                pending.push((id, url.clone(), file));
+                            }
+                        }
+                    }
+                }
+
+                offset += page.items.len() as u32;
+                if !page.pagination.has_more || page.items.is_empty() {
+                    break;
+                }
+            }
+
+            let mut tasks = JoinSet::new();
+            let mut pending = pending.into_iter();
+
+            loop {
+                while tasks.len() < options.concurrency {
+                    let Some((id, url, file)) = pending.next() else {
+                        break;
+                    };
+                    let path = dest_dir.join(&file);
+                    if !options.overwrite && tokio::fs::try_exists(&path).await? {
+                        manifest.images.insert(id, file);
+                        continue;
+                    }
+
+                    let client = self.clone();
+                    let max_retries = options.max_retries;
+                    tasks.spawn(async move {
+                        let result = download_to(&client, &url, &path, max_retries).await;
+                        (id, file, result)
+                    });
+                }
+
+                let Some(joined) = tasks.join_next().await else {
+                    break;
+                };
+                match joined {
+                    Ok((id, file, Ok(()))) => {
+                        manifest.images.insert(id, file);
+                    }
+                    Ok((_, _, Err(error @ PeerCatError::Io(_)))) => {
+                        // The destination isn't writable, so the remaining downloads would fail too
+                        tasks.abort_all();
+                        return Err(error);
+                    }
+                    Ok((id, _, Err(error))) => {
+                        manifest.failed.insert(id, error.to_string());
+                    }
+                    Err(error) => std::panic::resume_unwind(error.into_panic()),
+                }
+            }
+
+            let json = serde_json::to_vec_pretty(&manifest)?;
+            tokio::fs::write(dest_dir.join(MANIFEST_FILE), json).await?;
+            Ok(manifest)
+        }
+    }
+
+    /// Download an image to `path`, retrying transient failures
+    ///
+    /// The image is written to a temporary file first so an interrupted
+    /// download never leaves a partial image behind.
+    async fn download_to(client: &PeerCat, url: &str, path: &Path, max_retries: u32) -> Result<()> {
+        let partial = partial_path(path);
+        let mut attempt = 0;
+
+        loop {
+            let result = async {
+                let stream = client.download_stream(url).await?;
+                let mut file = tokio::fs::File::create(&partial).await?;
+                stream.copy_to(&mut file).await
+            }
+            .await;
+
+            match result {
+                Ok(_) => {
+                    tokio::fs::rename(&partial, path).await?;
+                    return Ok(());
+                }
+                Err(error) if error.is_retryable() && attempt < max_retries => {
+                    rt::sleep(backoff_delay(attempt, error.retry_after())).await;
+                    attempt += 1;
+                }
+                Err(error) => {
+                    let _ = tokio::fs::remove_file(&partial).await;
+                    return Err(error);
+                }
+            }
+        }
+    }
+
+    fn partial_path(path: &Path) -> PathBuf {
+        let mut name = path.file_name().unwrap_or_default().to_os_string();
+        name.push(".part");
+        path.with_file_name(name)
+    }
+
+    /// File name for an image, keeping the extension from its URL
+    ///
+    /// Bytes of the ID other than lowercase letters, digits, `-` and `_` are
+    /// percent-encoded with lowercase hex, e.g. `Gen/1` becomes `%47en%2f1`.
+    /// The encoding is reversible and never produces uppercase letters, so
+    /// distinct IDs get distinct names even on case-insensitive file systems.
+    fn file_name(id: &str, url: &str) -> String {
+        let mut stem = String::with_capacity(id.len());
+        for byte in id.bytes() {
+            match byte {
+                b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' => stem.push(char::from(byte)),
+                _ => stem.push_str(&format!("%{:02x}", byte)),
+            }
+        }
+
+        let path = url.split(['?', '#']).next().unwrap_or_default();
+        let extension = path
+            .rsplit('/')
+            .next()
+            .and_then(|name| name.rsplit_once('.'))
+            .map(|(_, extension)| extension)
+            .filter(|extension| {
+                !extension.is_empty()
+                    && extension.len() <= 5
+                    && extension.chars().all(|c| c.is_ascii_alphanumeric())
+            })
+            .unwrap_or("png");
+
+        format!("{}.{}", stem, extension.to_ascii_lowercase())
+    }
+}
@@ -0,0 +1,249 @@
+//! Ready-made response values for downstream tests
+//!
+//! Requires the `test-util` feature. Fixtures refer to each other where it
+//! makes sense, e.g. [`HistoryItem::fixture`] records
+//! [`GenerateResult::fixture`].
+
+use std::collections::HashMap;
+
+use crate::types::{
+    ApiKey, Balance, GenerateResult, GenerateUsage, GenerationMetadata, GenerationMode,
+    HistoryItem, HistoryStatus, KeyEnvironment, Model, OnChainGenerationStatus, OnChainStatus,
+    PromptSubmission, RequiredAmount, SafetyCheck, SubmissionStatus, Timestamp, WithdrawalResult,
+    WithdrawalStatus,
+};
+
+/// Generation ID shared by the fixtures that refer to one
+const GENERATION_ID: &str = "gen_fixture";
+
+/// Model used by the fixtures
+const MODEL: &str = "stable-diffusion-xl";
+
+/// Price of [`MODEL`] in USD
+const PRICE_USD: f64 = 0.28;
+
+/// Transaction signature shared by the on-chain fixtures
+const TX_SIGNATURE: &str =
+    "5VERv8NMvzbJMEkV8xnrLkEaWRtSz9CosKDYjCJjBRnbJLgp8uirBgmQpjKhoR4tjF3ZpRzrFmBV6UjKdiSZkQUW";
+
+/// Treasury address used for payments
+const TREASURY: &str = "9JKi6Tr7JdsTJw1zNedF5vML9GpPnjHD9DWuZq1oE6nV";
+
+/// Wallet address used for withdrawals
+const WALLET: &str = "7xKXtg2CW87d97TXJSDpbD5jBkheTqA83TZRuJosgAsU";
+
+/// IPFS hash of the fixture image
+const IPFS_HASH: &str = "QmYwAPJzv5CZsnA625s3Xf2nemtYgPpHdWEz79ojWnPbdG";
+
+/// When every fixture happened
+fn timestamp() -> Timestamp {
+    "2024-01-01T00:00:00Z"
+        .parse()
+        .expect("fixture timestamp is valid")
+}
+
+fn image_url() -> String {
+    format!("https://cdn.peerc.at/images/{}.png", GENERATION_ID)
+}
+
+impl GenerateResult {
+    /// A completed production generation with `stable-diffusion-xl`
+    pub fn fixture() -> Self {
+        Self {
+            id: GENERATION_ID.to_string(),
+            image_url: image_url(),
+            ipfs_hash: Some(IPFS_HASH.to_string()),
+            model: MODEL.to_string(),
+            mode: GenerationMode::Production,
+            usage: GenerateUsage {
+                credits_used: PRICE_USD,
+                balance_remaining: 9.72,
+            },
+            private: false,
+            safety_level: None,
+            seed: Some(42),
+            prompt: Some("A sunset over the ocean".to_string()),
+            options: None,
+            prompts: Vec::new(),
+            quality: None,
+            adapters: Vec::new(),
+            controlnet: None,
+            auto_translate: None,
+            source_language: None,
+            expected_file_size: None,
+            translated_prompt: None,
+        }
+    }
+}
+
+impl GenerationMetadata {
+    /// Metadata for [`GenerateResult::fixture`]
+    pub fn fixture() -> Self {
+        Self {
+            id: GENERATION_ID.to_string(),
+            model: MODEL.to_string(),
+            prompt: "A sunset over the ocean".to_string(),
+            seed: 42,
+            width: 1024,
+            height: 1024,
+            inference_ms: 2_400,
+            total_ms: 3_100,
+            safety: SafetyCheck {
+                flagged: false,
+                categories: Vec::new(),
+            },
+            created_at: timestamp(),
+        }
+    }
+}
+
+impl Model {
+    /// `stable-diffusion-xl`, without reported capabilities
+    pub fn fixture() -> Self {
+        Self {
+            id: MODEL.to_string(),
+            name: "Stable Diffusion XL".to_string(),
+            description: "High quality image generation".to_string(),
+            provider: "Stability AI".to_string(),
+            max_prompt_length: 2000,
+            output_format: "png".to_string(),
+            output_resolution: "1024x1024".to_string(),
+            price_usd: PRICE_USD,
+            capabilities: None,
+        }
+    }
+}
+
+impl Balance {
+    /// An account with 10 credits left after one generation
+    pub fn fixture() -> Self {
+        Self {
+            credits: 10.0,
+            total_deposited: 10.28,
+            total_spent: PRICE_USD,
+            total_withdrawn: 0.0,
+            total_generated: 1,
+        }
+    }
+}
+
+impl HistoryItem {
+    /// The history entry for [`GenerateResult::fixture`]
+    pub fn fixture() -> Self {
+        Self {
+            id: "usage_fixture".to_string(),
+            endpoint: "/v1/generate".to_string(),
+            model: Some(MODEL.to_string()),
+            credits_used: PRICE_USD,
+            request_id: Some(GENERATION_ID.to_string()),
+            status: HistoryStatus::Completed,
+            created_at: timestamp(),
+            completed_at: Some(timestamp()),
+            image_url: Some(image_url()),
+            tags: Vec::new(),
+        }
+    }
+}
+
+impl WithdrawalResult {
+    /// A completed withdrawal of 5 credits
+    pub fn fixture() -> Self {
+        Self {
+            withdrawal_id: "wd_fixture".to_string(),
+            status: WithdrawalStatus::Completed,
+            amount_usd: 5.0,
+            amount_sol: Some(0.027),
+            destination_wallet: WALLET.to_string(),
+            tx_signature: Some(TX_SIGNATURE.to_string()),
+            created_at: timestamp(),
+            completed_at: Some(timestamp()),
+            error: None,
+        }
+    }
+}
+
+impl ApiKey {
+    /// An unrestricted live key that has never been used
+    pub fn fixture() -> Self {
+        Self {
+            id: "key_fixture".to_string(),
+            name: Some("Fixture Key".to_string()),
+            key_prefix: "pcat_live_fix".to_string(),
+            environment: KeyEnvironment::Live,
+            rate_limit_tier: "standard".to_string(),
+            created_at: timestamp(),
+            last_used_at: None,
+            revoked: false,
+            scopes: Vec::new(),
+            expires_at: None,
+            ip_allowlist: Vec::new(),
+        }
+    }
+}
+
+impl PromptSubmission {
+    /// A submission awaiting payment for a `stable-diffusion-xl` generation
+    pub fn fixture() -> Self {
+        let instructions = HashMap::from([
+            ("1".to_string(), "Send SOL to payment address".to_string()),
+            ("2".to_string(), "Include memo in transaction".to_string()),
+        ]);
+        Self {
+            submission_id: "sub_fixture".to_string(),
+            prompt_hash: IPFS_HASH.to_string(),
+            payment_address: TREASURY.to_string(),
+            required_amount: RequiredAmount {
+                sol: 0.0015,
+                lamports: 1_500_000,
+                usd: PRICE_USD,
+            },
+            memo: format!("PCAT:v1:{}:sub_fixture", MODEL),
+            model: MODEL.to_string(),
+            slippage_tolerance: 0.05,
+            expires_at: timestamp(),
+            instructions,
+            status: SubmissionStatus::Open,
+            swap_route: None,
+        }
+    }
+}
+
+impl OnChainGenerationStatus {
+    /// A paid generation that is still being processed
+    pub fn fixture_processing() -> Self {
+        Self {
+            tx_signature: TX_SIGNATURE.to_string(),
+            status: OnChainStatus::Processing,
+            model: Some(MODEL.to_string()),
+            created_at: Some(timestamp()),
+            image_url: None,
+            ipfs_hash: None,
+            completed_at: None,
+            error: None,
+            message: Some("Generation in progress".to_string()),
+        }
+    }
+
+    /// A finished generation with its image
+    pub fn fixture_completed() -> Self {
+        Self {
+            status: OnChainStatus::Completed,
+            image_url: Some(image_url()),
+            ipfs_hash: Some(IPFS_HASH.to_string()),
+            completed_at: Some(timestamp()),
+            message: None,
+            ..Self::fixture_processing()
+        }
+    }
+
+    /// A generation that failed
+    pub fn fixture_failed() -> Self {
+        Self {
+            status: OnChainStatus::Failed,
+            error: Some("Model unavailable".to_string()),
+            completed_at: Some(timestamp()),
+            message: None,
+            ..Self::fixture_processing()
+        }
+    }
+}
@@ -0,0 +1,223 @@
+//! Multi-key failover and routing
+
+use std::fmt;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use web_time::Instant;
+
+use crate::client::retry_after_delay;
+use crate::secret::SecretString;
+
+/// Default time a key is skipped after a rate limit without a Retry-After value
+const DEFAULT_RATE_LIMIT_COOLDOWN: Duration = Duration::from_secs(60);
+
+/// Current state of a key in a [`KeyPool`], as seen by a [`KeySelector`]
+#[derive(Debug, Clone)]
+pub struct KeyStatus {
+    /// Position of the key in the pool
+    pub index: usize,
+    /// The key is rate limited until this instant
+    pub rate_limited_until: Option<Instant>,
+    /// When the key was last rate limited
+    pub last_rate_limited: Option<Instant>,
+    /// The key was rejected by the API (revoked or invalid)
+    pub revoked: bool,
+}
+
+impl KeyStatus {
+    /// Returns true if the key can be used right now
+    pub fn is_available(&self, now: Instant) -> bool {
+        !self.revoked && self.rate_limited_until.map_or(true, |until| until <= now)
+    }
+}
+
+/// Strategy for choosing which key in a [`KeyPool`] to use for a request
+///
+/// Implementations return the index of the key to use, or `None` if no key
+/// is currently available. When `None` is returned, the pool falls back to
+/// the non-revoked key whose rate limit expires soonest.
+pub trait KeySelector: Send + Sync + fmt::Debug {
+    /// Choose a key for the next request
+    fn select(&self, keys: &[KeyStatus], now: Instant) -> Option<usize>;
+}
+
+/// Rotate through available keys in order
+#[derive(Debug, Default)]
+pub struct RoundRobin {
+    next: AtomicUsize,
+}
+
+impl KeySelector for RoundRobin {
+    fn select(&self, keys: &[KeyStatus], now: Instant) -> Option<usize> {
+        let start = self.next.fetch_add(1, Ordering::Relaxed);
+        (0..keys.len())
+            .map(|offset| (start + offset) % keys.len())
+            .find(|&i| keys[i].is_available(now))
+    }
+}
+
+/// Prefer the available key that was rate limited longest ago (or never)
+#[derive(Debug, Default)]
+pub struct LeastRecentlyLimited;
+
+impl KeySelector for LeastRecentlyLimited {
+    fn select(&self, keys: &[KeyStatus], now: Instant) -> Option<usize> {
+        keys.iter()
+            .filter(|key| key.is_available(now))
+            .min_by_key(|key| key.last_rate_limited)
+            .map(|key| key.index)
+    }
+}
+
+/// A set of API keys the client rotates between
+///
+/// When a key is rate limited it is skipped until its limit resets; when a
+/// key is rejected as invalid or revoked it is skipped for the lifetime of
+/// the client.
+///
+/// # Example
+///
+/// ```no_run
+/// use peercat::{KeyPool, LeastRecentlyLimited, PeerCat, PeerCatConfig};
+///
+/// let pool = KeyPool::new(["pcat_live_aaa", "pcat_live_bbb", "pcat_live_ccc"])
+///     .with_selector(LeastRecentlyLimited);
+///
+/// let client = PeerCat::with_config(PeerCatConfig::from_key_pool(pool))?;
+/// # Ok::<(), peercat::PeerCatError>(())
+/// ```
+#[derive(Clone)]
+pub struct KeyPool {
+    keys: Arc<[SecretString]>,
+    status: Arc<Mutex<Vec<KeyStatus>>>,
+    selector: Arc<dyn KeySelector>,
+}
+
+/// A key chosen from a [`KeyPool`] for one request attempt
+#[derive(Debug, Clone)]
+pub(crate) struct SelectedKey {
+    pub index: usize,
+    pub key: SecretString,
+}
+
+impl KeyPool {
+    /// Create a pool from a list of keys, using round-robin selection
+    pub fn new<I, K>(keys: I) -> Self
+    where
+        I: IntoIterator<Item = K>,
+        K: Into<String>,
+    {
+        let keys: Arc<[SecretString]> = keys.into_iter().map(SecretString::new).collect();
+        let status = (0..keys.len())
+            .map(|index| KeyStatus {
+                index,
+                rate_limited_until: None,
+                last_rate_limited: None,
+                revoked: false,
+            })
+            .collect();
+
+        Self {
+            keys,
+            status: Arc::new(Mutex::new(status)),
+            selector: Arc::new(RoundRobin::default()),
+        }
+    }
+
+    /// Set the key selection strategy
+    pub fn with_selector(mut self, selector: impl KeySelector + 'static) -> Self {
+        self.selector = Arc::new(selector);
+        self
+    }
+
+    /// Number of keys in the pool
+    pub fn len(&self) -> usize {
+        self.keys.len()
+    }
+
+    /// Returns true if the pool has no keys
+    pub fn is_empty(&self) -> bool {
+        self.keys.is_empty()
+    }
+
+    /// Snapshot of the current state of every key
+    pub fn status(&self) -> Vec<KeyStatus> {
+        self.lock().clone()
+    }
+
+    /// The first key in the pool
+    pub(crate) fn primary(&self) -> Option<&SecretString> {
+        self.keys.first()
+    }
+
+    pub(crate) fn keys(&self) -> &[SecretString] {
+        &self.keys
+    }
+
+    /// Choose a key for the next request attempt
+    pub(crate) fn select(&self) -> SelectedKey {
+        let now = Instant::now();
+        let status = self.lock();
+
+        let index = self
+            .selector
+            .select(&status, now)
+            .filter(|&i| i < self.keys.len())
+            .or_else(|| {
+                status
+                    .iter()
+                    .filter(|key| !key.revoked)
+                    .min_by_key(|key| key.rate_limited_until)
+                    .map(|key| key.index)
+            })
+            .unwrap_or(0);
+
+        SelectedKey {
+            index,
+            key: self.keys[index].clone(),
+        }
+    }
+
+    /// Returns true if any key other than `index` can be used right now
+    pub(crate) fn has_alternative(&self, index: usize) -> bool {
+        let now = Instant::now();
+        self.lock()
+            .iter()
+            .any(|key| key.index != index && key.is_available(now))
+    }
+
+    /// Record that a key hit a rate limit
+    pub(crate) fn mark_rate_limited(&self, index: usize, retry_after: Option<u64>) {
+        let now = Instant::now();
+        let cooldown = retry_after
+            .map(retry_after_delay)
+            .unwrap_or(DEFAULT_RATE_LIMIT_COOLDOWN);
+
+        if let Some(key) = self.lock().get_mut(index) {
+            key.rate_limited_until = Some(now + cooldown);
+            key.last_rate_limited = Some(now);
+        }
+    }
+
+    /// Record that a key was rejected as invalid or revoked
+    pub(crate) fn mark_revoked(&self, index: usize) {
+        if let Some(key) = self.lock().get_mut(index) {
+            key.revoked = true;
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Vec<KeyStatus>> {
+        self.status.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+impl fmt::Debug for KeyPool {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("KeyPool")
+            .field("keys", &self.keys.len())
+            .field("selector", &self.selector)
+            .finish()
+    }
+}
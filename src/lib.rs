@@ -59,6 +59,10 @@
 //! # }
 //! ```
 //!
+//! [`PeerCat::offline_demo`] goes further: generations return deterministic
+//! placeholder results built locally, and nothing is sent over the network,
+//! for unit tests and demos without connectivity.
+//!
 //! ## Error Handling
 //!
 //! ```no_run
@@ -88,6 +92,39 @@
 //! # }
 //! ```
 //!
+//! ## Cancellation
+//!
+//! Every client future is cancel-safe: dropping it (for example when a
+//! `tokio::select!` branch loses or a timeout fires) aborts the HTTP request
+//! and frees its connection. A request that already reached the server may
+//! still be processed there, so a dropped `generate` call can still be
+//! charged. When the leader of a coalesced GET is dropped, the next waiting
+//! caller sends the request itself.
+//!
+//! Wait helpers and image downloads also take a [`CancellationToken`], so
+//! work can be stopped from elsewhere without holding on to the future:
+//!
+//! ```no_run
+//! use peercat::{CancellationToken, PeerCat, PeerCatError, PollOptions};
+//!
+//! # async fn example() -> peercat::Result<()> {
+//! let client = PeerCat::new("pcat_live_xxx")?;
+//! let token = CancellationToken::new();
+//!
+//! // Call token.cancel() from anywhere, e.g. a shutdown handler
+//! let options = PollOptions::new().with_cancellation(token.clone());
+//! match client.wait_for_generation("use_123", options).await {
+//!     Ok(item) => println!("{:?}", item.status),
+//!     Err(PeerCatError::Cancelled) => println!("Stopped waiting"),
+//!     Err(e) => return Err(e),
+//! }
+//!
+//! // Any other call can be cancelled the same way
+//! let balance = token.run_until_cancelled(client.get_balance()).await;
+//! # Ok(())
+//! # }
+//! ```
+//!
 //! ## On-Chain Payments
 //!
 //! For direct SOL payments without credits:
@@ -116,47 +153,192 @@
 //! # }
 //! ```
 
+#[cfg(not(target_arch = "wasm32"))]
+mod api;
+#[cfg(feature = "proptest")]
+mod arbitrary;
+mod builder;
+#[cfg(all(feature = "runtime-tokio", not(target_arch = "wasm32")))]
+mod bulk;
+mod canonical;
+mod catalog;
 mod client;
+mod conditional;
+#[cfg(not(target_arch = "wasm32"))]
+mod download;
 mod error;
+mod events;
+#[cfg(feature = "test-util")]
+mod fixture;
+mod key_pool;
+mod metrics;
+mod offline;
+mod pacer;
+mod poll;
+#[cfg(any(feature = "audit", feature = "vcr"))]
+mod redact;
+mod report;
+mod retry_budget;
+mod rt;
+mod secret;
+mod single_flight;
+mod spans;
+mod spend;
+#[cfg(all(feature = "runtime-tokio", not(target_arch = "wasm32")))]
+mod stream;
 mod types;
+#[cfg(all(feature = "runtime-tokio", not(target_arch = "wasm32")))]
+mod watch;
+
+#[cfg(feature = "audit")]
+pub mod audit;
+#[cfg(all(feature = "blocking", not(target_arch = "wasm32")))]
+pub mod blocking;
+#[cfg(all(feature = "chaos", not(target_arch = "wasm32")))]
+pub mod chaos;
+#[cfg(feature = "export")]
+pub mod export;
+#[cfg(all(feature = "live-tests", not(target_arch = "wasm32")))]
+pub mod live;
+#[cfg(all(feature = "mock", not(target_arch = "wasm32")))]
+pub mod mock;
+#[cfg(all(feature = "tower", not(target_arch = "wasm32")))]
+pub mod service;
+#[cfg(feature = "signer")]
+pub mod signer;
+#[cfg(feature = "solana")]
+pub mod solana;
+#[cfg(all(feature = "testing", not(target_arch = "wasm32")))]
+pub mod testing;
+#[cfg(feature = "vcr")]
+pub mod vcr;
+#[cfg(feature = "webhook")]
+pub mod webhook;
 
 // Re-export main types
+#[cfg(not(target_arch = "wasm32"))]
+pub use api::{DynPeerCat, DynPeerCatApi, PeerCatApi};
+pub use builder::PeerCatBuilder;
+#[cfg(all(feature = "runtime-tokio", not(target_arch = "wasm32")))]
+pub use bulk::{BulkController, BulkGenerator, BulkProgress, BulkReport};
+pub use canonical::CanonicalJson;
+pub use catalog::ModelCatalog;
 pub use client::PeerCat;
-pub use error::{PeerCatError, RateLimitInfo, Result};
+#[cfg(not(target_arch = "wasm32"))]
+pub use download::ImageStream;
+pub use error::{ConfigError, ConfigProblem, PeerCatError, RateLimitInfo, Result};
+pub use events::{ClientEvent, ClientEvents};
+pub use ipnet::IpNet;
+pub use key_pool::{KeyPool, KeySelector, KeyStatus, LeastRecentlyLimited, RoundRobin};
+#[cfg(feature = "prometheus")]
+pub use metrics::PrometheusMetrics;
+pub use metrics::{MetricEvent, PeerCatMetrics};
+pub use poll::PollOptions;
+#[cfg(feature = "sentry")]
+pub use report::SentryReporter;
+pub use report::{ErrorContext, ErrorReporter};
+pub use retry_budget::{RetryBudget, RetryBudgetStatus};
+pub use secret::SecretString;
+pub use spend::SessionSpend;
+#[cfg(all(feature = "runtime-tokio", not(target_arch = "wasm32")))]
+pub use stream::{OnChainStatusStream, PriceStream};
+pub use tokio_util::sync::CancellationToken;
 pub use types::{
+    // Timestamps
+    Timestamp,
     // Configuration
+    HttpVersion,
     PeerCatConfig,
+    WarmupOptions,
     // Models
+    AspectRatio,
+    KnownModel,
+    Lora,
+    LorasResponse,
     Model,
+    ModelCapabilities,
     ModelsResponse,
+    ParamRange,
+    Resolution,
     // Pricing
     ModelPrice,
     PriceResponse,
+    Lamports,
+    PriceGranularity,
+    PriceHistory,
+    PricePoint,
     // Generation
+    ControlImage,
+    ControlMode,
+    ControlNetParams,
     GenerateParams,
     GenerateResult,
     GenerateUsage,
+    GenerationDeletion,
+    GenerationMetadata,
     GenerationMode,
+    IpfsStatus,
+    LoraAdapter,
+    SafetyCheck,
+    SafetyLevel,
+    Sampler,
+    ShareLink,
+    ShareOptions,
+    SignedUrl,
+    ThumbSize,
+    UploadedImage,
+    ValidationIssue,
+    WeightedPrompt,
+    // Collections
+    Collection,
+    CollectionItem,
+    CollectionItemsResponse,
+    CollectionsResponse,
+    CreateCollectionParams,
+    // Presets
+    Preset,
+    PresetsResponse,
     // Account
     Balance,
+    DepositInstructions,
+    DepositState,
+    DepositStatus,
     HistoryItem,
     HistoryParams,
     HistoryResponse,
     HistoryStatus,
     Pagination,
+    WithdrawParams,
+    WithdrawalResult,
+    WithdrawalStatus,
+    // Usage Analytics
+    UsageBucket,
+    UsageGroupBy,
+    UsageSummary,
+    UsageSummaryParams,
     // API Keys
     ApiKey,
     CreateKeyParams,
     CreateKeyResult,
     KeyEnvironment,
+    KeyScope,
     KeysResponse,
     // On-Chain Payments
+    OnChainEvent,
+    OnChainEventKind,
+    OnChainEvents,
     OnChainGenerationStatus,
     OnChainStatus,
     PromptSubmission,
     RequiredAmount,
+    SubmissionFilter,
+    SubmissionStatus,
+    SubmissionsResponse,
     SubmitPromptParams,
+    SwapRoute,
 };
+#[cfg(all(feature = "runtime-tokio", not(target_arch = "wasm32")))]
+pub use watch::BalanceWatcher;
 
 #[cfg(test)]
 mod tests {
@@ -169,12 +351,54 @@ mod tests {
             .with_timeout(30)
             .with_max_retries(5);
 
-        assert_eq!(config.api_key, "test_key");
+        assert_eq!(config.api_key.expose_secret(), "test_key");
         assert_eq!(config.base_url, Some("https://custom.url".to_string()));
         assert_eq!(config.timeout, Some(30));
         assert_eq!(config.max_retries, Some(5));
     }
 
+    #[test]
+    fn test_debug_redacts_api_key() {
+        let config = PeerCatConfig::new("pcat_live_supersecret");
+        assert!(!format!("{:?}", config).contains("supersecret"));
+
+        let client = PeerCat::with_config(config).unwrap();
+        assert!(!format!("{:?}", client).contains("supersecret"));
+    }
+
+    #[test]
+    fn test_key_pool_config() {
+        let config = PeerCatConfig::from_key_pool(KeyPool::new(["key_a", "key_b"]));
+        assert_eq!(config.api_key.expose_secret(), "key_a");
+
+        let result = PeerCat::with_config(PeerCatConfig::from_key_pool(KeyPool::new(
+            Vec::<String>::new(),
+        )));
+        assert!(matches!(result, Err(PeerCatError::EmptyApiKey)));
+    }
+
+    #[test]
+    fn test_retry_budget() {
+        use retry_budget::RetryDecision;
+
+        let budget = RetryBudget::new(0.5).with_min_retries(0);
+        assert_eq!(budget.try_retry(), RetryDecision::Refused { opened: true });
+
+        for _ in 0..4 {
+            budget.record_request();
+        }
+        assert_eq!(budget.try_retry(), RetryDecision::Allowed);
+        assert_eq!(budget.clone().try_retry(), RetryDecision::Allowed);
+        assert_eq!(budget.try_retry(), RetryDecision::Refused { opened: true });
+        assert_eq!(budget.try_retry(), RetryDecision::Refused { opened: false });
+
+        let status = budget.status();
+        assert_eq!(status.requests, 4);
+        assert_eq!(status.retries, 2);
+        assert_eq!(status.available, 0);
+        assert_eq!(status.rejected, 3);
+    }
+
     #[test]
     fn test_generate_params_builder() {
         let params = GenerateParams::new("test prompt")
@@ -186,6 +410,130 @@ mod tests {
         assert_eq!(params.mode, Some(GenerationMode::Demo));
     }
 
+    #[test]
+    fn test_thumbnail_url() {
+        let mut result: GenerateResult = serde_json::from_value(serde_json::json!({
+            "id": "gen_123",
+            "imageUrl": "https://cdn.peerc.at/images/gen_123.png",
+            "ipfsHash": null,
+            "model": "stable-diffusion-xl",
+            "mode": "production",
+            "usage": { "creditsUsed": 0.28, "balanceRemaining": 9.72 }
+        }))
+        .unwrap();
+
+        assert_eq!(
+            result.thumbnail_url(ThumbSize::Small),
+            "https://cdn.peerc.at/images/gen_123.png?width=128"
+        );
+
+        result.image_url.push_str("?v=2");
+        assert_eq!(
+            result.thumbnail_url(ThumbSize::Large),
+            "https://cdn.peerc.at/images/gen_123.png?v=2&width=512"
+        );
+    }
+
+    #[test]
+    fn test_known_model() {
+        let params = GenerateParams::new("test prompt").with_model(KnownModel::Imagen3);
+        assert_eq!(params.model, Some("imagen-3".to_string()));
+
+        let params = GenerateParams::new("test prompt")
+            .with_model(KnownModel::Other("my-custom-model".to_string()));
+        assert_eq!(params.model, Some("my-custom-model".to_string()));
+
+        assert_eq!(
+            KnownModel::from("stable-diffusion-xl"),
+            KnownModel::StableDiffusionXl
+        );
+        assert_eq!(
+            KnownModel::from("new-model"),
+            KnownModel::Other("new-model".to_string())
+        );
+    }
+
+    #[test]
+    fn test_price_conversions() {
+        let prices: PriceResponse = serde_json::from_value(serde_json::json!({
+            "solPrice": 200.0,
+            "slippageTolerance": 0.02,
+            "updatedAt": "2024-01-15T10:30:00Z",
+            "treasury": "treasury",
+            "models": [
+                {
+                    "model": "stable-diffusion-xl",
+                    "priceUsd": 0.28,
+                    "priceSol": 0.0014,
+                    "priceSolWithSlippage": 0.001428
+                }
+            ]
+        }))
+        .unwrap();
+
+        assert_eq!(prices.usd_to_sol(1.0), 0.005);
+        assert_eq!(PriceResponse::sol_to_lamports(0.0014), Lamports(1_400_000));
+        assert_eq!(Lamports(1_500_000_000).as_sol(), 1.5);
+        assert_eq!(Lamports::from_sol(-1.0), Lamports(0));
+
+        let price = prices.price_for("stable-diffusion-xl").unwrap();
+        assert_eq!(price.price_usd, 0.28);
+        assert!(prices.price_for("unknown").is_none());
+    }
+
+    #[test]
+    fn test_models_response_selection() {
+        let models: ModelsResponse = serde_json::from_value(serde_json::json!({
+            "models": [
+                {
+                    "id": "stable-diffusion-xl",
+                    "name": "Stable Diffusion XL",
+                    "description": "",
+                    "provider": "stability",
+                    "maxPromptLength": 2000,
+                    "outputFormat": "png",
+                    "outputResolution": "1024x1024",
+                    "priceUsd": 0.28
+                },
+                {
+                    "id": "imagen-3",
+                    "name": "Imagen 3",
+                    "description": "",
+                    "provider": "google",
+                    "maxPromptLength": 2000,
+                    "outputFormat": "png",
+                    "outputResolution": "1024x1024",
+                    "priceUsd": 1.50,
+                    "capabilities": {
+                        "supportedResolutions": ["1024x1024", "2048x2048"]
+                    }
+                }
+            ]
+        }))
+        .unwrap();
+
+        assert_eq!(
+            models.cheapest_under(2.0).unwrap().id,
+            "stable-diffusion-xl"
+        );
+        assert!(models.cheapest_under(0.10).is_none());
+
+        assert_eq!(
+            models.best_for_resolution("1024x1024").unwrap().id,
+            "stable-diffusion-xl"
+        );
+        assert_eq!(
+            models.best_for_resolution("2048x2048").unwrap().id,
+            "imagen-3"
+        );
+        assert!(models.best_for_resolution("4096x4096").is_none());
+        assert!(models.best_for_resolution("large").is_none());
+
+        let google: Vec<_> = models.filter_by_provider("Google").collect();
+        assert_eq!(google.len(), 1);
+        assert_eq!(google[0].id, "imagen-3");
+    }
+
     #[test]
     fn test_history_params_builder() {
         let params = HistoryParams::new().with_limit(10).with_offset(20);
@@ -194,6 +542,21 @@ mod tests {
         assert_eq!(params.offset, Some(20));
     }
 
+    #[test]
+    fn test_history_params_filters() {
+        let params = HistoryParams::new()
+            .with_status(HistoryStatus::Completed)
+            .with_model("stable-diffusion-xl")
+            .with_date_range("2024-01-01T00:00:00Z", "2024-02-01T00:00:00Z")
+            .with_endpoint("/v1/generate");
+
+        assert_eq!(params.status, Some(HistoryStatus::Completed));
+        assert_eq!(params.model, Some("stable-diffusion-xl".to_string()));
+        assert_eq!(params.from, Some("2024-01-01T00:00:00Z".to_string()));
+        assert_eq!(params.to, Some("2024-02-01T00:00:00Z".to_string()));
+        assert_eq!(params.endpoint, Some("/v1/generate".to_string()));
+    }
+
     #[test]
     fn test_submit_prompt_params_builder() {
         let params = SubmitPromptParams::new("test prompt")
@@ -205,6 +568,105 @@ mod tests {
         assert_eq!(params.callback_url, Some("https://callback.url".to_string()));
     }
 
+    #[test]
+    fn test_create_key_params_builder() {
+        let params = CreateKeyParams::new("message", "signature", "pubkey")
+            .with_name("CI")
+            .with_scopes([KeyScope::Read]);
+
+        assert_eq!(params.name, Some("CI".to_string()));
+        assert_eq!(params.scopes, Some(vec![KeyScope::Read]));
+
+        let params = CreateKeyParams::new("message", "signature", "pubkey")
+            .with_ttl(std::time::Duration::from_secs(3600));
+        assert_eq!(params.ttl_seconds, Some(3600));
+        assert_eq!(
+            serde_json::to_value(&params).unwrap()["ttlSeconds"],
+            serde_json::json!(3600)
+        );
+    }
+
+    #[test]
+    fn test_api_key_has_scope() {
+        let mut key: ApiKey = serde_json::from_value(serde_json::json!({
+            "id": "key_123",
+            "name": null,
+            "keyPrefix": "pcat_live_xx",
+            "environment": "live",
+            "rateLimitTier": "standard",
+            "createdAt": "2024-01-15T10:00:00Z",
+            "lastUsedAt": null,
+            "revoked": false
+        }))
+        .unwrap();
+
+        // Legacy keys without scopes have full access
+        assert!(key.has_scope(KeyScope::Admin));
+
+        key.scopes = vec![KeyScope::Generate];
+        assert!(key.has_scope(KeyScope::Generate));
+        assert!(!key.has_scope(KeyScope::Read));
+
+        key.scopes = vec![KeyScope::Admin];
+        assert!(key.has_scope(KeyScope::Read));
+    }
+
+    #[test]
+    fn test_api_key_allows_ip() {
+        let mut key: ApiKey = serde_json::from_value(serde_json::json!({
+            "id": "key_123",
+            "name": null,
+            "keyPrefix": "pcat_live_xx",
+            "environment": "live",
+            "rateLimitTier": "standard",
+            "createdAt": "2024-01-15T10:00:00Z",
+            "lastUsedAt": null,
+            "revoked": false,
+            "ipAllowlist": ["10.0.0.0/8"]
+        }))
+        .unwrap();
+
+        assert!(key.allows_ip("10.1.2.3".parse().unwrap()));
+        assert!(!key.allows_ip("192.168.1.1".parse().unwrap()));
+
+        key.ip_allowlist.clear();
+        assert!(key.allows_ip("192.168.1.1".parse().unwrap()));
+    }
+
+    #[test]
+    fn test_api_key_is_expired() {
+        let mut key: ApiKey = serde_json::from_value(serde_json::json!({
+            "id": "key_123",
+            "name": null,
+            "keyPrefix": "pcat_test_xx",
+            "environment": "test",
+            "rateLimitTier": "standard",
+            "createdAt": "2024-01-15T10:00:00Z",
+            "lastUsedAt": null,
+            "revoked": false,
+            "expiresAt": "2024-01-16T10:00:00Z"
+        }))
+        .unwrap();
+        assert!(key.is_expired());
+
+        key.expires_at = None;
+        assert!(!key.is_expired());
+
+        // Offsets and fractions are honoured without chrono too
+        key.expires_at =
+            serde_json::from_value(serde_json::json!("2024-01-16T10:00:00.5+05:30")).unwrap();
+        assert!(key.is_expired());
+        key.expires_at =
+            serde_json::from_value(serde_json::json!("2999-01-01T00:00:00-08:00")).unwrap();
+        assert!(!key.is_expired());
+
+        #[cfg(feature = "chrono")]
+        {
+            key.expires_at = Some(chrono::Utc::now() + chrono::Duration::hours(1));
+            assert!(!key.is_expired());
+        }
+    }
+
     #[test]
     fn test_error_is_retryable() {
         let auth_error = PeerCatError::Authentication {
@@ -0,0 +1,404 @@
+//! Contract checks against the live test environment
+//!
+//! Requires the `live-tests` feature.
+//!
+//! [`run_contract_checks`] calls every endpoint it safely can with a test
+//! key and records whether each response matched the types this SDK
+//! expects. The crate's own `live_tests` suite runs it, and downstream
+//! crates can run it too, e.g. to confirm an SDK upgrade against the real
+//! service before deploying:
+//!
+//! ```no_run
+//! # async fn example() -> peercat::Result<()> {
+//! use peercat::live;
+//!
+//! if let Some(client) = live::client_from_env()? {
+//!     live::run_contract_checks(&client).await.assert_ok();
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+use std::fmt;
+use std::time::Duration;
+
+use crate::client::PeerCat;
+use crate::error::{PeerCatError, Result};
+use crate::types::{
+    CreateCollectionParams, GenerateParams, GenerateResult, HistoryParams, PeerCatConfig,
+    PriceGranularity, ShareOptions, SubmissionFilter, SubmitPromptParams, ThumbSize, UsageGroupBy,
+    UsageSummaryParams,
+};
+
+/// Environment variable holding the `pcat_test_` key to check with
+pub const API_KEY_VAR: &str = "PEERCAT_API_KEY";
+
+/// Environment variable overriding the API base URL
+pub const BASE_URL_VAR: &str = "PEERCAT_BASE_URL";
+
+/// Prompt used for the generations the checks make
+const PROMPT: &str = "A red cube on a white background";
+
+/// Create a client for the checks from the environment
+///
+/// Returns `None` when [`API_KEY_VAR`] is unset, so suites can skip rather
+/// than fail on machines without credentials. The client is a
+/// [`PeerCatConfig::test`] client, so live keys are rejected and
+/// generations run in demo mode. Deleting generations and presets is
+/// allowed, so the checks can clean up after themselves.
+///
+/// # Errors
+///
+/// Returns `PeerCatError::InvalidConfig` if the key isn't a test key.
+pub fn client_from_env() -> Result<Option<PeerCat>> {
+    let Ok(api_key) = std::env::var(API_KEY_VAR) else {
+        return Ok(None);
+    };
+    let mut config = PeerCatConfig::test(api_key).allow_production_operations();
+    if let Ok(base_url) = std::env::var(BASE_URL_VAR) {
+        config = config.with_base_url(base_url);
+    }
+    PeerCat::with_config(config).map(Some)
+}
+
+/// How a single check went
+#[derive(Debug)]
+pub enum CheckOutcome {
+    /// The call succeeded and its response parsed
+    Passed,
+    /// The call failed, or its response didn't match the SDK's types
+    Failed(PeerCatError),
+    /// The call wasn't made, for the given reason
+    Skipped(&'static str),
+}
+
+/// The outcome of checking one endpoint
+#[derive(Debug)]
+pub struct ContractCheck {
+    /// Client method that was checked
+    pub name: &'static str,
+    /// How it went
+    pub outcome: CheckOutcome,
+}
+
+/// The outcome of [`run_contract_checks`]
+#[derive(Debug, Default)]
+pub struct ContractReport {
+    /// Every check, in the order it ran
+    pub checks: Vec<ContractCheck>,
+}
+
+impl ContractReport {
+    /// Returns true if no check failed
+    pub fn is_ok(&self) -> bool {
+        self.failures().next().is_none()
+    }
+
+    /// The checks that failed
+    pub fn failures(&self) -> impl Iterator<Item = &ContractCheck> {
+        self.checks
+            .iter()
+            .filter(|check| matches!(check.outcome, CheckOutcome::Failed(_)))
+    }
+
+    /// Panic with the full report if any check failed
+    pub fn assert_ok(&self) {
+        assert!(self.is_ok(), "live contract checks failed:\n{}", self);
+    }
+
+    fn record<T>(&mut self, name: &'static str, result: Result<T>) -> Option<T> {
+        let (outcome, value) = match result {
+            Ok(value) => (CheckOutcome::Passed, Some(value)),
+            Err(PeerCatError::OperationNotAllowed { .. }) => (
+                CheckOutcome::Skipped("not allowed by the client's configuration"),
+                None,
+            ),
+            Err(e) => (CheckOutcome::Failed(e), None),
+        };
+        self.checks.push(ContractCheck { name, outcome });
+        value
+    }
+
+    fn skip(&mut self, name: &'static str, reason: &'static str) {
+        self.checks.push(ContractCheck {
+            name,
+            outcome: CheckOutcome::Skipped(reason),
+        });
+    }
+}
+
+impl fmt::Display for ContractReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for check in &self.checks {
+            match &check.outcome {
+                CheckOutcome::Passed => writeln!(f, "  ok    {}", check.name)?,
+                CheckOutcome::Failed(e) => writeln!(f, "  FAIL  {}: {}", check.name, e)?,
+                CheckOutcome::Skipped(reason) => writeln!(f, "  skip  {}: {}", check.name, reason)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Call every endpoint that is safe to call with a test key
+///
+/// Checks that depend on an earlier one, like fetching the metadata of a
+/// generation, are skipped if it failed. Endpoints that need a wallet
+/// signature or an on-chain transaction are always skipped. Everything the
+/// checks create is deleted again where the API allows it.
+pub async fn run_contract_checks(client: &PeerCat) -> ContractReport {
+    let mut report = ContractReport::default();
+
+    check_catalog(client, &mut report).await;
+    report.record("get_balance", client.get_balance().await);
+
+    let generation = report.record(
+        "generate",
+        client.generate(GenerateParams::new(PROMPT)).await,
+    );
+    match &generation {
+        Some(generation) => check_generation(client, generation, &mut report).await,
+        None => {
+            for name in GENERATION_CHECKS {
+                report.skip(name, "generate failed");
+            }
+        }
+    }
+
+    check_presets(client, &mut report).await;
+    check_account(client, &mut report).await;
+    check_payments(client, &mut report).await;
+
+    if let Some(generation) = &generation {
+        report.record(
+            "delete_generation",
+            client.delete_generation(&generation.id, true).await,
+        );
+    }
+    report
+}
+
+async fn check_catalog(client: &PeerCat, report: &mut ContractReport) {
+    let models = report.record("get_models", client.get_models().await);
+    match models.as_ref().and_then(|models| models.first()) {
+        Some(model) => {
+            report.record("get_model", client.get_model(&model.id).await);
+            report.record(
+                "get_available_loras",
+                client.get_available_loras(&model.id).await,
+            );
+        }
+        None => {
+            report.skip("get_model", "no models listed");
+            report.skip("get_available_loras", "no models listed");
+        }
+    }
+    report.record("get_prices", client.get_prices().await);
+    let now = chrono::Utc::now();
+    report.record(
+        "get_price_history",
+        client
+            .get_price_history(now - chrono::Duration::days(1)..now, PriceGranularity::Hour)
+            .await,
+    );
+}
+
+/// Checks that need a generation to work with
+const GENERATION_CHECKS: &[&str] = &[
+    "get_generation_metadata",
+    "get_ipfs_status",
+    "create_signed_url",
+    "create_share_link",
+    "tag_generation",
+    "download_image_stream",
+    "download_thumbnail",
+    "upload_image",
+    "create_collection",
+    "add_to_collection",
+    "list_collections",
+    "list_collection_items",
+];
+
+async fn check_generation(
+    client: &PeerCat,
+    generation: &GenerateResult,
+    report: &mut ContractReport,
+) {
+    let id = generation.id.as_str();
+    report.record(
+        "get_generation_metadata",
+        client.get_generation_metadata(id).await,
+    );
+    match &generation.ipfs_hash {
+        Some(hash) => {
+            report.record("get_ipfs_status", client.get_ipfs_status(hash).await);
+        }
+        None => report.skip("get_ipfs_status", "generation wasn't pinned to IPFS"),
+    }
+    report.record(
+        "create_signed_url",
+        client.create_signed_url(id, Duration::from_secs(300)).await,
+    );
+    report.record(
+        "create_share_link",
+        client.create_share_link(id, ShareOptions::new()).await,
+    );
+    report.record(
+        "tag_generation",
+        client
+            .tag_generation(id, vec!["contract-check".to_string()])
+            .await,
+    );
+
+    let image = match client.download_image_stream(generation).await {
+        Ok(mut stream) => {
+            let mut bytes = Vec::new();
+            loop {
+                match stream.next().await {
+                    Some(Ok(chunk)) => bytes.extend_from_slice(&chunk),
+                    Some(Err(e)) => break Err(e),
+                    None => break Ok(bytes),
+                }
+            }
+        }
+        Err(e) => Err(e),
+    };
+    let image = report.record("download_image_stream", image);
+    report.record(
+        "download_thumbnail",
+        client
+            .download_thumbnail(generation, ThumbSize::Small)
+            .await,
+    );
+    match image {
+        Some(image) => {
+            report.record("upload_image", client.upload_image(image).await);
+        }
+        None => report.skip("upload_image", "download_image_stream failed"),
+    }
+
+    let collection = report.record(
+        "create_collection",
+        client
+            .create_collection(CreateCollectionParams::new("Contract checks"))
+            .await,
+    );
+    match &collection {
+        Some(collection) => {
+            report.record(
+                "add_to_collection",
+                client.add_to_collection(&collection.id, id).await,
+            );
+        }
+        None => report.skip("add_to_collection", "create_collection failed"),
+    }
+    report.record("list_collections", client.list_collections().await);
+    match &collection {
+        Some(collection) => {
+            report.record(
+                "list_collection_items",
+                client.list_collection_items(&collection.id).await,
+            );
+        }
+        None => report.skip("list_collection_items", "create_collection failed"),
+    }
+}
+
+async fn check_presets(client: &PeerCat, report: &mut ContractReport) {
+    let preset = report.record(
+        "create_preset",
+        client
+            .create_preset("contract-check", GenerateParams::new(PROMPT))
+            .await,
+    );
+    report.record("list_presets", client.list_presets().await);
+    match preset {
+        Some(preset) => {
+            if let Some(generation) = report.record(
+                "generate_from_preset",
+                client
+                    .generate_from_preset(&preset.id, GenerateParams::new(PROMPT))
+                    .await,
+            ) {
+                // Only here to clean up, so not a check of its own
+                let _ = client.delete_generation(&generation.id, true).await;
+            }
+            report.record("delete_preset", client.delete_preset(&preset.id).await);
+        }
+        None => {
+            report.skip("generate_from_preset", "create_preset failed");
+            report.skip("delete_preset", "create_preset failed");
+        }
+    }
+}
+
+async fn check_account(client: &PeerCat, report: &mut ContractReport) {
+    let history = report.record(
+        "get_history",
+        client.get_history(HistoryParams::new().with_limit(5)).await,
+    );
+    match history.as_ref().and_then(|history| history.items.first()) {
+        Some(item) => {
+            report.record("get_history_item", client.get_history_item(&item.id).await);
+        }
+        None => report.skip("get_history_item", "history is empty"),
+    }
+    report.record(
+        "get_usage_summary",
+        client
+            .get_usage_summary(UsageSummaryParams::new(UsageGroupBy::Model))
+            .await,
+    );
+
+    let keys = report.record("list_keys", client.list_keys().await);
+    match keys.as_ref().and_then(|keys| keys.keys.first()) {
+        Some(key) => {
+            report.record("get_key", client.get_key(&key.id).await);
+            match &key.name {
+                // Renaming to the current name checks the call without changing anything
+                Some(name) => {
+                    report.record(
+                        "update_key_name",
+                        client.update_key_name(&key.id, name).await,
+                    );
+                }
+                None => report.skip("update_key_name", "key has no name to restore"),
+            }
+        }
+        None => {
+            report.skip("get_key", "no keys listed");
+            report.skip("update_key_name", "no keys listed");
+        }
+    }
+    report.skip("update_key_allowlist", "could lock the key out");
+    report.skip("create_key", "needs a wallet signature");
+    report.skip("revoke_key", "would revoke a real key");
+}
+
+async fn check_payments(client: &PeerCat, report: &mut ContractReport) {
+    report.record("create_deposit", client.create_deposit(1.0).await);
+    report.skip("get_deposit_status", "needs an on-chain transaction");
+    report.skip("withdraw", "needs a wallet signature");
+    report.skip("get_withdrawal", "needs a withdrawal");
+
+    let submission = report.record(
+        "submit_prompt",
+        client.submit_prompt(SubmitPromptParams::new(PROMPT)).await,
+    );
+    report.record(
+        "list_submissions",
+        client.list_submissions(SubmissionFilter::default()).await,
+    );
+    match submission {
+        Some(submission) => {
+            report.record(
+                "get_submission",
+                client.get_submission(&submission.submission_id).await,
+            );
+        }
+        None => report.skip("get_submission", "submit_prompt failed"),
+    }
+    report.skip("link_transaction", "needs an on-chain transaction");
+    report.skip("get_onchain_status", "needs an on-chain transaction");
+    report.skip("get_onchain_events", "needs an on-chain transaction");
+}
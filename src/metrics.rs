@@ -0,0 +1,244 @@
+//! Pluggable metrics for requests, retries, rate limits and spending
+
+use std::fmt;
+use std::time::Duration;
+
+use crate::error::PeerCatError;
+
+/// Something that happened while the client was talking to the API
+///
+/// `endpoint` is the request path without its query string, so it can
+/// contain IDs; aggregate on a normalized route if cardinality matters.
+#[derive(Debug, Clone, Copy)]
+#[non_exhaustive]
+pub enum MetricEvent<'a> {
+    /// A call finished, after any retries
+    Request {
+        method: &'a str,
+        endpoint: &'a str,
+        duration: Duration,
+        /// The error the call failed with, or `None` if it succeeded
+        error: Option<&'a PeerCatError>,
+    },
+    /// An attempt failed and is about to be retried
+    Retry {
+        method: &'a str,
+        endpoint: &'a str,
+        /// Number of the retry about to be sent, starting at 1
+        attempt: u32,
+        /// Backoff before the retry is sent
+        delay: Duration,
+    },
+    /// An attempt was rejected with HTTP 429
+    RateLimited {
+        method: &'a str,
+        endpoint: &'a str,
+        retry_after: Option<u64>,
+    },
+    /// A successful response reported the credits it cost
+    CreditsSpent { endpoint: &'a str, credits: f64 },
+}
+
+/// A sink for [`MetricEvent`]s
+///
+/// Set one with [`PeerCatConfig::with_metrics`](crate::PeerCatConfig::with_metrics);
+/// without it, no events are recorded. `record` is called inline on the
+/// request path, so it should be cheap and must not block.
+///
+/// # Example
+///
+/// ```no_run
+/// use peercat::{MetricEvent, PeerCat, PeerCatConfig, PeerCatMetrics};
+/// use std::sync::atomic::{AtomicU64, Ordering};
+///
+/// #[derive(Debug, Default)]
+/// struct RetryCounter(AtomicU64);
+///
+/// impl PeerCatMetrics for RetryCounter {
+///     fn record(&self, event: MetricEvent<'_>) {
+///         if let MetricEvent::Retry { .. } = event {
+///             self.0.fetch_add(1, Ordering::Relaxed);
+///         }
+///     }
+/// }
+///
+/// let client = PeerCat::with_config(
+///     PeerCatConfig::new("pcat_live_xxx").with_metrics(RetryCounter::default()),
+/// )?;
+/// # Ok::<(), peercat::PeerCatError>(())
+/// ```
+pub trait PeerCatMetrics: Send + Sync + fmt::Debug {
+    /// Record one event
+    fn record(&self, event: MetricEvent<'_>);
+}
+
+/// Short label for the kind of error a call failed with
+#[cfg(feature = "prometheus")]
+fn error_kind(error: &PeerCatError) -> &'static str {
+    match error {
+        PeerCatError::Authentication { .. } => "authentication",
+        PeerCatError::InvalidRequest { .. } => "invalid_request",
+        PeerCatError::InsufficientCredits { .. } => "insufficient_credits",
+        PeerCatError::RateLimit { .. } => "rate_limit",
+        PeerCatError::NotFound { .. } => "not_found",
+        PeerCatError::Server { .. } => "server",
+        PeerCatError::Network(_) | PeerCatError::Transport(_) => "network",
+        PeerCatError::Timeout => "timeout",
+        PeerCatError::Cancelled => "cancelled",
+        PeerCatError::Json(_) => "decode",
+        _ => "other",
+    }
+}
+
+/// Replace path segments that look like IDs (anything with a digit or an
+/// uppercase letter) with `{id}`, so labels stay low-cardinality
+#[cfg(feature = "prometheus")]
+fn route(endpoint: &str) -> String {
+    endpoint
+        .split('/')
+        .map(|segment| {
+            if segment
+                .chars()
+                .any(|c| c.is_ascii_digit() || c.is_uppercase())
+                && !is_version(segment)
+            {
+                "{id}"
+            } else {
+                segment
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// Returns true for API version segments like `v1`
+#[cfg(feature = "prometheus")]
+fn is_version(segment: &str) -> bool {
+    segment
+        .strip_prefix('v')
+        .is_some_and(|n| !n.is_empty() && n.chars().all(|c| c.is_ascii_digit()))
+}
+
+/// [`PeerCatMetrics`] backed by a Prometheus registry (requires the `prometheus` feature)
+///
+/// Registers these metrics, labelled by `method` and `route` (the endpoint
+/// with IDs replaced by `{id}`):
+///
+/// - `peercat_requests_total` — calls, also labelled by `outcome`
+///   (`success` or the kind of error)
+/// - `peercat_request_duration_seconds` — call latency, including retries
+/// - `peercat_retries_total` — retries sent
+/// - `peercat_rate_limited_total` — responses with HTTP 429
+/// - `peercat_credits_spent_total` — credits spent (labelled by `route` only)
+///
+/// # Example
+///
+/// ```no_run
+/// use peercat::{PeerCat, PeerCatConfig, PrometheusMetrics};
+///
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let registry = prometheus::Registry::new();
+/// let client = PeerCat::with_config(
+///     PeerCatConfig::new("pcat_live_xxx").with_metrics(PrometheusMetrics::new(&registry)?),
+/// )?;
+/// # Ok(())
+/// # }
+/// ```
+#[cfg(feature = "prometheus")]
+#[derive(Debug, Clone)]
+pub struct PrometheusMetrics {
+    requests: prometheus::IntCounterVec,
+    duration: prometheus::HistogramVec,
+    retries: prometheus::IntCounterVec,
+    rate_limited: prometheus::IntCounterVec,
+    credits: prometheus::CounterVec,
+}
+
+#[cfg(feature = "prometheus")]
+impl PrometheusMetrics {
+    /// Create the metrics and register them with `registry`
+    ///
+    /// # Errors
+    ///
+    /// Fails if metrics with the same names are already registered.
+    pub fn new(registry: &prometheus::Registry) -> prometheus::Result<Self> {
+        use prometheus::{CounterVec, HistogramOpts, HistogramVec, IntCounterVec, Opts};
+
+        let metrics = Self {
+            requests: IntCounterVec::new(
+                Opts::new("peercat_requests_total", "PeerCat API calls"),
+                &["method", "route", "outcome"],
+            )?,
+            duration: HistogramVec::new(
+                HistogramOpts::new(
+                    "peercat_request_duration_seconds",
+                    "PeerCat API call latency, including retries",
+                ),
+                &["method", "route"],
+            )?,
+            retries: IntCounterVec::new(
+                Opts::new("peercat_retries_total", "PeerCat API retries"),
+                &["method", "route"],
+            )?,
+            rate_limited: IntCounterVec::new(
+                Opts::new(
+                    "peercat_rate_limited_total",
+                    "PeerCat API responses with HTTP 429",
+                ),
+                &["method", "route"],
+            )?,
+            credits: CounterVec::new(
+                Opts::new("peercat_credits_spent_total", "PeerCat credits spent"),
+                &["route"],
+            )?,
+        };
+        registry.register(Box::new(metrics.requests.clone()))?;
+        registry.register(Box::new(metrics.duration.clone()))?;
+        registry.register(Box::new(metrics.retries.clone()))?;
+        registry.register(Box::new(metrics.rate_limited.clone()))?;
+        registry.register(Box::new(metrics.credits.clone()))?;
+        Ok(metrics)
+    }
+}
+
+#[cfg(feature = "prometheus")]
+impl PeerCatMetrics for PrometheusMetrics {
+    fn record(&self, event: MetricEvent<'_>) {
+        match event {
+            MetricEvent::Request {
+                method,
+                endpoint,
+                duration,
+                error,
+            } => {
+                let route = route(endpoint);
+                let outcome = error.map_or("success", error_kind);
+                self.requests
+                    .with_label_values(&[method, &route, outcome])
+                    .inc();
+                self.duration
+                    .with_label_values(&[method, &route])
+                    .observe(duration.as_secs_f64());
+            }
+            MetricEvent::Retry {
+                method, endpoint, ..
+            } => {
+                self.retries
+                    .with_label_values(&[method, &route(endpoint)])
+                    .inc();
+            }
+            MetricEvent::RateLimited {
+                method, endpoint, ..
+            } => {
+                self.rate_limited
+                    .with_label_values(&[method, &route(endpoint)])
+                    .inc();
+            }
+            MetricEvent::CreditsSpent { endpoint, credits } => {
+                self.credits
+                    .with_label_values(&[&route(endpoint)])
+                    .inc_by(credits);
+            }
+        }
+    }
+}
@@ -0,0 +1,228 @@
+//! In-memory test double for [`PeerCatApi`]
+//!
+//! Requires the `mock` feature.
+
+use std::fmt;
+use std::future::{ready, Future};
+use std::ops::Range;
+use std::time::Duration;
+
+use ipnet::IpNet;
+
+use crate::api::PeerCatApi;
+use crate::error::Result;
+use crate::types::*;
+
+type Handler<A, T> = Box<dyn Fn(A) -> Result<T> + Send + Sync>;
+
+macro_rules! mock_api {
+    ($(
+        $(#[$doc:meta])*
+        fn $name:ident($($arg:ident: $ty:ty => $owned:ty),*) -> $ret:ty, $on:ident;
+    )*) => {
+        /// A [`PeerCatApi`] implementation with programmable responses
+        ///
+        /// Set a handler for each method the code under test calls. Calling a
+        /// method without a handler panics, so unexpected API calls fail the
+        /// test loudly.
+        ///
+        /// # Example
+        ///
+        /// ```
+        /// use peercat::mock::MockPeerCat;
+        /// use peercat::{Balance, PeerCatApi};
+        ///
+        /// # async fn example() -> peercat::Result<()> {
+        /// let api = MockPeerCat::new().on_get_balance(|| {
+        ///     Ok(Balance {
+        ///         credits: 1.5,
+        ///         total_deposited: 10.0,
+        ///         total_spent: 8.5,
+        ///         total_withdrawn: 0.0,
+        ///         total_generated: 30,
+        ///     })
+        /// });
+        ///
+        /// assert_eq!(api.get_balance().await?.credits, 1.5);
+        /// # Ok(())
+        /// # }
+        /// ```
+        #[derive(Default)]
+        pub struct MockPeerCat {
+            $($name: Option<Handler<($($owned,)*), $ret>>,)*
+        }
+
+        impl MockPeerCat {
+            $(
+                $(#[$doc])*
+                pub fn $on<F>(mut self, handler: F) -> Self
+                where
+                    F: Fn($($owned),*) -> Result<$ret> + Send + Sync + 'static,
+                {
+                    self.$name = Some(Box::new(move |($($arg,)*)| handler($($arg),*)));
+                    self
+                }
+            )*
+        }
+
+        #[allow(clippy::useless_conversion)]
+        impl PeerCatApi for MockPeerCat {
+            $(
+                fn $name(&self, $($arg: $ty),*) -> impl Future<Output = Result<$ret>> + Send {
+                    let handler = self.$name.as_ref().unwrap_or_else(|| {
+                        panic!(concat!("MockPeerCat: no handler set for ", stringify!($name)))
+                    });
+                    ready(handler(($($arg.into(),)*)))
+                }
+            )*
+        }
+
+        impl fmt::Debug for MockPeerCat {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                let mut handled = Vec::new();
+                $(
+                    if self.$name.is_some() {
+                        handled.push(stringify!($name));
+                    }
+                )*
+                f.debug_struct("MockPeerCat").field("handlers", &handled).finish()
+            }
+        }
+    };
+}
+
+mock_api! {
+    /// Respond to `generate`
+    fn generate(params: GenerateParams => GenerateParams) -> GenerateResult, on_generate;
+    /// Respond to `upload_image`
+    fn upload_image(data: Vec<u8> => Vec<u8>) -> UploadedImage, on_upload_image;
+    /// Respond to `get_ipfs_status`
+    fn get_ipfs_status(id_or_hash: &str => String) -> IpfsStatus, on_get_ipfs_status;
+    /// Respond to `get_generation_metadata`
+    fn get_generation_metadata(
+        id: &str => String
+    ) -> GenerationMetadata, on_get_generation_metadata;
+    /// Respond to `create_signed_url`
+    fn create_signed_url(
+        generation_id: &str => String,
+        ttl: Duration => Duration
+    ) -> SignedUrl, on_create_signed_url;
+    /// Respond to `create_share_link`
+    fn create_share_link(
+        generation_id: &str => String,
+        options: ShareOptions => ShareOptions
+    ) -> ShareLink, on_create_share_link;
+    /// Respond to `tag_generation`
+    fn tag_generation(
+        id: &str => String,
+        tags: Vec<String> => Vec<String>
+    ) -> (), on_tag_generation;
+    /// Respond to `delete_generation`
+    fn delete_generation(
+        id: &str => String,
+        unpin_ipfs: bool => bool
+    ) -> GenerationDeletion, on_delete_generation;
+    /// Respond to `create_collection`
+    fn create_collection(
+        params: CreateCollectionParams => CreateCollectionParams
+    ) -> Collection, on_create_collection;
+    /// Respond to `add_to_collection`
+    fn add_to_collection(
+        collection_id: &str => String,
+        generation_id: &str => String
+    ) -> CollectionItem, on_add_to_collection;
+    /// Respond to `list_collections`
+    fn list_collections() -> CollectionsResponse, on_list_collections;
+    /// Respond to `list_collection_items`
+    fn list_collection_items(
+        collection_id: &str => String
+    ) -> CollectionItemsResponse, on_list_collection_items;
+    /// Respond to `create_preset`
+    fn create_preset(
+        name: &str => String,
+        params: GenerateParams => GenerateParams
+    ) -> Preset, on_create_preset;
+    /// Respond to `list_presets`
+    fn list_presets() -> PresetsResponse, on_list_presets;
+    /// Respond to `delete_preset`
+    fn delete_preset(preset_id: &str => String) -> (), on_delete_preset;
+    /// Respond to `generate_from_preset`
+    fn generate_from_preset(
+        preset_id: &str => String,
+        overrides: GenerateParams => GenerateParams
+    ) -> GenerateResult, on_generate_from_preset;
+    /// Respond to `get_models`
+    fn get_models() -> Vec<Model>, on_get_models;
+    /// Respond to `get_model`
+    fn get_model(model_id: &str => String) -> Model, on_get_model;
+    /// Respond to `get_available_loras`
+    fn get_available_loras(model_id: &str => String) -> Vec<Lora>, on_get_available_loras;
+    /// Respond to `get_prices`
+    fn get_prices() -> PriceResponse, on_get_prices;
+    /// Respond to `get_price_history`
+    fn get_price_history(
+        range: Range<Timestamp> => Range<Timestamp>,
+        granularity: PriceGranularity => PriceGranularity
+    ) -> PriceHistory, on_get_price_history;
+    /// Respond to `get_balance`
+    fn get_balance() -> Balance, on_get_balance;
+    /// Respond to `create_deposit`
+    fn create_deposit(amount_usd: f64 => f64) -> DepositInstructions, on_create_deposit;
+    /// Respond to `get_deposit_status`
+    fn get_deposit_status(tx_signature: &str => String) -> DepositStatus, on_get_deposit_status;
+    /// Respond to `withdraw`
+    fn withdraw(params: WithdrawParams => WithdrawParams) -> WithdrawalResult, on_withdraw;
+    /// Respond to `get_withdrawal`
+    fn get_withdrawal(withdrawal_id: &str => String) -> WithdrawalResult, on_get_withdrawal;
+    /// Respond to `get_history`
+    fn get_history(params: HistoryParams => HistoryParams) -> HistoryResponse, on_get_history;
+    /// Respond to `get_history_item`
+    fn get_history_item(id: &str => String) -> HistoryItem, on_get_history_item;
+    /// Respond to `get_usage_summary`
+    fn get_usage_summary(
+        params: UsageSummaryParams => UsageSummaryParams
+    ) -> UsageSummary, on_get_usage_summary;
+    /// Respond to `create_key`
+    fn create_key(params: CreateKeyParams => CreateKeyParams) -> CreateKeyResult, on_create_key;
+    /// Respond to `list_keys`
+    fn list_keys() -> KeysResponse, on_list_keys;
+    /// Respond to `get_key`
+    fn get_key(key_id: &str => String) -> ApiKey, on_get_key;
+    /// Respond to `revoke_key`
+    fn revoke_key(key_id: &str => String) -> (), on_revoke_key;
+    /// Respond to `update_key_name`
+    fn update_key_name(key_id: &str => String, name: &str => String) -> (), on_update_key_name;
+    /// Respond to `update_key_allowlist`
+    fn update_key_allowlist(
+        key_id: &str => String,
+        networks: Vec<IpNet> => Vec<IpNet>
+    ) -> (), on_update_key_allowlist;
+    /// Respond to `submit_prompt`
+    fn submit_prompt(
+        params: SubmitPromptParams => SubmitPromptParams
+    ) -> PromptSubmission, on_submit_prompt;
+    /// Respond to `list_submissions`
+    fn list_submissions(
+        filter: SubmissionFilter => SubmissionFilter
+    ) -> SubmissionsResponse, on_list_submissions;
+    /// Respond to `get_submission`
+    fn get_submission(submission_id: &str => String) -> PromptSubmission, on_get_submission;
+    /// Respond to `link_transaction`
+    fn link_transaction(
+        submission_id: &str => String,
+        tx_signature: &str => String
+    ) -> PromptSubmission, on_link_transaction;
+    /// Respond to `get_onchain_status`
+    fn get_onchain_status(
+        tx_signature: &str => String
+    ) -> OnChainGenerationStatus, on_get_onchain_status;
+    /// Respond to `get_onchain_events`
+    fn get_onchain_events(tx_signature: &str => String) -> OnChainEvents, on_get_onchain_events;
+}
+
+impl MockPeerCat {
+    /// Create a mock with no handlers set
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
@@ -0,0 +1,58 @@
+//! Placeholder generations for clients that never touch the network
+
+use crate::types::{GenerateParams, GenerateResult, GenerateUsage, GenerationMode, KnownModel};
+
+/// Image URL returned for every offline generation
+const PLACEHOLDER_IMAGE_URL: &str = "https://cdn.peerc.at/demo/placeholder.png";
+
+/// The result of a demo generation, built locally from its params
+///
+/// The ID (and the seed, if the params don't set one) is derived from the
+/// params, so the same params always give the same result.
+pub(crate) fn demo_result(mut params: GenerateParams) -> GenerateResult {
+    // Options are a HashMap, so hash them separately in a fixed order
+    let options = params.options.take();
+    let mut entries: Vec<String> = options
+        .iter()
+        .flatten()
+        .map(|(key, value)| format!("{}={}", key, value))
+        .collect();
+    entries.sort();
+    let mut bytes = serde_json::to_vec(&params).unwrap_or_default();
+    bytes.extend_from_slice(entries.join("&").as_bytes());
+    let hash = fnv1a(&bytes);
+
+    GenerateResult {
+        id: format!("demo_{:016x}", hash),
+        image_url: PLACEHOLDER_IMAGE_URL.to_string(),
+        ipfs_hash: None,
+        model: params
+            .model
+            .unwrap_or_else(|| KnownModel::StableDiffusionXl.as_str().to_string()),
+        mode: GenerationMode::Demo,
+        usage: GenerateUsage {
+            credits_used: 0.0,
+            balance_remaining: 0.0,
+        },
+        private: false,
+        safety_level: params.safety_level,
+        seed: Some(params.seed.unwrap_or(hash >> 32)),
+        prompt: Some(params.prompt),
+        options,
+        prompts: params.prompts,
+        quality: params.quality,
+        adapters: params.adapters,
+        controlnet: params.controlnet,
+        auto_translate: params.auto_translate,
+        source_language: params.source_language,
+        expected_file_size: None,
+        translated_prompt: None,
+    }
+}
+
+/// 64-bit FNV-1a, which unlike `DefaultHasher` is stable across Rust releases
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+        (hash ^ u64::from(*byte)).wrapping_mul(0x0100_0000_01b3)
+    })
+}
@@ -0,0 +1,100 @@
+//! Rate-limit-aware pacing of requests
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use web_time::{Instant, SystemTime, UNIX_EPOCH};
+
+use crate::client::{retry_after_delay, MAX_RETRY_AFTER};
+use crate::error::RateLimitInfo;
+
+/// The last rate limit window reported for a key
+#[derive(Debug, Clone, Copy)]
+struct Window {
+    /// Requests left in the window, less those reserved since the last response
+    remaining: u32,
+    /// When the window resets
+    reset: Instant,
+    /// Earliest time the next request may start
+    next_slot: Instant,
+}
+
+/// Spreads requests evenly over each key's rate limit window
+///
+/// Shared by all clones of a client, so concurrent tasks draw from the same
+/// budget instead of each running into 429s.
+#[derive(Debug, Default)]
+pub(crate) struct RateLimitPacer {
+    windows: Mutex<HashMap<usize, Window>>,
+}
+
+impl RateLimitPacer {
+    /// Reserve a request slot for a key, returning how long to wait for it
+    pub fn reserve(&self, key: usize) -> Option<Duration> {
+        let now = Instant::now();
+        let mut windows = self.lock();
+        let window = windows.get_mut(&key)?;
+
+        if window.reset <= now {
+            windows.remove(&key);
+            return None;
+        }
+        if window.remaining == 0 {
+            return Some(window.reset - now);
+        }
+
+        let slot = window.next_slot.max(now);
+        let spacing = (window.reset - slot) / window.remaining;
+        window.remaining -= 1;
+        window.next_slot = slot + spacing;
+        Some(slot - now).filter(|delay| !delay.is_zero())
+    }
+
+    /// Record the rate limit state reported by a response
+    pub fn update(&self, key: usize, info: Option<&RateLimitInfo>, rate_limited: bool) {
+        let Some(info) = info else { return };
+        let now = Instant::now();
+
+        // Both come from the server, so they're capped like any Retry-After
+        let reset = match (info.retry_after, info.reset) {
+            (Some(secs), _) if rate_limited => now + retry_after_delay(secs),
+            (_, Some(reset)) => now + seconds_until(reset).min(MAX_RETRY_AFTER),
+            _ => return,
+        };
+        let remaining = if rate_limited {
+            0
+        } else {
+            match info.remaining {
+                Some(remaining) => remaining,
+                None => return,
+            }
+        };
+
+        let mut windows = self.lock();
+        let next_slot = windows
+            .get(&key)
+            .map_or(now, |window| window.next_slot.min(reset));
+        windows.insert(
+            key,
+            Window {
+                remaining,
+                reset,
+                next_slot,
+            },
+        );
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, HashMap<usize, Window>> {
+        self.windows.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+/// Time from now until a Unix timestamp, or zero if it has passed
+fn seconds_until(timestamp: i64) -> Duration {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs_f64();
+    Duration::from_secs_f64((timestamp as f64 - now).max(0.0))
+}
@@ -0,0 +1,232 @@
+//! Waiting for asynchronous work to finish
+
+use std::future::Future;
+use std::time::Duration;
+
+use tokio_util::sync::CancellationToken;
+use web_time::Instant;
+
+use crate::client::PeerCat;
+use crate::error::{PeerCatError, Result};
+use crate::rt;
+use crate::types::{HistoryItem, HistoryStatus, OnChainGenerationStatus};
+
+/// How a wait helper polls: delays, backoff, and when to give up
+///
+/// Defaults to polling every 2 seconds for up to 5 minutes.
+///
+/// # Example
+///
+/// ```
+/// use peercat::PollOptions;
+/// use std::time::Duration;
+///
+/// // Back off from 1s to at most 10s between polls, giving up after 2 minutes
+/// let options = PollOptions::new()
+///     .with_interval(Duration::from_secs(1))
+///     .with_backoff(2.0, Duration::from_secs(10))
+///     .with_deadline(Duration::from_secs(120));
+/// ```
+#[derive(Debug, Clone)]
+pub struct PollOptions {
+    /// Wait before the first poll
+    pub initial_delay: Duration,
+    /// Wait between the first and second polls
+    pub interval: Duration,
+    /// Factor the interval grows by after each poll (1.0 or less polls at a fixed interval)
+    pub backoff: f64,
+    /// Longest wait between polls when backing off
+    pub max_interval: Duration,
+    /// Give up once this much time has passed since the wait started
+    pub deadline: Option<Duration>,
+    /// Give up after this many polls
+    pub max_polls: Option<u32>,
+    /// Stop waiting with [`PeerCatError::Cancelled`] once this token is cancelled
+    pub cancellation: Option<CancellationToken>,
+}
+
+impl Default for PollOptions {
+    fn default() -> Self {
+        Self {
+            initial_delay: Duration::ZERO,
+            interval: Duration::from_secs(2),
+            backoff: 1.0,
+            max_interval: Duration::from_secs(30),
+            deadline: Some(Duration::from_secs(300)),
+            max_polls: None,
+            cancellation: None,
+        }
+    }
+}
+
+impl PollOptions {
+    /// Create poll options with the defaults
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the wait before the first poll
+    pub fn with_initial_delay(mut self, delay: Duration) -> Self {
+        self.initial_delay = delay;
+        self
+    }
+
+    /// Set the wait between polls
+    pub fn with_interval(mut self, interval: Duration) -> Self {
+        self.interval = interval;
+        self
+    }
+
+    /// Grow the interval by `factor` after each poll, up to `max_interval`
+    pub fn with_backoff(mut self, factor: f64, max_interval: Duration) -> Self {
+        self.backoff = factor;
+        self.max_interval = max_interval;
+        self
+    }
+
+    /// Set the overall deadline
+    pub fn with_deadline(mut self, deadline: Duration) -> Self {
+        self.deadline = Some(deadline);
+        self
+    }
+
+    /// Wait without a deadline (bounded only by `max_polls`, if set)
+    pub fn without_deadline(mut self) -> Self {
+        self.deadline = None;
+        self
+    }
+
+    /// Set the maximum number of polls
+    pub fn with_max_polls(mut self, max_polls: u32) -> Self {
+        self.max_polls = Some(max_polls);
+        self
+    }
+
+    /// Stop waiting as soon as `token` is cancelled, even mid-request
+    pub fn with_cancellation(mut self, token: CancellationToken) -> Self {
+        self.cancellation = Some(token);
+        self
+    }
+
+    /// Poll `fetch` until `done` accepts a value, or the deadline or poll limit is reached
+    async fn run<T, F, Fut>(&self, mut fetch: F, done: impl Fn(&T) -> bool) -> Result<T>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Result<T>>,
+    {
+        let start = Instant::now();
+        let mut interval = self.interval;
+        let mut polls = 0;
+        let token = self.cancellation.as_ref();
+        until_cancelled(token, rt::sleep(self.initial_delay)).await?;
+
+        loop {
+            let value = until_cancelled(token, fetch()).await??;
+            polls += 1;
+            if done(&value) {
+                return Ok(value);
+            }
+
+            let elapsed = start.elapsed();
+            let remaining = self
+                .deadline
+                .map(|deadline| deadline.saturating_sub(elapsed));
+            if remaining == Some(Duration::ZERO) || self.max_polls.is_some_and(|max| polls >= max) {
+                return Err(PeerCatError::DeadlineExceeded { elapsed, polls });
+            }
+
+            let delay = remaining.map_or(interval, |remaining| interval.min(remaining));
+            until_cancelled(token, rt::sleep(delay)).await?;
+            interval = Duration::try_from_secs_f64(interval.as_secs_f64() * self.backoff.max(1.0))
+                .map_or(self.max_interval, |next| next.min(self.max_interval));
+        }
+    }
+}
+
+/// Run `future` to completion, or stop early if `token` is cancelled
+pub(crate) async fn until_cancelled<F: Future>(
+    token: Option<&CancellationToken>,
+    future: F,
+) -> Result<F::Output> {
+    match token {
+        Some(token) => token
+            .run_until_cancelled(future)
+            .await
+            .ok_or(PeerCatError::Cancelled),
+        None => Ok(future.await),
+    }
+}
+
+impl PeerCat {
+    /// Wait until a generation's usage record is no longer pending
+    ///
+    /// `usage_id` is the history item ID. Returns the completed (or refunded) item, or
+    /// [`PeerCatError::DeadlineExceeded`] if it's still pending when `options`
+    /// says to give up.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use peercat::{PeerCat, PollOptions};
+    /// use std::time::Duration;
+    ///
+    /// # async fn example() -> peercat::Result<()> {
+    /// let client = PeerCat::new("pcat_live_xxx")?;
+    ///
+    /// let item = client
+    ///     .wait_for_generation("use_123", PollOptions::new().with_deadline(Duration::from_secs(60)))
+    ///     .await?;
+    /// println!("{:?}: {:?}", item.status, item.image_url);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn wait_for_generation(
+        &self,
+        usage_id: &str,
+        options: PollOptions,
+    ) -> Result<HistoryItem> {
+        options
+            .run(
+                || self.get_history_item(usage_id),
+                |item| item.status != HistoryStatus::Pending,
+            )
+            .await
+    }
+
+    /// Wait until an on-chain generation completes, fails, or is refunded
+    ///
+    /// Returns the final status, or [`PeerCatError::DeadlineExceeded`] if the
+    /// generation is still in progress when `options` says to give up.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use peercat::{OnChainStatus, PeerCat, PollOptions};
+    /// use std::time::Duration;
+    ///
+    /// # async fn example() -> peercat::Result<()> {
+    /// let client = PeerCat::new("pcat_live_xxx")?;
+    ///
+    /// let options = PollOptions::new()
+    ///     .with_initial_delay(Duration::from_secs(5))
+    ///     .with_backoff(1.5, Duration::from_secs(15));
+    /// let status = client.wait_for_onchain_completion("5xyz...", options).await?;
+    /// if status.status == OnChainStatus::Completed {
+    ///     println!("Image: {:?}", status.image_url);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn wait_for_onchain_completion(
+        &self,
+        tx_signature: &str,
+        options: PollOptions,
+    ) -> Result<OnChainGenerationStatus> {
+        options
+            .run(
+                || self.get_onchain_status(tx_signature),
+                |status| status.status.is_terminal(),
+            )
+            .await
+    }
+}
@@ -0,0 +1,105 @@
+//! Redaction of user-configured fields from request data the SDK writes out
+
+use std::sync::Arc;
+
+use serde_json::Value;
+
+/// Replacement for redacted values
+pub(crate) const REDACTED: &str = "[REDACTED]";
+
+/// Field paths whose values are replaced before request data is logged
+///
+/// A path is a dot-separated list of field names, such as `prompt` or
+/// `options.negativePrompt`. It matches a field wherever it appears, as
+/// long as the field's location ends with the path; arrays don't add a
+/// segment. Names are compared ignoring case, `_` and `-`, so
+/// `callback_url` also matches `callbackUrl`.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct Redactor {
+    paths: Arc<[Vec<String>]>,
+}
+
+impl Redactor {
+    pub fn new(paths: &[String]) -> Self {
+        Self {
+            paths: paths
+                .iter()
+                .map(|path| path.split('.').map(normalize).collect())
+                .filter(|path: &Vec<String>| path.iter().all(|segment| !segment.is_empty()))
+                .collect(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.paths.is_empty()
+    }
+
+    /// Replace the values of matching fields in `value`
+    pub fn redact(&self, value: &mut Value) {
+        if !self.is_empty() {
+            self.walk(value, &mut Vec::new(), &mut |value| {
+                *value = Value::String(REDACTED.to_string())
+            });
+        }
+    }
+
+    /// Replace any redacted string values of `body` appearing in `text`
+    ///
+    /// For messages that may echo request fields back, like API errors.
+    #[cfg(feature = "audit")]
+    pub fn scrub(&self, body: &Value, text: &str) -> String {
+        let mut secrets = Vec::new();
+        self.walk(&mut body.clone(), &mut Vec::new(), &mut |value| {
+            collect_strings(value, &mut secrets)
+        });
+        // Longest first, so a value containing another is replaced whole
+        secrets.sort_by_key(|s| std::cmp::Reverse(s.len()));
+        secrets.iter().fold(text.to_string(), |text, secret| {
+            text.replace(secret, REDACTED)
+        })
+    }
+
+    /// Call `f` on every field matching a path
+    fn walk(&self, value: &mut Value, location: &mut Vec<String>, f: &mut impl FnMut(&mut Value)) {
+        match value {
+            Value::Object(fields) => {
+                for (name, value) in fields {
+                    location.push(normalize(name));
+                    if self.matches(location) {
+                        f(value);
+                    } else {
+                        self.walk(value, location, f);
+                    }
+                    location.pop();
+                }
+            }
+            Value::Array(items) => {
+                for item in items {
+                    self.walk(item, location, f);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn matches(&self, location: &[String]) -> bool {
+        self.paths.iter().any(|path| location.ends_with(path))
+    }
+}
+
+fn normalize(name: &str) -> String {
+    name.chars()
+        .filter(|c| *c != '_' && *c != '-')
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+#[cfg(feature = "audit")]
+fn collect_strings(value: &Value, out: &mut Vec<String>) {
+    match value {
+        Value::String(s) if !s.is_empty() => out.push(s.clone()),
+        Value::Array(items) => items.iter().for_each(|v| collect_strings(v, out)),
+        Value::Object(fields) => fields.values().for_each(|v| collect_strings(v, out)),
+        _ => {}
+    }
+}
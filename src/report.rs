@@ -0,0 +1,91 @@
+//! Reporting failed calls to an error tracker
+
+use std::fmt;
+use std::time::Duration;
+
+use crate::error::PeerCatError;
+
+/// The call an error came from, as passed to [`ErrorReporter::report`]
+#[derive(Debug, Clone, Copy)]
+#[non_exhaustive]
+pub struct ErrorContext<'a> {
+    pub method: &'a str,
+    /// Request path without its query string
+    pub endpoint: &'a str,
+    /// Time taken by the call, including retries
+    pub duration: Duration,
+}
+
+/// Receives every error a call fails with, after any retries
+///
+/// Set one with [`PeerCatConfig::with_error_reporter`](crate::PeerCatConfig::with_error_reporter).
+/// `report` is called once per failed call, inline before the error is
+/// returned, so it should be cheap and must not block.
+///
+/// # Example
+///
+/// ```no_run
+/// use peercat::{ErrorContext, ErrorReporter, PeerCat, PeerCatConfig, PeerCatError};
+///
+/// #[derive(Debug)]
+/// struct LogReporter;
+///
+/// impl ErrorReporter for LogReporter {
+///     fn report(&self, error: &PeerCatError, context: &ErrorContext<'_>) {
+///         eprintln!("{} {} failed: {}", context.method, context.endpoint, error);
+///     }
+/// }
+///
+/// let client = PeerCat::with_config(
+///     PeerCatConfig::new("pcat_live_xxx").with_error_reporter(LogReporter),
+/// )?;
+/// # Ok::<(), peercat::PeerCatError>(())
+/// ```
+pub trait ErrorReporter: Send + Sync + fmt::Debug {
+    /// Report one failed call
+    fn report(&self, error: &PeerCatError, context: &ErrorContext<'_>);
+}
+
+/// [`ErrorReporter`] that captures errors with Sentry (requires the `sentry` feature)
+///
+/// Errors are sent through the current Sentry hub, so `sentry::init` must
+/// have been called. Each event is tagged with `peercat.method`,
+/// `peercat.endpoint` and, if the API returned one, `peercat.error_code`.
+/// Cancelled calls aren't reported.
+///
+/// # Example
+///
+/// ```no_run
+/// use peercat::{PeerCat, PeerCatConfig, SentryReporter};
+///
+/// let client = PeerCat::with_config(
+///     PeerCatConfig::new("pcat_live_xxx").with_error_reporter(SentryReporter),
+/// )?;
+/// # Ok::<(), peercat::PeerCatError>(())
+/// ```
+#[cfg(feature = "sentry")]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SentryReporter;
+
+#[cfg(feature = "sentry")]
+impl ErrorReporter for SentryReporter {
+    fn report(&self, error: &PeerCatError, context: &ErrorContext<'_>) {
+        if matches!(error, PeerCatError::Cancelled) {
+            return;
+        }
+        sentry_core::with_scope(
+            |scope| {
+                scope.set_tag("peercat.method", context.method);
+                scope.set_tag("peercat.endpoint", context.endpoint);
+                if let Some(code) = error.code() {
+                    scope.set_tag("peercat.error_code", code);
+                }
+                scope.set_extra(
+                    "peercat.duration_ms",
+                    (context.duration.as_millis() as u64).into(),
+                );
+            },
+            || sentry_core::capture_error(error),
+        );
+    }
+}
@@ -0,0 +1,204 @@
+//! Client-wide limit on retries
+
+use std::collections::VecDeque;
+use std::fmt;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use web_time::Instant;
+
+/// Default length of the window requests and retries are counted over
+const DEFAULT_WINDOW: Duration = Duration::from_secs(10);
+
+/// Current state of a [`RetryBudget`]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RetryBudgetStatus {
+    /// Requests made in the current window
+    pub requests: u64,
+    /// Retries made in the current window
+    pub retries: u64,
+    /// Retries still allowed in the current window
+    pub available: u64,
+    /// Retries refused because the budget was spent, since the budget was created
+    pub rejected: u64,
+}
+
+/// Outcome of asking a [`RetryBudget`] for a retry
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum RetryDecision {
+    Allowed,
+    /// The budget is spent; `opened` is set for the first refusal since a retry was allowed
+    Refused {
+        opened: bool,
+    },
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Bucket {
+    second: u64,
+    requests: u64,
+    retries: u64,
+}
+
+#[derive(Debug)]
+struct BudgetState {
+    created: Instant,
+    buckets: VecDeque<Bucket>,
+    rejected: u64,
+    exhausted: bool,
+}
+
+/// Caps retries at a share of recent requests, across every task using a client
+///
+/// During a widespread outage every request fails, and unlimited retries
+/// multiply the load on the API. With a budget, retries stop once they
+/// exceed `ratio` of the requests in the last 10 seconds (plus a small
+/// allowance so low-traffic clients can still retry) and failures are
+/// returned immediately instead.
+///
+/// Clones share the same budget.
+///
+/// # Example
+///
+/// ```no_run
+/// use peercat::{PeerCat, PeerCatConfig, RetryBudget};
+///
+/// // At most 20% of requests may be retries
+/// let budget = RetryBudget::new(0.2);
+/// let client = PeerCat::with_config(
+///     PeerCatConfig::new("pcat_live_xxx").with_retry_budget(budget.clone()),
+/// )?;
+///
+/// println!("{:?}", budget.status());
+/// # Ok::<(), peercat::PeerCatError>(())
+/// ```
+#[derive(Clone)]
+pub struct RetryBudget {
+    ratio: f64,
+    min_retries: u64,
+    window: Duration,
+    state: Arc<Mutex<BudgetState>>,
+}
+
+impl RetryBudget {
+    /// Allow retries up to `ratio` of requests (e.g. `0.2` for 20%)
+    pub fn new(ratio: f64) -> Self {
+        Self {
+            ratio: ratio.max(0.0),
+            min_retries: 10,
+            window: DEFAULT_WINDOW,
+            state: Arc::new(Mutex::new(BudgetState {
+                created: Instant::now(),
+                buckets: VecDeque::new(),
+                rejected: 0,
+                exhausted: false,
+            })),
+        }
+    }
+
+    /// Set the number of retries always allowed per window (default: 10)
+    pub fn with_min_retries(mut self, min_retries: u64) -> Self {
+        self.min_retries = min_retries;
+        self
+    }
+
+    /// Set the window requests and retries are counted over (default: 10 seconds)
+    pub fn with_window(mut self, window: Duration) -> Self {
+        self.window = window.max(Duration::from_secs(1));
+        self
+    }
+
+    /// Snapshot of the current budget
+    pub fn status(&self) -> RetryBudgetStatus {
+        let mut state = self.lock();
+        self.prune(&mut state);
+        let (requests, retries) = totals(&state);
+
+        RetryBudgetStatus {
+            requests,
+            retries,
+            available: self.allowed(requests).saturating_sub(retries),
+            rejected: state.rejected,
+        }
+    }
+
+    /// Count a new request
+    pub(crate) fn record_request(&self) {
+        let mut state = self.lock();
+        self.current_bucket(&mut state).requests += 1;
+    }
+
+    /// Spend one retry, unless the budget is exhausted
+    pub(crate) fn try_retry(&self) -> RetryDecision {
+        let mut state = self.lock();
+        self.prune(&mut state);
+        let (requests, retries) = totals(&state);
+
+        if retries >= self.allowed(requests) {
+            state.rejected += 1;
+            let opened = !std::mem::replace(&mut state.exhausted, true);
+            return RetryDecision::Refused { opened };
+        }
+        state.exhausted = false;
+        self.current_bucket(&mut state).retries += 1;
+        RetryDecision::Allowed
+    }
+
+    fn allowed(&self, requests: u64) -> u64 {
+        self.min_retries + (requests as f64 * self.ratio) as u64
+    }
+
+    fn current_bucket<'a>(&self, state: &'a mut BudgetState) -> &'a mut Bucket {
+        self.prune(state);
+        let second = state.created.elapsed().as_secs();
+        if state
+            .buckets
+            .back()
+            .map_or(true, |bucket| bucket.second != second)
+        {
+            state.buckets.push_back(Bucket {
+                second,
+                requests: 0,
+                retries: 0,
+            });
+        }
+        state.buckets.back_mut().expect("bucket was just pushed")
+    }
+
+    /// Drop buckets that have left the window
+    fn prune(&self, state: &mut BudgetState) {
+        let second = state.created.elapsed().as_secs();
+        let window = self.window.as_secs();
+        while state
+            .buckets
+            .front()
+            .is_some_and(|bucket| bucket.second + window <= second)
+        {
+            state.buckets.pop_front();
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, BudgetState> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+fn totals(state: &BudgetState) -> (u64, u64) {
+    state
+        .buckets
+        .iter()
+        .fold((0, 0), |(requests, retries), bucket| {
+            (requests + bucket.requests, retries + bucket.retries)
+        })
+}
+
+impl fmt::Debug for RetryBudget {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RetryBudget")
+            .field("ratio", &self.ratio)
+            .field("min_retries", &self.min_retries)
+            .field("window", &self.window)
+            .field("status", &self.status())
+            .finish()
+    }
+}
@@ -0,0 +1,44 @@
+//! Runtime-specific async primitives
+//!
+//! The timer is selected by feature: `runtime-tokio` (default) uses Tokio's
+//! timer, `runtime-async-io` uses `async-io` (async-std, smol). With neither,
+//! a helper thread is used so the crate still works on any executor.
+
+use std::time::Duration;
+
+/// Wait for `duration` without blocking the executor
+#[cfg(target_arch = "wasm32")]
+pub(crate) async fn sleep(duration: Duration) {
+    gloo_timers::future::sleep(duration).await;
+}
+
+/// Wait for `duration` without blocking the executor
+#[cfg(all(not(target_arch = "wasm32"), feature = "runtime-tokio"))]
+pub(crate) async fn sleep(duration: Duration) {
+    tokio::time::sleep(duration).await;
+}
+
+/// Wait for `duration` without blocking the executor
+#[cfg(all(
+    not(target_arch = "wasm32"),
+    not(feature = "runtime-tokio"),
+    feature = "runtime-async-io"
+))]
+pub(crate) async fn sleep(duration: Duration) {
+    async_io::Timer::after(duration).await;
+}
+
+/// Wait for `duration` without blocking the executor
+#[cfg(all(
+    not(target_arch = "wasm32"),
+    not(feature = "runtime-tokio"),
+    not(feature = "runtime-async-io")
+))]
+pub(crate) async fn sleep(duration: Duration) {
+    let (tx, rx) = tokio::sync::oneshot::channel();
+    std::thread::spawn(move || {
+        std::thread::sleep(duration);
+        let _ = tx.send(());
+    });
+    let _ = rx.await;
+}
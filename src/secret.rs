@@ -0,0 +1,73 @@
+//! Secret value wrapper for credentials
+
+use std::fmt;
+
+use reqwest::header::HeaderValue;
+use zeroize::Zeroizing;
+
+use crate::error::{PeerCatError, Result};
+
+/// A string that holds a secret such as an API key
+///
+/// The contents are never shown in `Debug` output and are zeroed in memory
+/// when the value is dropped. Use [`SecretString::expose_secret`] to read it.
+/// Comparisons take the same time wherever two secrets of equal length differ.
+#[derive(Clone, Default)]
+pub struct SecretString(Zeroizing<String>);
+
+impl SecretString {
+    /// Wrap a secret value
+    pub fn new(secret: impl Into<String>) -> Self {
+        Self(Zeroizing::new(secret.into()))
+    }
+
+    /// Access the secret value
+    pub fn expose_secret(&self) -> &str {
+        &self.0
+    }
+
+    /// Returns true if the secret is empty
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// `Authorization` header value for the secret as a bearer token
+    ///
+    /// The value is marked sensitive so HTTP libraries leave it out of their
+    /// logs, and the formatted copy is wiped.
+    pub(crate) fn bearer_header(&self) -> Result<HeaderValue> {
+        let bearer = Zeroizing::new(format!("Bearer {}", self.expose_secret()));
+        let mut value = HeaderValue::from_str(&bearer).map_err(|_| {
+            PeerCatError::InvalidConfig("API key contains invalid header characters".to_string())
+        })?;
+        value.set_sensitive(true);
+        Ok(value)
+    }
+}
+
+impl PartialEq for SecretString {
+    fn eq(&self, other: &Self) -> bool {
+        let (a, b) = (self.0.as_bytes(), other.0.as_bytes());
+        a.len() == b.len() && a.iter().zip(b).fold(0, |diff, (x, y)| diff | (x ^ y)) == 0
+    }
+}
+
+impl Eq for SecretString {}
+
+impl fmt::Debug for SecretString {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("SecretString([REDACTED])")
+    }
+}
+
+impl From<String> for SecretString {
+    fn from(secret: String) -> Self {
+        Self::new(secret)
+    }
+}
+
+impl From<&str> for SecretString {
+    fn from(secret: &str) -> Self {
+        Self::new(secret)
+    }
+}
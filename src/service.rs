@@ -0,0 +1,341 @@
+//! Tower integration for the HTTP transport
+//!
+//! [`HttpService`] is the SDK's HTTP layer as a [`tower::Service`], and
+//! [`AuthLayer`] and [`RetryLayer`] provide the client's authentication and
+//! retry behavior as reusable middleware. Wrap the service in your own layers
+//! and hand it to the client with
+//! [`PeerCatConfig::with_transport`](crate::PeerCatConfig::with_transport).
+//!
+//! # Example
+//!
+//! ```no_run
+//! use peercat::service::HttpService;
+//! use peercat::{PeerCat, PeerCatConfig};
+//! use tower::ServiceBuilder;
+//!
+//! let transport = ServiceBuilder::new()
+//!     .map_request(|request: peercat::service::HttpRequest| {
+//!         println!("{} {}", request.method(), request.uri());
+//!         request
+//!     })
+//!     .service(HttpService::new());
+//!
+//! let client = PeerCat::with_config(
+//!     PeerCatConfig::new("pcat_live_xxx").with_transport(transport),
+//! )?;
+//! # Ok::<(), peercat::PeerCatError>(())
+//! ```
+
+use std::fmt;
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use std::time::Duration;
+
+use http::header::{HeaderMap, AUTHORIZATION, CONTENT_TYPE, USER_AGENT};
+use reqwest::{Method, StatusCode};
+use tower::util::BoxCloneSyncService;
+use tower::{Layer, Service, ServiceExt};
+
+use crate::client::{backoff_delay, transport_error, RawResponse, USER_AGENT as SDK_USER_AGENT};
+use crate::error::{PeerCatError, RateLimitInfo, Result};
+use crate::rt;
+use crate::secret::SecretString;
+
+/// HTTP request handled by the transport
+pub type HttpRequest = http::Request<Vec<u8>>;
+
+/// HTTP response returned by the transport
+pub type HttpResponse = http::Response<Vec<u8>>;
+
+/// Error type accepted from user-supplied services
+pub type BoxError = Box<dyn std::error::Error + Send + Sync>;
+
+type BoxFuture<T> = Pin<Box<dyn Future<Output = Result<T>> + Send>>;
+
+/// Per-request timeout set by the client and applied by [`HttpService`]
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct RequestTimeout(pub Duration);
+
+// ============ Transport ============
+
+/// A type-erased HTTP service used by the client to send requests
+///
+/// Errors returned by the service are passed through when they are a
+/// [`PeerCatError`], and wrapped in [`PeerCatError::Transport`] otherwise.
+#[derive(Clone)]
+pub struct Transport(BoxCloneSyncService<HttpRequest, HttpResponse, PeerCatError>);
+
+impl Transport {
+    /// Wrap a service
+    pub fn new<S>(service: S) -> Self
+    where
+        S: Service<HttpRequest, Response = HttpResponse> + Clone + Send + Sync + 'static,
+        S::Error: Into<BoxError>,
+        S::Future: Send + 'static,
+    {
+        Self(BoxCloneSyncService::new(service.map_err(into_error)))
+    }
+
+    /// Send one attempt of a client request
+    pub(crate) async fn send(
+        &self,
+        method: &Method,
+        url: &str,
+        timeout: Duration,
+        body: Option<&serde_json::Value>,
+        headers: &HeaderMap,
+        key: &SecretString,
+    ) -> Result<RawResponse> {
+        let body = body
+            .map(serde_json::to_vec)
+            .transpose()?
+            .unwrap_or_default();
+        let mut request = http::Request::builder()
+            .method(method.clone())
+            .uri(url)
+            .header(AUTHORIZATION, key.bearer_header()?)
+            .header(CONTENT_TYPE, "application/json")
+            .header(USER_AGENT, SDK_USER_AGENT)
+            .body(body)
+            .map_err(|e| PeerCatError::InvalidConfig(e.to_string()))?;
+        request.headers_mut().extend(headers.clone());
+        request.extensions_mut().insert(RequestTimeout(timeout));
+
+        let response = self.0.clone().oneshot(request).await?;
+        let (parts, body) = response.into_parts();
+        Ok(RawResponse {
+            status: parts.status,
+            headers: parts.headers,
+            body,
+        })
+    }
+}
+
+impl fmt::Debug for Transport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Transport")
+    }
+}
+
+fn into_error(error: impl Into<BoxError>) -> PeerCatError {
+    match error.into().downcast::<PeerCatError>() {
+        Ok(error) => *error,
+        Err(error) => PeerCatError::Transport(error),
+    }
+}
+
+// ============ HTTP Service ============
+
+/// The SDK's HTTP layer as a [`tower::Service`]
+///
+/// Sends requests with reqwest and reads the whole response body.
+#[derive(Debug, Clone)]
+pub struct HttpService {
+    client: reqwest::Client,
+}
+
+impl HttpService {
+    /// Create a service with the SDK's default HTTP client
+    pub fn new() -> Self {
+        Self::from_client(
+            reqwest::Client::builder()
+                .user_agent(SDK_USER_AGENT)
+                .build()
+                .expect("Failed to create HTTP client"),
+        )
+    }
+
+    /// Create a service that sends requests with an existing reqwest client
+    pub fn from_client(client: reqwest::Client) -> Self {
+        Self { client }
+    }
+}
+
+impl Default for HttpService {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Service<HttpRequest> for HttpService {
+    type Response = HttpResponse;
+    type Error = PeerCatError;
+    type Future = BoxFuture<HttpResponse>;
+
+    fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<()>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, request: HttpRequest) -> Self::Future {
+        let client = self.client.clone();
+        Box::pin(async move {
+            let timeout = request.extensions().get::<RequestTimeout>().map(|t| t.0);
+            let mut request = reqwest::Request::try_from(request).map_err(transport_error)?;
+            if timeout.is_some() {
+                *request.timeout_mut() = timeout;
+            }
+
+            let response = client.execute(request).await.map_err(transport_error)?;
+            let mut builder = http::Response::builder().status(response.status());
+            if let Some(headers) = builder.headers_mut() {
+                *headers = response.headers().clone();
+            }
+            let body = response.bytes().await.map_err(transport_error)?.to_vec();
+            builder
+                .body(body)
+                .map_err(|e| PeerCatError::Transport(e.into()))
+        })
+    }
+}
+
+// ============ Authentication ============
+
+/// Layer that adds a bearer API key to requests without an `Authorization` header
+#[derive(Debug, Clone)]
+pub struct AuthLayer {
+    api_key: SecretString,
+}
+
+impl AuthLayer {
+    /// Authenticate requests with an API key
+    pub fn new(api_key: impl Into<String>) -> Self {
+        Self {
+            api_key: SecretString::new(api_key),
+        }
+    }
+}
+
+impl<S> Layer<S> for AuthLayer {
+    type Service = Auth<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        Auth {
+            inner,
+            api_key: self.api_key.clone(),
+        }
+    }
+}
+
+/// Service produced by [`AuthLayer`]
+#[derive(Debug, Clone)]
+pub struct Auth<S> {
+    inner: S,
+    api_key: SecretString,
+}
+
+impl<S> Service<HttpRequest> for Auth<S>
+where
+    S: Service<HttpRequest>,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = S::Future;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<std::result::Result<(), S::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, mut request: HttpRequest) -> Self::Future {
+        if !request.headers().contains_key(AUTHORIZATION) {
+            if let Ok(value) = self.api_key.bearer_header() {
+                request.headers_mut().insert(AUTHORIZATION, value);
+            }
+        }
+        self.inner.call(request)
+    }
+}
+
+// ============ Retries ============
+
+/// Layer that retries server errors, rate limits, and transient failures
+///
+/// Uses the same exponential backoff as the client, honoring `Retry-After`.
+/// When this layer is part of a client transport, set
+/// [`with_max_retries(0)`](crate::PeerCatConfig::with_max_retries) on the
+/// client so attempts aren't retried twice.
+#[derive(Debug, Clone)]
+pub struct RetryLayer {
+    max_retries: u32,
+}
+
+impl RetryLayer {
+    /// Retry each request up to `max_retries` times
+    pub fn new(max_retries: u32) -> Self {
+        Self { max_retries }
+    }
+}
+
+impl<S> Layer<S> for RetryLayer {
+    type Service = Retry<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        Retry {
+            inner,
+            max_retries: self.max_retries,
+        }
+    }
+}
+
+/// Service produced by [`RetryLayer`]
+#[derive(Debug, Clone)]
+pub struct Retry<S> {
+    inner: S,
+    max_retries: u32,
+}
+
+impl<S> Service<HttpRequest> for Retry<S>
+where
+    S: Service<HttpRequest, Response = HttpResponse, Error = PeerCatError> + Clone + Send + 'static,
+    S::Future: Send,
+{
+    type Response = HttpResponse;
+    type Error = PeerCatError;
+    type Future = BoxFuture<HttpResponse>;
+
+    fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<()>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, request: HttpRequest) -> Self::Future {
+        let mut inner = self.inner.clone();
+        let max_retries = self.max_retries;
+
+        Box::pin(async move {
+            let mut attempt = 0;
+            loop {
+                let result = inner.ready().await?.call(clone_request(&request)).await;
+                let retry_after = match &result {
+                    Ok(response) if is_retryable_status(response.status()) => {
+                        RateLimitInfo::from_headers(response.headers())
+                            .and_then(|info| info.retry_after)
+                    }
+                    Err(error) if error.is_retryable() => error.retry_after(),
+                    _ => return result,
+                };
+
+                if attempt >= max_retries {
+                    return result;
+                }
+                rt::sleep(backoff_delay(attempt, retry_after)).await;
+                attempt += 1;
+            }
+        })
+    }
+}
+
+fn is_retryable_status(status: StatusCode) -> bool {
+    status.is_server_error() || status == StatusCode::TOO_MANY_REQUESTS
+}
+
+fn clone_request(request: &HttpRequest) -> HttpRequest {
+    let mut clone = http::Request::new(request.body().clone());
+    *clone.method_mut() = request.method().clone();
+    *clone.uri_mut() = request.uri().clone();
+    *clone.version_mut() = request.version();
+    *clone.headers_mut() = request.headers().clone();
+    if let Some(timeout) = request.extensions().get::<RequestTimeout>() {
+        clone.extensions_mut().insert(*timeout);
+    }
+    clone
+}
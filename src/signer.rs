@@ -0,0 +1,141 @@
+//! Wallet signing for API key creation
+//!
+//! Enabled with the `signer` feature. Produces the message, signature, and
+//! public key that [`CreateKeyParams`] requires from an ed25519 (Solana)
+//! keypair.
+//!
+//! ```no_run
+//! use peercat::PeerCat;
+//! use peercat::signer::WalletSigner;
+//!
+//! # async fn example() -> peercat::Result<()> {
+//! let client = PeerCat::new("pcat_live_xxx")?;
+//!
+//! let signer = WalletSigner::from_base58("base58SecretKey...")?;
+//! let new_key = client
+//!     .create_key(signer.create_key_params(Some("Production App".to_string())))
+//!     .await?;
+//!
+//! println!("API Key: {}", new_key.key);
+//! # Ok(())
+//! # }
+//! ```
+
+use ed25519_dalek::{Signer, SigningKey};
+use web_time::{SystemTime, UNIX_EPOCH};
+use zeroize::Zeroizing;
+
+use crate::error::{PeerCatError, Result};
+use crate::types::CreateKeyParams;
+
+/// Prefix of the message signed to create an API key
+const CREATE_KEY_MESSAGE_PREFIX: &str = "Create API key for PeerCat";
+
+/// Build the canonical message signed to create an API key
+///
+/// The message binds the wallet and a Unix timestamp (in seconds) so a
+/// captured signature cannot be replayed later.
+pub fn create_key_message(public_key: &str, timestamp: u64) -> String {
+    format!(
+        "{}\nWallet: {}\nTimestamp: {}",
+        CREATE_KEY_MESSAGE_PREFIX, public_key, timestamp
+    )
+}
+
+/// An ed25519 wallet keypair used to sign PeerCat requests
+pub struct WalletSigner {
+    key: SigningKey,
+}
+
+impl WalletSigner {
+    /// Create a signer from a 32-byte ed25519 secret key
+    pub fn from_secret_key_bytes(secret: &[u8; 32]) -> Self {
+        Self {
+            key: SigningKey::from_bytes(secret),
+        }
+    }
+
+    /// Create a signer from a 64-byte Solana keypair (secret key followed by
+    /// public key), as stored in `solana-keygen` JSON files
+    ///
+    /// # Errors
+    ///
+    /// Returns `PeerCatError::Signer` if the public half does not match the
+    /// secret half.
+    pub fn from_keypair_bytes(keypair: &[u8; 64]) -> Result<Self> {
+        let key = SigningKey::from_keypair_bytes(keypair)
+            .map_err(|e| PeerCatError::Signer(e.to_string()))?;
+        Ok(Self { key })
+    }
+
+    /// Create a signer from a base58-encoded 64-byte keypair, as exported by
+    /// most Solana wallets
+    ///
+    /// # Errors
+    ///
+    /// Returns `PeerCatError::Signer` if the string is not valid base58 or
+    /// does not decode to a valid 64-byte keypair.
+    pub fn from_base58(encoded: &str) -> Result<Self> {
+        // Wipe the decoded secret key once the signing key holds it
+        let bytes = Zeroizing::new(
+            bs58::decode(encoded.trim())
+                .into_vec()
+                .map_err(|e| PeerCatError::Signer(e.to_string()))?,
+        );
+        if bytes.len() != 64 {
+            return Err(PeerCatError::Signer(format!(
+                "expected 64-byte keypair, got {} bytes",
+                bytes.len()
+            )));
+        }
+        let mut keypair = Zeroizing::new([0u8; 64]);
+        keypair.copy_from_slice(&bytes);
+        Self::from_keypair_bytes(&keypair)
+    }
+
+    /// Wallet public key (base58)
+    pub fn public_key(&self) -> String {
+        bs58::encode(self.key.verifying_key().as_bytes()).into_string()
+    }
+
+    /// Sign a message, returning the base58-encoded signature
+    pub fn sign(&self, message: &str) -> String {
+        bs58::encode(self.key.sign(message.as_bytes()).to_bytes()).into_string()
+    }
+
+    #[cfg(feature = "solana")]
+    pub(crate) fn public_key_bytes(&self) -> [u8; 32] {
+        self.key.verifying_key().to_bytes()
+    }
+
+    #[cfg(feature = "solana")]
+    pub(crate) fn sign_bytes(&self, message: &[u8]) -> [u8; 64] {
+        self.key.sign(message).to_bytes()
+    }
+
+    /// Build signed parameters for [`PeerCat::create_key`](crate::PeerCat::create_key)
+    /// using the canonical message and the current time
+    pub fn create_key_params(&self, name: Option<String>) -> CreateKeyParams {
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0);
+        let public_key = self.public_key();
+        let message = create_key_message(&public_key, timestamp);
+        let signature = self.sign(&message);
+
+        let params = CreateKeyParams::new(message, signature, public_key);
+        match name {
+            Some(name) => params.with_name(name),
+            None => params,
+        }
+    }
+}
+
+impl std::fmt::Debug for WalletSigner {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("WalletSigner")
+            .field("public_key", &self.public_key())
+            .finish_non_exhaustive()
+    }
+}
@@ -0,0 +1,66 @@
+//! Coalescing of identical in-flight GET requests
+
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+
+use tokio::sync::OnceCell;
+
+use crate::error::Result;
+
+/// Response body shared by every caller of a coalesced request
+type SharedBody = Arc<Vec<u8>>;
+
+/// Outcome of a coalesced request, kept until its leader finishes
+type Call = Arc<OnceCell<Result<SharedBody>>>;
+
+/// Shares one upstream request between concurrent callers of the same GET
+///
+/// Shared by all clones of a client. The first caller for a key and path
+/// sends the request; callers arriving while it's in flight wait for its
+/// outcome instead of sending their own, and get a copy of its error if it
+/// fails. If the first caller is dropped, the next waiting caller sends the
+/// request. Finished calls are forgotten, so later callers start afresh.
+#[derive(Debug, Default)]
+pub(crate) struct SingleFlight {
+    calls: Mutex<HashMap<(usize, String), Call>>,
+}
+
+impl SingleFlight {
+    /// Run `fetch` for `path` with the key at `key`, or join the same call in flight
+    pub async fn run<F, Fut>(&self, key: usize, path: &str, fetch: F) -> Result<SharedBody>
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<Vec<u8>>>,
+    {
+        let id = (key, path.to_string());
+        let call = self.lock().entry(id.clone()).or_default().clone();
+        let mut own_error = None;
+        let outcome = call
+            .get_or_init(|| async {
+                fetch().await.map(Arc::new).map_err(|e| {
+                    let shared = e.duplicate();
+                    own_error = Some(e);
+                    shared
+                })
+            })
+            .await;
+
+        let mut calls = self.lock();
+        if calls.get(&id).is_some_and(|c| Arc::ptr_eq(c, &call)) {
+            calls.remove(&id);
+        }
+        drop(calls);
+
+        // The caller that sent the request keeps its original error
+        match (own_error, outcome) {
+            (Some(e), _) => Err(e),
+            (None, Ok(body)) => Ok(body.clone()),
+            (None, Err(e)) => Err(e.duplicate()),
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, HashMap<(usize, String), Call>> {
+        self.calls.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
@@ -0,0 +1,637 @@
+//! Paying for on-chain generations from a Solana wallet
+//!
+//! Requires the `solana` feature. [`SolanaPayer`] sends the SOL transfer for
+//! a [`PromptSubmission`] through a Solana RPC node, with the submission's
+//! memo attached, and waits for the transaction to reach the requested
+//! commitment. [`SolanaRpc::verify_on_chain`] checks a payment against the
+//! chain directly. On-chain programs that pay from a PDA can use
+//! [`payment_instructions`] to build the instructions to invoke instead.
+//!
+//! ```no_run
+//! use peercat::solana::{Commitment, PaymentOptions, SolanaPayer};
+//! use peercat::signer::WalletSigner;
+//! use peercat::{PeerCat, SubmitPromptParams};
+//!
+//! # async fn example() -> peercat::Result<()> {
+//! let client = PeerCat::new("pcat_live_xxx")?;
+//! let payer = SolanaPayer::new(
+//!     "https://api.mainnet-beta.solana.com",
+//!     WalletSigner::from_base58("base58SecretKey...")?,
+//! );
+//!
+//! let submission = client.submit_prompt(SubmitPromptParams::new("A majestic dragon")).await?;
+//!
+//! // Outbid congestion so the payment lands before the submission expires
+//! let options = PaymentOptions::new()
+//!     .with_priority_fee(50_000)
+//!     .with_commitment(Commitment::Confirmed);
+//! let tx_signature = payer.pay(&submission, &options).await?;
+//! # Ok(())
+//! # }
+//! ```
+
+use std::time::Duration;
+
+use base64::prelude::*;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use web_time::Instant;
+
+use crate::client::transport_error;
+use crate::error::{PeerCatError, Result};
+use crate::rt;
+use crate::signer::WalletSigner;
+use crate::types::PromptSubmission;
+
+/// System program, which handles SOL transfers
+const SYSTEM_PROGRAM_ID: &str = "11111111111111111111111111111111";
+/// SPL Memo program (v2)
+const MEMO_PROGRAM_ID: &str = "MemoSq4gqABAXKb96qnH8TysNcWxMyWCqXgDLGmfcHr";
+/// Compute budget program, which sets priority fees
+const COMPUTE_BUDGET_PROGRAM_ID: &str = "ComputeBudget111111111111111111111111111111";
+
+/// How often to check whether a sent transaction has been confirmed
+const CONFIRM_POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// How final a transaction must be before it counts as landed
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Commitment {
+    /// Seen by the RPC node's leader; may still be dropped
+    Processed,
+    /// Voted on by a supermajority of the cluster
+    #[default]
+    Confirmed,
+    /// Rooted; can no longer be rolled back
+    Finalized,
+}
+
+/// Fee and confirmation settings for a payment
+#[derive(Debug, Clone, PartialEq)]
+pub struct PaymentOptions {
+    /// Priority fee, as a compute unit price in micro-lamports (default: none)
+    pub compute_unit_price: Option<u64>,
+    /// Compute unit limit for the transaction (default: the cluster's)
+    pub compute_unit_limit: Option<u32>,
+    /// Commitment to wait for before returning
+    pub commitment: Commitment,
+    /// How long to wait for the commitment before giving up
+    pub confirm_timeout: Duration,
+}
+
+impl Default for PaymentOptions {
+    fn default() -> Self {
+        Self {
+            compute_unit_price: None,
+            compute_unit_limit: None,
+            commitment: Commitment::default(),
+            confirm_timeout: Duration::from_secs(60),
+        }
+    }
+}
+
+impl PaymentOptions {
+    /// Create payment options with the defaults
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the priority fee, as a compute unit price in micro-lamports
+    pub fn with_priority_fee(mut self, micro_lamports_per_unit: u64) -> Self {
+        self.compute_unit_price = Some(micro_lamports_per_unit);
+        self
+    }
+
+    /// Set the compute unit limit, which caps the total priority fee
+    pub fn with_compute_unit_limit(mut self, units: u32) -> Self {
+        self.compute_unit_limit = Some(units);
+        self
+    }
+
+    /// Set the commitment to wait for
+    pub fn with_commitment(mut self, commitment: Commitment) -> Self {
+        self.commitment = commitment;
+        self
+    }
+
+    /// Set how long to wait for the commitment
+    pub fn with_confirm_timeout(mut self, timeout: Duration) -> Self {
+        self.confirm_timeout = timeout;
+        self
+    }
+}
+
+/// Outcome of checking a submission's payment against the chain
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PaymentVerification {
+    /// The transaction pays the submission in full, with its memo
+    Verified {
+        /// Slot the transaction landed in
+        slot: u64,
+        /// Lamports transferred to the payment address
+        lamports: u64,
+    },
+    /// The RPC node has no such transaction: the payment never landed (or
+    /// hasn't reached the commitment yet)
+    NotFound,
+    /// The transaction landed but failed, so nothing was transferred
+    Failed {
+        /// Error reported by the runtime
+        error: String,
+    },
+    /// The transaction landed but doesn't pay the submission
+    Mismatch {
+        /// Lamports transferred to the payment address (0 if sent elsewhere)
+        lamports: u64,
+        /// Whether the submission's memo was attached
+        memo_matches: bool,
+    },
+}
+
+impl PaymentVerification {
+    /// Returns true if the payment landed and matches the submission
+    pub fn is_verified(&self) -> bool {
+        matches!(self, PaymentVerification::Verified { .. })
+    }
+}
+
+/// A minimal Solana JSON-RPC client for checking payments
+#[derive(Debug, Clone)]
+pub struct SolanaRpc {
+    url: String,
+    http: reqwest::Client,
+}
+
+impl SolanaRpc {
+    /// Create a client for the RPC node at `url`
+    pub fn new(url: impl Into<String>) -> Self {
+        Self {
+            url: url.into(),
+            http: reqwest::Client::new(),
+        }
+    }
+
+    /// Check a transaction against the submission it should pay for
+    ///
+    /// Confirms, independently of the PeerCat API, that the transaction
+    /// transferred at least the required lamports to the payment address
+    /// with the submission's memo. Use it to tell a payment that never landed
+    /// ([`PaymentVerification::NotFound`]) from one the API hasn't indexed yet.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use peercat::solana::{Commitment, SolanaRpc};
+    /// # use peercat::PromptSubmission;
+    ///
+    /// # async fn example(submission: PromptSubmission) -> peercat::Result<()> {
+    /// let rpc = SolanaRpc::new("https://api.mainnet-beta.solana.com");
+    ///
+    /// let verification = rpc
+    ///     .verify_on_chain("5xyz...", &submission, Commitment::Confirmed)
+    ///     .await?;
+    /// if verification.is_verified() {
+    ///     println!("Paid; waiting for the API to pick it up");
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn verify_on_chain(
+        &self,
+        tx_signature: &str,
+        expected: &PromptSubmission,
+        commitment: Commitment,
+    ) -> Result<PaymentVerification> {
+        #[derive(Deserialize)]
+        struct Transaction {
+            slot: u64,
+            meta: Option<Meta>,
+            transaction: TransactionBody,
+        }
+
+        #[derive(Deserialize)]
+        struct Meta {
+            err: Option<serde_json::Value>,
+        }
+
+        #[derive(Deserialize)]
+        struct TransactionBody {
+            message: Message,
+        }
+
+        #[derive(Deserialize)]
+        struct Message {
+            instructions: Vec<ParsedInstruction>,
+        }
+
+        #[derive(Deserialize)]
+        #[serde(rename_all = "camelCase")]
+        struct ParsedInstruction {
+            program_id: String,
+            #[serde(default)]
+            parsed: Option<serde_json::Value>,
+        }
+
+        let transaction: Option<Transaction> = self
+            .call(
+                "getTransaction",
+                json!([
+                    tx_signature,
+                    {
+                        "encoding": "jsonParsed",
+                        "commitment": commitment,
+                        "maxSupportedTransactionVersion": 0,
+                    },
+                ]),
+            )
+            .await?;
+        let Some(transaction) = transaction else {
+            return Ok(PaymentVerification::NotFound);
+        };
+        if let Some(err) = transaction.meta.and_then(|meta| meta.err) {
+            return Ok(PaymentVerification::Failed {
+                error: err.to_string(),
+            });
+        }
+
+        let mut lamports = 0;
+        let mut memo_matches = false;
+        for instruction in transaction.transaction.message.instructions {
+            let Some(parsed) = instruction.parsed else {
+                continue;
+            };
+            match instruction.program_id.as_str() {
+                SYSTEM_PROGRAM_ID if parsed["type"] == "transfer" => {
+                    let info = &parsed["info"];
+                    if info["destination"] == expected.payment_address.as_str() {
+                        lamports += info["lamports"].as_u64().unwrap_or(0);
+                    }
+                }
+                MEMO_PROGRAM_ID => memo_matches |= parsed == expected.memo.as_str(),
+                _ => {}
+            }
+        }
+
+        if lamports >= expected.required_amount.lamports && memo_matches {
+            Ok(PaymentVerification::Verified {
+                slot: transaction.slot,
+                lamports,
+            })
+        } else {
+            Ok(PaymentVerification::Mismatch {
+                lamports,
+                memo_matches,
+            })
+        }
+    }
+
+    /// Make a JSON-RPC call; a null result deserializes as `T` if it can (e.g., `Option`)
+    async fn call<T: DeserializeOwned>(
+        &self,
+        method: &str,
+        params: serde_json::Value,
+    ) -> Result<T> {
+        #[derive(Deserialize)]
+        struct RpcResponse {
+            #[serde(default)]
+            result: serde_json::Value,
+            error: Option<RpcError>,
+        }
+
+        #[derive(Deserialize)]
+        struct RpcError {
+            code: i64,
+            message: String,
+        }
+
+        let response: RpcResponse = self
+            .http
+            .post(&self.url)
+            .json(&json!({ "jsonrpc": "2.0", "id": 1, "method": method, "params": params }))
+            .send()
+            .await
+            .map_err(transport_error)?
+            .json()
+            .await
+            .map_err(transport_error)?;
+
+        if let Some(error) = response.error {
+            return Err(PeerCatError::Solana(format!(
+                "{} failed ({}): {}",
+                method, error.code, error.message
+            )));
+        }
+        if response.result.is_null() {
+            return serde_json::from_value(serde_json::Value::Null)
+                .map_err(|_| PeerCatError::Solana(format!("{} returned no result", method)));
+        }
+        Ok(serde_json::from_value(response.result)?)
+    }
+}
+
+/// Pays for prompt submissions from a wallet through a Solana RPC node
+#[derive(Debug)]
+pub struct SolanaPayer {
+    rpc: SolanaRpc,
+    signer: WalletSigner,
+}
+
+impl SolanaPayer {
+    /// Create a payer that sends transactions through `rpc_url`
+    pub fn new(rpc_url: impl Into<String>, signer: WalletSigner) -> Self {
+        Self {
+            rpc: SolanaRpc::new(rpc_url),
+            signer,
+        }
+    }
+
+    /// The RPC client payments are sent through, e.g. to verify them later
+    pub fn rpc(&self) -> &SolanaRpc {
+        &self.rpc
+    }
+
+    /// Wallet public key (base58)
+    pub fn public_key(&self) -> String {
+        self.signer.public_key()
+    }
+
+    /// Send the payment for a submission and wait for it to reach the
+    /// configured commitment, returning the transaction signature
+    ///
+    /// # Errors
+    ///
+    /// Returns `PeerCatError::Solana` if the RPC node rejects the transaction
+    /// or it fails on-chain, and `PeerCatError::DeadlineExceeded` if it isn't
+    /// confirmed within `options.confirm_timeout`.
+    pub async fn pay(
+        &self,
+        submission: &PromptSubmission,
+        options: &PaymentOptions,
+    ) -> Result<String> {
+        let blockhash = self.latest_blockhash(options.commitment).await?;
+        let transaction = self.payment_transaction(submission, options, &blockhash)?;
+
+        let signature: String = self
+            .rpc
+            .call(
+                "sendTransaction",
+                json!([
+                    BASE64_STANDARD.encode(transaction),
+                    { "encoding": "base64", "preflightCommitment": options.commitment },
+                ]),
+            )
+            .await?;
+        self.confirm(&signature, options).await?;
+        Ok(signature)
+    }
+
+    /// Build and sign the payment transaction for a submission without sending it
+    ///
+    /// Returns the serialized transaction: a SOL transfer of the required
+    /// amount to the payment address, the submission's memo, and compute
+    /// budget instructions for any priority fee.
+    pub fn payment_transaction(
+        &self,
+        submission: &PromptSubmission,
+        options: &PaymentOptions,
+        recent_blockhash: &str,
+    ) -> Result<Vec<u8>> {
+        let payer = self.signer.public_key_bytes();
+        let treasury = decode_key(&submission.payment_address)?;
+        let blockhash = decode_key(recent_blockhash)?;
+
+        // Writable signer, writable recipient, then read-only programs
+        let mut accounts = vec![
+            payer,
+            treasury,
+            decode_key(SYSTEM_PROGRAM_ID)?,
+            decode_key(MEMO_PROGRAM_ID)?,
+        ];
+        let mut instructions = Vec::new();
+        if options.compute_unit_limit.is_some() || options.compute_unit_price.is_some() {
+            accounts.push(decode_key(COMPUTE_BUDGET_PROGRAM_ID)?);
+            if let Some(units) = options.compute_unit_limit {
+                instructions.push(CompiledInstruction::new(
+                    4,
+                    vec![],
+                    [&[2][..], &units.to_le_bytes()],
+                ));
+            }
+            if let Some(price) = options.compute_unit_price {
+                instructions.push(CompiledInstruction::new(
+                    4,
+                    vec![],
+                    [&[3][..], &price.to_le_bytes()],
+                ));
+            }
+        }
+        let lamports = submission.required_amount.lamports;
+        instructions.push(CompiledInstruction::new(
+            2,
+            vec![0, 1],
+            [&transfer_data(lamports)],
+        ));
+        instructions.push(CompiledInstruction::new(
+            3,
+            vec![0],
+            [submission.memo.as_bytes()],
+        ));
+
+        let mut message = vec![1, 0, (accounts.len() - 2) as u8];
+        push_compact_len(&mut message, accounts.len());
+        for account in &accounts {
+            message.extend_from_slice(account);
+        }
+        message.extend_from_slice(&blockhash);
+        push_compact_len(&mut message, instructions.len());
+        for instruction in &instructions {
+            instruction.write(&mut message);
+        }
+
+        let mut transaction = Vec::with_capacity(1 + 64 + message.len());
+        push_compact_len(&mut transaction, 1);
+        transaction.extend_from_slice(&self.signer.sign_bytes(&message));
+        transaction.extend_from_slice(&message);
+        Ok(transaction)
+    }
+
+    async fn latest_blockhash(&self, commitment: Commitment) -> Result<String> {
+        #[derive(Deserialize)]
+        struct Blockhash {
+            blockhash: String,
+        }
+
+        let response: RpcContext<Blockhash> = self
+            .rpc
+            .call("getLatestBlockhash", json!([{ "commitment": commitment }]))
+            .await?;
+        Ok(response.value.blockhash)
+    }
+
+    /// Poll the signature's status until it reaches the commitment, fails, or times out
+    async fn confirm(&self, signature: &str, options: &PaymentOptions) -> Result<()> {
+        #[derive(Deserialize)]
+        #[serde(rename_all = "camelCase")]
+        struct SignatureStatus {
+            confirmation_status: Option<Commitment>,
+            err: Option<serde_json::Value>,
+        }
+
+        let start = Instant::now();
+        let mut polls = 0;
+        loop {
+            let statuses: RpcContext<Vec<Option<SignatureStatus>>> = self
+                .rpc
+                .call("getSignatureStatuses", json!([[signature]]))
+                .await?;
+            polls += 1;
+            if let Some(Some(status)) = statuses.value.into_iter().next() {
+                if let Some(err) = status.err {
+                    return Err(PeerCatError::Solana(format!("transaction failed: {}", err)));
+                }
+                if status.confirmation_status >= Some(options.commitment) {
+                    return Ok(());
+                }
+            }
+
+            let elapsed = start.elapsed();
+            if elapsed >= options.confirm_timeout {
+                return Err(PeerCatError::DeadlineExceeded { elapsed, polls });
+            }
+            rt::sleep(CONFIRM_POLL_INTERVAL).await;
+        }
+    }
+}
+
+/// Account an [`Instruction`] reads or writes
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AccountMeta {
+    /// Account public key
+    pub pubkey: [u8; 32],
+    /// Whether the account must sign
+    pub is_signer: bool,
+    /// Whether the account is written to
+    pub is_writable: bool,
+}
+
+/// Uncompiled instruction, laid out like `solana_program::instruction::Instruction`
+///
+/// Keys are raw 32-byte public keys, so converting is a matter of
+/// `Pubkey::new_from_array` on each.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Instruction {
+    /// Program to invoke
+    pub program_id: [u8; 32],
+    /// Accounts, in the order the program expects
+    pub accounts: Vec<AccountMeta>,
+    /// Instruction data
+    pub data: Vec<u8>,
+}
+
+/// Build the instructions that pay for a submission from `payer`, without signing
+///
+/// Returns a system transfer of the required lamports to the payment address,
+/// followed by the submission's memo signed by `payer`. Meant for on-chain
+/// programs paying from a PDA: invoke each with `invoke_signed` and the PDA's
+/// seeds. The PDA must be owned by the system program to transfer SOL.
+///
+/// # Example
+///
+/// ```no_run
+/// use peercat::solana::payment_instructions;
+/// # fn example(submission: &peercat::PromptSubmission) -> peercat::Result<()> {
+/// let instructions = payment_instructions(submission, "PdaAddress...")?;
+/// assert_eq!(instructions.len(), 2);
+/// # Ok(())
+/// # }
+/// ```
+pub fn payment_instructions(
+    submission: &PromptSubmission,
+    payer: &str,
+) -> Result<Vec<Instruction>> {
+    let payer = decode_key(payer)?;
+    let signer = |pubkey, is_writable| AccountMeta {
+        pubkey,
+        is_signer: true,
+        is_writable,
+    };
+
+    Ok(vec![
+        Instruction {
+            program_id: decode_key(SYSTEM_PROGRAM_ID)?,
+            accounts: vec![
+                signer(payer, true),
+                AccountMeta {
+                    pubkey: decode_key(&submission.payment_address)?,
+                    is_signer: false,
+                    is_writable: true,
+                },
+            ],
+            data: transfer_data(submission.required_amount.lamports),
+        },
+        Instruction {
+            program_id: decode_key(MEMO_PROGRAM_ID)?,
+            accounts: vec![signer(payer, false)],
+            data: submission.memo.as_bytes().to_vec(),
+        },
+    ])
+}
+
+/// System program `Transfer` instruction data
+fn transfer_data(lamports: u64) -> Vec<u8> {
+    [&2u32.to_le_bytes()[..], &lamports.to_le_bytes()].concat()
+}
+
+/// An RPC result wrapped with the slot it was read at
+#[derive(Deserialize)]
+struct RpcContext<T> {
+    value: T,
+}
+
+/// A compiled instruction, referencing accounts by index
+struct CompiledInstruction {
+    program: u8,
+    accounts: Vec<u8>,
+    data: Vec<u8>,
+}
+
+impl CompiledInstruction {
+    fn new<const N: usize>(program: u8, accounts: Vec<u8>, data: [&[u8]; N]) -> Self {
+        Self {
+            program,
+            accounts,
+            data: data.concat(),
+        }
+    }
+
+    fn write(&self, out: &mut Vec<u8>) {
+        out.push(self.program);
+        push_compact_len(out, self.accounts.len());
+        out.extend_from_slice(&self.accounts);
+        push_compact_len(out, self.data.len());
+        out.extend_from_slice(&self.data);
+    }
+}
+
+/// Append a length in Solana's compact-u16 encoding
+fn push_compact_len(out: &mut Vec<u8>, mut len: usize) {
+    loop {
+        let byte = (len & 0x7f) as u8;
+        len >>= 7;
+        if len == 0 {
+            out.push(byte);
+            return;
+        }
+        out.push(byte | 0x80);
+    }
+}
+
+fn decode_key(key: &str) -> Result<[u8; 32]> {
+    let bytes = bs58::decode(key)
+        .into_vec()
+        .map_err(|e| PeerCatError::Solana(format!("invalid public key {}: {}", key, e)))?;
+    bytes
+        .try_into()
+        .map_err(|_| PeerCatError::Solana(format!("invalid public key {}", key)))
+}
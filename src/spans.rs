@@ -0,0 +1,109 @@
+//! Tracing spans for SDK calls and their HTTP attempts
+//!
+//! With the `tracing` feature, each call is wrapped in a `peercat.request`
+//! span and each HTTP attempt it makes in a child `peercat.attempt` span.
+//! Without it, every method here is a no-op.
+
+use std::future::Future;
+
+use reqwest::{Method, StatusCode};
+
+#[cfg(feature = "tracing")]
+use crate::client::endpoint;
+use crate::error::Result;
+
+/// A `peercat.request` or `peercat.attempt` span
+#[derive(Clone)]
+pub(crate) struct RequestSpan {
+    #[cfg(feature = "tracing")]
+    span: tracing::Span,
+}
+
+impl RequestSpan {
+    /// Start the span for one logical call
+    ///
+    /// The endpoint is the request path without its query string.
+    #[cfg_attr(not(feature = "tracing"), allow(unused_variables))]
+    pub fn call(method: &Method, path: &str) -> Self {
+        Self {
+            #[cfg(feature = "tracing")]
+            span: tracing::info_span!(
+                "peercat.request",
+                http.method = %method,
+                peercat.endpoint = endpoint(path),
+                http.status_code = tracing::field::Empty,
+                retry.count = tracing::field::Empty,
+                peercat.credits_used = tracing::field::Empty,
+                error = tracing::field::Empty,
+            ),
+        }
+    }
+
+    /// Start a child span for an attempt, `attempt` being the number sent before it
+    #[cfg_attr(not(feature = "tracing"), allow(unused_variables))]
+    pub fn attempt(&self, attempt: u32) -> Self {
+        #[cfg(feature = "tracing")]
+        self.span.record("retry.count", attempt);
+        Self {
+            #[cfg(feature = "tracing")]
+            span: tracing::info_span!(
+                parent: &self.span,
+                "peercat.attempt",
+                retry.attempt = attempt,
+                http.status_code = tracing::field::Empty,
+                error = tracing::field::Empty,
+            ),
+        }
+    }
+
+    /// Run `future` inside this span
+    pub fn instrument<F: Future>(&self, future: F) -> impl Future<Output = F::Output> {
+        #[cfg(feature = "tracing")]
+        let future = tracing::Instrument::instrument(future, self.span.clone());
+        future
+    }
+
+    /// Record the response status
+    #[cfg_attr(not(feature = "tracing"), allow(unused_variables))]
+    pub fn record_status(&self, status: StatusCode) {
+        #[cfg(feature = "tracing")]
+        self.span.record("http.status_code", status.as_u16());
+    }
+
+    /// Returns true if a subscriber is interested in this span
+    pub fn is_recording(&self) -> bool {
+        #[cfg(feature = "tracing")]
+        let recording = !self.span.is_disabled();
+        #[cfg(not(feature = "tracing"))]
+        let recording = false;
+        recording
+    }
+
+    /// Record the credits the call cost
+    #[cfg_attr(not(feature = "tracing"), allow(unused_variables))]
+    pub fn record_credits(&self, credits: f64) {
+        #[cfg(feature = "tracing")]
+        self.span.record("peercat.credits_used", credits);
+    }
+
+    /// Log a warning about a slow attempt
+    #[cfg_attr(not(feature = "tracing"), allow(unused_variables))]
+    pub fn warn_slow(&self, attempt: u32, duration: std::time::Duration) {
+        #[cfg(feature = "tracing")]
+        tracing::warn!(
+            parent: &self.span,
+            retry.attempt = attempt,
+            duration_ms = duration.as_millis() as u64,
+            "slow PeerCat request"
+        );
+    }
+
+    /// Record the error a call or attempt failed with, if any
+    #[cfg_attr(not(feature = "tracing"), allow(unused_variables))]
+    pub fn record_result<T>(&self, result: &Result<T>) {
+        #[cfg(feature = "tracing")]
+        if let Err(e) = result {
+            self.span.record("error", tracing::field::display(e));
+        }
+    }
+}
@@ -0,0 +1,42 @@
+//! Credits spent by a client since it was created or last reset
+
+use std::sync::Mutex;
+
+/// Credits spent on generations by a client and its clones
+///
+/// Returned by [`PeerCat::session_spend`](crate::PeerCat::session_spend).
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct SessionSpend {
+    /// Credits charged, as reported by each generation's `usage.credits_used`
+    pub credits: f64,
+    /// Generations that reported a charge (including zero-credit demo runs)
+    pub generations: u64,
+}
+
+/// Running total shared by all clones of a client
+#[derive(Debug, Default)]
+pub(crate) struct SpendTracker {
+    total: Mutex<SessionSpend>,
+}
+
+impl SpendTracker {
+    /// Add one generation's charge
+    pub fn add(&self, credits: f64) {
+        let mut total = self.lock();
+        total.credits += credits;
+        total.generations += 1;
+    }
+
+    pub fn total(&self) -> SessionSpend {
+        *self.lock()
+    }
+
+    /// Start a new session, returning the previous one's total
+    pub fn reset(&self) -> SessionSpend {
+        std::mem::take(&mut *self.lock())
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, SessionSpend> {
+        self.total.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
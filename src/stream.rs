@@ -0,0 +1,202 @@
+//! Polling streams
+
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use std::time::Duration;
+
+use futures_core::Stream;
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+
+use crate::client::PeerCat;
+use crate::error::Result;
+use crate::types::{OnChainGenerationStatus, PriceResponse};
+
+/// Shortest polling interval; shorter ones, including zero, are raised to it
+pub(crate) const MIN_INTERVAL: Duration = Duration::from_millis(10);
+
+/// A stream of price quotes, polled in the background
+///
+/// Only quotes with a new `updated_at` are emitted; failed polls are emitted
+/// as errors and polling continues. Polling stops when the stream is dropped.
+#[derive(Debug)]
+pub struct PriceStream {
+    rx: mpsc::Receiver<Result<PriceResponse>>,
+    handle: JoinHandle<()>,
+}
+
+impl PriceStream {
+    /// Wait for the next price update
+    pub async fn next(&mut self) -> Option<Result<PriceResponse>> {
+        self.rx.recv().await
+    }
+}
+
+impl Stream for PriceStream {
+    type Item = Result<PriceResponse>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.rx.poll_recv(cx)
+    }
+}
+
+impl Drop for PriceStream {
+    fn drop(&mut self) {
+        self.handle.abort();
+    }
+}
+
+/// A stream of on-chain generation status transitions, polled in the background
+///
+/// Each status change is emitted once, and the stream ends after a terminal
+/// status. Retryable errors are emitted and polling continues; other errors
+/// end the stream. Polling stops when the stream is dropped.
+#[derive(Debug)]
+pub struct OnChainStatusStream {
+    rx: mpsc::Receiver<Result<OnChainGenerationStatus>>,
+    handle: JoinHandle<()>,
+}
+
+impl OnChainStatusStream {
+    /// Wait for the next status transition
+    pub async fn next(&mut self) -> Option<Result<OnChainGenerationStatus>> {
+        self.rx.recv().await
+    }
+}
+
+impl Stream for OnChainStatusStream {
+    type Item = Result<OnChainGenerationStatus>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.rx.poll_recv(cx)
+    }
+}
+
+impl Drop for OnChainStatusStream {
+    fn drop(&mut self) {
+        self.handle.abort();
+    }
+}
+
+impl PeerCat {
+    /// Poll prices every `interval`, emitting each new quote
+    ///
+    /// The first quote is fetched immediately. Updates whose `updated_at`
+    /// matches the previous quote are skipped. Intervals under 10ms are
+    /// raised to 10ms.
+    ///
+    /// Must be called from within a Tokio runtime.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use peercat::PeerCat;
+    /// use std::time::Duration;
+    ///
+    /// # async fn example() -> peercat::Result<()> {
+    /// let client = PeerCat::new("pcat_live_xxx")?;
+    /// let mut prices = client.price_stream(Duration::from_secs(30));
+    ///
+    /// while let Some(quote) = prices.next().await {
+    ///     let quote = quote?;
+    ///     println!("SOL/USD: ${}", quote.sol_price);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn price_stream(&self, interval: Duration) -> PriceStream {
+        let client = self.clone();
+        let (tx, rx) = mpsc::channel(1);
+
+        let handle = tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval.max(MIN_INTERVAL));
+            ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+            let mut last_updated = None;
+
+            loop {
+                ticker.tick().await;
+                let item = match client.get_prices().await {
+                    Ok(prices) if last_updated.as_ref() == Some(&prices.updated_at) => continue,
+                    Ok(prices) => {
+                        // `Timestamp` is only `Copy` with the `chrono` feature
+                        #[allow(clippy::clone_on_copy)]
+                        let updated_at = prices.updated_at.clone();
+                        last_updated = Some(updated_at);
+                        Ok(prices)
+                    }
+                    Err(e) => Err(e),
+                };
+                if tx.send(item).await.is_err() {
+                    break;
+                }
+            }
+        });
+
+        PriceStream { rx, handle }
+    }
+
+    /// Poll an on-chain generation every `interval`, emitting each status change
+    ///
+    /// The first status is fetched immediately, and the stream ends once the
+    /// generation completes, fails, or is refunded. Intervals under 10ms are
+    /// raised to 10ms.
+    ///
+    /// Must be called from within a Tokio runtime.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use peercat::{OnChainStatus, PeerCat};
+    /// use std::time::Duration;
+    ///
+    /// # async fn example() -> peercat::Result<()> {
+    /// let client = PeerCat::new("pcat_live_xxx")?;
+    /// let mut updates = client.onchain_status_stream("5xyz...", Duration::from_secs(2));
+    ///
+    /// while let Some(update) = updates.next().await {
+    ///     let update = update?;
+    ///     match update.status {
+    ///         OnChainStatus::Completed => println!("Image: {:?}", update.image_url),
+    ///         status => println!("Status: {:?}", status),
+    ///     }
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn onchain_status_stream(
+        &self,
+        tx_signature: &str,
+        interval: Duration,
+    ) -> OnChainStatusStream {
+        let client = self.clone();
+        let tx_signature = tx_signature.to_string();
+        let (tx, rx) = mpsc::channel(1);
+
+        let handle = tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval.max(MIN_INTERVAL));
+            ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+            let mut last_status = None;
+
+            loop {
+                ticker.tick().await;
+                let (item, done) = match client.get_onchain_status(&tx_signature).await {
+                    Ok(update) if last_status == Some(update.status) => continue,
+                    Ok(update) => {
+                        last_status = Some(update.status);
+                        let done = update.status.is_terminal();
+                        (Ok(update), done)
+                    }
+                    Err(e) => {
+                        let done = !e.is_retryable();
+                        (Err(e), done)
+                    }
+                };
+                if tx.send(item).await.is_err() || done {
+                    break;
+                }
+            }
+        });
+
+        OnChainStatusStream { rx, handle }
+    }
+}
@@ -0,0 +1,857 @@
+//! In-process fake of the PeerCat API for tests
+//!
+//! Requires the `testing` feature. [`FakePeerCat`] starts an HTTP server on
+//! localhost that answers every endpoint the client uses with realistic
+//! payloads, so tests can exercise real [`PeerCat`] calls without writing
+//! a mock for each one. Generations are charged against a fake balance and
+//! show up in history; everything else returns canned data built from the
+//! request.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use peercat::testing::{FakeError, FakePeerCat};
+//! use peercat::{GenerateParams, PeerCatError};
+//!
+//! # async fn example() -> peercat::Result<()> {
+//! let fake = FakePeerCat::start().await;
+//! let client = fake.client()?;
+//!
+//! let result = client.generate(GenerateParams::new("A sunset")).await?;
+//! assert_eq!(result.usage.credits_used, 0.28);
+//! assert_eq!(fake.balance(), 9.72);
+//!
+//! fake.set_balance(0.0);
+//! assert!(matches!(
+//!     client.generate(GenerateParams::new("A sunset")).await,
+//!     Err(PeerCatError::InsufficientCredits { .. })
+//! ));
+//!
+//! fake.set_balance(5.0);
+//! fake.fail_next(1, FakeError::Unauthorized);
+//! assert!(client.get_balance().await.is_err());
+//! assert_eq!(client.get_balance().await?.credits, 5.0);
+//! # Ok(())
+//! # }
+//! ```
+
+use std::collections::{HashMap, VecDeque};
+use std::fmt;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use serde_json::{json, Value};
+use wiremock::matchers::any;
+use wiremock::{Mock, MockServer, Request, Respond, ResponseTemplate};
+
+use crate::client::PeerCat;
+use crate::error::Result;
+use crate::types::PeerCatConfig;
+
+/// Credits a new fake account starts with
+const STARTING_CREDITS: f64 = 10.0;
+
+/// Model used when a generation doesn't name one
+const DEFAULT_MODEL: &str = "stable-diffusion-xl";
+
+/// Models the fake serves, with their price in USD
+const MODELS: &[(&str, &str, &str, f64)] = &[
+    (
+        "stable-diffusion-xl",
+        "Stable Diffusion XL",
+        "Stability AI",
+        0.28,
+    ),
+    ("imagen-3", "Imagen 3", "Google", 1.50),
+];
+
+/// SOL price in USD used for on-chain amounts
+const SOL_PRICE: f64 = 185.5;
+
+/// Treasury address used for payment instructions
+const TREASURY: &str = "9JKi6Tr7JdsTJw1zNedF5vML9GpPnjHD9DWuZq1oE6nV";
+
+/// A 1x1 transparent PNG, served for generated images
+const PNG: &[u8] = &[
+    0x89, 0x50, 0x4e, 0x47, 0x0d, 0x0a, 0x1a, 0x0a, 0x00, 0x00, 0x00, 0x0d, 0x49, 0x48, 0x44, 0x52,
+    0x00, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00, 0x01, 0x08, 0x06, 0x00, 0x00, 0x00, 0x1f, 0x15, 0xc4,
+    0x89, 0x00, 0x00, 0x00, 0x0a, 0x49, 0x44, 0x41, 0x54, 0x78, 0x9c, 0x63, 0x00, 0x01, 0x00, 0x00,
+    0x05, 0x00, 0x01, 0x0d, 0x0a, 0x2d, 0xb4, 0x00, 0x00, 0x00, 0x00, 0x49, 0x45, 0x4e, 0x44, 0xae,
+    0x42, 0x60, 0x82,
+];
+
+/// An error response for [`FakePeerCat::fail_next`] to return
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub enum FakeError {
+    /// HTTP 401 `authentication_error`
+    Unauthorized,
+    /// HTTP 400 `invalid_request_error` with this message
+    InvalidRequest(String),
+    /// HTTP 402 `insufficient_credits`
+    InsufficientCredits,
+    /// HTTP 404 `not_found`
+    NotFound,
+    /// HTTP 429 `rate_limit_error`, asking the client to wait `retry_after` seconds
+    RateLimited { retry_after: u64 },
+    /// HTTP 500 `server_error`
+    Server,
+    /// HTTP 503 `server_error`
+    Unavailable,
+}
+
+impl FakeError {
+    fn response(&self) -> ResponseTemplate {
+        match self {
+            FakeError::Unauthorized => error(
+                401,
+                "authentication_error",
+                "invalid_api_key",
+                "Invalid API key",
+            ),
+            FakeError::InvalidRequest(message) => {
+                error(400, "invalid_request_error", "invalid_request", message)
+            }
+            FakeError::InsufficientCredits => error(
+                402,
+                "insufficient_credits",
+                "insufficient_credits",
+                "Insufficient credits",
+            ),
+            FakeError::NotFound => error(404, "not_found", "not_found", "Not found"),
+            FakeError::RateLimited { retry_after } => error(
+                429,
+                "rate_limit_error",
+                "rate_limit_exceeded",
+                "Too many requests",
+            )
+            .insert_header("Retry-After", retry_after.to_string().as_str()),
+            FakeError::Server => error(500, "server_error", "internal_error", "Internal error"),
+            FakeError::Unavailable => error(
+                503,
+                "server_error",
+                "service_unavailable",
+                "Service temporarily unavailable",
+            ),
+        }
+    }
+}
+
+/// A fake PeerCat API server running in the current process
+///
+/// The server stops when this is dropped. See the [module docs](self) for an
+/// example.
+pub struct FakePeerCat {
+    server: MockServer,
+    state: Arc<Mutex<State>>,
+}
+
+impl FakePeerCat {
+    /// Start a fake server on a free localhost port
+    ///
+    /// The account starts with 10 credits and no history.
+    pub async fn start() -> Self {
+        let server = MockServer::start().await;
+        let state = Arc::new(Mutex::new(State::new(server.uri())));
+        Mock::given(any())
+            .respond_with(Responder {
+                state: state.clone(),
+            })
+            .mount(&server)
+            .await;
+        Self { server, state }
+    }
+
+    /// Base URL of the fake API
+    pub fn uri(&self) -> String {
+        self.server.uri()
+    }
+
+    /// A client configuration pointing at the fake, to customize before use
+    pub fn config(&self) -> PeerCatConfig {
+        PeerCatConfig::new("pcat_test_fake").with_base_url(self.uri())
+    }
+
+    /// A client for the fake, with default settings
+    pub fn client(&self) -> Result<PeerCat> {
+        PeerCat::with_config(self.config())
+    }
+
+    /// Set the account's credit balance
+    pub fn set_balance(&self, credits: f64) {
+        self.lock().credits = credits;
+    }
+
+    /// The account's current credit balance
+    pub fn balance(&self) -> f64 {
+        self.lock().credits
+    }
+
+    /// Answer the next `n` requests, to any endpoint, with `error`
+    ///
+    /// Failures queued by earlier calls are returned first. Retries count as
+    /// requests, so with the client's default of 3 retries, `fail_next(3, ..)`
+    /// still ends in a successful call.
+    pub fn fail_next(&self, n: u32, error: FakeError) {
+        self.lock()
+            .failures
+            .extend(std::iter::repeat(error).take(n as usize));
+    }
+
+    /// Answer `method` requests to `path` with `body` instead of the default payload
+    ///
+    /// `path` is matched without its query string, e.g. `/v1/balance`. The
+    /// override stays in place until [`clear_responses`](Self::clear_responses).
+    pub fn set_response(&self, method: &str, path: &str, body: Value) {
+        self.lock()
+            .overrides
+            .insert((method.to_ascii_uppercase(), path.to_string()), body);
+    }
+
+    /// Remove all overrides set with [`set_response`](Self::set_response)
+    pub fn clear_responses(&self) {
+        self.lock().overrides.clear();
+    }
+
+    /// Every request received so far, as `"METHOD /path"`
+    pub fn requests(&self) -> Vec<String> {
+        self.lock().requests.clone()
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, State> {
+        lock(&self.state)
+    }
+}
+
+impl fmt::Debug for FakePeerCat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("FakePeerCat")
+            .field("uri", &self.uri())
+            .finish_non_exhaustive()
+    }
+}
+
+fn lock(state: &Mutex<State>) -> std::sync::MutexGuard<'_, State> {
+    state.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// A generation made through the fake
+#[derive(Debug, Clone)]
+struct Generation {
+    id: String,
+    usage_id: String,
+    model: String,
+    prompt: String,
+    seed: u64,
+    credits: f64,
+    created_at: String,
+    tags: Vec<String>,
+}
+
+#[derive(Debug)]
+struct State {
+    uri: String,
+    credits: f64,
+    total_spent: f64,
+    total_withdrawn: f64,
+    generations: Vec<Generation>,
+    failures: VecDeque<FakeError>,
+    overrides: HashMap<(String, String), Value>,
+    requests: Vec<String>,
+    next_id: u64,
+}
+
+impl State {
+    fn new(uri: String) -> Self {
+        Self {
+            uri,
+            credits: STARTING_CREDITS,
+            total_spent: 0.0,
+            total_withdrawn: 0.0,
+            generations: Vec::new(),
+            failures: VecDeque::new(),
+            overrides: HashMap::new(),
+            requests: Vec::new(),
+            next_id: 0,
+        }
+    }
+
+    fn id(&mut self, prefix: &str) -> String {
+        self.next_id += 1;
+        format!("{}_{:06}", prefix, self.next_id)
+    }
+
+    fn generation(&self, id: &str) -> Option<&Generation> {
+        self.generations.iter().find(|g| g.id == id)
+    }
+
+    fn balance(&self) -> Value {
+        json!({
+            "credits": self.credits,
+            "totalDeposited": STARTING_CREDITS,
+            "totalSpent": self.total_spent,
+            "totalWithdrawn": self.total_withdrawn,
+            "totalGenerated": self.generations.len()
+        })
+    }
+
+    fn generate(&mut self, body: &Value) -> ResponseTemplate {
+        let prompt = body["prompt"].as_str().unwrap_or_default();
+        if prompt.is_empty() {
+            return error(
+                400,
+                "invalid_request_error",
+                "missing_prompt",
+                "A prompt is required",
+            );
+        }
+        let model = body["model"].as_str().unwrap_or(DEFAULT_MODEL);
+        let Some(&(_, _, _, price)) = MODELS.iter().find(|(id, ..)| *id == model) else {
+            return error(
+                400,
+                "invalid_request_error",
+                "model_not_found",
+                "Unknown model",
+            );
+        };
+        let demo = body["mode"] == "demo";
+        let credits = if demo { 0.0 } else { price };
+        if credits > self.credits {
+            return FakeError::InsufficientCredits.response();
+        }
+        self.credits = round(self.credits - credits);
+        self.total_spent = round(self.total_spent + credits);
+
+        let id = self.id("gen");
+        let usage_id = self.id("use");
+        let generation = Generation {
+            seed: body["seed"].as_u64().unwrap_or(self.next_id * 7919),
+            id,
+            usage_id,
+            model: model.to_string(),
+            prompt: prompt.to_string(),
+            credits,
+            created_at: rfc3339(SystemTime::now()),
+            tags: Vec::new(),
+        };
+        let result = json!({
+            "id": generation.id,
+            "imageUrl": self.image_url(&generation.id),
+            "ipfsHash": if demo { Value::Null } else { ipfs_hash(&generation.id).into() },
+            "model": generation.model,
+            "mode": if demo { "demo" } else { "production" },
+            "usage": {
+                "creditsUsed": credits,
+                "balanceRemaining": self.credits
+            },
+            "seed": generation.seed,
+            "prompt": generation.prompt,
+            "options": body.get("options")
+        });
+        self.generations.push(generation);
+        ok(result)
+    }
+
+    fn image_url(&self, id: &str) -> String {
+        format!("{}/images/{}.png", self.uri, id)
+    }
+
+    fn history_item(&self, generation: &Generation) -> Value {
+        json!({
+            "id": generation.usage_id,
+            "endpoint": "/v1/generate",
+            "model": generation.model,
+            "creditsUsed": generation.credits,
+            "requestId": generation.id,
+            "status": "completed",
+            "createdAt": generation.created_at,
+            "completedAt": generation.created_at,
+            "imageUrl": self.image_url(&generation.id),
+            "tags": generation.tags
+        })
+    }
+
+    fn respond(&mut self, request: &Request) -> ResponseTemplate {
+        let method = request.method.as_str().to_string();
+        let path = request.url.path().to_string();
+        self.requests.push(format!("{} {}", method, path));
+
+        if let Some(failure) = self.failures.pop_front() {
+            return failure.response();
+        }
+        if let Some(body) = self.overrides.get(&(method.clone(), path.clone())) {
+            return ok(body.clone());
+        }
+
+        let body: Value = serde_json::from_slice(&request.body).unwrap_or(Value::Null);
+        let query: HashMap<String, String> = request.url.query_pairs().into_owned().collect();
+        let segments: Vec<&str> = path.trim_start_matches('/').split('/').collect();
+        let now = rfc3339(SystemTime::now());
+
+        match (method.as_str(), segments.as_slice()) {
+            ("GET", ["images", _]) => ResponseTemplate::new(200).set_body_raw(PNG, "image/png"),
+            ("POST", ["v1", "generate"]) => self.generate(&body),
+            ("GET", ["v1", "generate", tx]) => ok(json!({
+                "txSignature": tx,
+                "status": "completed",
+                "model": DEFAULT_MODEL,
+                "createdAt": now,
+                "imageUrl": self.image_url(tx),
+                "ipfsHash": ipfs_hash(tx),
+                "completedAt": now
+            })),
+            ("GET", ["v1", "generate", tx, "events"]) => ok(json!({
+                "txSignature": tx,
+                "events": [
+                    { "type": "payment_detected", "timestamp": now },
+                    { "type": "amount_verified", "timestamp": now },
+                    { "type": "queued", "timestamp": now },
+                    { "type": "completed", "timestamp": now }
+                ]
+            })),
+            ("POST", ["v1", "uploads"]) => {
+                let id = self.id("upl");
+                ok(json!({ "id": id, "url": format!("{}/uploads/{}", self.uri, id) }))
+            }
+            ("GET", ["v1", "ipfs", id, "status"]) => ok(json!({
+                "hash": if id.starts_with("Qm") { id.to_string() } else { ipfs_hash(id) },
+                "pinned": true,
+                "providers": 3,
+                "size": PNG.len()
+            })),
+            ("GET", ["v1", "generations", id, "metadata"]) => match self.generation(id) {
+                Some(g) => ok(json!({
+                    "id": g.id,
+                    "model": g.model,
+                    "prompt": g.prompt,
+                    "seed": g.seed,
+                    "width": 1024,
+                    "height": 1024,
+                    "inferenceMs": 2350,
+                    "totalMs": 3100,
+                    "safety": { "flagged": false },
+                    "createdAt": g.created_at
+                })),
+                None => generation_not_found(),
+            },
+            ("POST", ["v1", "generations", id, "signed-url"]) => match self.generation(id) {
+                Some(g) => {
+                    let ttl = body["ttlSeconds"].as_u64().unwrap_or(3600);
+                    ok(json!({
+                        "url": format!("{}?token=fake", self.image_url(&g.id)),
+                        "expiresAt": rfc3339(SystemTime::now() + Duration::from_secs(ttl))
+                    }))
+                }
+                None => generation_not_found(),
+            },
+            ("POST", ["v1", "generations", id, "share"]) => match self.generation(id) {
+                Some(_) => {
+                    let share = self.id("shr");
+                    let expires_at = body["expiresInSeconds"]
+                        .as_u64()
+                        .map(|secs| rfc3339(SystemTime::now() + Duration::from_secs(secs)));
+                    ok(json!({
+                        "id": share,
+                        "url": format!("{}/s/{}", self.uri, share),
+                        "expiresAt": expires_at,
+                        "allowDownload": body["allowDownload"].as_bool().unwrap_or(false)
+                    }))
+                }
+                None => generation_not_found(),
+            },
+            ("POST", ["v1", "generations", id, "tags"]) => {
+                match self.generations.iter_mut().find(|g| g.id == *id) {
+                    Some(g) => {
+                        g.tags = serde_json::from_value(body["tags"].clone()).unwrap_or_default();
+                        ok(json!({ "success": true }))
+                    }
+                    None => generation_not_found(),
+                }
+            }
+            ("DELETE", ["v1", "generations", id]) => {
+                let Some(index) = self.generations.iter().position(|g| g.id == *id) else {
+                    return generation_not_found();
+                };
+                self.generations.remove(index);
+                ok(json!({
+                    "id": id,
+                    "cdnRemoved": true,
+                    "ipfsUnpinned": query.get("unpin_ipfs").is_some_and(|v| v == "true"),
+                    "deletedAt": now
+                }))
+            }
+            ("POST", ["v1", "collections"]) => {
+                let id = self.id("col");
+                ok(collection(
+                    &id,
+                    &body["name"],
+                    &body["description"],
+                    0,
+                    &now,
+                ))
+            }
+            ("GET", ["v1", "collections"]) => ok(json!({
+                "collections": [collection("col_default", &"Favorites".into(), &Value::Null, 0, &now)]
+            })),
+            ("POST", ["v1", "collections", _, "items"]) => {
+                let generation_id = body["generationId"].as_str().unwrap_or_default();
+                match self.generation(generation_id) {
+                    Some(g) => ok(json!({
+                        "generationId": g.id,
+                        "imageUrl": self.image_url(&g.id),
+                        "model": g.model,
+                        "addedAt": now
+                    })),
+                    None => generation_not_found(),
+                }
+            }
+            ("GET", ["v1", "collections", _, "items"]) => ok(json!({ "items": [] })),
+            ("POST", ["v1", "presets"]) => {
+                let id = self.id("preset");
+                ok(json!({
+                    "id": id,
+                    "name": body["name"],
+                    "params": body["params"],
+                    "createdAt": now,
+                    "updatedAt": now
+                }))
+            }
+            ("GET", ["v1", "presets"]) => ok(json!({ "presets": [] })),
+            ("DELETE", ["v1", "presets", _]) => ok(json!({ "success": true })),
+            ("POST", ["v1", "presets", _, "generate"]) => {
+                let mut params = body;
+                if params.get("prompt").is_none() {
+                    params["prompt"] = "A preset prompt".into();
+                }
+                self.generate(&params)
+            }
+            ("GET", ["v1", "models"]) => ok(json!({
+                "models": MODELS.iter().map(|m| model(*m)).collect::<Vec<_>>()
+            })),
+            ("GET", ["v1", "models", id]) => match MODELS.iter().find(|m| m.0 == *id) {
+                Some(m) => ok(model(*m)),
+                None => error(404, "not_found", "model_not_found", "Model not found"),
+            },
+            ("GET", ["v1", "models", _, "loras"]) => ok(json!({
+                "loras": [{
+                    "id": "lora_pixel",
+                    "name": "Pixel Art",
+                    "description": null,
+                    "triggerWords": ["pixel art", "8-bit"],
+                    "defaultWeight": 0.8
+                }]
+            })),
+            ("GET", ["v1", "price"]) => ok(json!({
+                "solPrice": SOL_PRICE,
+                "slippageTolerance": 0.05,
+                "updatedAt": now,
+                "treasury": TREASURY,
+                "models": MODELS.iter().map(|m| model_price(*m)).collect::<Vec<_>>()
+            })),
+            ("GET", ["v1", "price", "history"]) => ok(json!({
+                "granularity": query.get("granularity").map_or("day", String::as_str),
+                "points": [{
+                    "timestamp": now,
+                    "solPrice": SOL_PRICE,
+                    "models": MODELS.iter().map(|m| model_price(*m)).collect::<Vec<_>>()
+                }]
+            })),
+            ("GET", ["v1", "balance"]) => ok(self.balance()),
+            ("POST", ["v1", "deposits"]) => {
+                let usd = body["amountUsd"].as_f64().unwrap_or_default();
+                let id = self.id("dep");
+                ok(json!({
+                    "depositId": id,
+                    "paymentAddress": TREASURY,
+                    "requiredAmount": sol_amount(usd),
+                    "memo": format!("PCAT:v1:deposit:{}", id),
+                    "expiresAt": rfc3339(SystemTime::now() + Duration::from_secs(3600))
+                }))
+            }
+            ("GET", ["v1", "deposits", tx]) => ok(json!({
+                "txSignature": tx,
+                "status": "confirmed",
+                "amountSol": 0.1,
+                "creditsAdded": round(0.1 * SOL_PRICE),
+                "createdAt": now,
+                "confirmedAt": now,
+                "error": null
+            })),
+            ("POST", ["v1", "withdrawals"]) => {
+                let amount = body["amount"].as_f64().unwrap_or_default();
+                if amount > self.credits {
+                    return FakeError::InsufficientCredits.response();
+                }
+                self.credits = round(self.credits - amount);
+                self.total_withdrawn = round(self.total_withdrawn + amount);
+                let id = self.id("wd");
+                ok(withdrawal(&id, amount, &body["destinationWallet"], &now))
+            }
+            ("GET", ["v1", "withdrawals", id]) => {
+                ok(withdrawal(id, 10.0, &"Wallet123".into(), &now))
+            }
+            ("GET", ["v1", "history"]) => {
+                let limit = query
+                    .get("limit")
+                    .and_then(|v| v.parse().ok())
+                    .unwrap_or(50);
+                let offset = query
+                    .get("offset")
+                    .and_then(|v| v.parse().ok())
+                    .unwrap_or(0);
+                let items: Vec<Value> = self
+                    .generations
+                    .iter()
+                    .rev()
+                    .skip(offset)
+                    .take(limit)
+                    .map(|g| self.history_item(g))
+                    .collect();
+                let total = self.generations.len();
+                ok(json!({
+                    "items": items,
+                    "pagination": {
+                        "total": total,
+                        "limit": limit,
+                        "offset": offset,
+                        "hasMore": offset + limit < total
+                    }
+                }))
+            }
+            ("GET", ["v1", "history", id]) => {
+                match self.generations.iter().find(|g| g.usage_id == *id) {
+                    Some(g) => ok(self.history_item(g)),
+                    None => error(404, "not_found", "not_found", "History item not found"),
+                }
+            }
+            ("GET", ["v1", "analytics", "usage"]) => {
+                let mut buckets: Vec<(String, f64, u32)> = Vec::new();
+                for g in &self.generations {
+                    match buckets.iter_mut().find(|b| b.0 == g.model) {
+                        Some(bucket) => {
+                            bucket.1 = round(bucket.1 + g.credits);
+                            bucket.2 += 1;
+                        }
+                        None => buckets.push((g.model.clone(), g.credits, 1)),
+                    }
+                }
+                ok(json!({
+                    "groupBy": query.get("groupBy").map_or("model", String::as_str),
+                    "from": query.get("from").map_or("2024-01-01T00:00:00Z", String::as_str),
+                    "to": query.get("to").unwrap_or(&now),
+                    "totalCreditsUsed": self.total_spent,
+                    "buckets": buckets
+                        .iter()
+                        .map(|(key, credits, count)| {
+                            json!({ "key": key, "creditsUsed": credits, "requestCount": count })
+                        })
+                        .collect::<Vec<_>>()
+                }))
+            }
+            ("POST", ["v1", "keys"]) => {
+                let id = self.id("key");
+                ok(json!({
+                    "id": id,
+                    "key": format!("pcat_live_fake{}", id),
+                    "keyPrefix": "pcat_live_fak",
+                    "name": body["name"].as_str().unwrap_or("New Key"),
+                    "environment": "live",
+                    "createdAt": now,
+                    "warning": "Store this key securely. It will not be shown again."
+                }))
+            }
+            ("GET", ["v1", "keys"]) => ok(json!({ "keys": [api_key("key_default", &now)] })),
+            ("GET", ["v1", "keys", id]) => ok(api_key(id, &now)),
+            ("PATCH" | "DELETE", ["v1", "keys", _]) => ok(json!({ "success": true })),
+            ("POST", ["v1", "prompts"]) => {
+                let id = self.id("sub");
+                let model = body["model"].as_str().unwrap_or(DEFAULT_MODEL);
+                ok(submission(&id, model, "open"))
+            }
+            ("GET", ["v1", "prompts"]) => ok(json!({
+                "submissions": [],
+                "pagination": { "total": 0, "limit": 20, "offset": 0, "hasMore": false }
+            })),
+            ("GET", ["v1", "prompts", id]) => ok(submission(id, DEFAULT_MODEL, "open")),
+            ("POST", ["v1", "prompts", id, "transactions"]) => {
+                ok(submission(id, DEFAULT_MODEL, "paid"))
+            }
+            _ => error(
+                404,
+                "not_found",
+                "unknown_endpoint",
+                &format!("No such endpoint: {} {}", method, path),
+            ),
+        }
+    }
+}
+
+/// Serves requests from the shared state
+struct Responder {
+    state: Arc<Mutex<State>>,
+}
+
+impl Respond for Responder {
+    fn respond(&self, request: &Request) -> ResponseTemplate {
+        let mut state = lock(&self.state);
+        let request_id = state.id("req");
+        state
+            .respond(request)
+            .insert_header("X-Request-Id", request_id.as_str())
+    }
+}
+
+fn ok(body: Value) -> ResponseTemplate {
+    ResponseTemplate::new(200).set_body_json(body)
+}
+
+fn error(status: u16, error_type: &str, code: &str, message: &str) -> ResponseTemplate {
+    ResponseTemplate::new(status).set_body_json(json!({
+        "error": {
+            "type": error_type,
+            "code": code,
+            "message": message
+        }
+    }))
+}
+
+fn generation_not_found() -> ResponseTemplate {
+    error(
+        404,
+        "not_found",
+        "generation_not_found",
+        "Generation not found",
+    )
+}
+
+/// Round away floating point noise from credit arithmetic
+fn round(credits: f64) -> f64 {
+    (credits * 1e6).round() / 1e6
+}
+
+/// A stable, IPFS-looking hash for an ID
+fn ipfs_hash(id: &str) -> String {
+    let digits: String = id.bytes().map(|b| format!("{:02x}", b)).collect();
+    format!("Qm{:0<44}", digits)
+}
+
+fn model((id, name, provider, price): (&str, &str, &str, f64)) -> Value {
+    json!({
+        "id": id,
+        "name": name,
+        "description": format!("{} (fake)", name),
+        "provider": provider,
+        "maxPromptLength": 2000,
+        "outputFormat": "png",
+        "outputResolution": "1024x1024",
+        "priceUsd": price,
+        "capabilities": {
+            "supportedResolutions": ["1024x1024", "832x1216", "1216x832", "1344x768"]
+        }
+    })
+}
+
+fn model_price((id, _, _, price): (&str, &str, &str, f64)) -> Value {
+    let sol = price / SOL_PRICE;
+    json!({
+        "model": id,
+        "priceUsd": price,
+        "priceSol": round(sol),
+        "priceSolWithSlippage": round(sol * 1.05)
+    })
+}
+
+fn sol_amount(usd: f64) -> Value {
+    let sol = round(usd / SOL_PRICE);
+    json!({
+        "sol": sol,
+        "lamports": (sol * 1e9).round() as u64,
+        "usd": usd
+    })
+}
+
+fn collection(id: &str, name: &Value, description: &Value, items: u32, now: &str) -> Value {
+    json!({
+        "id": id,
+        "name": name,
+        "description": description,
+        "itemCount": items,
+        "createdAt": now,
+        "updatedAt": now
+    })
+}
+
+fn withdrawal(id: &str, amount: f64, wallet: &Value, now: &str) -> Value {
+    json!({
+        "withdrawalId": id,
+        "status": "completed",
+        "amountUsd": amount,
+        "amountSol": round(amount / SOL_PRICE),
+        "destinationWallet": wallet,
+        "txSignature": format!("payout_{}", id),
+        "createdAt": now,
+        "completedAt": now
+    })
+}
+
+fn api_key(id: &str, now: &str) -> Value {
+    json!({
+        "id": id,
+        "name": "Fake Key",
+        "keyPrefix": "pcat_test_fa",
+        "environment": "test",
+        "rateLimitTier": "standard",
+        "createdAt": now,
+        "lastUsedAt": now,
+        "revoked": false
+    })
+}
+
+fn submission(id: &str, model: &str, status: &str) -> Value {
+    let price = MODELS
+        .iter()
+        .find(|m| m.0 == model)
+        .map_or(MODELS[0].3, |m| m.3);
+    json!({
+        "submissionId": id,
+        "promptHash": ipfs_hash(id),
+        "paymentAddress": TREASURY,
+        "requiredAmount": sol_amount(price),
+        "memo": format!("PCAT:v1:{}:{}", model, id),
+        "model": model,
+        "slippageTolerance": 0.05,
+        "expiresAt": rfc3339(SystemTime::now() + Duration::from_secs(3600)),
+        "instructions": {
+            "1": "Send SOL to payment address",
+            "2": "Include memo in transaction"
+        },
+        "status": status
+    })
+}
+
+/// Format a time as an RFC 3339 UTC timestamp
+fn rfc3339(time: SystemTime) -> String {
+    let secs = time.duration_since(UNIX_EPOCH).map_or(0, |d| d.as_secs());
+    let (days, secs) = ((secs / 86_400) as i64, secs % 86_400);
+
+    // Civil date from days since the epoch
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z",
+        year,
+        month,
+        day,
+        secs / 3600,
+        secs % 3600 / 60,
+        secs % 60
+    )
+}
@@ -1,7 +1,98 @@
 //! PeerCat API types
 
+use base64::prelude::*;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::net::IpAddr;
+
+use ipnet::IpNet;
+
+use crate::error::{ConfigError, ConfigProblem, PeerCatError, Result};
+use crate::key_pool::KeyPool;
+use crate::metrics::PeerCatMetrics;
+use crate::report::ErrorReporter;
+use crate::retry_budget::RetryBudget;
+use crate::secret::SecretString;
+
+// ============ Timestamps ============
+
+/// Timestamp type used in API responses
+///
+/// With the default `chrono` feature, timestamps are parsed into
+/// `chrono::DateTime<Utc>`. Without it, they are kept as the raw RFC 3339
+/// strings returned by the API.
+#[cfg(feature = "chrono")]
+pub type Timestamp = chrono::DateTime<chrono::Utc>;
+
+/// Timestamp type used in API responses
+///
+/// With the default `chrono` feature, timestamps are parsed into
+/// `chrono::DateTime<Utc>`. Without it, they are kept as the raw RFC 3339
+/// strings returned by the API.
+#[cfg(not(feature = "chrono"))]
+pub type Timestamp = String;
+
+/// Format a timestamp as an RFC 3339 string
+#[cfg(all(feature = "export", feature = "chrono"))]
+pub(crate) fn format_timestamp(timestamp: &Timestamp) -> String {
+    timestamp.to_rfc3339_opts(chrono::SecondsFormat::AutoSi, true)
+}
+
+/// Format a timestamp as an RFC 3339 string
+#[cfg(all(feature = "export", not(feature = "chrono")))]
+pub(crate) fn format_timestamp(timestamp: &Timestamp) -> String {
+    timestamp.clone()
+}
+
+/// Returns true if the timestamp is now or in the past
+#[cfg(feature = "chrono")]
+fn is_past(timestamp: &Timestamp) -> bool {
+    *timestamp <= chrono::Utc::now()
+}
+
+/// Returns true if the timestamp is now or in the past
+///
+/// Timestamps that aren't valid RFC 3339 are treated as in the future.
+#[cfg(not(feature = "chrono"))]
+fn is_past(timestamp: &Timestamp) -> bool {
+    let now = web_time::SystemTime::now()
+        .duration_since(web_time::UNIX_EPOCH)
+        .map_or(0, |now| now.as_secs() as i64);
+    unix_seconds(timestamp).is_some_and(|seconds| seconds <= now)
+}
+
+/// Seconds since the Unix epoch for an RFC 3339 timestamp, ignoring fractions
+#[cfg(not(feature = "chrono"))]
+fn unix_seconds(timestamp: &str) -> Option<i64> {
+    let (date, time) = timestamp.split_once(['T', 't', ' '])?;
+    let (time, offset) = match time.strip_suffix(['Z', 'z']) {
+        Some(time) => (time, 0),
+        None => {
+            let (time, offset) = time.split_at(time.rfind(['+', '-'])?);
+            let sign = if offset.starts_with('-') { -1 } else { 1 };
+            let (hours, minutes) = offset[1..].split_once(':')?;
+            let seconds = hours.parse::<i64>().ok()? * 3600 + minutes.parse::<i64>().ok()? * 60;
+            (time, sign * seconds)
+        }
+    };
+
+    let fields = |s: &str, separator: char| -> Option<[i64; 3]> {
+        let mut parts = s.splitn(3, separator).map(|part| part.parse().ok());
+        Some([parts.next()??, parts.next()??, parts.next()??])
+    };
+    let [year, month, day] = fields(date, '-')?;
+    let [hour, minute, second] = fields(time.split('.').next()?, ':')?;
+
+    // Days since the epoch in the proleptic Gregorian calendar
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let day_of_year = (153 * ((month + 9) % 12) + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    let days = era * 146_097 + day_of_era - 719_468;
+
+    Some(days * 86_400 + hour * 3600 + minute * 60 + second - offset)
+}
 
 // ============ Configuration ============
 
@@ -9,26 +100,158 @@ use std::collections::HashMap;
 #[derive(Debug, Clone)]
 pub struct PeerCatConfig {
     /// API key for authentication
-    pub api_key: String,
+    pub api_key: SecretString,
     /// Base URL for the API (default: https://api.peerc.at)
     pub base_url: Option<String>,
-    /// Request timeout in seconds (default: 60)
+    /// Timeout for each attempt of a request, in seconds (default: 60)
     pub timeout: Option<u64>,
+    /// Timeout for each attempt, with sub-second precision (overrides `timeout`)
+    pub attempt_timeout: Option<std::time::Duration>,
+    /// Bound on a request's total time, across all attempts and backoff
+    pub overall_deadline: Option<std::time::Duration>,
     /// Number of retry attempts for failed requests (default: 3)
     pub max_retries: Option<u32>,
+    /// Pool of keys to rotate between (overrides `api_key` when set)
+    pub key_pool: Option<KeyPool>,
+    /// Mode used by `generate` when the params don't specify one
+    pub default_mode: Option<GenerationMode>,
+    /// Reject keys that don't have the `pcat_test_` prefix
+    pub require_test_key: bool,
+    /// Refuse production-only operations (withdrawals, key revocation, deletions)
+    pub block_production_operations: bool,
+    /// Answer generations locally with placeholders and refuse all network access
+    pub offline_demo: bool,
+    /// Refuse paid generations once the session has spent this many credits
+    pub budget: Option<f64>,
+    /// Pace requests using the rate limit headers of earlier responses
+    pub pace_rate_limits: bool,
+    /// Limit on retries shared by every request the client makes
+    pub retry_budget: Option<RetryBudget>,
+    /// Send a duplicate GET request when the first takes longer than this
+    pub hedge_after: Option<std::time::Duration>,
+    /// Share one upstream request between concurrent identical GETs (default: on)
+    pub coalesce_requests: bool,
+    /// Revalidate repeated GETs with their ETag or Last-Modified validators
+    pub conditional_requests: bool,
+    /// Warn about attempts that take longer than this
+    pub slow_request_threshold: Option<std::time::Duration>,
+    /// Sink for request, retry, rate limit and spending events (default: none)
+    pub metrics: Option<std::sync::Arc<dyn PeerCatMetrics>>,
+    /// Receives the error of every failed call (default: none)
+    pub error_reporter: Option<std::sync::Arc<dyn ErrorReporter>>,
+    /// Sink for a record of every call the client makes (default: none)
+    #[cfg(feature = "audit")]
+    pub audit_log: Option<std::sync::Arc<dyn crate::audit::AuditSink>>,
+    /// Fields redacted from audit records and VCR cassettes
+    #[cfg(any(feature = "audit", feature = "vcr"))]
+    pub redacted_fields: Vec<String>,
+    /// IPFS gateway used to resolve IPFS hashes (default: https://ipfs.io)
+    pub ipfs_gateway: Option<String>,
+    /// Idle connections kept open per host (default: unlimited)
+    pub pool_max_idle_per_host: Option<usize>,
+    /// How long idle connections are kept open (default: 90 seconds)
+    pub pool_idle_timeout: Option<std::time::Duration>,
+    /// Interval for TCP keepalive probes (default: off)
+    pub tcp_keepalive: Option<std::time::Duration>,
+    /// Which HTTP versions to use (default: negotiate)
+    pub http_version: HttpVersion,
+    /// Size HTTP/2 flow control windows from measured bandwidth
+    pub http2_adaptive_window: bool,
+    /// Addresses to connect to for these hosts instead of resolving them with DNS
+    pub resolve: Vec<(String, std::net::SocketAddr)>,
+    /// HTTP service used to send requests instead of the built-in client
+    #[cfg(all(feature = "tower", not(target_arch = "wasm32")))]
+    pub transport: Option<crate::service::Transport>,
+    /// Record interactions to, or replay them from, a cassette file
+    #[cfg(feature = "vcr")]
+    pub vcr: Option<crate::vcr::VcrMode>,
 }
 
 impl PeerCatConfig {
     /// Create a new configuration with just an API key
     pub fn new(api_key: impl Into<String>) -> Self {
         Self {
-            api_key: api_key.into(),
+            api_key: SecretString::new(api_key),
             base_url: None,
             timeout: None,
+            attempt_timeout: None,
+            overall_deadline: None,
             max_retries: None,
+            key_pool: None,
+            default_mode: None,
+            require_test_key: false,
+            block_production_operations: false,
+            offline_demo: false,
+            budget: None,
+            pace_rate_limits: false,
+            retry_budget: None,
+            hedge_after: None,
+            coalesce_requests: true,
+            conditional_requests: false,
+            slow_request_threshold: None,
+            metrics: None,
+            error_reporter: None,
+            #[cfg(feature = "audit")]
+            audit_log: None,
+            #[cfg(any(feature = "audit", feature = "vcr"))]
+            redacted_fields: Vec::new(),
+            ipfs_gateway: None,
+            pool_max_idle_per_host: None,
+            pool_idle_timeout: None,
+            tcp_keepalive: None,
+            http_version: HttpVersion::default(),
+            http2_adaptive_window: false,
+            resolve: Vec::new(),
+            #[cfg(all(feature = "tower", not(target_arch = "wasm32")))]
+            transport: None,
+            #[cfg(feature = "vcr")]
+            vcr: None,
         }
     }
 
+    /// Create a configuration for the test environment
+    ///
+    /// The key must have the `pcat_test_` prefix, generations default to
+    /// demo mode, and production-only operations (withdrawals, key
+    /// revocation, deletions) are refused unless
+    /// [`allow_production_operations`](Self::allow_production_operations)
+    /// is called.
+    pub fn test(api_key: impl Into<String>) -> Self {
+        let mut config = Self::new(api_key);
+        config.default_mode = Some(GenerationMode::Demo);
+        config.require_test_key = true;
+        config.block_production_operations = true;
+        config
+    }
+
+    /// Create a configuration for a client that works without any network
+    ///
+    /// `generate` and `generate_from_preset` return deterministic demo
+    /// results built locally: the same params always give the same ID and
+    /// seed, and the image URL is a shared placeholder. Every other call,
+    /// including image downloads, fails with
+    /// [`PeerCatError::OperationNotAllowed`](crate::PeerCatError::OperationNotAllowed)
+    /// instead of reaching the network. No API key is needed.
+    pub fn offline_demo() -> Self {
+        let mut config = Self::new("pcat_test_offline");
+        config.default_mode = Some(GenerationMode::Demo);
+        config.offline_demo = true;
+        config
+    }
+
+    /// Create a configuration that rotates between several API keys
+    ///
+    /// The first key in the pool is used as `api_key`.
+    pub fn from_key_pool(pool: KeyPool) -> Self {
+        let mut config = Self::new(
+            pool.primary()
+                .map(|key| key.expose_secret().to_string())
+                .unwrap_or_default(),
+        );
+        config.key_pool = Some(pool);
+        config
+    }
+
     /// Set a custom base URL
     pub fn with_base_url(mut self, url: impl Into<String>) -> Self {
         self.base_url = Some(url.into());
@@ -36,16 +259,434 @@ impl PeerCatConfig {
     }
 
     /// Set a custom timeout in seconds
+    ///
+    /// Applies to each attempt; with retries, a request can take several
+    /// times as long. See [`with_overall_deadline`](Self::with_overall_deadline).
     pub fn with_timeout(mut self, timeout: u64) -> Self {
         self.timeout = Some(timeout);
         self
     }
 
+    /// Set the timeout for each attempt of a request
+    pub fn with_attempt_timeout(mut self, timeout: std::time::Duration) -> Self {
+        self.attempt_timeout = Some(timeout);
+        self
+    }
+
+    /// Bound the total time a request may take, including retries and backoff
+    ///
+    /// An attempt still running at the deadline fails with
+    /// `PeerCatError::Timeout`, and no retry is started if its backoff would
+    /// end past the deadline; the last error is returned instead.
+    pub fn with_overall_deadline(mut self, deadline: std::time::Duration) -> Self {
+        self.overall_deadline = Some(deadline);
+        self
+    }
+
     /// Set the maximum number of retries
     pub fn with_max_retries(mut self, retries: u32) -> Self {
         self.max_retries = Some(retries);
         self
     }
+
+    /// Pace requests to stay within the API rate limit
+    ///
+    /// The client reads `X-RateLimit-Remaining` and `X-RateLimit-Reset` from
+    /// each response and spreads later requests over the rest of the window,
+    /// across all tasks sharing the client.
+    pub fn with_rate_limit_pacing(mut self) -> Self {
+        self.pace_rate_limits = true;
+        self
+    }
+
+    /// Limit retries to a share of recent requests (see [`RetryBudget`])
+    pub fn with_retry_budget(mut self, budget: RetryBudget) -> Self {
+        self.retry_budget = Some(budget);
+        self
+    }
+
+    /// Hedge GET requests that are slower than `after`
+    ///
+    /// When a GET (balance, models, prices, status, ...) hasn't responded
+    /// within `after`, a duplicate is sent and whichever succeeds first is
+    /// used. This trims tail latency at the cost of extra requests; pick a
+    /// threshold around the 95th percentile latency. Duplicates count as
+    /// retries against a [`RetryBudget`] and wait for rate limit pacing, so
+    /// they aren't sent while the API is throttling. Other methods are never
+    /// hedged.
+    pub fn with_hedging(mut self, after: std::time::Duration) -> Self {
+        self.hedge_after = Some(after);
+        self
+    }
+
+    /// Warn about attempts slower than `threshold`
+    ///
+    /// Each attempt that takes longer emits a
+    /// [`ClientEvent::SlowRequest`](crate::ClientEvent::SlowRequest) and, with
+    /// the `tracing` feature, a warning log.
+    pub fn with_slow_request_threshold(mut self, threshold: std::time::Duration) -> Self {
+        self.slow_request_threshold = Some(threshold);
+        self
+    }
+
+    /// Report requests, retries, rate limits and credits spent to `metrics`
+    pub fn with_metrics(mut self, metrics: impl PeerCatMetrics + 'static) -> Self {
+        self.metrics = Some(std::sync::Arc::new(metrics));
+        self
+    }
+
+    /// Pass the error of every failed call to `reporter`, after any retries
+    pub fn with_error_reporter(mut self, reporter: impl ErrorReporter + 'static) -> Self {
+        self.error_reporter = Some(std::sync::Arc::new(reporter));
+        self
+    }
+
+    /// Write an [`AuditRecord`](crate::audit::AuditRecord) of every call to `sink`
+    /// (requires the `audit` feature)
+    #[cfg(feature = "audit")]
+    pub fn with_audit_log(mut self, sink: impl crate::audit::AuditSink + 'static) -> Self {
+        self.audit_log = Some(std::sync::Arc::new(sink));
+        self
+    }
+
+    /// Redact a field from audit records and VCR cassettes
+    ///
+    /// `path` is a dot-separated list of field names, like `prompt` or
+    /// `options.negativePrompt`, matched wherever the field appears in a
+    /// request body (or, in cassettes, a response body). Names match the Rust
+    /// or JSON spelling, so `callback_url` covers `callbackUrl`. The field's
+    /// value is replaced with `"[REDACTED]"`, and any text of it echoed in an
+    /// error message is removed from audit records.
+    ///
+    /// ```
+    /// use peercat::PeerCatConfig;
+    ///
+    /// let config = PeerCatConfig::new("pcat_live_xxx")
+    ///     .with_redacted_field("prompt")
+    ///     .with_redacted_field("callback_url");
+    /// ```
+    #[cfg(any(feature = "audit", feature = "vcr"))]
+    pub fn with_redacted_field(mut self, path: impl Into<String>) -> Self {
+        self.redacted_fields.push(path.into());
+        self
+    }
+
+    /// Make repeated GETs conditional on the last response's validators
+    ///
+    /// Responses with an `ETag` or `Last-Modified` header are kept, and the
+    /// next GET for the same URL sends `If-None-Match`/`If-Modified-Since`.
+    /// On `304 Not Modified` the kept response is returned, so polling
+    /// endpoints like `/v1/models` and `/v1/price` costs no body transfer
+    /// while nothing changes.
+    pub fn with_conditional_requests(mut self) -> Self {
+        self.conditional_requests = true;
+        self
+    }
+
+    /// Send every GET request, even when an identical one is in flight
+    ///
+    /// By default, concurrent calls for the same GET (e.g. 50 tasks calling
+    /// `get_prices`) share one upstream request and its outcome: if it fails,
+    /// every waiting caller gets the error. Calls starting on different keys
+    /// of a [`KeyPool`](crate::KeyPool) aren't shared.
+    pub fn without_request_coalescing(mut self) -> Self {
+        self.coalesce_requests = false;
+        self
+    }
+
+    /// Set the IPFS gateway used to resolve image hashes
+    ///
+    /// Paths are resolved as `{gateway}/ipfs/{hash}`.
+    pub fn with_ipfs_gateway(mut self, url: impl Into<String>) -> Self {
+        self.ipfs_gateway = Some(url.into());
+        self
+    }
+
+    /// Set how many idle connections to keep open per host
+    ///
+    /// Raise this for services making many concurrent requests, so
+    /// connections are reused instead of reopened. Ignored on wasm32.
+    pub fn with_pool_max_idle_per_host(mut self, max: usize) -> Self {
+        self.pool_max_idle_per_host = Some(max);
+        self
+    }
+
+    /// Set how long idle connections are kept open. Ignored on wasm32.
+    pub fn with_pool_idle_timeout(mut self, timeout: std::time::Duration) -> Self {
+        self.pool_idle_timeout = Some(timeout);
+        self
+    }
+
+    /// Send TCP keepalive probes at this interval. Ignored on wasm32.
+    pub fn with_tcp_keepalive(mut self, interval: std::time::Duration) -> Self {
+        self.tcp_keepalive = Some(interval);
+        self
+    }
+
+    /// Choose the HTTP version instead of negotiating it. Ignored on wasm32.
+    ///
+    /// Use [`HttpVersion::Http1Only`] behind proxies that break HTTP/2.
+    pub fn with_http_version(mut self, version: HttpVersion) -> Self {
+        self.http_version = version;
+        self
+    }
+
+    /// Size HTTP/2 flow control windows from measured bandwidth. Ignored on wasm32.
+    pub fn with_http2_adaptive_window(mut self) -> Self {
+        self.http2_adaptive_window = true;
+        self
+    }
+
+    /// Connect to `addr` for `host` instead of resolving it with DNS. Ignored on wasm32.
+    ///
+    /// TLS and the `Host` header still use `host`, so this pins the API to
+    /// an internal egress address without custom DNS. The port in `addr` is
+    /// used in place of the URL's port unless it's 0. Can be called more
+    /// than once.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use peercat::PeerCatConfig;
+    ///
+    /// let config = PeerCatConfig::new("pcat_live_xxx")
+    ///     .with_resolve("api.peerc.at", "10.0.0.12:443".parse().unwrap());
+    /// ```
+    pub fn with_resolve(mut self, host: impl Into<String>, addr: std::net::SocketAddr) -> Self {
+        self.resolve.push((host.into(), addr));
+        self
+    }
+
+    /// Set the mode used by `generate` when the params don't specify one
+    pub fn with_default_mode(mut self, mode: GenerationMode) -> Self {
+        self.default_mode = Some(mode);
+        self
+    }
+
+    /// Allow production-only operations on a test configuration
+    pub fn allow_production_operations(mut self) -> Self {
+        self.block_production_operations = false;
+        self
+    }
+
+    /// Refuse paid generations once the session has spent `max_credits`
+    ///
+    /// A safety net for runaway loops: when
+    /// [`PeerCat::session_spend`](crate::PeerCat::session_spend) reaches the
+    /// limit, generations that aren't in demo mode fail with
+    /// [`PeerCatError::BudgetExceeded`] without being sent. The check runs
+    /// before each generation, so generations already in flight can take the
+    /// total past the limit. Resetting the session spend lifts the block.
+    pub fn with_budget(mut self, max_credits: f64) -> Self {
+        self.budget = Some(max_credits);
+        self
+    }
+
+    /// Send requests through a tower service (see [`crate::service`])
+    #[cfg(all(feature = "tower", not(target_arch = "wasm32")))]
+    pub fn with_transport<S>(mut self, service: S) -> Self
+    where
+        S: tower::Service<crate::service::HttpRequest, Response = crate::service::HttpResponse>
+            + Clone
+            + Send
+            + Sync
+            + 'static,
+        S::Error: Into<crate::service::BoxError>,
+        S::Future: Send + 'static,
+    {
+        self.transport = Some(crate::service::Transport::new(service));
+        self
+    }
+
+    /// Record or replay API interactions (see [`crate::vcr`])
+    #[cfg(feature = "vcr")]
+    pub fn with_vcr(mut self, mode: crate::vcr::VcrMode) -> Self {
+        self.vcr = Some(mode);
+        self
+    }
+
+    /// Check the configuration for problems without creating a client
+    ///
+    /// Runs the checks of [`PeerCatBuilder::build`](crate::PeerCatBuilder::build):
+    /// the API keys, base URL syntax, timeout ranges and retry settings.
+    /// [`PeerCat::with_config`](crate::PeerCat::with_config) only checks the
+    /// keys, so it accepts settings this rejects, e.g. more than 10 retries.
+    ///
+    /// # Errors
+    ///
+    /// Returns a `ConfigError` listing every problem found.
+    pub fn validate(&self) -> std::result::Result<(), ConfigError> {
+        let mut problems = self.key_problems();
+        let mut problem = |field, message: &str| {
+            problems.push(ConfigProblem {
+                field,
+                message: message.to_string(),
+            })
+        };
+
+        if let Some(url) = &self.base_url {
+            if let Err(message) = check_base_url(url) {
+                problem("base_url", message);
+            }
+        }
+        if let Some(url) = &self.ipfs_gateway {
+            if let Err(message) = check_base_url(url) {
+                problem("ipfs_gateway", message);
+            }
+        }
+
+        match self.timeout {
+            Some(0) => problem("timeout", "must be at least 1 second"),
+            Some(secs) if secs > MAX_ATTEMPT_TIMEOUT.as_secs() => {
+                problem("timeout", "must be at most one hour")
+            }
+            _ => {}
+        }
+        match self.attempt_timeout {
+            Some(timeout) if timeout.is_zero() => {
+                problem("attempt_timeout", "must be greater than zero")
+            }
+            Some(timeout) if timeout > MAX_ATTEMPT_TIMEOUT => {
+                problem("attempt_timeout", "must be at most one hour")
+            }
+            _ => {}
+        }
+        if self
+            .overall_deadline
+            .is_some_and(|deadline| deadline.is_zero())
+        {
+            problem("overall_deadline", "must be greater than zero");
+        }
+        if self
+            .max_retries
+            .is_some_and(|retries| retries > MAX_RETRIES)
+        {
+            problem("max_retries", "must be at most 10");
+        }
+        if self.hedge_after.is_some_and(|after| after.is_zero()) {
+            problem("hedge_after", "must be greater than zero");
+        }
+        if self
+            .slow_request_threshold
+            .is_some_and(|threshold| threshold.is_zero())
+        {
+            problem("slow_request_threshold", "must be greater than zero");
+        }
+        if self
+            .budget
+            .is_some_and(|budget| !(budget >= 0.0 && budget.is_finite()))
+        {
+            problem("budget", "must be a finite, non-negative number of credits");
+        }
+
+        if problems.is_empty() {
+            Ok(())
+        } else {
+            Err(ConfigError::new(problems))
+        }
+    }
+
+    /// Problems with the API keys, which every client is checked for
+    pub(crate) fn key_problems(&self) -> Vec<ConfigProblem> {
+        let keys = match &self.key_pool {
+            Some(pool) => pool.keys().iter().map(|key| key.expose_secret()).collect(),
+            None => vec![self.api_key.expose_secret()],
+        };
+        let field = if self.key_pool.is_some() {
+            "key_pool"
+        } else {
+            "api_key"
+        };
+        let message = if keys.is_empty() || keys.iter().any(|key| key.is_empty()) {
+            "is required"
+        } else if keys
+            .iter()
+            .any(|key| key.starts_with(KEY_PREFIX) && KeyEnvironment::from_key(key).is_none())
+        {
+            "must start with pcat_live_ or pcat_test_"
+        } else if keys
+            .iter()
+            .any(|key| KeyEnvironment::from_key(key) != KeyEnvironment::from_key(keys[0]))
+        {
+            "mixes keys from different environments"
+        } else if self.require_test_key
+            && !keys
+                .iter()
+                .all(|key| KeyEnvironment::from_key(key) == Some(KeyEnvironment::Test))
+        {
+            "test client requires a pcat_test_ API key"
+        } else {
+            return Vec::new();
+        };
+        vec![ConfigProblem {
+            field,
+            message: message.to_string(),
+        }]
+    }
+}
+
+/// Prefix shared by every PeerCat API key
+const KEY_PREFIX: &str = "pcat_";
+
+/// Longest attempt timeout [`PeerCatConfig::validate`] accepts
+const MAX_ATTEMPT_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(3600);
+
+/// Most retries [`PeerCatConfig::validate`] accepts
+const MAX_RETRIES: u32 = 10;
+
+/// Check that a URL can be used as a base for API paths
+fn check_base_url(url: &str) -> std::result::Result<(), &'static str> {
+    let url = reqwest::Url::parse(url).map_err(|_| "is not a valid URL")?;
+    if !matches!(url.scheme(), "http" | "https") {
+        return Err("must use http or https");
+    }
+    if url.host_str().is_none() {
+        return Err("must have a host");
+    }
+    if url.query().is_some() || url.fragment().is_some() {
+        return Err("must not have a query or fragment");
+    }
+    Ok(())
+}
+
+/// HTTP versions the client may use
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum HttpVersion {
+    /// Negotiate HTTP/2 over TLS, falling back to HTTP/1.1
+    #[default]
+    Auto,
+    /// Only use HTTP/1.1
+    Http1Only,
+    /// Use HTTP/2 without negotiating (prior knowledge), e.g. for h2c
+    Http2PriorKnowledge,
+}
+
+/// What [`PeerCat::warmup`](crate::PeerCat::warmup) loads besides opening a connection
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct WarmupOptions {
+    /// Load the model list into the catalog cache
+    pub models: bool,
+    /// Fetch current prices
+    pub prices: bool,
+}
+
+impl WarmupOptions {
+    /// Only open a connection
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Also load the model list into the catalog cache
+    pub fn with_models(mut self) -> Self {
+        self.models = true;
+        self
+    }
+
+    /// Also fetch current prices, priming conditional requests if enabled
+    pub fn with_prices(mut self) -> Self {
+        self.prices = true;
+        self
+    }
 }
 
 // ============ Models ============
@@ -70,6 +711,310 @@ pub struct Model {
     pub output_resolution: String,
     /// Price in USD
     pub price_usd: f64,
+    /// Structured capabilities (absent for models that don't report them)
+    #[serde(default)]
+    pub capabilities: Option<ModelCapabilities>,
+}
+
+impl Model {
+    /// Returns true if the model can produce the given resolution
+    ///
+    /// Falls back to `output_resolution` for models that don't report
+    /// capabilities.
+    pub fn supports_resolution(&self, resolution: Resolution) -> bool {
+        match &self.capabilities {
+            Some(caps) if !caps.supported_resolutions.is_empty() => {
+                caps.supported_resolutions.contains(&resolution)
+            }
+            _ => self.output_resolution.parse() == Ok(resolution),
+        }
+    }
+
+    /// The supported resolution closest to an aspect ratio
+    ///
+    /// Ties go to the larger resolution. Returns `None` if the model reports
+    /// no resolutions.
+    pub fn resolution_for(&self, aspect_ratio: AspectRatio) -> Option<Resolution> {
+        let target = aspect_ratio.ratio().ln();
+        let distance = |r: &Resolution| (r.ratio().ln() - target).abs();
+
+        let resolutions: Vec<Resolution> = match &self.capabilities {
+            Some(caps) if !caps.supported_resolutions.is_empty() => {
+                caps.supported_resolutions.clone()
+            }
+            _ => self.output_resolution.parse().into_iter().collect(),
+        };
+        resolutions.into_iter().min_by(|a, b| {
+            distance(a)
+                .total_cmp(&distance(b))
+                .then_with(|| b.pixels().cmp(&a.pixels()))
+        })
+    }
+}
+
+/// Features and limits supported by a model
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ModelCapabilities {
+    /// Output resolutions the model can produce
+    #[serde(default)]
+    pub supported_resolutions: Vec<Resolution>,
+    /// Whether the model accepts a negative prompt
+    #[serde(default)]
+    pub supports_negative_prompt: bool,
+    /// Whether the model accepts a seed for reproducible output
+    #[serde(default)]
+    pub supports_seed: bool,
+    /// Maximum number of images per request
+    #[serde(default = "default_max_images_per_request")]
+    pub max_images_per_request: u32,
+    /// Accepted number of inference steps
+    #[serde(default)]
+    pub steps: Option<ParamRange>,
+    /// Accepted guidance scale
+    #[serde(default)]
+    pub guidance_scale: Option<ParamRange>,
+    /// Accepted image-to-image strength
+    #[serde(default)]
+    pub strength: Option<ParamRange>,
+}
+
+/// Inclusive range of values a model accepts for a numeric parameter
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub struct ParamRange {
+    /// Smallest accepted value
+    pub min: f64,
+    /// Largest accepted value
+    pub max: f64,
+}
+
+impl ParamRange {
+    /// Returns true if `value` is within the range
+    pub fn contains(&self, value: f64) -> bool {
+        (self.min..=self.max).contains(&value)
+    }
+}
+
+fn default_max_images_per_request() -> u32 {
+    1
+}
+
+/// An image resolution, serialized as `"{width}x{height}"`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct Resolution {
+    /// Width in pixels
+    pub width: u32,
+    /// Height in pixels
+    pub height: u32,
+}
+
+impl Resolution {
+    /// Create a resolution from width and height in pixels
+    pub fn new(width: u32, height: u32) -> Self {
+        Self { width, height }
+    }
+
+    fn ratio(&self) -> f64 {
+        f64::from(self.width.max(1)) / f64::from(self.height.max(1))
+    }
+
+    fn pixels(&self) -> u64 {
+        u64::from(self.width) * u64::from(self.height)
+    }
+}
+
+/// Shape of an image, resolved to a resolution the chosen model supports
+///
+/// Serialized as `"square"`, `"portrait"`, `"landscape"` or `"{width}:{height}"`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub enum AspectRatio {
+    /// 1:1
+    Square,
+    /// 2:3, taller than wide
+    Portrait,
+    /// 3:2, wider than tall
+    Landscape,
+    /// Any other width-to-height ratio, e.g. `Custom(16, 9)`
+    Custom(u32, u32),
+}
+
+impl AspectRatio {
+    /// Width divided by height
+    pub fn ratio(&self) -> f64 {
+        match self {
+            AspectRatio::Square => 1.0,
+            AspectRatio::Portrait => 2.0 / 3.0,
+            AspectRatio::Landscape => 1.5,
+            AspectRatio::Custom(width, height) => Resolution::new(*width, *height).ratio(),
+        }
+    }
+}
+
+impl std::fmt::Display for AspectRatio {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AspectRatio::Square => f.write_str("square"),
+            AspectRatio::Portrait => f.write_str("portrait"),
+            AspectRatio::Landscape => f.write_str("landscape"),
+            AspectRatio::Custom(width, height) => write!(f, "{}:{}", width, height),
+        }
+    }
+}
+
+impl std::str::FromStr for AspectRatio {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s {
+            "square" => Ok(AspectRatio::Square),
+            "portrait" => Ok(AspectRatio::Portrait),
+            "landscape" => Ok(AspectRatio::Landscape),
+            _ => {
+                let invalid = || format!("invalid aspect ratio: {}", s);
+                let (width, height) = s.split_once(':').ok_or_else(invalid)?;
+                Ok(AspectRatio::Custom(
+                    width.trim().parse().map_err(|_| invalid())?,
+                    height.trim().parse().map_err(|_| invalid())?,
+                ))
+            }
+        }
+    }
+}
+
+impl TryFrom<String> for AspectRatio {
+    type Error = String;
+
+    fn try_from(s: String) -> std::result::Result<Self, Self::Error> {
+        s.parse()
+    }
+}
+
+impl From<AspectRatio> for String {
+    fn from(aspect_ratio: AspectRatio) -> Self {
+        aspect_ratio.to_string()
+    }
+}
+
+impl std::fmt::Display for Resolution {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}x{}", self.width, self.height)
+    }
+}
+
+impl std::str::FromStr for Resolution {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        let (width, height) = s
+            .split_once('x')
+            .ok_or_else(|| format!("invalid resolution: {}", s))?;
+        Ok(Self {
+            width: width
+                .trim()
+                .parse()
+                .map_err(|_| format!("invalid resolution width: {}", s))?,
+            height: height
+                .trim()
+                .parse()
+                .map_err(|_| format!("invalid resolution height: {}", s))?,
+        })
+    }
+}
+
+impl TryFrom<String> for Resolution {
+    type Error = String;
+
+    fn try_from(s: String) -> std::result::Result<Self, Self::Error> {
+        s.parse()
+    }
+}
+
+impl From<Resolution> for String {
+    fn from(resolution: Resolution) -> Self {
+        resolution.to_string()
+    }
+}
+
+/// Model IDs known to this SDK version
+///
+/// Accepted anywhere a model ID is, e.g. `with_model(KnownModel::Imagen3)`.
+/// Use [`KnownModel::Other`] for models added after this release.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(from = "String", into = "String")]
+pub enum KnownModel {
+    /// `stable-diffusion-xl`
+    StableDiffusionXl,
+    /// `imagen-3`
+    Imagen3,
+    /// Any other model ID
+    Other(String),
+}
+
+impl KnownModel {
+    /// The model ID sent to the API
+    pub fn as_str(&self) -> &str {
+        match self {
+            KnownModel::StableDiffusionXl => "stable-diffusion-xl",
+            KnownModel::Imagen3 => "imagen-3",
+            KnownModel::Other(id) => id,
+        }
+    }
+}
+
+impl std::fmt::Display for KnownModel {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl From<String> for KnownModel {
+    fn from(id: String) -> Self {
+        match id.as_str() {
+            "stable-diffusion-xl" => KnownModel::StableDiffusionXl,
+            "imagen-3" => KnownModel::Imagen3,
+            _ => KnownModel::Other(id),
+        }
+    }
+}
+
+impl From<&str> for KnownModel {
+    fn from(id: &str) -> Self {
+        id.to_string().into()
+    }
+}
+
+impl From<KnownModel> for String {
+    fn from(model: KnownModel) -> Self {
+        match model {
+            KnownModel::Other(id) => id,
+            known => known.as_str().to_string(),
+        }
+    }
+}
+
+/// A style adapter (LoRA) available for a model
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Lora {
+    /// LoRA ID
+    pub id: String,
+    /// Human-readable name
+    pub name: String,
+    /// Description
+    pub description: Option<String>,
+    /// Words to include in the prompt to trigger the style
+    #[serde(default)]
+    pub trigger_words: Vec<String>,
+    /// Recommended weight
+    pub default_weight: f64,
+}
+
+/// Response containing the LoRAs available for a model
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LorasResponse {
+    pub loras: Vec<Lora>,
 }
 
 /// Response containing available models
@@ -78,6 +1023,34 @@ pub struct ModelsResponse {
     pub models: Vec<Model>,
 }
 
+impl ModelsResponse {
+    /// The cheapest model priced at or below `usd`
+    pub fn cheapest_under(&self, usd: f64) -> Option<&Model> {
+        self.models
+            .iter()
+            .filter(|m| m.price_usd <= usd)
+            .min_by(|a, b| a.price_usd.total_cmp(&b.price_usd))
+    }
+
+    /// The cheapest model that supports a resolution such as `"2048x2048"`
+    ///
+    /// Returns `None` if no model supports it or the resolution is malformed.
+    pub fn best_for_resolution(&self, resolution: &str) -> Option<&Model> {
+        let resolution: Resolution = resolution.parse().ok()?;
+        self.models
+            .iter()
+            .filter(|m| m.supports_resolution(resolution))
+            .min_by(|a, b| a.price_usd.total_cmp(&b.price_usd))
+    }
+
+    /// Models from the given provider (case-insensitive)
+    pub fn filter_by_provider<'a>(&'a self, provider: &'a str) -> impl Iterator<Item = &'a Model> {
+        self.models
+            .iter()
+            .filter(move |m| m.provider.eq_ignore_ascii_case(provider))
+    }
+}
+
 // ============ Pricing ============
 
 /// Price information for a specific model
@@ -103,32 +1076,133 @@ pub struct PriceResponse {
     /// Slippage tolerance (e.g., 0.02 = 2%)
     pub slippage_tolerance: f64,
     /// Timestamp of price update
-    pub updated_at: String,
+    pub updated_at: Timestamp,
     /// Treasury PDA address to send payments to
     pub treasury: String,
     /// Prices for each model
     pub models: Vec<ModelPrice>,
 }
 
-// ============ Generation ============
+impl PriceResponse {
+    /// Convert a USD amount to SOL at the current price
+    pub fn usd_to_sol(&self, usd: f64) -> f64 {
+        usd / self.sol_price
+    }
 
-/// Generation mode
-#[derive(Debug, Clone, Copy, Serialize, Deserialize, Default, PartialEq, Eq)]
-#[serde(rename_all = "lowercase")]
-pub enum GenerationMode {
-    /// Production mode - uses credits
-    #[default]
-    Production,
-    /// Demo mode - free, returns placeholder images
-    Demo,
+    /// Convert a SOL amount to lamports
+    pub fn sol_to_lamports(sol: f64) -> Lamports {
+        Lamports::from_sol(sol)
+    }
+
+    /// Look up the price entry for a model
+    pub fn price_for(&self, model: &str) -> Option<&ModelPrice> {
+        self.models.iter().find(|price| price.model == model)
+    }
 }
 
-/// Parameters for image generation
-#[derive(Debug, Clone, Serialize)]
-pub struct GenerateParams {
-    /// Text prompt for image generation (max 2000 characters)
-    pub prompt: String,
-    /// Model to use (default: stable-diffusion-xl)
+/// An amount of SOL in lamports (1 SOL = 1,000,000,000 lamports)
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default, Serialize, Deserialize,
+)]
+#[serde(transparent)]
+pub struct Lamports(pub u64);
+
+impl Lamports {
+    /// Number of lamports in one SOL
+    pub const PER_SOL: u64 = 1_000_000_000;
+
+    /// Convert a SOL amount to lamports, rounding to the nearest lamport
+    ///
+    /// Negative and NaN amounts convert to zero.
+    pub fn from_sol(sol: f64) -> Self {
+        Self((sol * Self::PER_SOL as f64).round() as u64)
+    }
+
+    /// The amount in SOL
+    pub fn as_sol(self) -> f64 {
+        self.0 as f64 / Self::PER_SOL as f64
+    }
+}
+
+impl std::fmt::Display for Lamports {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} lamports", self.0)
+    }
+}
+
+impl From<u64> for Lamports {
+    fn from(lamports: u64) -> Self {
+        Self(lamports)
+    }
+}
+
+/// Resolution of historical price data
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum PriceGranularity {
+    Minute,
+    Hour,
+    Day,
+}
+
+/// Prices in effect at a point in time
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PricePoint {
+    /// Start of the period this point covers
+    pub timestamp: Timestamp,
+    /// SOL/USD price
+    pub sol_price: f64,
+    /// Prices for each model
+    #[serde(default)]
+    pub models: Vec<ModelPrice>,
+}
+
+/// Response containing historical prices
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PriceHistory {
+    /// Granularity of the points
+    pub granularity: PriceGranularity,
+    /// Price points, oldest first
+    pub points: Vec<PricePoint>,
+}
+
+impl PriceHistory {
+    /// The price point in effect at `timestamp` (the latest point at or before it)
+    pub fn at(&self, timestamp: &Timestamp) -> Option<&PricePoint> {
+        self.points
+            .iter()
+            .filter(|point| &point.timestamp <= timestamp)
+            .max_by(|a, b| a.timestamp.cmp(&b.timestamp))
+    }
+}
+
+// ============ Generation ============
+
+/// Generation mode
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum GenerationMode {
+    /// Production mode - uses credits
+    #[default]
+    Production,
+    /// Demo mode - free, returns placeholder images
+    Demo,
+}
+
+/// Parameters for image generation
+///
+/// `aspect_ratio` and `sampler` are resolved into `options` when generating,
+/// so they're never sent to the API themselves.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct GenerateParams {
+    /// Text prompt for image generation (max 2000 characters)
+    pub prompt: String,
+    /// Weighted prompts to blend, sent alongside `prompt`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub prompts: Vec<WeightedPrompt>,
+    /// Model to use (default: stable-diffusion-xl)
     #[serde(skip_serializing_if = "Option::is_none")]
     pub model: Option<String>,
     /// Mode: production (default) or demo (free, placeholder images)
@@ -137,6 +1211,33 @@ pub struct GenerateParams {
     /// Additional model-specific options
     #[serde(skip_serializing_if = "Option::is_none")]
     pub options: Option<HashMap<String, serde_json::Value>>,
+    /// Content filter strictness (default: the account's setting)
+    #[serde(rename = "safetyLevel", skip_serializing_if = "Option::is_none")]
+    pub safety_level: Option<SafetyLevel>,
+    /// Random seed, for reproducible output (default: picked at random)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub seed: Option<u64>,
+    /// Shape of the image, resolved to a `resolution` option when generating
+    #[serde(rename = "aspectRatio", skip_serializing_if = "Option::is_none")]
+    pub aspect_ratio: Option<AspectRatio>,
+    /// Output quality from 1 to 100; lower values produce smaller files
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub quality: Option<u8>,
+    /// Sampling method, sent under the option key the model expects
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sampler: Option<Sampler>,
+    /// Style adapters (LoRAs) to apply, in order
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub adapters: Vec<LoraAdapter>,
+    /// Structural guidance from a control image
+    #[serde(rename = "controlNet", skip_serializing_if = "Option::is_none")]
+    pub controlnet: Option<ControlNetParams>,
+    /// Translate the prompt to English server-side before generating
+    #[serde(rename = "autoTranslate", skip_serializing_if = "Option::is_none")]
+    pub auto_translate: Option<bool>,
+    /// Language of the prompt as an ISO 639-1 code, e.g. "ja" (default: detected)
+    #[serde(rename = "sourceLanguage", skip_serializing_if = "Option::is_none")]
+    pub source_language: Option<String>,
 }
 
 impl GenerateParams {
@@ -144,12 +1245,46 @@ impl GenerateParams {
     pub fn new(prompt: impl Into<String>) -> Self {
         Self {
             prompt: prompt.into(),
+            prompts: Vec::new(),
             model: None,
             mode: None,
             options: None,
+            safety_level: None,
+            seed: None,
+            aspect_ratio: None,
+            quality: None,
+            sampler: None,
+            adapters: Vec::new(),
+            controlnet: None,
+            auto_translate: None,
+            source_language: None,
         }
     }
 
+    /// Blend several prompts by weight, e.g. `[("cyberpunk city", 0.7), ("watercolor", 0.3)]`
+    ///
+    /// `prompt` is set to the texts joined with commas, for models and
+    /// records that only take a single prompt.
+    pub fn with_prompts<S: Into<String>>(
+        mut self,
+        prompts: impl IntoIterator<Item = (S, f64)>,
+    ) -> Self {
+        self.prompts = prompts
+            .into_iter()
+            .map(|(text, weight)| WeightedPrompt {
+                text: text.into(),
+                weight,
+            })
+            .collect();
+        self.prompt = self
+            .prompts
+            .iter()
+            .map(|prompt| prompt.text.as_str())
+            .collect::<Vec<_>>()
+            .join(", ");
+        self
+    }
+
     /// Set the model to use
     pub fn with_model(mut self, model: impl Into<String>) -> Self {
         self.model = Some(model.into());
@@ -174,34 +1309,980 @@ impl GenerateParams {
         options.insert(key.into(), value);
         self
     }
+
+    /// Set how strictly prompts and images are filtered
+    pub fn with_safety_level(mut self, level: SafetyLevel) -> Self {
+        self.safety_level = Some(level);
+        self
+    }
+
+    /// Translate a non-English prompt server-side before generating
+    pub fn with_auto_translate(mut self, enabled: bool) -> Self {
+        self.auto_translate = Some(enabled);
+        self
+    }
+
+    /// Hint the prompt's language (an ISO 639-1 code such as "ja") for translation
+    pub fn with_source_language(mut self, language: impl Into<String>) -> Self {
+        self.source_language = Some(language.into());
+        self
+    }
+
+    /// Set the random seed, so the same params produce the same image
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.seed = Some(seed);
+        self
+    }
+
+    /// Set the shape of the image
+    ///
+    /// When generating, this is resolved to the closest resolution the model
+    /// supports, using the client's cached model list. A `resolution` set with
+    /// [`with_option`](Self::with_option) takes precedence.
+    pub fn with_aspect_ratio(mut self, aspect_ratio: AspectRatio) -> Self {
+        self.aspect_ratio = Some(aspect_ratio);
+        self
+    }
+
+    /// Set the output quality (clamped to 1-100), trading detail for file size
+    pub fn with_quality(mut self, quality: u8) -> Self {
+        self.quality = Some(quality.clamp(1, 100));
+        self
+    }
+
+    /// Set the sampling method
+    ///
+    /// Models name this option differently, so the key is chosen when
+    /// generating, based on the model.
+    pub fn with_sampler(mut self, sampler: Sampler) -> Self {
+        self.sampler = Some(sampler);
+        self
+    }
+
+    /// Apply a style adapter (LoRA) with the given weight; call again to stack adapters
+    pub fn with_lora(mut self, id: impl Into<String>, weight: f64) -> Self {
+        self.adapters.push(LoraAdapter {
+            id: id.into(),
+            weight,
+        });
+        self
+    }
+
+    /// Guide the image's structure with a control image
+    pub fn with_controlnet(mut self, controlnet: ControlNetParams) -> Self {
+        self.controlnet = Some(controlnet);
+        self
+    }
+
+    /// Set the number of inference steps
+    pub fn with_steps(self, steps: u32) -> Self {
+        self.with_option(STEPS_OPTION, steps.into())
+    }
+
+    /// Set how closely the image follows the prompt
+    pub fn with_guidance_scale(self, guidance_scale: f64) -> Self {
+        self.with_option(GUIDANCE_SCALE_OPTION, guidance_scale.into())
+    }
+
+    /// Set how far an image-to-image generation may stray from the input (0 to 1)
+    pub fn with_strength(self, strength: f64) -> Self {
+        self.with_option(STRENGTH_OPTION, strength.into())
+    }
+
+    /// Check numeric options against the ranges the model reports
+    ///
+    /// Catches out-of-range `steps`, `guidanceScale`, and `strength` values
+    /// before a request is sent. [`PeerCat::generate`](crate::PeerCat::generate)
+    /// runs this automatically when any of them are set.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`PeerCatError::Validation`] naming the first invalid option.
+    pub fn validate_for(&self, model: &Model) -> Result<()> {
+        match self.range_issues(model).into_iter().next() {
+            Some(issue) => Err(issue.into()),
+            None => Ok(()),
+        }
+    }
+
+    /// Check the params against their model, collecting every problem
+    ///
+    /// Unlike [`validate_for`](Self::validate_for), this doesn't stop at the
+    /// first problem, so a form can show them all before submitting. Besides
+    /// option ranges, it checks that:
+    ///
+    /// - the prompt is set and within the model's `max_prompt_length`
+    /// - the seed and negative prompt aren't set for a model whose
+    ///   capabilities rule them out. Other option keys are model-specific
+    ///   and passed through as-is, so they aren't reported
+    /// - demo mode isn't combined with options, adapters or a control image,
+    ///   which have no effect on its placeholder images
+    /// - a source language isn't set with translation turned off
+    ///
+    /// `model` should be the one the params select, e.g. from
+    /// [`PeerCat::get_model`](crate::PeerCat::get_model). Returns an empty
+    /// list if the params are valid.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use peercat::{GenerateParams, Model};
+    ///
+    /// # fn example(model: &Model) {
+    /// let params = GenerateParams::new("").with_demo_mode().with_steps(30);
+    /// for issue in params.validate(model) {
+    ///     println!("{}", issue);
+    /// }
+    /// # }
+    /// ```
+    pub fn validate(&self, model: &Model) -> Vec<ValidationIssue> {
+        let mut issues = Vec::new();
+
+        let length = self.prompt.chars().count();
+        if self.prompt.trim().is_empty() {
+            issues.push(ValidationIssue::new("prompt", "is required"));
+        } else if length > model.max_prompt_length as usize {
+            issues.push(ValidationIssue::new(
+                "prompt",
+                format!(
+                    "is {} characters, but {} accepts at most {}",
+                    length, model.id, model.max_prompt_length
+                ),
+            ));
+        }
+
+        issues.extend(self.range_issues(model));
+
+        if let Some(caps) = &model.capabilities {
+            if self.seed.is_some() && !caps.supports_seed {
+                issues.push(ValidationIssue::new(
+                    "seed",
+                    format!("{} doesn't accept a seed", model.id),
+                ));
+            }
+            let mut keys: Vec<_> = self
+                .options
+                .iter()
+                .flat_map(|options| options.keys())
+                .collect();
+            keys.sort();
+            for key in keys {
+                if rules_out_option(caps, key) {
+                    issues.push(ValidationIssue::new(
+                        key.clone(),
+                        format!("isn't an option {} accepts", model.id),
+                    ));
+                }
+            }
+            let resolution = self
+                .option(RESOLUTION_OPTION)
+                .filter(|_| !caps.supported_resolutions.is_empty());
+            if let Some(value) = resolution {
+                let resolution = value.as_str().and_then(|value| value.parse().ok());
+                if !resolution
+                    .is_some_and(|resolution| caps.supported_resolutions.contains(&resolution))
+                {
+                    issues.push(ValidationIssue::new(
+                        RESOLUTION_OPTION,
+                        format!("{} isn't a resolution {} supports", value, model.id),
+                    ));
+                }
+            }
+        }
+
+        if self.mode == Some(GenerationMode::Demo) {
+            let demo_conflicts = [
+                (
+                    "options",
+                    self.options
+                        .as_ref()
+                        .is_some_and(|options| !options.is_empty()),
+                ),
+                ("adapters", !self.adapters.is_empty()),
+                ("controlnet", self.controlnet.is_some()),
+            ];
+            for (field, set) in demo_conflicts {
+                if set {
+                    issues.push(ValidationIssue::new(
+                        field,
+                        "has no effect in demo mode, which returns placeholder images",
+                    ));
+                }
+            }
+        }
+        if self.auto_translate == Some(false) && self.source_language.is_some() {
+            issues.push(ValidationIssue::new(
+                "source_language",
+                "has no effect with auto_translate turned off",
+            ));
+        }
+        issues
+    }
+
+    /// Options outside the ranges the model reports
+    fn range_issues(&self, model: &Model) -> Vec<ValidationIssue> {
+        let Some(caps) = &model.capabilities else {
+            return Vec::new();
+        };
+        let checks = [
+            (STEPS_OPTION, caps.steps),
+            (GUIDANCE_SCALE_OPTION, caps.guidance_scale),
+            (STRENGTH_OPTION, caps.strength),
+        ];
+
+        let mut issues = Vec::new();
+        for (field, range) in checks {
+            let (Some(value), Some(range)) = (self.option(field), range) else {
+                continue;
+            };
+            match value.as_f64() {
+                None => issues.push(ValidationIssue::new(
+                    field,
+                    format!("expected a number, got {}", value),
+                )),
+                Some(number) if !range.contains(number) => issues.push(ValidationIssue::new(
+                    field,
+                    format!(
+                        "{} must be between {} and {} for {}",
+                        number, range.min, range.max, model.id
+                    ),
+                )),
+                Some(_) => {}
+            }
+        }
+        issues
+    }
+
+    /// Returns true if any option with a model-specific range is set
+    pub(crate) fn has_ranged_options(&self) -> bool {
+        [STEPS_OPTION, GUIDANCE_SCALE_OPTION, STRENGTH_OPTION]
+            .iter()
+            .any(|key| self.option(key).is_some())
+    }
+
+    /// Move the sampler into the options under the model's key
+    pub(crate) fn resolve_sampler(&mut self) {
+        let Some(sampler) = self.sampler.take() else {
+            return;
+        };
+        let model = self.model.as_deref().map(KnownModel::from);
+        let key = sampler_option(&model.unwrap_or(KnownModel::StableDiffusionXl));
+        self.options
+            .get_or_insert_with(HashMap::new)
+            .entry(key.to_string())
+            .or_insert_with(|| sampler.as_str().into());
+    }
+
+    fn option(&self, key: &str) -> Option<&serde_json::Value> {
+        self.options.as_ref()?.get(key)
+    }
+}
+
+const STEPS_OPTION: &str = "steps";
+const GUIDANCE_SCALE_OPTION: &str = "guidanceScale";
+const STRENGTH_OPTION: &str = "strength";
+const RESOLUTION_OPTION: &str = "resolution";
+const NEGATIVE_PROMPT_OPTION: &str = "negativePrompt";
+
+/// The option key a model takes its sampling method under
+fn sampler_option(model: &KnownModel) -> &'static str {
+    match model {
+        KnownModel::StableDiffusionXl => "scheduler",
+        _ => "sampler",
+    }
+}
+
+/// Whether a model's capabilities rule out an option
+///
+/// Unreported ranges and unknown keys aren't ruled out, since options are
+/// model-specific and the capabilities don't list them all.
+fn rules_out_option(caps: &ModelCapabilities, key: &str) -> bool {
+    match key {
+        NEGATIVE_PROMPT_OPTION => !caps.supports_negative_prompt,
+        _ => false,
+    }
+}
+
+/// A problem with generation params, found by [`GenerateParams::validate`]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct ValidationIssue {
+    /// Param or option key the problem is with, e.g. `prompt` or `steps`
+    pub field: String,
+    /// What is wrong with it
+    pub message: String,
+}
+
+impl ValidationIssue {
+    fn new(field: impl Into<String>, message: impl Into<String>) -> Self {
+        Self {
+            field: field.into(),
+            message: message.into(),
+        }
+    }
+}
+
+impl std::fmt::Display for ValidationIssue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {}", self.field, self.message)
+    }
+}
+
+impl From<ValidationIssue> for PeerCatError {
+    fn from(issue: ValidationIssue) -> Self {
+        PeerCatError::Validation {
+            field: issue.field,
+            message: issue.message,
+        }
+    }
+}
+
+/// One part of a blended prompt
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct WeightedPrompt {
+    /// Prompt text
+    pub text: String,
+    /// Relative weight of this prompt in the blend
+    pub weight: f64,
+}
+
+/// A style adapter applied to a generation
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct LoraAdapter {
+    /// LoRA ID, from [`PeerCat::get_available_loras`](crate::PeerCat::get_available_loras)
+    pub id: String,
+    /// How strongly the adapter is applied (typically 0 to 1)
+    pub weight: f64,
+}
+
+/// What a ControlNet control image describes
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ControlMode {
+    /// A human pose skeleton
+    Pose,
+    /// Canny edges
+    Canny,
+    /// A depth map
+    Depth,
+}
+
+/// A ControlNet control image
+#[derive(Debug, Clone, PartialEq)]
+pub enum ControlImage {
+    /// An image already reachable by URL
+    Url(String),
+    /// Raw image bytes, uploaded before generating
+    Bytes(Vec<u8>),
+}
+
+impl From<&str> for ControlImage {
+    fn from(url: &str) -> Self {
+        ControlImage::Url(url.to_string())
+    }
+}
+
+impl From<String> for ControlImage {
+    fn from(url: String) -> Self {
+        ControlImage::Url(url)
+    }
+}
+
+impl From<Vec<u8>> for ControlImage {
+    fn from(bytes: Vec<u8>) -> Self {
+        ControlImage::Bytes(bytes)
+    }
+}
+
+/// ControlNet conditioning for a generation
+///
+/// Control images given as bytes are uploaded by
+/// [`PeerCat::generate`](crate::PeerCat::generate) before the request is sent.
+/// Until then they're serialized as base64 under `imageData`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ControlNetParams {
+    /// Control image
+    pub image: ControlImage,
+    /// What the control image describes
+    pub mode: ControlMode,
+    /// How strongly the control image guides the result (default: 1.0)
+    pub weight: f64,
+}
+
+impl ControlNetParams {
+    /// Create ControlNet params from an image URL or bytes
+    pub fn new(image: impl Into<ControlImage>, mode: ControlMode) -> Self {
+        Self {
+            image: image.into(),
+            mode,
+            weight: 1.0,
+        }
+    }
+
+    /// Set how strongly the control image guides the result
+    pub fn with_weight(mut self, weight: f64) -> Self {
+        self.weight = weight;
+        self
+    }
+}
+
+impl Serialize for ControlNetParams {
+    fn serialize<S: serde::Serializer>(
+        &self,
+        serializer: S,
+    ) -> std::result::Result<S::Ok, S::Error> {
+        #[derive(Serialize)]
+        #[serde(rename_all = "camelCase")]
+        struct Wire<'a> {
+            #[serde(skip_serializing_if = "Option::is_none")]
+            image_url: Option<&'a str>,
+            #[serde(skip_serializing_if = "Option::is_none")]
+            image_data: Option<String>,
+            mode: ControlMode,
+            weight: f64,
+        }
+
+        let (image_url, image_data) = match &self.image {
+            ControlImage::Url(url) => (Some(url.as_str()), None),
+            ControlImage::Bytes(data) => (None, Some(BASE64_STANDARD.encode(data))),
+        };
+        Wire {
+            image_url,
+            image_data,
+            mode: self.mode,
+            weight: self.weight,
+        }
+        .serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for ControlNetParams {
+    fn deserialize<D: serde::Deserializer<'de>>(
+        deserializer: D,
+    ) -> std::result::Result<Self, D::Error> {
+        #[derive(Deserialize)]
+        #[serde(rename_all = "camelCase")]
+        struct Wire {
+            image_url: Option<String>,
+            image_data: Option<String>,
+            mode: ControlMode,
+            weight: f64,
+        }
+
+        let wire = Wire::deserialize(deserializer)?;
+        let image = match (wire.image_url, wire.image_data) {
+            (Some(url), _) => ControlImage::Url(url),
+            (None, Some(data)) => ControlImage::Bytes(
+                BASE64_STANDARD
+                    .decode(data)
+                    .map_err(serde::de::Error::custom)?,
+            ),
+            (None, None) => return Err(serde::de::Error::missing_field("imageUrl")),
+        };
+        Ok(Self {
+            image,
+            mode: wire.mode,
+            weight: wire.weight,
+        })
+    }
+}
+
+/// An image uploaded for use as a generation input
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UploadedImage {
+    /// Upload ID
+    pub id: String,
+    /// URL to reference the image by
+    pub url: String,
+}
+
+/// Sampling method used during inference
+///
+/// Serialized by its API name, e.g. `"dpmpp_2m"`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(from = "String", into = "String")]
+pub enum Sampler {
+    /// Euler
+    Euler,
+    /// Euler ancestral
+    EulerA,
+    /// DPM++ 2M
+    DpmPlusPlus2M,
+    /// DPM++ 2M Karras
+    DpmPlusPlus2MKarras,
+    /// DDIM
+    Ddim,
+    /// LMS
+    Lms,
+    /// Any other sampler, by the name the model uses
+    Other(String),
+}
+
+impl Sampler {
+    /// The sampler name sent to the API
+    pub fn as_str(&self) -> &str {
+        match self {
+            Sampler::Euler => "euler",
+            Sampler::EulerA => "euler_a",
+            Sampler::DpmPlusPlus2M => "dpmpp_2m",
+            Sampler::DpmPlusPlus2MKarras => "dpmpp_2m_karras",
+            Sampler::Ddim => "ddim",
+            Sampler::Lms => "lms",
+            Sampler::Other(name) => name,
+        }
+    }
+}
+
+impl std::fmt::Display for Sampler {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl From<String> for Sampler {
+    fn from(name: String) -> Self {
+        match name.as_str() {
+            "euler" => Sampler::Euler,
+            "euler_a" => Sampler::EulerA,
+            "dpmpp_2m" => Sampler::DpmPlusPlus2M,
+            "dpmpp_2m_karras" => Sampler::DpmPlusPlus2MKarras,
+            "ddim" => Sampler::Ddim,
+            "lms" => Sampler::Lms,
+            _ => Sampler::Other(name),
+        }
+    }
+}
+
+impl From<Sampler> for String {
+    fn from(sampler: Sampler) -> Self {
+        sampler.as_str().to_string()
+    }
+}
+
+/// Content filter strictness for a generation
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum SafetyLevel {
+    /// Block anything that may be unsuitable for general audiences
+    Strict,
+    /// The API's default filtering
+    Standard,
+    /// Block only clearly prohibited content, for platforms with their own moderation
+    Relaxed,
+}
+
+/// Usage information from a generation
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GenerateUsage {
+    /// Credits used for this generation
+    pub credits_used: f64,
+    /// Remaining credit balance
+    pub balance_remaining: f64,
+}
+
+/// Result of an image generation
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GenerateResult {
+    /// Unique generation ID
+    pub id: String,
+    /// URL to the generated image
+    pub image_url: String,
+    /// IPFS hash (if uploaded)
+    pub ipfs_hash: Option<String>,
+    /// Model used
+    pub model: String,
+    /// Mode used
+    pub mode: GenerationMode,
+    /// Usage information
+    pub usage: GenerateUsage,
+    /// Whether the image is private, in which case `image_url` needs a signed URL
+    /// from [`PeerCat::create_signed_url`](crate::PeerCat::create_signed_url)
+    #[serde(default)]
+    pub private: bool,
+    /// Content filter strictness that was applied
+    #[serde(default)]
+    pub safety_level: Option<SafetyLevel>,
+    /// Seed used, including when one was picked at random
+    #[serde(default)]
+    pub seed: Option<u64>,
+    /// Prompt the image was generated from
+    #[serde(default)]
+    pub prompt: Option<String>,
+    /// Model-specific options that were sent
+    #[serde(default)]
+    pub options: Option<HashMap<String, serde_json::Value>>,
+    /// Weighted prompts the image was blended from
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub prompts: Vec<WeightedPrompt>,
+    /// Output quality that was requested
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub quality: Option<u8>,
+    /// LoRA adapters that were applied
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub adapters: Vec<LoraAdapter>,
+    /// ControlNet guidance that was applied
+    #[serde(
+        default,
+        rename = "controlNet",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub controlnet: Option<ControlNetParams>,
+    /// Whether the prompt was allowed to be auto-translated
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub auto_translate: Option<bool>,
+    /// Language the prompt was written in, if it was given
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source_language: Option<String>,
+    /// Approximate size of the image file in bytes, before downloading it
+    #[serde(default)]
+    pub expected_file_size: Option<u64>,
+    /// The English prompt actually used, when the prompt was auto-translated
+    #[serde(default)]
+    pub translated_prompt: Option<String>,
+}
+
+/// Public IPFS gateway used when none is configured
+pub(crate) const DEFAULT_IPFS_GATEWAY: &str = "https://ipfs.io";
+
+impl GenerateResult {
+    /// URL of the image on the public IPFS gateway, if it was pinned
+    ///
+    /// Use [`PeerCat::ipfs_url`](crate::PeerCat::ipfs_url) to resolve through
+    /// the gateway configured on a client.
+    pub fn ipfs_url(&self) -> Option<String> {
+        self.ipfs_url_via(DEFAULT_IPFS_GATEWAY)
+    }
+
+    pub(crate) fn ipfs_url_via(&self, gateway: &str) -> Option<String> {
+        self.ipfs_hash
+            .as_ref()
+            .map(|hash| format!("{}/ipfs/{}", gateway.trim_end_matches('/'), hash))
+    }
+
+    /// Params that regenerate this exact image
+    ///
+    /// Every param sent with the request is kept on results from
+    /// [`PeerCat::generate`](crate::PeerCat::generate). Aspect ratios and
+    /// samplers are carried as the options they were resolved to. Returns
+    /// `None` if the prompt or seed isn't known, e.g. for results from an API
+    /// version that doesn't report them.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use peercat::{GenerateParams, PeerCat};
+    ///
+    /// # async fn example() -> peercat::Result<()> {
+    /// let client = PeerCat::new("pcat_live_xxx")?;
+    /// let result = client.generate(GenerateParams::new("A paper crane")).await?;
+    ///
+    /// if let Some(params) = result.reproduce_params() {
+    ///     let again = client.generate(params).await?;
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn reproduce_params(&self) -> Option<GenerateParams> {
+        let mut params = GenerateParams::new(self.prompt.clone()?)
+            .with_model(self.model.clone())
+            .with_seed(self.seed?);
+        params.mode = Some(self.mode);
+        params.options = self.options.clone();
+        params.safety_level = self.safety_level;
+        params.prompts = self.prompts.clone();
+        params.quality = self.quality;
+        params.adapters = self.adapters.clone();
+        params.controlnet = self.controlnet.clone();
+        params.auto_translate = self.auto_translate;
+        params.source_language = self.source_language.clone();
+        Some(params)
+    }
+
+    /// Fill in what was asked for where the API didn't report it back
+    pub(crate) fn keep_params(&mut self, params: GenerateParams) {
+        if self.prompt.is_none() {
+            self.prompt = Some(params.prompt);
+        }
+        if self.options.is_none() {
+            self.options = params.options;
+        }
+        if self.prompts.is_empty() {
+            self.prompts = params.prompts;
+        }
+        if self.adapters.is_empty() {
+            self.adapters = params.adapters;
+        }
+        self.seed = self.seed.or(params.seed);
+        self.safety_level = self.safety_level.or(params.safety_level);
+        self.quality = self.quality.or(params.quality);
+        self.controlnet = self.controlnet.take().or(params.controlnet);
+        self.auto_translate = self.auto_translate.or(params.auto_translate);
+        self.source_language = self.source_language.take().or(params.source_language);
+    }
+
+    /// URL of a smaller variant of the image, served by the CDN
+    pub fn thumbnail_url(&self, size: ThumbSize) -> String {
+        let separator = if self.image_url.contains('?') {
+            '&'
+        } else {
+            '?'
+        };
+        format!("{}{}width={}", self.image_url, separator, size.width())
+    }
+}
+
+/// Size variants of generated images available from the CDN
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ThumbSize {
+    /// 128 pixels wide
+    Small,
+    /// 256 pixels wide
+    Medium,
+    /// 512 pixels wide
+    Large,
+}
+
+impl ThumbSize {
+    /// Width of the variant in pixels (height keeps the aspect ratio)
+    pub fn width(&self) -> u32 {
+        match self {
+            ThumbSize::Small => 128,
+            ThumbSize::Medium => 256,
+            ThumbSize::Large => 512,
+        }
+    }
+}
+
+/// Pin status of a generated image on IPFS
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IpfsStatus {
+    /// IPFS content hash
+    pub hash: String,
+    /// Whether the content is pinned
+    pub pinned: bool,
+    /// Number of IPFS nodes currently providing the content
+    pub providers: u32,
+    /// Size of the pinned content in bytes
+    pub size: Option<u64>,
+}
+
+/// Outcome of the safety filter for a generation
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SafetyCheck {
+    /// Whether the prompt or image was flagged
+    pub flagged: bool,
+    /// Categories that triggered the filter
+    #[serde(default)]
+    pub categories: Vec<String>,
+}
+
+/// Everything needed to reproduce a generation
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GenerationMetadata {
+    /// Generation ID
+    pub id: String,
+    /// Model used
+    pub model: String,
+    /// Prompt as sent to the model
+    pub prompt: String,
+    /// Seed actually used, including when one was picked at random
+    pub seed: u64,
+    /// Final image width in pixels
+    pub width: u32,
+    /// Final image height in pixels
+    pub height: u32,
+    /// Time spent running the model, in milliseconds
+    pub inference_ms: u64,
+    /// Time from request to result, in milliseconds
+    pub total_ms: u64,
+    /// Safety filter decision
+    pub safety: SafetyCheck,
+    /// When the generation was created
+    pub created_at: Timestamp,
+}
+
+/// A time-limited URL for a private image
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SignedUrl {
+    /// Signed image URL
+    pub url: String,
+    /// When the URL stops working
+    pub expires_at: Timestamp,
+}
+
+impl SignedUrl {
+    /// Returns true if the URL has expired
+    pub fn is_expired(&self) -> bool {
+        is_past(&self.expires_at)
+    }
+}
+
+/// Options for [`PeerCat::create_share_link`](crate::PeerCat::create_share_link)
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ShareOptions {
+    /// How long the link works for (rounded down to whole seconds; `None` never expires)
+    pub expires_in: Option<std::time::Duration>,
+    /// Whether viewers may download the full-size image
+    pub allow_download: bool,
+}
+
+impl ShareOptions {
+    /// Create options for a link that never expires and doesn't allow downloads
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Expire the link after the given lifetime
+    pub fn with_expires_in(mut self, expires_in: std::time::Duration) -> Self {
+        self.expires_in = Some(expires_in);
+        self
+    }
+
+    /// Let viewers download the full-size image
+    pub fn with_download(mut self) -> Self {
+        self.allow_download = true;
+        self
+    }
+}
+
+/// A public link to a generation
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ShareLink {
+    /// Share link ID
+    pub id: String,
+    /// Public URL of the share page
+    pub url: String,
+    /// When the link stops working (None if it never expires)
+    pub expires_at: Option<Timestamp>,
+    /// Whether viewers may download the full-size image
+    pub allow_download: bool,
+}
+
+/// Confirmation that a generation was deleted
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GenerationDeletion {
+    /// Generation ID
+    pub id: String,
+    /// Whether the image was removed from the CDN
+    pub cdn_removed: bool,
+    /// Whether the image was unpinned from IPFS
+    ///
+    /// Unpinned content may still be served by other IPFS nodes that cached it.
+    pub ipfs_unpinned: bool,
+    /// When the generation was deleted
+    pub deleted_at: Timestamp,
+}
+
+impl GenerationMetadata {
+    /// Time spent running the model
+    pub fn inference_time(&self) -> std::time::Duration {
+        std::time::Duration::from_millis(self.inference_ms)
+    }
+}
+
+// ============ Collections ============
+
+/// Parameters for creating a collection
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateCollectionParams {
+    /// Collection name
+    pub name: String,
+    /// Optional description
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+}
+
+impl CreateCollectionParams {
+    /// Create parameters for a collection with the given name
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            description: None,
+        }
+    }
+
+    /// Set a description
+    pub fn with_description(mut self, description: impl Into<String>) -> Self {
+        self.description = Some(description.into());
+        self
+    }
 }
 
-/// Usage information from a generation
+/// A named group of generations
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
-pub struct GenerateUsage {
-    /// Credits used for this generation
-    pub credits_used: f64,
-    /// Remaining credit balance
-    pub balance_remaining: f64,
+pub struct Collection {
+    /// Collection ID
+    pub id: String,
+    /// Collection name
+    pub name: String,
+    /// Description
+    pub description: Option<String>,
+    /// Number of generations in the collection
+    pub item_count: u32,
+    /// Creation timestamp
+    pub created_at: Timestamp,
+    /// Last time the collection changed
+    pub updated_at: Timestamp,
 }
 
-/// Result of an image generation
+/// A generation in a collection
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
-pub struct GenerateResult {
-    /// Unique generation ID
-    pub id: String,
+pub struct CollectionItem {
+    /// Generation ID
+    pub generation_id: String,
     /// URL to the generated image
     pub image_url: String,
-    /// IPFS hash (if uploaded)
-    pub ipfs_hash: Option<String>,
     /// Model used
     pub model: String,
-    /// Mode used
-    pub mode: GenerationMode,
-    /// Usage information
-    pub usage: GenerateUsage,
+    /// When the generation was added to the collection
+    pub added_at: Timestamp,
+}
+
+/// Response containing collections
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CollectionsResponse {
+    pub collections: Vec<Collection>,
+}
+
+/// Response containing the generations in a collection
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CollectionItemsResponse {
+    pub items: Vec<CollectionItem>,
+}
+
+// ============ Presets ============
+
+/// Generation settings saved on the server
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Preset {
+    /// Preset ID
+    pub id: String,
+    /// Preset name
+    pub name: String,
+    /// Saved generation parameters, as sent to the API
+    pub params: serde_json::Map<String, serde_json::Value>,
+    /// Creation timestamp
+    pub created_at: Timestamp,
+    /// Last time the preset changed
+    pub updated_at: Timestamp,
+}
+
+/// Response containing saved presets
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PresetsResponse {
+    pub presets: Vec<Preset>,
 }
 
 // ============ Balance ============
@@ -222,10 +2303,109 @@ pub struct Balance {
     pub total_generated: u64,
 }
 
+// ============ Deposits ============
+
+/// Instructions for depositing SOL to top up credits
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DepositInstructions {
+    /// Deposit ID
+    pub deposit_id: String,
+    /// Treasury address to send payment to
+    pub payment_address: String,
+    /// Amount to send
+    pub required_amount: RequiredAmount,
+    /// Memo to include in the transaction
+    pub memo: String,
+    /// Expiration timestamp
+    pub expires_at: Timestamp,
+}
+
+/// Status of a credit deposit
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum DepositState {
+    Pending,
+    Confirmed,
+    Failed,
+}
+
+/// Status of a deposit transaction
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DepositStatus {
+    /// Transaction signature
+    pub tx_signature: String,
+    /// Status
+    pub status: DepositState,
+    /// Amount received in SOL (once detected)
+    pub amount_sol: Option<f64>,
+    /// Credits added in USD (once confirmed)
+    pub credits_added: Option<f64>,
+    /// Creation timestamp
+    pub created_at: Option<Timestamp>,
+    /// Confirmation timestamp
+    pub confirmed_at: Option<Timestamp>,
+    /// Error message (when failed)
+    pub error: Option<String>,
+}
+
+// ============ Withdrawals ============
+
+/// Parameters for withdrawing credits to a wallet (requires wallet signature)
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct WithdrawParams {
+    /// Amount of credits to withdraw in USD
+    pub amount: f64,
+    /// Destination wallet address (base58)
+    pub destination_wallet: String,
+    /// Message that was signed
+    pub message: String,
+    /// Wallet signature (base58)
+    pub signature: String,
+    /// Wallet public key (base58)
+    pub public_key: String,
+}
+
+/// Status of a withdrawal
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum WithdrawalStatus {
+    Pending,
+    Processing,
+    Completed,
+    Failed,
+}
+
+/// Result of a withdrawal request
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WithdrawalResult {
+    /// Withdrawal ID
+    pub withdrawal_id: String,
+    /// Status
+    pub status: WithdrawalStatus,
+    /// Amount withdrawn in USD
+    pub amount_usd: f64,
+    /// Amount sent in SOL
+    pub amount_sol: Option<f64>,
+    /// Destination wallet address
+    pub destination_wallet: String,
+    /// Payout transaction signature (once sent)
+    pub tx_signature: Option<String>,
+    /// Creation timestamp
+    pub created_at: Timestamp,
+    /// Completion timestamp
+    pub completed_at: Option<Timestamp>,
+    /// Error message (when failed)
+    pub error: Option<String>,
+}
+
 // ============ History ============
 
 /// Parameters for fetching usage history
-#[derive(Debug, Clone, Default, Serialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
 pub struct HistoryParams {
     /// Number of items to return (default: 50, max: 100)
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -233,6 +2413,29 @@ pub struct HistoryParams {
     /// Pagination offset
     #[serde(skip_serializing_if = "Option::is_none")]
     pub offset: Option<u32>,
+    /// Only return records with this status
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub status: Option<HistoryStatus>,
+    /// Only return records for this model
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
+    /// Only return records created at or after this timestamp (ISO 8601)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub from: Option<String>,
+    /// Only return records created before this timestamp (ISO 8601)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub to: Option<String>,
+    /// Only return records for this API endpoint (e.g., "/v1/generate")
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub endpoint: Option<String>,
+    /// Only return records with all of these tags
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        serialize_with = "serialize_comma_separated",
+        deserialize_with = "deserialize_comma_separated"
+    )]
+    pub tags: Option<Vec<String>>,
 }
 
 impl HistoryParams {
@@ -252,6 +2455,67 @@ impl HistoryParams {
         self.offset = Some(offset);
         self
     }
+
+    /// Filter by record status
+    pub fn with_status(mut self, status: HistoryStatus) -> Self {
+        self.status = Some(status);
+        self
+    }
+
+    /// Filter by model ID
+    pub fn with_model(mut self, model: impl Into<String>) -> Self {
+        self.model = Some(model.into());
+        self
+    }
+
+    /// Filter by creation date range (ISO 8601 timestamps, `to` is exclusive)
+    pub fn with_date_range(mut self, from: impl Into<String>, to: impl Into<String>) -> Self {
+        self.from = Some(from.into());
+        self.to = Some(to.into());
+        self
+    }
+
+    /// Filter by API endpoint path
+    pub fn with_endpoint(mut self, endpoint: impl Into<String>) -> Self {
+        self.endpoint = Some(endpoint.into());
+        self
+    }
+
+    /// Filter by tag; records must have every tag added this way
+    pub fn with_tag(mut self, tag: impl Into<String>) -> Self {
+        self.tags.get_or_insert_with(Vec::new).push(tag.into());
+        self
+    }
+}
+
+/// Serialize a list as a single comma-separated query parameter
+fn serialize_comma_separated<S: serde::Serializer>(
+    values: &Option<Vec<String>>,
+    serializer: S,
+) -> std::result::Result<S::Ok, S::Error> {
+    serializer.serialize_str(&values.as_deref().unwrap_or_default().join(","))
+}
+
+/// Accept a list either as a comma-separated string or as a sequence
+fn deserialize_comma_separated<'de, D: serde::Deserializer<'de>>(
+    deserializer: D,
+) -> std::result::Result<Option<Vec<String>>, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum List {
+        Joined(String),
+        Items(Vec<String>),
+    }
+
+    let list = Option::<List>::deserialize(deserializer)?;
+    Ok(list.map(|list| match list {
+        List::Joined(joined) => joined
+            .split(',')
+            .filter(|tag| !tag.is_empty())
+            .map(str::to_string)
+            .collect(),
+        List::Items(items) => items,
+    }))
 }
 
 /// Status of a usage record
@@ -280,9 +2544,15 @@ pub struct HistoryItem {
     /// Status
     pub status: HistoryStatus,
     /// Creation timestamp
-    pub created_at: String,
+    pub created_at: Timestamp,
     /// Completion timestamp
-    pub completed_at: Option<String>,
+    pub completed_at: Option<Timestamp>,
+    /// URL of the generated image, for completed generations
+    #[serde(default)]
+    pub image_url: Option<String>,
+    /// Tags added with [`PeerCat::tag_generation`](crate::PeerCat::tag_generation)
+    #[serde(default)]
+    pub tags: Vec<String>,
 }
 
 /// Pagination information
@@ -304,10 +2574,81 @@ pub struct HistoryResponse {
     pub pagination: Pagination,
 }
 
+// ============ Usage Analytics ============
+
+/// Dimension to group usage analytics by
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum UsageGroupBy {
+    Day,
+    Model,
+    Endpoint,
+}
+
+/// Parameters for fetching a usage summary
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct UsageSummaryParams {
+    /// Dimension to group results by
+    pub group_by: UsageGroupBy,
+    /// Start of the range, inclusive (ISO 8601)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub from: Option<String>,
+    /// End of the range, exclusive (ISO 8601)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub to: Option<String>,
+}
+
+impl UsageSummaryParams {
+    /// Create new usage summary parameters grouped by the given dimension
+    pub fn new(group_by: UsageGroupBy) -> Self {
+        Self {
+            group_by,
+            from: None,
+            to: None,
+        }
+    }
+
+    /// Limit the summary to a date range (ISO 8601 timestamps, `to` is exclusive)
+    pub fn with_date_range(mut self, from: impl Into<String>, to: impl Into<String>) -> Self {
+        self.from = Some(from.into());
+        self.to = Some(to.into());
+        self
+    }
+}
+
+/// Aggregated usage for a single group
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UsageBucket {
+    /// Group key (a date, model ID, or endpoint path depending on grouping)
+    pub key: String,
+    /// Credits spent in this group
+    pub credits_used: f64,
+    /// Number of requests in this group
+    pub request_count: u64,
+}
+
+/// Response containing aggregated usage
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UsageSummary {
+    /// Dimension the buckets are grouped by
+    pub group_by: UsageGroupBy,
+    /// Start of the summarized range
+    pub from: Option<Timestamp>,
+    /// End of the summarized range
+    pub to: Option<Timestamp>,
+    /// Total credits spent across all buckets
+    pub total_credits_used: f64,
+    /// Usage buckets
+    pub buckets: Vec<UsageBucket>,
+}
+
 // ============ API Keys ============
 
 /// Parameters for creating an API key
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 #[serde(rename_all = "camelCase")]
 pub struct CreateKeyParams {
     /// Optional name for the key
@@ -319,6 +2660,80 @@ pub struct CreateKeyParams {
     pub signature: String,
     /// Wallet public key (base58)
     pub public_key: String,
+    /// Permissions granted to the key (default: all)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub scopes: Option<Vec<KeyScope>>,
+    /// Absolute expiration time for the key
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub expires_at: Option<Timestamp>,
+    /// Key lifetime in seconds from creation (alternative to `expires_at`)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ttl_seconds: Option<u64>,
+    /// Restrict the key to requests from these networks (default: any)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ip_allowlist: Option<Vec<IpNet>>,
+}
+
+impl CreateKeyParams {
+    /// Create new key parameters from a signed wallet message
+    pub fn new(
+        message: impl Into<String>,
+        signature: impl Into<String>,
+        public_key: impl Into<String>,
+    ) -> Self {
+        Self {
+            name: None,
+            message: message.into(),
+            signature: signature.into(),
+            public_key: public_key.into(),
+            scopes: None,
+            expires_at: None,
+            ttl_seconds: None,
+            ip_allowlist: None,
+        }
+    }
+
+    /// Set the key name
+    pub fn with_name(mut self, name: impl Into<String>) -> Self {
+        self.name = Some(name.into());
+        self
+    }
+
+    /// Restrict the key to the given permissions
+    pub fn with_scopes(mut self, scopes: impl IntoIterator<Item = KeyScope>) -> Self {
+        self.scopes = Some(scopes.into_iter().collect());
+        self
+    }
+
+    /// Expire the key at the given time
+    pub fn with_expires_at(mut self, expires_at: Timestamp) -> Self {
+        self.expires_at = Some(expires_at);
+        self
+    }
+
+    /// Expire the key after the given lifetime (rounded down to whole seconds)
+    pub fn with_ttl(mut self, ttl: std::time::Duration) -> Self {
+        self.ttl_seconds = Some(ttl.as_secs());
+        self
+    }
+
+    /// Restrict the key to requests from the given networks
+    pub fn with_ip_allowlist(mut self, networks: impl IntoIterator<Item = IpNet>) -> Self {
+        self.ip_allowlist = Some(networks.into_iter().collect());
+        self
+    }
+}
+
+/// Permission scope for API keys
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[serde(rename_all = "lowercase")]
+pub enum KeyScope {
+    /// Generate images and submit prompts
+    Generate,
+    /// Read-only access to models, pricing, balance, and history
+    Read,
+    /// Full access, including key management and withdrawals
+    Admin,
 }
 
 /// Environment type for API keys
@@ -329,6 +2744,25 @@ pub enum KeyEnvironment {
     Test,
 }
 
+impl KeyEnvironment {
+    /// The environment an API key belongs to, from its prefix
+    ///
+    /// Returns `None` for keys without a `pcat_live_` or `pcat_test_` prefix.
+    pub fn from_key(key: &str) -> Option<Self> {
+        [Self::Live, Self::Test]
+            .into_iter()
+            .find(|environment| key.starts_with(environment.key_prefix()))
+    }
+
+    /// Prefix of API keys in this environment
+    pub fn key_prefix(self) -> &'static str {
+        match self {
+            Self::Live => "pcat_live_",
+            Self::Test => "pcat_test_",
+        }
+    }
+}
+
 /// API key information
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -344,11 +2778,42 @@ pub struct ApiKey {
     /// Rate limit tier
     pub rate_limit_tier: String,
     /// Creation timestamp
-    pub created_at: String,
+    pub created_at: Timestamp,
     /// Last used timestamp
-    pub last_used_at: Option<String>,
+    pub last_used_at: Option<Timestamp>,
     /// Whether the key has been revoked
     pub revoked: bool,
+    /// Permissions granted to the key (empty for legacy keys with full access)
+    #[serde(default)]
+    pub scopes: Vec<KeyScope>,
+    /// Expiration timestamp (None for keys that never expire)
+    #[serde(default)]
+    pub expires_at: Option<Timestamp>,
+    /// Networks the key may be used from (empty if unrestricted)
+    #[serde(default)]
+    pub ip_allowlist: Vec<IpNet>,
+}
+
+impl ApiKey {
+    /// Returns true if the key has the given scope
+    ///
+    /// Keys with no explicit scopes and keys with [`KeyScope::Admin`] are
+    /// treated as having every scope.
+    pub fn has_scope(&self, scope: KeyScope) -> bool {
+        self.scopes.is_empty()
+            || self.scopes.contains(&KeyScope::Admin)
+            || self.scopes.contains(&scope)
+    }
+
+    /// Returns true if the key may be used from the given address
+    pub fn allows_ip(&self, addr: IpAddr) -> bool {
+        self.ip_allowlist.is_empty() || self.ip_allowlist.iter().any(|net| net.contains(&addr))
+    }
+
+    /// Returns true if the key has an expiration time in the past
+    pub fn is_expired(&self) -> bool {
+        self.expires_at.as_ref().is_some_and(is_past)
+    }
 }
 
 /// Result of creating an API key
@@ -366,9 +2831,15 @@ pub struct CreateKeyResult {
     /// Environment
     pub environment: KeyEnvironment,
     /// Creation timestamp
-    pub created_at: String,
+    pub created_at: Timestamp,
     /// Warning message
     pub warning: String,
+    /// Permissions granted to the key
+    #[serde(default)]
+    pub scopes: Vec<KeyScope>,
+    /// Expiration timestamp (None for keys that never expire)
+    #[serde(default)]
+    pub expires_at: Option<Timestamp>,
 }
 
 /// Response containing API keys
@@ -380,7 +2851,7 @@ pub struct KeysResponse {
 // ============ On-Chain Payments ============
 
 /// Parameters for submitting a prompt for on-chain payment
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 #[serde(rename_all = "camelCase")]
 pub struct SubmitPromptParams {
     /// Text prompt for image generation
@@ -394,9 +2865,18 @@ pub struct SubmitPromptParams {
     /// Callback URL for result notification
     #[serde(skip_serializing_if = "Option::is_none")]
     pub callback_url: Option<String>,
+    /// Slippage tolerance for the quoted SOL amount (e.g., 0.02 = 2%)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub slippage_tolerance: Option<f64>,
+    /// SPL token mint to pay with, swapped to SOL on the way
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub payment_token: Option<String>,
 }
 
 impl SubmitPromptParams {
+    /// Largest slippage tolerance the API accepts (50%)
+    pub const MAX_SLIPPAGE_TOLERANCE: f64 = 0.5;
+
     /// Create new prompt submission parameters
     pub fn new(prompt: impl Into<String>) -> Self {
         Self {
@@ -404,6 +2884,8 @@ impl SubmitPromptParams {
             model: None,
             options: None,
             callback_url: None,
+            slippage_tolerance: None,
+            payment_token: None,
         }
     }
 
@@ -418,6 +2900,24 @@ impl SubmitPromptParams {
         self.callback_url = Some(url.into());
         self
     }
+
+    /// Set the slippage tolerance instead of using the server default
+    ///
+    /// Must be between 0 and [`MAX_SLIPPAGE_TOLERANCE`](Self::MAX_SLIPPAGE_TOLERANCE);
+    /// this is checked when the prompt is submitted.
+    pub fn with_slippage_tolerance(mut self, tolerance: f64) -> Self {
+        self.slippage_tolerance = Some(tolerance);
+        self
+    }
+
+    /// Pay in an SPL token instead of SOL
+    ///
+    /// The submission then includes a [`SwapRoute`] quoting how much of the
+    /// token to send; the swap to SOL happens in the same transaction.
+    pub fn with_payment_token(mut self, mint: impl Into<String>) -> Self {
+        self.payment_token = Some(mint.into());
+        self
+    }
 }
 
 /// Required payment amount in different units
@@ -450,9 +2950,98 @@ pub struct PromptSubmission {
     /// Slippage tolerance
     pub slippage_tolerance: f64,
     /// Expiration timestamp
-    pub expires_at: String,
+    pub expires_at: Timestamp,
     /// Payment instructions
     pub instructions: HashMap<String, String>,
+    /// Whether the submission is still awaiting payment
+    #[serde(default)]
+    pub status: SubmissionStatus,
+    /// Swap quote, when paying in a token other than SOL
+    #[serde(default)]
+    pub swap_route: Option<SwapRoute>,
+}
+
+/// Quote for paying a submission in an SPL token, swapped to SOL
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SwapRoute {
+    /// Mint of the token being paid
+    pub input_mint: String,
+    /// Amount of the token to pay, in its smallest unit
+    pub input_amount: u64,
+    /// Decimals of the token
+    pub input_decimals: u8,
+    /// Lamports the swap yields, at least the required amount
+    pub output_lamports: u64,
+    /// Price impact of the swap, in percent
+    pub price_impact_pct: f64,
+    /// DEXes the swap is routed through, in order
+    pub route: Vec<String>,
+    /// Unsigned swap-and-pay transaction (base64), ready for the payer to sign
+    pub swap_transaction: Option<String>,
+    /// When the quote stops being honored
+    pub quote_expires_at: Timestamp,
+}
+
+/// Payment state of a prompt submission
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum SubmissionStatus {
+    /// Awaiting payment
+    #[default]
+    Open,
+    /// Payment received
+    Paid,
+    /// Expired before payment arrived
+    Expired,
+}
+
+/// Filters for listing prompt submissions
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct SubmissionFilter {
+    /// Only return submissions with this status
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub status: Option<SubmissionStatus>,
+    /// Number of submissions to return (default: 50, max: 100)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub limit: Option<u32>,
+    /// Pagination offset
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub offset: Option<u32>,
+}
+
+impl SubmissionFilter {
+    /// Create a filter matching all submissions
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Only return submissions with this status
+    pub fn with_status(mut self, status: SubmissionStatus) -> Self {
+        self.status = Some(status);
+        self
+    }
+
+    /// Set the limit
+    pub fn with_limit(mut self, limit: u32) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+
+    /// Set the offset
+    pub fn with_offset(mut self, offset: u32) -> Self {
+        self.offset = Some(offset);
+        self
+    }
+}
+
+/// Response containing prompt submissions
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SubmissionsResponse {
+    /// Submissions
+    pub submissions: Vec<PromptSubmission>,
+    /// Pagination info
+    pub pagination: Pagination,
 }
 
 /// Status of an on-chain generation
@@ -466,6 +3055,16 @@ pub enum OnChainStatus {
     Refunded,
 }
 
+impl OnChainStatus {
+    /// Returns true if the status won't change again
+    pub fn is_terminal(&self) -> bool {
+        matches!(
+            self,
+            OnChainStatus::Completed | OnChainStatus::Failed | OnChainStatus::Refunded
+        )
+    }
+}
+
 /// Status and result of an on-chain generation
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -477,19 +3076,56 @@ pub struct OnChainGenerationStatus {
     /// Model used
     pub model: Option<String>,
     /// Creation timestamp
-    pub created_at: Option<String>,
+    pub created_at: Option<Timestamp>,
     /// Image URL (when completed)
     pub image_url: Option<String>,
     /// IPFS hash (when completed)
     pub ipfs_hash: Option<String>,
     /// Completion timestamp
-    pub completed_at: Option<String>,
+    pub completed_at: Option<Timestamp>,
     /// Error message (when failed)
     pub error: Option<String>,
     /// Status message
     pub message: Option<String>,
 }
 
+/// Step in the processing of an on-chain generation
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum OnChainEventKind {
+    PaymentDetected,
+    AmountVerified,
+    Queued,
+    GenerationStarted,
+    UploadedToIpfs,
+    Completed,
+    Failed,
+    RefundIssued,
+}
+
+/// Timestamped event in an on-chain generation's timeline
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OnChainEvent {
+    /// What happened
+    #[serde(rename = "type")]
+    pub kind: OnChainEventKind,
+    /// When it happened
+    pub timestamp: Timestamp,
+    /// Details, e.g. the verified amount or the failure reason
+    pub message: Option<String>,
+}
+
+/// Ordered event timeline of an on-chain generation
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OnChainEvents {
+    /// Transaction signature
+    pub tx_signature: String,
+    /// Events, oldest first
+    pub events: Vec<OnChainEvent>,
+}
+
 // ============ Internal Types ============
 
 /// API error response
@@ -0,0 +1,291 @@
+//! Record/replay of API interactions for deterministic tests
+//!
+//! Requires the `vcr` feature. In [`VcrMode::Record`] mode every response is
+//! kept in memory and written to a JSON cassette file when the client (and
+//! every clone of it) is dropped, or earlier with
+//! [`PeerCat::save_cassette`](crate::PeerCat::save_cassette). In
+//! [`VcrMode::Replay`] mode responses are served from the cassette without
+//! touching the network.
+//!
+//! API keys are never written to cassettes: the `Authorization` header isn't
+//! recorded, and any of the client's keys (or newly created `pcat_` keys)
+//! appearing in bodies are replaced with `[REDACTED]`. So are fields set with
+//! [`PeerCatConfig::with_redacted_field`](crate::PeerCatConfig::with_redacted_field),
+//! in both requests and responses; requests are matched against the cassette
+//! after the same redaction, so replay works as long as the client redacts
+//! the same fields.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use peercat::vcr::VcrMode;
+//! use peercat::{PeerCat, PeerCatConfig};
+//!
+//! # async fn example() -> peercat::Result<()> {
+//! let cassette = std::path::PathBuf::from("tests/cassettes/balance.json");
+//!
+//! // Record once against the real API...
+//! let client = PeerCat::with_config(
+//!     PeerCatConfig::new("pcat_test_xxx").with_vcr(VcrMode::Record(cassette.clone())),
+//! )?;
+//! client.get_balance().await?;
+//! client.save_cassette()?;
+//!
+//! // ...then replay in tests
+//! let client = PeerCat::with_config(
+//!     PeerCatConfig::new("unused").with_vcr(VcrMode::Replay(cassette)),
+//! )?;
+//! let balance = client.get_balance().await?;
+//! # Ok(())
+//! # }
+//! ```
+
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue};
+use reqwest::{Method, StatusCode};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::client::RawResponse;
+use crate::error::{PeerCatError, Result};
+use crate::redact::{Redactor, REDACTED};
+use crate::secret::SecretString;
+
+/// Whether to record interactions or replay them
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum VcrMode {
+    /// Send requests to the API and record each interaction, replacing any
+    /// existing cassette when they're saved
+    Record(PathBuf),
+    /// Serve responses from the cassette; requests never reach the network
+    Replay(PathBuf),
+}
+
+/// A recorded request/response pair
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Interaction {
+    method: String,
+    path: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    request_body: Option<Value>,
+    status: u16,
+    #[serde(default)]
+    headers: BTreeMap<String, String>,
+    response_body: Value,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct Cassette {
+    interactions: Vec<Interaction>,
+}
+
+#[derive(Debug)]
+struct State {
+    cassette: Cassette,
+    /// Interactions already served in replay mode
+    used: Vec<bool>,
+    /// File to write to in record mode
+    file: Option<PathBuf>,
+    /// Whether interactions were recorded since the cassette was last saved
+    unsaved: bool,
+}
+
+impl State {
+    fn save(&mut self) -> Result<()> {
+        let Some(file) = self.file.as_ref().filter(|_| self.unsaved) else {
+            return Ok(());
+        };
+        if let Some(parent) = file.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(file, serde_json::to_vec_pretty(&self.cassette)?)?;
+        self.unsaved = false;
+        Ok(())
+    }
+}
+
+impl Drop for State {
+    fn drop(&mut self) {
+        // Nowhere to report a failure to; call `save_cassette` to see it
+        let _ = self.save();
+    }
+}
+
+/// A cassette attached to a client
+#[derive(Debug, Clone)]
+pub(crate) struct Vcr {
+    mode: VcrMode,
+    keys: Vec<SecretString>,
+    redactor: Redactor,
+    state: Arc<Mutex<State>>,
+}
+
+impl Vcr {
+    /// Open a cassette, loading it in replay mode
+    pub(crate) fn new(mode: VcrMode, keys: &[SecretString], redactor: Redactor) -> Result<Self> {
+        let (cassette, file) = match &mode {
+            VcrMode::Record(path) => (Cassette::default(), Some(path.clone())),
+            VcrMode::Replay(path) => (serde_json::from_slice(&std::fs::read(path)?)?, None),
+        };
+        let used = vec![false; cassette.interactions.len()];
+
+        Ok(Self {
+            mode,
+            keys: keys.to_vec(),
+            redactor,
+            state: Arc::new(Mutex::new(State {
+                cassette,
+                used,
+                file,
+                unsaved: false,
+            })),
+        })
+    }
+
+    pub(crate) fn is_replaying(&self) -> bool {
+        matches!(self.mode, VcrMode::Replay(_))
+    }
+
+    /// Serve the next unused interaction matching the request
+    ///
+    /// The request is redacted the way [`record`](Self::record) stores it
+    /// before looking for a match.
+    pub(crate) fn replay(
+        &self,
+        method: &Method,
+        path: &str,
+        body: Option<&Value>,
+    ) -> Result<RawResponse> {
+        let path = self.redact_str(path);
+        let body = body
+            .cloned()
+            .map(|body| self.redact(self.redact_fields(body)));
+        let mut state = self.lock();
+        let State { cassette, used, .. } = &mut *state;
+
+        let index = cassette
+            .interactions
+            .iter()
+            .enumerate()
+            .position(|(i, interaction)| {
+                !used[i]
+                    && interaction.method == method.as_str()
+                    && interaction.path == path
+                    && (interaction.request_body.is_none() || interaction.request_body == body)
+            })
+            .ok_or_else(|| {
+                PeerCatError::InvalidConfig(format!(
+                    "no recorded interaction left for {} {}",
+                    method, path
+                ))
+            })?;
+        used[index] = true;
+
+        let interaction = &cassette.interactions[index];
+        let mut headers = HeaderMap::new();
+        for (name, value) in &interaction.headers {
+            if let (Ok(name), Ok(value)) = (
+                HeaderName::from_bytes(name.as_bytes()),
+                HeaderValue::from_str(value),
+            ) {
+                headers.insert(name, value);
+            }
+        }
+
+        Ok(RawResponse {
+            status: StatusCode::from_u16(interaction.status)
+                .unwrap_or(StatusCode::INTERNAL_SERVER_ERROR),
+            headers,
+            body: serde_json::to_vec(&interaction.response_body)?,
+        })
+    }
+
+    /// Append an interaction, to be written when the cassette is saved
+    pub(crate) fn record(
+        &self,
+        method: &Method,
+        path: &str,
+        body: Option<&Value>,
+        response: &RawResponse,
+    ) {
+        if self.is_replaying() {
+            return;
+        }
+
+        let response_body = serde_json::from_slice(&response.body)
+            .unwrap_or_else(|_| Value::String(String::from_utf8_lossy(&response.body).into()));
+        let headers = response
+            .headers
+            .iter()
+            .filter(|(name, _)| *name != reqwest::header::SET_COOKIE)
+            .filter_map(|(name, value)| Some((name.to_string(), value.to_str().ok()?.to_string())))
+            .collect();
+
+        let interaction = Interaction {
+            method: method.to_string(),
+            path: self.redact_str(path),
+            request_body: body
+                .cloned()
+                .map(|body| self.redact(self.redact_fields(body))),
+            status: response.status.as_u16(),
+            headers,
+            response_body: self.redact(self.redact_fields(response_body)),
+        };
+
+        let mut state = self.lock();
+        state.cassette.interactions.push(interaction);
+        state.used.push(true);
+        state.unsaved = true;
+    }
+
+    /// Write the interactions recorded so far to the cassette file
+    pub(crate) fn save(&self) -> Result<()> {
+        self.lock().save()
+    }
+
+    /// Replace the fields the client was configured to redact
+    fn redact_fields(&self, mut body: Value) -> Value {
+        self.redactor.redact(&mut body);
+        body
+    }
+
+    /// Replace API keys anywhere in a JSON value
+    fn redact(&self, value: Value) -> Value {
+        match value {
+            Value::String(s) => Value::String(self.redact_str(&s)),
+            Value::Array(items) => {
+                Value::Array(items.into_iter().map(|v| self.redact(v)).collect())
+            }
+            Value::Object(fields) => Value::Object(
+                fields
+                    .into_iter()
+                    .map(|(name, value)| match value {
+                        // Newly created keys are returned in a `key` field
+                        Value::String(s) if name == "key" && s.starts_with("pcat_") => {
+                            (name, Value::String(REDACTED.to_string()))
+                        }
+                        value => (name, self.redact(value)),
+                    })
+                    .collect(),
+            ),
+            other => other,
+        }
+    }
+
+    fn redact_str(&self, s: &str) -> String {
+        self.keys
+            .iter()
+            .filter(|key| !key.is_empty())
+            .fold(s.to_string(), |s, key| {
+                s.replace(key.expose_secret(), REDACTED)
+            })
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, State> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
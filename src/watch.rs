@@ -0,0 +1,94 @@
+//! Background balance monitoring
+
+use std::time::Duration;
+
+use tokio::task::JoinHandle;
+
+use crate::client::PeerCat;
+use crate::stream::MIN_INTERVAL;
+use crate::types::Balance;
+
+/// Handle to a background balance watcher
+///
+/// The watcher stops when [`BalanceWatcher::stop`] is called or the handle
+/// is dropped.
+#[derive(Debug)]
+pub struct BalanceWatcher {
+    handle: JoinHandle<()>,
+}
+
+impl BalanceWatcher {
+    /// Stop polling
+    pub fn stop(self) {
+        self.handle.abort();
+    }
+
+    /// Returns true if the watcher task is no longer running
+    pub fn is_finished(&self) -> bool {
+        self.handle.is_finished()
+    }
+}
+
+impl Drop for BalanceWatcher {
+    fn drop(&mut self) {
+        self.handle.abort();
+    }
+}
+
+impl PeerCat {
+    /// Poll the credit balance in the background and call `on_low` when it
+    /// drops below `threshold`
+    ///
+    /// The callback fires once each time the balance crosses below the
+    /// threshold, and is re-armed once the balance is back at or above it.
+    /// Failed polls are skipped; the next tick tries again. Intervals under
+    /// 10ms, including zero, are raised to 10ms.
+    ///
+    /// Must be called from within a Tokio runtime.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use peercat::PeerCat;
+    /// use std::time::Duration;
+    ///
+    /// # async fn example() -> peercat::Result<()> {
+    /// let client = PeerCat::new("pcat_live_xxx")?;
+    ///
+    /// let watcher = client.watch_balance(Duration::from_secs(60), 5.0, |balance| {
+    ///     eprintln!("Low balance: ${}", balance.credits);
+    /// });
+    ///
+    /// // ... run the batch ...
+    ///
+    /// watcher.stop();
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn watch_balance<F>(&self, interval: Duration, threshold: f64, on_low: F) -> BalanceWatcher
+    where
+        F: Fn(&Balance) + Send + 'static,
+    {
+        let client = self.clone();
+        let interval = interval.max(MIN_INTERVAL);
+
+        let handle = tokio::spawn(async move {
+            let mut below = false;
+            loop {
+                if let Ok(balance) = client.get_balance().await {
+                    if balance.credits < threshold {
+                        if !below {
+                            on_low(&balance);
+                        }
+                        below = true;
+                    } else {
+                        below = false;
+                    }
+                }
+                tokio::time::sleep(interval).await;
+            }
+        });
+
+        BalanceWatcher { handle }
+    }
+}
@@ -0,0 +1,183 @@
+//! Webhook signature verification
+//!
+//! Requires the `webhook` feature. PeerCat signs each callback body with
+//! HMAC-SHA256 using your webhook secret and sends the hex digest in the
+//! [`SIGNATURE_HEADER`] header, optionally prefixed with `sha256=`.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use peercat::webhook::parse_event;
+//!
+//! # fn handle(body: &[u8], signature: &str) -> peercat::Result<()> {
+//! let event = parse_event(b"whsec_xxx", body, signature)?;
+//! println!("{}: {}", event.event_type, event.id);
+//! # Ok(())
+//! # }
+//! ```
+
+use hmac::{Hmac, Mac};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+
+use crate::error::{PeerCatError, Result};
+use crate::types::Timestamp;
+
+/// Header carrying the payload signature
+pub const SIGNATURE_HEADER: &str = "x-peercat-signature";
+
+/// A webhook event delivered to a callback URL
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WebhookEvent {
+    /// Event ID
+    pub id: String,
+    /// Event type (e.g., `generation.completed`)
+    #[serde(rename = "type")]
+    pub event_type: String,
+    /// When the event occurred
+    pub created_at: Timestamp,
+    /// Event payload
+    pub data: serde_json::Value,
+}
+
+impl WebhookEvent {
+    /// Deserialize the payload into a concrete type
+    pub fn data_as<T: DeserializeOwned>(&self) -> Result<T> {
+        Ok(T::deserialize(&self.data)?)
+    }
+}
+
+/// Check that `signature` is the HMAC-SHA256 of `payload` under `secret`
+///
+/// The comparison is constant-time.
+pub fn verify_signature(secret: &[u8], payload: &[u8], signature: &str) -> Result<()> {
+    let signature = signature.trim();
+    let signature = signature.strip_prefix("sha256=").unwrap_or(signature);
+    let signature = hex::decode(signature).map_err(|_| PeerCatError::InvalidSignature)?;
+
+    let mut mac =
+        Hmac::<Sha256>::new_from_slice(secret).map_err(|_| PeerCatError::InvalidSignature)?;
+    mac.update(payload);
+    mac.verify_slice(&signature)
+        .map_err(|_| PeerCatError::InvalidSignature)
+}
+
+/// Verify the signature and deserialize the event
+pub fn parse_event(secret: &[u8], payload: &[u8], signature: &str) -> Result<WebhookEvent> {
+    verify_signature(secret, payload, signature)?;
+    Ok(serde_json::from_slice(payload)?)
+}
+
+/// Axum extractor for verified webhook events
+///
+/// Requires the `axum` feature.
+///
+/// # Example
+///
+/// ```no_run
+/// use axum::routing::post;
+/// use axum::Router;
+/// use peercat::webhook::axum::{VerifiedWebhook, WebhookSecret};
+///
+/// async fn on_event(VerifiedWebhook(event): VerifiedWebhook) {
+///     println!("{}: {}", event.event_type, event.id);
+/// }
+///
+/// let app: Router = Router::new()
+///     .route("/webhooks/peercat", post(on_event))
+///     .with_state(WebhookSecret::new("whsec_xxx"));
+/// ```
+#[cfg(feature = "axum")]
+pub mod axum {
+    use std::fmt;
+    use std::sync::Arc;
+
+    use axum::body::Bytes;
+    use axum::extract::{FromRef, FromRequest, Request};
+    use axum::http::StatusCode;
+    use axum::response::{IntoResponse, Response};
+
+    use super::{parse_event, WebhookEvent, SIGNATURE_HEADER};
+    use crate::error::PeerCatError;
+
+    /// The webhook signing secret, provided as router state
+    ///
+    /// Use it directly as the state, or expose it from your own state type
+    /// with [`FromRef`].
+    #[derive(Clone)]
+    pub struct WebhookSecret(Arc<[u8]>);
+
+    impl WebhookSecret {
+        /// Create a secret from its raw bytes
+        pub fn new(secret: impl AsRef<[u8]>) -> Self {
+            Self(secret.as_ref().into())
+        }
+    }
+
+    impl fmt::Debug for WebhookSecret {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.write_str("WebhookSecret([REDACTED])")
+        }
+    }
+
+    /// A webhook event whose signature has been verified
+    #[derive(Debug, Clone)]
+    pub struct VerifiedWebhook(pub WebhookEvent);
+
+    /// Why a webhook request was rejected
+    #[derive(Debug)]
+    pub enum WebhookRejection {
+        /// The signature header is absent
+        MissingSignature,
+        /// The signature doesn't match the body
+        InvalidSignature,
+        /// The body couldn't be read or isn't a valid event
+        InvalidPayload,
+    }
+
+    impl IntoResponse for WebhookRejection {
+        fn into_response(self) -> Response {
+            let (status, message) = match self {
+                WebhookRejection::MissingSignature => {
+                    (StatusCode::UNAUTHORIZED, "missing webhook signature")
+                }
+                WebhookRejection::InvalidSignature => {
+                    (StatusCode::UNAUTHORIZED, "invalid webhook signature")
+                }
+                WebhookRejection::InvalidPayload => {
+                    (StatusCode::BAD_REQUEST, "invalid webhook payload")
+                }
+            };
+            (status, message).into_response()
+        }
+    }
+
+    impl<S> FromRequest<S> for VerifiedWebhook
+    where
+        WebhookSecret: FromRef<S>,
+        S: Send + Sync,
+    {
+        type Rejection = WebhookRejection;
+
+        async fn from_request(req: Request, state: &S) -> Result<Self, Self::Rejection> {
+            let secret = WebhookSecret::from_ref(state);
+            let signature = req
+                .headers()
+                .get(SIGNATURE_HEADER)
+                .and_then(|value| value.to_str().ok())
+                .map(str::to_owned)
+                .ok_or(WebhookRejection::MissingSignature)?;
+            let body = Bytes::from_request(req, state)
+                .await
+                .map_err(|_| WebhookRejection::InvalidPayload)?;
+
+            match parse_event(&secret.0, &body, &signature) {
+                Ok(event) => Ok(VerifiedWebhook(event)),
+                Err(PeerCatError::InvalidSignature) => Err(WebhookRejection::InvalidSignature),
+                Err(_) => Err(WebhookRejection::InvalidPayload),
+            }
+        }
+    }
+}
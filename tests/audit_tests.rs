@@ -0,0 +1,194 @@
+//! Tests for the audit log
+#![cfg(feature = "audit")]
+
+use std::sync::{Arc, Mutex};
+
+use peercat::audit::{AuditCallback, AuditRecord, AuditWriter};
+use peercat::{GenerateParams, PeerCat, PeerCatConfig};
+use sha2::{Digest, Sha256};
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+fn create_test_client(mock_server: &MockServer, records: &Arc<Mutex<Vec<AuditRecord>>>) -> PeerCat {
+    let records = records.clone();
+    PeerCat::with_config(
+        PeerCatConfig::new("test_api_key")
+            .with_base_url(mock_server.uri())
+            .with_max_retries(0)
+            .with_audit_log(AuditCallback::new(move |record| {
+                records.lock().unwrap().push(record.clone())
+            })),
+    )
+    .expect("Failed to create test client")
+}
+
+#[tokio::test]
+async fn test_audit_generate() {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("POST"))
+        .and(path("/v1/generate"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .insert_header("X-Request-Id", "req_abc")
+                .set_body_json(serde_json::json!({
+                    "id": "gen_123",
+                    "imageUrl": "https://cdn.peerc.at/images/gen_123.png",
+                    "ipfsHash": "QmXyz123",
+                    "model": "stable-diffusion-xl",
+                    "mode": "production",
+                    "usage": {
+                        "creditsUsed": 0.28,
+                        "balanceRemaining": 9.72
+                    }
+                })),
+        )
+        .mount(&mock_server)
+        .await;
+
+    let records = Arc::new(Mutex::new(Vec::new()));
+    let client = create_test_client(&mock_server, &records);
+    client
+        .generate(GenerateParams::new("A sunset"))
+        .await
+        .expect("Generate should succeed");
+
+    let requests = mock_server.received_requests().await.unwrap();
+    let sent: serde_json::Value = serde_json::from_slice(&requests[0].body).unwrap();
+    let records = records.lock().unwrap();
+    assert_eq!(records.len(), 1);
+    let record = &records[0];
+    assert_eq!(record.method, "POST");
+    assert_eq!(record.endpoint, "/v1/generate");
+    assert_eq!(
+        record.params_hash.as_deref(),
+        Some(hex::encode(Sha256::digest(sent.to_string())).as_str())
+    );
+    assert_eq!(record.params, Some(sent));
+    assert_eq!(record.status, Some(200));
+    assert_eq!(record.request_id.as_deref(), Some("req_abc"));
+    assert_eq!(record.result_id.as_deref(), Some("gen_123"));
+    assert_eq!(record.credits, Some(0.28));
+    assert!(record.timestamp_ms > 0);
+    assert_eq!(record.error, None);
+}
+
+#[tokio::test]
+async fn test_audit_failed_call() {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("GET"))
+        .and(path("/v1/balance"))
+        .respond_with(ResponseTemplate::new(401).set_body_json(serde_json::json!({
+            "error": {
+                "type": "authentication_error",
+                "code": "invalid_api_key",
+                "message": "Invalid API key"
+            }
+        })))
+        .mount(&mock_server)
+        .await;
+
+    let records = Arc::new(Mutex::new(Vec::new()));
+    let client = create_test_client(&mock_server, &records);
+    client.get_balance().await.unwrap_err();
+
+    let records = records.lock().unwrap();
+    assert_eq!(records.len(), 1);
+    let record = &records[0];
+    assert_eq!(record.method, "GET");
+    assert_eq!(record.endpoint, "/v1/balance");
+    assert_eq!(record.params_hash, None);
+    assert_eq!(record.status, None);
+    assert_eq!(record.result_id, None);
+    assert_eq!(
+        record.error.as_deref(),
+        Some("Authentication error: Invalid API key")
+    );
+}
+
+#[tokio::test]
+async fn test_audit_redacted_fields() {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("POST"))
+        .and(path("/v1/generate"))
+        .respond_with(ResponseTemplate::new(400).set_body_json(serde_json::json!({
+            "error": {
+                "type": "invalid_request_error",
+                "code": "content_policy",
+                "message": "Prompt 'Jane Doe at 12 Elm St' was rejected"
+            }
+        })))
+        .mount(&mock_server)
+        .await;
+
+    let records = Arc::new(Mutex::new(Vec::new()));
+    let sink_records = records.clone();
+    let client = PeerCat::with_config(
+        PeerCatConfig::new("test_api_key")
+            .with_base_url(mock_server.uri())
+            .with_max_retries(0)
+            .with_redacted_field("prompt")
+            .with_redacted_field("options.negativePrompt")
+            .with_audit_log(AuditCallback::new(move |record| {
+                sink_records.lock().unwrap().push(record.clone())
+            })),
+    )
+    .unwrap();
+    client
+        .generate(
+            GenerateParams::new("Jane Doe at 12 Elm St")
+                .with_model("stable-diffusion-xl")
+                .with_option("negativePrompt", "blurry".into()),
+        )
+        .await
+        .unwrap_err();
+
+    let requests = mock_server.received_requests().await.unwrap();
+    let sent: serde_json::Value = serde_json::from_slice(&requests[0].body).unwrap();
+    let records = records.lock().unwrap();
+    let record = &records[0];
+    let params = record.params.as_ref().unwrap();
+    assert_eq!(params["prompt"], "[REDACTED]");
+    assert_eq!(params["options"]["negativePrompt"], "[REDACTED]");
+    assert_eq!(params["model"], "stable-diffusion-xl");
+    // The hash still covers what was actually sent
+    assert_eq!(
+        record.params_hash.as_deref(),
+        Some(hex::encode(Sha256::digest(sent.to_string())).as_str())
+    );
+    // Prompts echoed back in errors are removed too
+    assert_eq!(
+        record.error.as_deref(),
+        Some("Invalid request: Prompt '[REDACTED]' was rejected")
+    );
+}
+
+#[test]
+fn test_audit_writer_json_lines() {
+    use peercat::audit::AuditSink;
+
+    let record = AuditRecord {
+        timestamp_ms: 1_700_000_000_000,
+        method: "GET".to_string(),
+        endpoint: "/v1/models".to_string(),
+        params_hash: None,
+        params: None,
+        status: Some(200),
+        request_id: Some("req_1".to_string()),
+        result_id: None,
+        credits: None,
+        duration_ms: 12,
+        error: None,
+    };
+    let writer = AuditWriter::new(Vec::new());
+    writer.record(&record);
+    writer.record(&record);
+
+    let output = String::from_utf8(writer.into_inner()).unwrap();
+    let lines: Vec<&str> = output.lines().collect();
+    assert_eq!(lines.len(), 2);
+    let parsed: AuditRecord = serde_json::from_str(lines[0]).unwrap();
+    assert_eq!(parsed, record);
+}
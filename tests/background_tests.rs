@@ -0,0 +1,363 @@
+//! Tests for background polling helpers (requires the `runtime-tokio` feature)
+
+#![cfg(feature = "runtime-tokio")]
+
+use peercat::{BulkGenerator, GenerateParams, OnChainStatus, PeerCat, PeerCatConfig};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use wiremock::matchers::{body_partial_json, method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+/// Helper to create a client configured for mock server
+fn create_test_client(mock_server: &MockServer) -> PeerCat {
+    PeerCat::with_config(
+        PeerCatConfig::new("test_api_key")
+            .with_base_url(mock_server.uri())
+            .with_max_retries(0),
+    )
+    .expect("Failed to create test client")
+}
+
+#[tokio::test]
+async fn test_watch_balance_fires_once_below_threshold() {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("GET"))
+        .and(path("/v1/balance"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "credits": 1.50,
+            "totalDeposited": 50.00,
+            "totalSpent": 48.50,
+            "totalWithdrawn": 0.00,
+            "totalGenerated": 170
+        })))
+        .mount(&mock_server)
+        .await;
+
+    let client = create_test_client(&mock_server);
+    let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+    let watcher = client.watch_balance(Duration::from_millis(10), 5.0, move |balance| {
+        let _ = tx.send(balance.credits);
+    });
+
+    let credits = tokio::time::timeout(Duration::from_secs(5), rx.recv())
+        .await
+        .expect("Callback should fire")
+        .expect("Channel should be open");
+    assert_eq!(credits, 1.50);
+
+    // Still below threshold on later polls; callback must not fire again
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    assert!(rx.try_recv().is_err());
+
+    watcher.stop();
+}
+
+#[tokio::test]
+async fn test_watch_balance_with_zero_interval() {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("GET"))
+        .and(path("/v1/balance"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "credits": 10.00,
+            "totalDeposited": 50.00,
+            "totalSpent": 40.00,
+            "totalWithdrawn": 0.00,
+            "totalGenerated": 140
+        })))
+        .mount(&mock_server)
+        .await;
+
+    let client = create_test_client(&mock_server);
+    let watcher = client.watch_balance(Duration::ZERO, 5.0, |_| {});
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    watcher.stop();
+
+    // Polls are spaced at least 10ms apart rather than sent back to back
+    let polls = mock_server.received_requests().await.unwrap().len();
+    assert!((1..=12).contains(&polls), "{} polls in 100ms", polls);
+}
+
+#[tokio::test]
+async fn test_price_stream_skips_unchanged_quotes() {
+    let mock_server = MockServer::start().await;
+
+    let quote = |sol_price: f64, updated_at: &str| {
+        serde_json::json!({
+            "solPrice": sol_price,
+            "slippageTolerance": 0.05,
+            "updatedAt": updated_at,
+            "treasury": "9JKi6Tr7JdsTJw1zNedF5vML9GpPnjHD9DWuZq1oE6nV",
+            "models": []
+        })
+    };
+
+    Mock::given(method("GET"))
+        .and(path("/v1/price"))
+        .respond_with(
+            ResponseTemplate::new(200).set_body_json(quote(185.50, "2024-01-15T12:00:00Z")),
+        )
+        .up_to_n_times(3)
+        .mount(&mock_server)
+        .await;
+
+    Mock::given(method("GET"))
+        .and(path("/v1/price"))
+        .respond_with(
+            ResponseTemplate::new(200).set_body_json(quote(190.00, "2024-01-15T12:01:00Z")),
+        )
+        .mount(&mock_server)
+        .await;
+
+    let client = create_test_client(&mock_server);
+    let mut prices = client.price_stream(Duration::from_millis(10));
+
+    let first = tokio::time::timeout(Duration::from_secs(5), prices.next())
+        .await
+        .expect("First quote should arrive")
+        .expect("Stream should be open")
+        .expect("Quote should succeed");
+    assert_eq!(first.sol_price, 185.50);
+
+    // Repeated quotes with the same timestamp are skipped
+    let second = tokio::time::timeout(Duration::from_secs(5), prices.next())
+        .await
+        .expect("Second quote should arrive")
+        .expect("Stream should be open")
+        .expect("Quote should succeed");
+    assert_eq!(second.sol_price, 190.00);
+}
+
+#[tokio::test]
+async fn test_price_stream_with_zero_interval() {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("GET"))
+        .and(path("/v1/price"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "solPrice": 185.50,
+            "slippageTolerance": 0.05,
+            "updatedAt": "2024-01-15T12:00:00Z",
+            "treasury": "9JKi6Tr7JdsTJw1zNedF5vML9GpPnjHD9DWuZq1oE6nV",
+            "models": []
+        })))
+        .mount(&mock_server)
+        .await;
+
+    let client = create_test_client(&mock_server);
+    let mut prices = client.price_stream(Duration::ZERO);
+
+    let quote = tokio::time::timeout(Duration::from_secs(5), prices.next())
+        .await
+        .expect("Quote should arrive")
+        .expect("Stream should be open")
+        .expect("Quote should succeed");
+    assert_eq!(quote.sol_price, 185.50);
+}
+
+#[tokio::test]
+async fn test_onchain_status_stream_emits_transitions_until_terminal() {
+    let mock_server = MockServer::start().await;
+
+    let update = |status: &str| {
+        serde_json::json!({
+            "txSignature": "txSig123",
+            "status": status,
+            "model": "stable-diffusion-xl",
+            "createdAt": "2024-01-15T10:00:00Z"
+        })
+    };
+
+    for (status, times) in [("pending", 2), ("processing", 2), ("completed", 1)] {
+        Mock::given(method("GET"))
+            .and(path("/v1/generate/txSig123"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(update(status)))
+            .up_to_n_times(times)
+            .mount(&mock_server)
+            .await;
+    }
+
+    let client = create_test_client(&mock_server);
+    let mut updates = client.onchain_status_stream("txSig123", Duration::from_millis(10));
+
+    let mut statuses = Vec::new();
+    while let Some(update) = tokio::time::timeout(Duration::from_secs(5), updates.next())
+        .await
+        .expect("Stream should make progress")
+    {
+        statuses.push(update.expect("Status should succeed").status);
+    }
+
+    assert_eq!(
+        statuses,
+        vec![
+            OnChainStatus::Pending,
+            OnChainStatus::Processing,
+            OnChainStatus::Completed
+        ]
+    );
+}
+
+#[tokio::test]
+async fn test_onchain_status_stream_with_zero_interval() {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("GET"))
+        .and(path("/v1/generate/txSig123"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "txSignature": "txSig123",
+            "status": "completed",
+            "model": "stable-diffusion-xl",
+            "createdAt": "2024-01-15T10:00:00Z"
+        })))
+        .mount(&mock_server)
+        .await;
+
+    let client = create_test_client(&mock_server);
+    let mut updates = client.onchain_status_stream("txSig123", Duration::ZERO);
+
+    let update = tokio::time::timeout(Duration::from_secs(5), updates.next())
+        .await
+        .expect("Status should arrive")
+        .expect("Stream should be open")
+        .expect("Status should succeed");
+    assert_eq!(update.status, OnChainStatus::Completed);
+}
+
+fn generate_response(id: &str) -> serde_json::Value {
+    serde_json::json!({
+        "id": id,
+        "imageUrl": format!("https://cdn.peerc.at/images/{}.png", id),
+        "ipfsHash": null,
+        "model": "stable-diffusion-xl",
+        "mode": "production",
+        "usage": {
+            "creditsUsed": 0.25,
+            "balanceRemaining": 10.0
+        }
+    })
+}
+
+#[tokio::test]
+async fn test_bulk_generator_reports_progress_and_failures() {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("POST"))
+        .and(path("/v1/generate"))
+        .and(body_partial_json(
+            serde_json::json!({ "prompt": "bad prompt" }),
+        ))
+        .respond_with(ResponseTemplate::new(400).set_body_json(serde_json::json!({
+            "error": {
+                "type": "invalid_request_error",
+                "code": "content_policy",
+                "message": "Prompt rejected"
+            }
+        })))
+        .with_priority(1)
+        .mount(&mock_server)
+        .await;
+
+    Mock::given(method("POST"))
+        .and(path("/v1/generate"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(generate_response("gen_1")))
+        .mount(&mock_server)
+        .await;
+
+    let client = create_test_client(&mock_server);
+    let prompts = ["a cat", "bad prompt", "a dog", "a bird", "a fish"];
+    let progress = Arc::new(Mutex::new(Vec::new()));
+    let seen = progress.clone();
+
+    let report = BulkGenerator::new(client, prompts.map(GenerateParams::new))
+        .with_concurrency(2)
+        .on_progress(move |p| seen.lock().unwrap().push(*p))
+        .run()
+        .await;
+
+    assert_eq!(report.completed.len(), 4);
+    assert_eq!(report.failed.len(), 1);
+    assert_eq!(report.failed[0].0, 1);
+    assert!(report.remaining.is_empty());
+    assert_eq!(report.credits_spent, 1.0);
+
+    let progress = progress.lock().unwrap();
+    assert_eq!(progress.len(), 5);
+    let last = progress.last().unwrap();
+    assert_eq!((last.completed, last.failed, last.remaining), (4, 1, 0));
+}
+
+#[tokio::test]
+async fn test_bulk_generator_stop_returns_remaining_params() {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("POST"))
+        .and(path("/v1/generate"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(generate_response("gen_1")))
+        .mount(&mock_server)
+        .await;
+
+    let client = create_test_client(&mock_server);
+    let generator = BulkGenerator::new(
+        client,
+        (0..10).map(|i| GenerateParams::new(format!("prompt {}", i))),
+    )
+    .with_concurrency(1);
+    let controller = generator.controller();
+
+    let report = generator
+        .on_progress(move |p| {
+            if p.completed == 3 {
+                controller.stop();
+            }
+        })
+        .run()
+        .await;
+
+    assert_eq!(report.completed.len(), 3);
+    assert_eq!(report.remaining.len(), 7);
+    assert_eq!(report.remaining[0].0, 3);
+    assert_eq!(report.remaining[0].1.prompt, "prompt 3");
+}
+
+#[tokio::test]
+async fn test_bulk_generator_pause_and_resume() {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("POST"))
+        .and(path("/v1/generate"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(generate_response("gen_1")))
+        .mount(&mock_server)
+        .await;
+
+    let client = create_test_client(&mock_server);
+    let generator =
+        BulkGenerator::new(client, ["a", "b", "c"].map(GenerateParams::new)).with_concurrency(1);
+    let controller = generator.controller();
+    let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+
+    let pause = controller.clone();
+    let run = tokio::spawn(
+        generator
+            .on_progress(move |p| {
+                if p.completed == 1 {
+                    pause.pause();
+                }
+                let _ = tx.send(p.completed);
+            })
+            .run(),
+    );
+
+    assert_eq!(rx.recv().await, Some(1));
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    assert!(controller.is_paused());
+    assert!(rx.try_recv().is_err());
+
+    controller.resume();
+    let report = tokio::time::timeout(Duration::from_secs(5), run)
+        .await
+        .expect("Run should finish after resume")
+        .unwrap();
+    assert_eq!(report.completed.len(), 3);
+}
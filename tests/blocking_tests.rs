@@ -0,0 +1,221 @@
+//! Tests for the blocking client (requires the `blocking` feature)
+
+#![cfg(feature = "blocking")]
+
+use peercat::blocking::PeerCat;
+use peercat::{GenerateParams, GenerateResult, PeerCatConfig, PeerCatError, ThumbSize};
+use wiremock::matchers::{method, path, query_param};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+/// Start a mock server on its own runtime, since blocking calls can't run
+/// inside an async test
+fn start_mock_server() -> (tokio::runtime::Runtime, MockServer) {
+    let runtime = tokio::runtime::Runtime::new().unwrap();
+    let mock_server = runtime.block_on(MockServer::start());
+    (runtime, mock_server)
+}
+
+/// Helper to create a client configured for mock server
+fn create_test_client(mock_server: &MockServer) -> PeerCat {
+    PeerCat::with_config(
+        PeerCatConfig::new("test_api_key")
+            .with_base_url(mock_server.uri())
+            .with_max_retries(0),
+    )
+    .expect("Failed to create test client")
+}
+
+#[test]
+fn test_blocking_generate() {
+    let (runtime, mock_server) = start_mock_server();
+
+    runtime.block_on(
+        Mock::given(method("POST"))
+            .and(path("/v1/generate"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "id": "gen_123",
+                "imageUrl": "https://cdn.peerc.at/gen_123.png",
+                "model": "stable-diffusion-xl",
+                "mode": "production",
+                "usage": {
+                    "creditsUsed": 0.28,
+                    "balanceRemaining": 9.72
+                }
+            })))
+            .mount(&mock_server),
+    );
+
+    let client = create_test_client(&mock_server);
+    let result = client
+        .generate(GenerateParams::new("A beautiful sunset"))
+        .expect("Generate should succeed");
+
+    assert_eq!(result.id, "gen_123");
+    assert_eq!(result.usage.credits_used, 0.28);
+}
+
+#[test]
+fn test_blocking_error() {
+    let (runtime, mock_server) = start_mock_server();
+
+    runtime.block_on(
+        Mock::given(method("GET"))
+            .and(path("/v1/balance"))
+            .respond_with(ResponseTemplate::new(401).set_body_json(serde_json::json!({
+                "error": {
+                    "type": "authentication_error",
+                    "code": "invalid_api_key",
+                    "message": "Invalid API key"
+                }
+            })))
+            .mount(&mock_server),
+    );
+
+    let client = create_test_client(&mock_server);
+    let err = client.get_balance().unwrap_err();
+
+    assert!(matches!(err, PeerCatError::Authentication { .. }));
+}
+
+#[test]
+fn test_blocking_downloads() {
+    let (runtime, mock_server) = start_mock_server();
+
+    runtime.block_on(async {
+        Mock::given(method("GET"))
+            .and(path("/images/gen_123.png"))
+            .and(query_param("width", "256"))
+            .respond_with(ResponseTemplate::new(200).set_body_bytes(b"thumbnail".to_vec()))
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/ipfs/QmXyz123"))
+            .respond_with(ResponseTemplate::new(200).set_body_bytes(b"from ipfs".to_vec()))
+            .mount(&mock_server)
+            .await;
+    });
+
+    let client = PeerCat::with_config(
+        PeerCatConfig::new("test_api_key")
+            .with_base_url(mock_server.uri())
+            .with_max_retries(0)
+            .with_ipfs_gateway(mock_server.uri()),
+    )
+    .unwrap();
+    let result: GenerateResult = serde_json::from_value(serde_json::json!({
+        "id": "gen_123",
+        "imageUrl": format!("{}/images/gen_123.png", mock_server.uri()),
+        "ipfsHash": "QmXyz123",
+        "model": "stable-diffusion-xl",
+        "mode": "production",
+        "usage": { "creditsUsed": 0.28, "balanceRemaining": 9.72 }
+    }))
+    .unwrap();
+
+    let thumbnail = client
+        .download_thumbnail(&result, ThumbSize::Medium)
+        .expect("Thumbnail should download");
+    assert_eq!(&thumbnail[..], b"thumbnail");
+
+    let image = client
+        .download_from_ipfs(&result)
+        .expect("IPFS image should download");
+    assert_eq!(&image[..], b"from ipfs");
+}
+
+#[cfg(feature = "export")]
+#[test]
+fn test_blocking_export_images() {
+    let (runtime, mock_server) = start_mock_server();
+
+    runtime.block_on(async {
+        Mock::given(method("GET"))
+            .and(path("/v1/history"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "items": [{
+                    "id": "use_1",
+                    "endpoint": "/v1/generate",
+                    "model": "stable-diffusion-xl",
+                    "creditsUsed": 0.28,
+                    "requestId": "gen_1",
+                    "status": "completed",
+                    "createdAt": "2024-01-15T10:00:00Z",
+                    "completedAt": null,
+                    "imageUrl": format!("{}/images/gen_1.png", mock_server.uri())
+                }],
+                "pagination": { "total": 1, "limit": 100, "offset": 0, "hasMore": false }
+            })))
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/images/gen_1.png"))
+            .respond_with(ResponseTemplate::new(200).set_body_bytes(b"image one".to_vec()))
+            .mount(&mock_server)
+            .await;
+    });
+
+    let dir = std::env::temp_dir().join(format!("peercat-blocking-export-{}", std::process::id()));
+    let manifest = create_test_client(&mock_server)
+        .export_images(
+            peercat::HistoryParams::new(),
+            &dir,
+            peercat::export::ExportOptions::new(),
+        )
+        .expect("Export should succeed");
+
+    assert_eq!(manifest.images["gen_1"], "gen_1.png");
+    assert_eq!(std::fs::read(dir.join("gen_1.png")).unwrap(), b"image one");
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[cfg(feature = "export")]
+#[test]
+fn test_blocking_export_history() {
+    let (runtime, mock_server) = start_mock_server();
+    let item = |id: &str| {
+        serde_json::json!({
+            "id": id,
+            "endpoint": "/v1/generate",
+            "model": "stable-diffusion-xl",
+            "creditsUsed": 0.28,
+            "requestId": null,
+            "status": "completed",
+            "createdAt": "2024-01-15T10:00:00Z",
+            "completedAt": null
+        })
+    };
+
+    runtime.block_on(async {
+        for (offset, has_more) in [(0, true), (1, false)] {
+            Mock::given(method("GET"))
+                .and(path("/v1/history"))
+                .and(query_param("offset", offset.to_string()))
+                .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                    "items": [item(&format!("use_{}", offset + 1))],
+                    "pagination": { "total": 2, "limit": 1, "offset": offset, "hasMore": has_more }
+                })))
+                .expect(1)
+                .mount(&mock_server)
+                .await;
+        }
+    });
+
+    // The limit is the page size, so both pages are exported
+    let mut out = Vec::new();
+    let rows = create_test_client(&mock_server)
+        .export_history(
+            peercat::HistoryParams::new().with_limit(1),
+            peercat::export::Format::Jsonl,
+            &mut out,
+        )
+        .expect("Export should succeed");
+
+    assert_eq!(rows, 2);
+    let ids: Vec<String> = String::from_utf8(out)
+        .unwrap()
+        .lines()
+        .map(|line| serde_json::from_str::<serde_json::Value>(line).unwrap()["id"].to_string())
+        .collect();
+    assert_eq!(ids, ["\"use_1\"", "\"use_2\""]);
+}
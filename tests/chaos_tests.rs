@@ -0,0 +1,105 @@
+//! Tests for the fault-injection wrapper (requires the `chaos` and `mock` features)
+
+#![cfg(all(feature = "chaos", feature = "mock"))]
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use peercat::chaos::ChaosPeerCat;
+use peercat::mock::MockPeerCat;
+use peercat::{Balance, PeerCatApi, PeerCatError};
+
+/// A mock whose `get_balance` counts its calls
+fn counting_mock(calls: &Arc<AtomicUsize>) -> MockPeerCat {
+    let calls = calls.clone();
+    MockPeerCat::new().on_get_balance(move || {
+        calls.fetch_add(1, Ordering::SeqCst);
+        Ok(Balance {
+            credits: 5.0,
+            total_deposited: 10.0,
+            total_spent: 5.0,
+            total_withdrawn: 0.0,
+            total_generated: 18,
+        })
+    })
+}
+
+#[tokio::test]
+async fn test_chaos_passes_through_without_faults() {
+    let calls = Arc::new(AtomicUsize::new(0));
+    let api = ChaosPeerCat::new(counting_mock(&calls));
+
+    for _ in 0..10 {
+        assert_eq!(api.get_balance().await.unwrap().credits, 5.0);
+    }
+    assert_eq!(calls.load(Ordering::SeqCst), 10);
+}
+
+#[tokio::test]
+async fn test_chaos_rate_limits_skip_the_call() {
+    let calls = Arc::new(AtomicUsize::new(0));
+    let api = ChaosPeerCat::new(counting_mock(&calls)).with_rate_limits(1.0);
+
+    let error = api.get_balance().await.unwrap_err();
+    assert!(matches!(error, PeerCatError::RateLimit { .. }));
+    assert_eq!(error.retry_after(), Some(1));
+    assert!(error.is_retryable());
+    assert_eq!(calls.load(Ordering::SeqCst), 0);
+}
+
+#[tokio::test]
+async fn test_chaos_timeouts_and_malformed_responses_after_the_call() {
+    let calls = Arc::new(AtomicUsize::new(0));
+    let api = ChaosPeerCat::new(counting_mock(&calls)).with_timeouts(1.0);
+    assert!(matches!(
+        api.get_balance().await,
+        Err(PeerCatError::Timeout)
+    ));
+    assert_eq!(calls.load(Ordering::SeqCst), 1);
+
+    let api = ChaosPeerCat::new(api.into_inner()).with_malformed_responses(1.0);
+    assert!(matches!(
+        api.get_balance().await,
+        Err(PeerCatError::Json(_))
+    ));
+    assert_eq!(calls.load(Ordering::SeqCst), 2);
+}
+
+#[tokio::test]
+async fn test_chaos_latency() {
+    let calls = Arc::new(AtomicUsize::new(0));
+    let api = ChaosPeerCat::new(counting_mock(&calls))
+        .with_latency(1.0, Duration::from_millis(20)..Duration::from_millis(30));
+
+    let started = Instant::now();
+    api.get_balance().await.unwrap();
+    assert!(started.elapsed() >= Duration::from_millis(20));
+}
+
+#[tokio::test]
+async fn test_chaos_seed_replays_faults() {
+    async fn outcomes(seed: u64) -> Vec<&'static str> {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let api = ChaosPeerCat::new(counting_mock(&calls))
+            .with_rate_limits(0.3)
+            .with_timeouts(0.3)
+            .with_seed(seed);
+        let mut outcomes = Vec::new();
+        for _ in 0..50 {
+            outcomes.push(match api.get_balance().await {
+                Ok(_) => "ok",
+                Err(PeerCatError::RateLimit { .. }) => "rate_limit",
+                Err(PeerCatError::Timeout) => "timeout",
+                Err(e) => panic!("Unexpected error: {:?}", e),
+            });
+        }
+        outcomes
+    }
+
+    let first = outcomes(7).await;
+    assert_eq!(first, outcomes(7).await);
+    for outcome in ["ok", "rate_limit", "timeout"] {
+        assert!(first.contains(&outcome), "never saw {}", outcome);
+    }
+}
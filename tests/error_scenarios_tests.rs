@@ -3,15 +3,19 @@
 //! These tests cover edge cases, network failures, malformed responses,
 //! and retry/rate-limit behavior to ensure SDK robustness.
 
-use peercat::{GenerateParams, PeerCat, PeerCatConfig, PeerCatError};
-use wiremock::matchers::{method, path};
+use peercat::{
+    ClientEvent, ClientEvents, ErrorContext, ErrorReporter, GenerateParams, GenerationMode,
+    KeyEnvironment, KeyPool, LeastRecentlyLimited, PeerCat, PeerCatBuilder, PeerCatConfig,
+    PeerCatError, RetryBudget,
+};
+use wiremock::matchers::{body_json, header, method, path};
 use wiremock::{Mock, MockServer, ResponseTemplate};
 
 /// Helper to create a client configured for mock server
 fn create_test_client(mock_server: &MockServer) -> PeerCat {
     PeerCat::with_config(
         PeerCatConfig::new("test_api_key")
-            .with_base_url(&mock_server.uri())
+            .with_base_url(mock_server.uri())
             .with_max_retries(0),
     )
     .expect("Failed to create test client")
@@ -318,6 +322,121 @@ async fn test_rate_limit_is_retryable() {
     assert!(error.is_retryable());
 }
 
+#[tokio::test]
+async fn test_rate_limit_retry_after_seconds() {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("GET"))
+        .and(path("/v1/balance"))
+        .respond_with(
+            ResponseTemplate::new(429)
+                .insert_header("Retry-After", "30")
+                .set_body_json(serde_json::json!({
+                    "error": {
+                        "type": "rate_limit_error",
+                        "code": "rate_limit_exceeded",
+                        "message": "Rate limited"
+                    }
+                })),
+        )
+        .mount(&mock_server)
+        .await;
+
+    let client = create_test_client(&mock_server);
+    let error = client.get_balance().await.unwrap_err();
+
+    assert_eq!(error.retry_after(), Some(30));
+}
+
+#[tokio::test]
+async fn test_rate_limit_huge_retry_after() {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("GET"))
+        .and(path("/v1/balance"))
+        .respond_with(
+            ResponseTemplate::new(429)
+                .insert_header("Retry-After", u64::MAX.to_string().as_str())
+                .set_body_json(serde_json::json!({
+                    "error": {
+                        "type": "rate_limit_error",
+                        "code": "rate_limit_exceeded",
+                        "message": "Rate limited"
+                    }
+                })),
+        )
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+
+    // The wait is capped, so it neither overflows nor fits in the deadline
+    let client = PeerCat::with_config(
+        PeerCatConfig::new("test_api_key")
+            .with_base_url(mock_server.uri())
+            .with_max_retries(1)
+            .with_overall_deadline(std::time::Duration::from_secs(5)),
+    )
+    .unwrap();
+    let error = client.get_balance().await.unwrap_err();
+
+    assert_eq!(error.retry_after(), Some(u64::MAX));
+}
+
+#[tokio::test]
+async fn test_rate_limit_retry_after_http_date() {
+    let mock_server = MockServer::start().await;
+
+    let retry_at = std::time::SystemTime::now() + std::time::Duration::from_secs(120);
+
+    Mock::given(method("GET"))
+        .and(path("/v1/balance"))
+        .respond_with(
+            ResponseTemplate::new(429)
+                .insert_header("Retry-After", httpdate::fmt_http_date(retry_at).as_str())
+                .set_body_json(serde_json::json!({
+                    "error": {
+                        "type": "rate_limit_error",
+                        "code": "rate_limit_exceeded",
+                        "message": "Rate limited"
+                    }
+                })),
+        )
+        .mount(&mock_server)
+        .await;
+
+    let client = create_test_client(&mock_server);
+    let error = client.get_balance().await.unwrap_err();
+
+    let retry_after = error.retry_after().expect("HTTP-date should be parsed");
+    assert!((115..=120).contains(&retry_after), "got {}", retry_after);
+}
+
+#[tokio::test]
+async fn test_rate_limit_retry_after_past_http_date() {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("GET"))
+        .and(path("/v1/balance"))
+        .respond_with(
+            ResponseTemplate::new(429)
+                .insert_header("Retry-After", "Wed, 21 Oct 2015 07:28:00 GMT")
+                .set_body_json(serde_json::json!({
+                    "error": {
+                        "type": "rate_limit_error",
+                        "code": "rate_limit_exceeded",
+                        "message": "Rate limited"
+                    }
+                })),
+        )
+        .mount(&mock_server)
+        .await;
+
+    let client = create_test_client(&mock_server);
+    let error = client.get_balance().await.unwrap_err();
+
+    assert_eq!(error.retry_after(), Some(0));
+}
+
 #[tokio::test]
 async fn test_server_error_is_retryable() {
     let mock_server = MockServer::start().await;
@@ -342,54 +461,939 @@ async fn test_server_error_is_retryable() {
         _ => panic!("Expected Server error, got {:?}", error),
     }
 
-    assert!(error.is_retryable());
+    assert!(error.is_retryable());
+}
+
+#[tokio::test]
+async fn test_auth_error_not_retryable() {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("GET"))
+        .and(path("/v1/balance"))
+        .respond_with(ResponseTemplate::new(401).set_body_json(serde_json::json!({
+            "error": {
+                "type": "authentication_error",
+                "code": "invalid_key",
+                "message": "Invalid"
+            }
+        })))
+        .mount(&mock_server)
+        .await;
+
+    let client = create_test_client(&mock_server);
+    let error = client.get_balance().await.unwrap_err();
+
+    assert!(!error.is_retryable());
+}
+
+#[tokio::test]
+async fn test_insufficient_credits_not_retryable() {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("POST"))
+        .and(path("/v1/generate"))
+        .respond_with(ResponseTemplate::new(402).set_body_json(serde_json::json!({
+            "error": {
+                "type": "insufficient_credits",
+                "code": "insufficient_balance",
+                "message": "Not enough"
+            }
+        })))
+        .mount(&mock_server)
+        .await;
+
+    let client = create_test_client(&mock_server);
+    let error = client
+        .generate(GenerateParams::new("test"))
+        .await
+        .unwrap_err();
+
+    assert!(!error.is_retryable());
+}
+
+// ============ Key Pool Tests ============
+
+fn create_pool_client(mock_server: &MockServer, pool: KeyPool) -> PeerCat {
+    PeerCat::with_config(
+        PeerCatConfig::from_key_pool(pool)
+            .with_base_url(mock_server.uri())
+            .with_max_retries(0),
+    )
+    .expect("Failed to create test client")
+}
+
+fn balance_body() -> serde_json::Value {
+    serde_json::json!({
+        "credits": 10.0,
+        "totalDeposited": 10.0,
+        "totalSpent": 0.0,
+        "totalWithdrawn": 0.0,
+        "totalGenerated": 0
+    })
+}
+
+#[tokio::test]
+async fn test_key_pool_fails_over_on_rate_limit() {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("GET"))
+        .and(path("/v1/balance"))
+        .and(header("Authorization", "Bearer key_a"))
+        .respond_with(
+            ResponseTemplate::new(429)
+                .insert_header("Retry-After", "60")
+                .set_body_json(serde_json::json!({
+                    "error": {
+                        "type": "rate_limit_error",
+                        "code": "rate_limit_exceeded",
+                        "message": "Rate limited"
+                    }
+                })),
+        )
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+
+    Mock::given(method("GET"))
+        .and(path("/v1/balance"))
+        .and(header("Authorization", "Bearer key_b"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(balance_body()))
+        .expect(2)
+        .mount(&mock_server)
+        .await;
+
+    let pool = KeyPool::new(["key_a", "key_b"]).with_selector(LeastRecentlyLimited);
+    let client = create_pool_client(&mock_server, pool.clone());
+
+    client.get_balance().await.expect("Should fail over to key_b");
+    // key_a is still cooling down, so the next call goes straight to key_b
+    client.get_balance().await.expect("Should use key_b");
+
+    let status = pool.status();
+    assert!(status[0].rate_limited_until.is_some());
+    assert!(status[1].rate_limited_until.is_none());
+}
+
+#[tokio::test]
+async fn test_key_pool_skips_revoked_key() {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("GET"))
+        .and(path("/v1/balance"))
+        .and(header("Authorization", "Bearer key_a"))
+        .respond_with(ResponseTemplate::new(401).set_body_json(serde_json::json!({
+            "error": {
+                "type": "authentication_error",
+                "code": "key_revoked",
+                "message": "Key revoked"
+            }
+        })))
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+
+    Mock::given(method("GET"))
+        .and(path("/v1/balance"))
+        .and(header("Authorization", "Bearer key_b"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(balance_body()))
+        .expect(3)
+        .mount(&mock_server)
+        .await;
+
+    let pool = KeyPool::new(["key_a", "key_b"]);
+    let client = create_pool_client(&mock_server, pool.clone());
+
+    for _ in 0..3 {
+        client.get_balance().await.expect("Should use key_b");
+    }
+
+    assert!(pool.status()[0].revoked);
+}
+
+#[tokio::test]
+async fn test_key_pool_all_keys_revoked_returns_error() {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("GET"))
+        .and(path("/v1/balance"))
+        .respond_with(ResponseTemplate::new(401).set_body_json(serde_json::json!({
+            "error": {
+                "type": "authentication_error",
+                "code": "key_revoked",
+                "message": "Key revoked"
+            }
+        })))
+        .expect(2)
+        .mount(&mock_server)
+        .await;
+
+    let client = create_pool_client(&mock_server, KeyPool::new(["key_a", "key_b"]));
+    let error = client.get_balance().await.unwrap_err();
+
+    assert!(matches!(error, PeerCatError::Authentication { .. }));
+}
+
+// ============ Rate Limit Pacing Tests ============
+
+#[tokio::test]
+async fn test_rate_limit_pacing_waits_for_reset() {
+    let mock_server = MockServer::start().await;
+    let reset = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_secs()
+        + 2;
+
+    Mock::given(method("GET"))
+        .and(path("/v1/balance"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .insert_header("X-RateLimit-Limit", "60")
+                .insert_header("X-RateLimit-Remaining", "0")
+                .insert_header("X-RateLimit-Reset", reset.to_string().as_str())
+                .set_body_json(balance_body()),
+        )
+        .expect(2)
+        .mount(&mock_server)
+        .await;
+
+    let client = PeerCat::with_config(
+        PeerCatConfig::new("test_api_key")
+            .with_base_url(mock_server.uri())
+            .with_max_retries(0)
+            .with_rate_limit_pacing(),
+    )
+    .unwrap();
+
+    client.get_balance().await.unwrap();
+    let started = std::time::Instant::now();
+    client.get_balance().await.unwrap();
+
+    // The window was exhausted, so the second request waits for the reset
+    assert!(started.elapsed() >= std::time::Duration::from_millis(900));
+}
+
+#[tokio::test]
+async fn test_rate_limit_pacing_caps_huge_retry_after() {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("GET"))
+        .and(path("/v1/balance"))
+        .respond_with(
+            ResponseTemplate::new(429)
+                .insert_header("Retry-After", u64::MAX.to_string().as_str())
+                .set_body_json(serde_json::json!({
+                    "error": {
+                        "type": "rate_limit_error",
+                        "code": "rate_limit_exceeded",
+                        "message": "Rate limited"
+                    }
+                })),
+        )
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+
+    let client = PeerCat::with_config(
+        PeerCatConfig::new("test_api_key")
+            .with_base_url(mock_server.uri())
+            .with_max_retries(0)
+            .with_rate_limit_pacing(),
+    )
+    .unwrap();
+
+    // The wait is capped rather than overflowing, and later requests are held back
+    let error = client.get_balance().await.unwrap_err();
+    assert_eq!(error.retry_after(), Some(u64::MAX));
+    let next = tokio::time::timeout(std::time::Duration::from_millis(200), client.get_balance());
+    assert!(next.await.is_err(), "The next request should be paced");
+}
+
+// ============ Retry Budget Tests ============
+
+#[tokio::test]
+async fn test_retry_budget_stops_retries_when_spent() {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("GET"))
+        .and(path("/v1/balance"))
+        .respond_with(ResponseTemplate::new(503))
+        .expect(2)
+        .mount(&mock_server)
+        .await;
+
+    let budget = RetryBudget::new(0.0).with_min_retries(1);
+    let client = PeerCat::with_config(
+        PeerCatConfig::new("test_api_key")
+            .with_base_url(mock_server.uri())
+            .with_max_retries(3)
+            .with_retry_budget(budget.clone()),
+    )
+    .unwrap();
+
+    // One retry is allowed, then the budget is spent
+    client.get_balance().await.unwrap_err();
+
+    let status = budget.status();
+    assert_eq!(status.requests, 1);
+    assert_eq!(status.retries, 1);
+    assert_eq!(status.available, 0);
+    assert_eq!(status.rejected, 1);
+}
+
+// ============ Client Event Tests ============
+
+/// Every event emitted until the client is dropped
+async fn collect_events(client: PeerCat, mut events: ClientEvents) -> Vec<ClientEvent> {
+    drop(client);
+    let mut collected = Vec::new();
+    while let Some(event) = events.next().await {
+        collected.push(event);
+    }
+    collected
+}
+
+#[tokio::test]
+async fn test_events_for_retries_and_spent_budget() {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("GET"))
+        .and(path("/v1/balance"))
+        .respond_with(ResponseTemplate::new(503))
+        .mount(&mock_server)
+        .await;
+
+    let client = PeerCat::with_config(
+        PeerCatConfig::new("pcat_test_xxx")
+            .with_base_url(mock_server.uri())
+            .with_max_retries(3)
+            .with_retry_budget(RetryBudget::new(0.0).with_min_retries(1)),
+    )
+    .unwrap();
+    let events = client.events();
+
+    // The second call is refused a retry too, but the circuit is already open
+    client.get_balance().await.unwrap_err();
+    client.get_balance().await.unwrap_err();
+
+    let started = ClientEvent::RequestStarted {
+        method: "GET".to_string(),
+        endpoint: "/v1/balance".to_string(),
+    };
+    assert_eq!(
+        collect_events(client, events).await,
+        [
+            started.clone(),
+            ClientEvent::Retried {
+                method: "GET".to_string(),
+                endpoint: "/v1/balance".to_string(),
+                attempt: 1,
+                delay: std::time::Duration::from_secs(1),
+            },
+            ClientEvent::CircuitOpened,
+            started,
+        ]
+    );
+}
+
+#[tokio::test]
+async fn test_events_for_rate_limits_and_credits() {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("POST"))
+        .and(path("/v1/generate"))
+        .respond_with(
+            ResponseTemplate::new(429)
+                .insert_header("Retry-After", "0")
+                .set_body_json(serde_json::json!({
+                    "error": {
+                        "type": "rate_limit_error",
+                        "code": "rate_limit_exceeded",
+                        "message": "Too many requests"
+                    }
+                })),
+        )
+        .up_to_n_times(1)
+        .mount(&mock_server)
+        .await;
+    Mock::given(method("POST"))
+        .and(path("/v1/generate"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "id": "gen_123",
+            "imageUrl": "https://cdn.peerc.at/images/gen_123.png",
+            "ipfsHash": "QmXyz123",
+            "model": "stable-diffusion-xl",
+            "mode": "production",
+            "usage": {
+                "creditsUsed": 0.28,
+                "balanceRemaining": 9.72
+            }
+        })))
+        .mount(&mock_server)
+        .await;
+
+    let client = PeerCat::with_config(
+        PeerCatConfig::new("pcat_test_xxx")
+            .with_base_url(mock_server.uri())
+            .with_max_retries(1),
+    )
+    .unwrap();
+    let events = client.events();
+    client
+        .generate(GenerateParams::new("A sunset"))
+        .await
+        .unwrap();
+
+    assert_eq!(
+        collect_events(client, events).await,
+        [
+            ClientEvent::RequestStarted {
+                method: "POST".to_string(),
+                endpoint: "/v1/generate".to_string(),
+            },
+            ClientEvent::RateLimited {
+                method: "POST".to_string(),
+                endpoint: "/v1/generate".to_string(),
+                retry_after: Some(0),
+            },
+            ClientEvent::Retried {
+                method: "POST".to_string(),
+                endpoint: "/v1/generate".to_string(),
+                attempt: 1,
+                delay: std::time::Duration::ZERO,
+            },
+            ClientEvent::CreditsSpent {
+                endpoint: "/v1/generate".to_string(),
+                credits: 0.28,
+            },
+        ]
+    );
+}
+
+#[tokio::test]
+async fn test_slow_request_event() {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("GET"))
+        .and(path("/v1/balance"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .set_body_json(balance_body())
+                .set_delay(std::time::Duration::from_millis(200)),
+        )
+        .up_to_n_times(1)
+        .mount(&mock_server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/v1/balance"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(balance_body()))
+        .mount(&mock_server)
+        .await;
+
+    let client = PeerCat::with_config(
+        PeerCatConfig::new("test_api_key")
+            .with_base_url(mock_server.uri())
+            .with_max_retries(0)
+            .with_slow_request_threshold(std::time::Duration::from_millis(100)),
+    )
+    .unwrap();
+    let events = client.events();
+    client.get_balance().await.unwrap();
+    client.get_balance().await.unwrap();
+
+    let slow: Vec<_> = collect_events(client, events)
+        .await
+        .into_iter()
+        .filter(|event| matches!(event, ClientEvent::SlowRequest { .. }))
+        .collect();
+    assert_eq!(slow.len(), 1, "Only the delayed attempt is slow");
+    let ClientEvent::SlowRequest {
+        method,
+        endpoint,
+        attempt,
+        duration,
+    } = &slow[0]
+    else {
+        unreachable!();
+    };
+    assert_eq!(method, "GET");
+    assert_eq!(endpoint, "/v1/balance");
+    assert_eq!(*attempt, 1);
+    assert!(*duration >= std::time::Duration::from_millis(200));
+}
+
+// ============ Timeout and Deadline Tests ============
+
+#[tokio::test]
+async fn test_attempt_timeout() {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("GET"))
+        .and(path("/v1/balance"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .set_body_json(balance_body())
+                .set_delay(std::time::Duration::from_secs(2)),
+        )
+        .mount(&mock_server)
+        .await;
+
+    let client = PeerCat::with_config(
+        PeerCatConfig::new("test_api_key")
+            .with_base_url(mock_server.uri())
+            .with_max_retries(0)
+            .with_attempt_timeout(std::time::Duration::from_millis(100)),
+    )
+    .unwrap();
+
+    let started = std::time::Instant::now();
+    let error = client.get_balance().await.unwrap_err();
+    assert!(matches!(error, PeerCatError::Timeout));
+    assert!(started.elapsed() < std::time::Duration::from_secs(1));
+}
+
+#[tokio::test]
+async fn test_overall_deadline_stops_retries() {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("GET"))
+        .and(path("/v1/balance"))
+        .respond_with(ResponseTemplate::new(503))
+        .expect(2)
+        .mount(&mock_server)
+        .await;
+
+    let client = PeerCat::with_config(
+        PeerCatConfig::new("test_api_key")
+            .with_base_url(mock_server.uri())
+            .with_max_retries(5)
+            .with_overall_deadline(std::time::Duration::from_millis(1500)),
+    )
+    .unwrap();
+
+    // The first retry starts after 1s; the second would start past the deadline
+    let started = std::time::Instant::now();
+    let error = client.get_balance().await.unwrap_err();
+    assert!(matches!(error, PeerCatError::Unknown { status: 503, .. }));
+    assert!(started.elapsed() < std::time::Duration::from_millis(1500));
+}
+
+#[tokio::test]
+async fn test_overall_deadline_cuts_attempt_short() {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("GET"))
+        .and(path("/v1/balance"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .set_body_json(balance_body())
+                .set_delay(std::time::Duration::from_secs(2)),
+        )
+        .mount(&mock_server)
+        .await;
+
+    let client = PeerCat::with_config(
+        PeerCatConfig::new("test_api_key")
+            .with_base_url(mock_server.uri())
+            .with_max_retries(3)
+            .with_overall_deadline(std::time::Duration::from_millis(300)),
+    )
+    .unwrap();
+
+    let started = std::time::Instant::now();
+    let error = client.get_balance().await.unwrap_err();
+    assert!(matches!(error, PeerCatError::Timeout));
+    assert!(started.elapsed() < std::time::Duration::from_secs(1));
+}
+
+// ============ Hedging Tests ============
+
+fn create_hedging_client(mock_server: &MockServer) -> PeerCat {
+    PeerCat::with_config(
+        PeerCatConfig::new("test_api_key")
+            .with_base_url(mock_server.uri())
+            .with_max_retries(0)
+            .with_hedging(std::time::Duration::from_millis(100)),
+    )
+    .unwrap()
+}
+
+#[tokio::test]
+async fn test_hedging_sends_duplicate_for_slow_get() {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("GET"))
+        .and(path("/v1/balance"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .set_body_json(balance_body())
+                .set_delay(std::time::Duration::from_secs(5)),
+        )
+        .up_to_n_times(1)
+        .mount(&mock_server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/v1/balance"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(balance_body()))
+        .mount(&mock_server)
+        .await;
+
+    let client = create_hedging_client(&mock_server);
+    let started = std::time::Instant::now();
+    client.get_balance().await.unwrap();
+
+    // The hedge answered long before the slow primary would have
+    assert!(started.elapsed() < std::time::Duration::from_secs(2));
+    assert_eq!(mock_server.received_requests().await.unwrap().len(), 2);
+}
+
+#[tokio::test]
+async fn test_hedging_skips_fast_get() {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("GET"))
+        .and(path("/v1/balance"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(balance_body()))
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+
+    create_hedging_client(&mock_server)
+        .get_balance()
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn test_hedging_waits_for_success_after_failed_hedge() {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("GET"))
+        .and(path("/v1/balance"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .set_body_json(balance_body())
+                .set_delay(std::time::Duration::from_millis(400)),
+        )
+        .up_to_n_times(1)
+        .mount(&mock_server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/v1/balance"))
+        .respond_with(ResponseTemplate::new(503))
+        .mount(&mock_server)
+        .await;
+
+    // The hedge fails fast, so the slower primary's success is used
+    create_hedging_client(&mock_server)
+        .get_balance()
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn test_hedging_ignores_rate_limited_hedge() {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("GET"))
+        .and(path("/v1/balance"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .set_body_json(balance_body())
+                .set_delay(std::time::Duration::from_millis(400)),
+        )
+        .up_to_n_times(1)
+        .mount(&mock_server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/v1/balance"))
+        .respond_with(ResponseTemplate::new(429).set_body_json(serde_json::json!({
+            "error": {
+                "type": "rate_limit_error",
+                "code": "rate_limit_exceeded",
+                "message": "Rate limited"
+            }
+        })))
+        .mount(&mock_server)
+        .await;
+
+    // Only a success wins, so the primary's answer is used over the fast 429
+    create_hedging_client(&mock_server)
+        .get_balance()
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn test_hedging_spends_retry_budget() {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("GET"))
+        .and(path("/v1/balance"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .set_body_json(balance_body())
+                .set_delay(std::time::Duration::from_millis(300)),
+        )
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+
+    let budget = RetryBudget::new(0.0).with_min_retries(0);
+    let client = PeerCat::with_config(
+        PeerCatConfig::new("test_api_key")
+            .with_base_url(mock_server.uri())
+            .with_max_retries(0)
+            .with_hedging(std::time::Duration::from_millis(100))
+            .with_retry_budget(budget.clone()),
+    )
+    .unwrap();
+
+    // With no budget left the slow request isn't hedged
+    client.get_balance().await.unwrap();
+    assert_eq!(budget.status().rejected, 1);
+}
+
+#[tokio::test]
+async fn test_hedging_never_duplicates_post() {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("POST"))
+        .and(path("/v1/generate"))
+        .respond_with(ResponseTemplate::new(500).set_delay(std::time::Duration::from_millis(300)))
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+
+    create_hedging_client(&mock_server)
+        .generate(GenerateParams::new("A beautiful sunset"))
+        .await
+        .unwrap_err();
+}
+
+// ============ Request Coalescing Tests ============
+
+async fn concurrent_balance_requests(client: PeerCat, tasks: usize) {
+    let handles: Vec<_> = (0..tasks)
+        .map(|_| {
+            let client = client.clone();
+            tokio::spawn(async move { client.get_balance().await })
+        })
+        .collect();
+    for handle in handles {
+        let balance = handle.await.unwrap().expect("Request should succeed");
+        assert_eq!(balance.credits, 10.0);
+    }
+}
+
+#[tokio::test]
+async fn test_concurrent_gets_share_one_request() {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("GET"))
+        .and(path("/v1/balance"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .set_body_json(balance_body())
+                .set_delay(std::time::Duration::from_millis(300)),
+        )
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+
+    concurrent_balance_requests(create_test_client(&mock_server), 50).await;
+}
+
+#[tokio::test]
+async fn test_request_coalescing_opt_out() {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("GET"))
+        .and(path("/v1/balance"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .set_body_json(balance_body())
+                .set_delay(std::time::Duration::from_millis(300)),
+        )
+        .expect(5)
+        .mount(&mock_server)
+        .await;
+
+    let client = PeerCat::with_config(
+        PeerCatConfig::new("test_api_key")
+            .with_base_url(mock_server.uri())
+            .with_max_retries(0)
+            .without_request_coalescing(),
+    )
+    .unwrap();
+    concurrent_balance_requests(client, 5).await;
+}
+
+#[tokio::test]
+async fn test_coalesced_failure_is_shared_with_waiters() {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("GET"))
+        .and(path("/v1/balance"))
+        .respond_with(
+            ResponseTemplate::new(503)
+                .set_body_json(serde_json::json!({
+                    "error": {
+                        "type": "api_error",
+                        "code": "service_unavailable",
+                        "message": "Service temporarily unavailable"
+                    }
+                }))
+                .set_delay(std::time::Duration::from_millis(300)),
+        )
+        .up_to_n_times(1)
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/v1/balance"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(balance_body()))
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+
+    let client = create_test_client(&mock_server);
+    let handles: Vec<_> = (0..5)
+        .map(|_| {
+            let client = client.clone();
+            tokio::spawn(async move { client.get_balance().await })
+        })
+        .collect();
+
+    // Waiters get a copy of the error rather than sending the request again
+    for handle in handles {
+        match handle.await.unwrap() {
+            Err(PeerCatError::Server { status, .. }) => assert_eq!(status, 503),
+            other => panic!("Expected shared Server error, got {:?}", other),
+        }
+    }
+
+    // The failed call is forgotten, so the next one starts afresh
+    let balance = client.get_balance().await.expect("Request should succeed");
+    assert_eq!(balance.credits, 10.0);
+}
+
+#[tokio::test]
+async fn test_coalesced_network_error_keeps_its_variant() {
+    // Accept connections, then close them without responding
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let base_url = format!("http://{}", listener.local_addr().unwrap());
+    tokio::spawn(async move {
+        while let Ok((socket, _)) = listener.accept().await {
+            tokio::time::sleep(std::time::Duration::from_millis(300)).await;
+            drop(socket);
+        }
+    });
+
+    let client = PeerCat::with_config(
+        PeerCatConfig::new("test_api_key")
+            .with_base_url(base_url)
+            .with_max_retries(0),
+    )
+    .unwrap();
+    let handles: Vec<_> = (0..5)
+        .map(|_| {
+            let client = client.clone();
+            tokio::spawn(async move { client.get_balance().await })
+        })
+        .collect();
+
+    // Every caller sees the same variant, whether it sent the request or joined it
+    for handle in handles {
+        match handle.await.unwrap() {
+            Err(PeerCatError::Network(_)) => {}
+            other => panic!("Expected shared Network error, got {:?}", other),
+        }
+    }
+}
+
+#[tokio::test]
+async fn test_coalescing_is_per_key() {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("GET"))
+        .and(path("/v1/balance"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .set_body_json(balance_body())
+                .set_delay(std::time::Duration::from_millis(300)),
+        )
+        .expect(2)
+        .mount(&mock_server)
+        .await;
+
+    let client = create_pool_client(&mock_server, KeyPool::new(["key_a", "key_b"]));
+    concurrent_balance_requests(client, 6).await;
 }
 
+// ============ Cancellation Tests ============
+
 #[tokio::test]
-async fn test_auth_error_not_retryable() {
+async fn test_dropped_request_leaves_client_usable() {
     let mock_server = MockServer::start().await;
 
     Mock::given(method("GET"))
         .and(path("/v1/balance"))
-        .respond_with(ResponseTemplate::new(401).set_body_json(serde_json::json!({
-            "error": {
-                "type": "authentication_error",
-                "code": "invalid_key",
-                "message": "Invalid"
-            }
-        })))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .set_body_json(balance_body())
+                .set_delay(std::time::Duration::from_secs(10)),
+        )
+        .up_to_n_times(1)
+        .mount(&mock_server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/v1/balance"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(balance_body()))
         .mount(&mock_server)
         .await;
 
     let client = create_test_client(&mock_server);
-    let error = client.get_balance().await.unwrap_err();
+    let dropped =
+        tokio::time::timeout(std::time::Duration::from_millis(100), client.get_balance()).await;
+    assert!(dropped.is_err(), "Slow request should be abandoned");
 
-    assert!(!error.is_retryable());
+    let balance = client
+        .get_balance()
+        .await
+        .expect("Client should still work");
+    assert_eq!(balance.credits, 10.0);
 }
 
 #[tokio::test]
-async fn test_insufficient_credits_not_retryable() {
+async fn test_dropped_coalesced_leader_hands_over_to_waiter() {
     let mock_server = MockServer::start().await;
 
-    Mock::given(method("POST"))
-        .and(path("/v1/generate"))
-        .respond_with(ResponseTemplate::new(402).set_body_json(serde_json::json!({
-            "error": {
-                "type": "insufficient_credits",
-                "code": "insufficient_balance",
-                "message": "Not enough"
-            }
-        })))
+    Mock::given(method("GET"))
+        .and(path("/v1/balance"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .set_body_json(balance_body())
+                .set_delay(std::time::Duration::from_millis(300)),
+        )
         .mount(&mock_server)
         .await;
 
     let client = create_test_client(&mock_server);
-    let error = client
-        .generate(GenerateParams::new("test"))
-        .await
-        .unwrap_err();
-
-    assert!(!error.is_retryable());
+    let leader = tokio::spawn({
+        let client = client.clone();
+        async move { client.get_balance().await }
+    });
+    tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+    let waiter = tokio::spawn({
+        let client = client.clone();
+        async move { client.get_balance().await }
+    });
+    tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+    leader.abort();
+
+    let balance = waiter.await.unwrap().expect("Waiter should take over");
+    assert_eq!(balance.credits, 10.0);
 }
 
 // ============ Edge Case Tests ============
@@ -567,3 +1571,569 @@ fn test_valid_api_key_succeeds() {
 
     assert!(result.is_ok());
 }
+
+#[test]
+fn test_builder_creates_client() {
+    let client = PeerCat::builder("pcat_test_xxx")
+        .base_url("https://api.peerc.at/")
+        .timeout(std::time::Duration::from_secs(30))
+        .overall_deadline(std::time::Duration::from_secs(90))
+        .max_retries(5)
+        .default_mode(GenerationMode::Demo)
+        .configure(|config| config.with_conditional_requests())
+        .build()
+        .expect("Configuration is valid");
+
+    assert_eq!(client.environment(), Some(KeyEnvironment::Test));
+}
+
+#[test]
+fn test_builder_reports_every_problem() {
+    let error = PeerCat::builder("")
+        .base_url("ftp://api.peerc.at")
+        .timeout(std::time::Duration::ZERO)
+        .max_retries(50)
+        .ipfs_gateway("not a url")
+        .build()
+        .unwrap_err();
+
+    let fields: Vec<_> = error
+        .problems()
+        .iter()
+        .map(|problem| problem.field)
+        .collect();
+    assert_eq!(
+        fields,
+        [
+            "api_key",
+            "base_url",
+            "ipfs_gateway",
+            "attempt_timeout",
+            "max_retries"
+        ]
+    );
+    assert_eq!(
+        error.to_string(),
+        "api_key: is required; base_url: must use http or https; \
+         ipfs_gateway: is not a valid URL; attempt_timeout: must be greater than zero; \
+         max_retries: must be at most 10"
+    );
+}
+
+#[test]
+fn test_builder_limits_are_not_enforced_by_with_config() {
+    let config = PeerCatConfig::new("pcat_live_xxx")
+        .with_max_retries(11)
+        .with_timeout(7200);
+
+    let error = config.validate().unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "timeout: must be at most one hour; max_retries: must be at most 10"
+    );
+    let error = PeerCatBuilder::from_config(config.clone())
+        .build()
+        .unwrap_err();
+    assert_eq!(error.problems().len(), 2);
+
+    assert!(PeerCat::with_config(config).is_ok());
+    assert!(PeerCatConfig::new("pcat_live_xxx").validate().is_ok());
+}
+
+// ============ Key Environment Tests ============
+
+#[test]
+fn test_client_reports_key_environment() {
+    let live = PeerCat::new("pcat_live_xxx").unwrap();
+    assert_eq!(live.environment(), Some(KeyEnvironment::Live));
+
+    let test = PeerCat::new("pcat_test_xxx").unwrap();
+    assert_eq!(test.environment(), Some(KeyEnvironment::Test));
+
+    let placeholder = PeerCat::new("test_api_key").unwrap();
+    assert_eq!(placeholder.environment(), None);
+}
+
+#[test]
+fn test_unknown_key_prefix_is_rejected() {
+    match PeerCat::new("pcat_prod_xxx") {
+        Err(PeerCatError::InvalidConfig(message)) => assert!(!message.contains("pcat_prod_xxx")),
+        Err(e) => panic!("Expected InvalidConfig error, got {:?}", e),
+        Ok(_) => panic!("Expected error for unknown key prefix"),
+    }
+}
+
+#[test]
+fn test_key_pool_mixing_environments_is_rejected() {
+    let pool = KeyPool::new(["pcat_live_a", "pcat_test_b"]);
+
+    match PeerCat::with_config(PeerCatConfig::from_key_pool(pool)) {
+        Err(PeerCatError::InvalidConfig(_)) => {}
+        Err(e) => panic!("Expected InvalidConfig error, got {:?}", e),
+        Ok(_) => panic!("Expected error for mixed key pool"),
+    }
+}
+
+#[tokio::test]
+async fn test_live_key_with_demo_default_emits_mismatch_event() {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("GET"))
+        .and(path("/v1/balance"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(balance_body()))
+        .mount(&mock_server)
+        .await;
+
+    let client = PeerCat::with_config(
+        PeerCatConfig::new("pcat_live_xxx")
+            .with_base_url(mock_server.uri())
+            .with_default_mode(GenerationMode::Demo)
+            .with_max_retries(0),
+    )
+    .unwrap();
+    let events = client.events();
+
+    client.get_balance().await.expect("Should succeed");
+    client.get_balance().await.expect("Should succeed");
+
+    let started = ClientEvent::RequestStarted {
+        method: "GET".to_string(),
+        endpoint: "/v1/balance".to_string(),
+    };
+    assert_eq!(
+        collect_events(client, events).await,
+        [
+            ClientEvent::EnvironmentMismatch {
+                reason: "live API key used with demo mode as the default".to_string(),
+            },
+            started.clone(),
+            started,
+        ]
+    );
+}
+
+#[tokio::test]
+async fn test_live_key_with_test_base_url_emits_mismatch_event() {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("GET"))
+        .and(path("/v1/balance"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(balance_body()))
+        .mount(&mock_server)
+        .await;
+
+    let addr = *mock_server.address();
+    let client = PeerCat::with_config(
+        PeerCatConfig::new("pcat_live_xxx")
+            .with_base_url(format!("http://api-sandbox.peerc.at:{}", addr.port()))
+            .with_resolve("api-sandbox.peerc.at", addr)
+            .with_max_retries(0),
+    )
+    .unwrap();
+    let events = client.events();
+
+    client.get_balance().await.expect("Should succeed");
+
+    assert_eq!(
+        collect_events(client, events).await[0],
+        ClientEvent::EnvironmentMismatch {
+            reason: "live API key used with a test base URL".to_string(),
+        }
+    );
+}
+
+#[tokio::test]
+async fn test_unprefixed_key_emits_mismatch_event() {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("GET"))
+        .and(path("/v1/balance"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(balance_body()))
+        .mount(&mock_server)
+        .await;
+
+    let client = create_test_client(&mock_server);
+    assert_eq!(client.environment(), None);
+    let events = client.events();
+
+    client.get_balance().await.expect("Should succeed");
+
+    assert_eq!(
+        collect_events(client, events).await[0],
+        ClientEvent::EnvironmentMismatch {
+            reason: "API key doesn't start with pcat_live_ or pcat_test_".to_string(),
+        }
+    );
+}
+
+#[tokio::test]
+async fn test_test_key_with_demo_default_emits_no_mismatch_event() {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("GET"))
+        .and(path("/v1/balance"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(balance_body()))
+        .mount(&mock_server)
+        .await;
+
+    let client = PeerCat::with_config(
+        PeerCatConfig::test("pcat_test_xxx")
+            .with_base_url(mock_server.uri())
+            .with_max_retries(0),
+    )
+    .unwrap();
+    let events = client.events();
+
+    client.get_balance().await.expect("Should succeed");
+
+    assert_eq!(
+        collect_events(client, events).await,
+        [ClientEvent::RequestStarted {
+            method: "GET".to_string(),
+            endpoint: "/v1/balance".to_string(),
+        }]
+    );
+}
+
+// ============ Budget Tests ============
+
+#[tokio::test]
+async fn test_budget_refuses_paid_generations_when_spent() {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("POST"))
+        .and(path("/v1/generate"))
+        .and(body_json(
+            serde_json::json!({ "prompt": "test", "mode": "demo" }),
+        ))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "id": "demo_123",
+            "imageUrl": "https://cdn.peerc.at/demo/placeholder.png",
+            "ipfsHash": null,
+            "model": "stable-diffusion-xl",
+            "mode": "demo",
+            "usage": { "creditsUsed": 0.0, "balanceRemaining": 9.5 }
+        })))
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+    Mock::given(method("POST"))
+        .and(path("/v1/generate"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "id": "gen_123",
+            "imageUrl": "https://cdn.peerc.at/images/gen_123.png",
+            "ipfsHash": "QmXyz123",
+            "model": "stable-diffusion-xl",
+            "mode": "production",
+            "usage": { "creditsUsed": 0.25, "balanceRemaining": 9.75 }
+        })))
+        .expect(3)
+        .mount(&mock_server)
+        .await;
+
+    let client = PeerCat::with_config(
+        PeerCatConfig::new("test_api_key")
+            .with_base_url(mock_server.uri())
+            .with_max_retries(0)
+            .with_budget(0.5),
+    )
+    .unwrap();
+
+    for _ in 0..2 {
+        client.generate(GenerateParams::new("test")).await.unwrap();
+    }
+    let error = client
+        .generate(GenerateParams::new("test"))
+        .await
+        .unwrap_err();
+    match error {
+        PeerCatError::BudgetExceeded { spent, limit } => {
+            assert_eq!(spent, 0.5);
+            assert_eq!(limit, 0.5);
+        }
+        e => panic!("Expected BudgetExceeded error, got {:?}", e),
+    }
+    assert!(!error.is_retryable());
+
+    // Demo generations are free, so they're still allowed
+    client
+        .generate(GenerateParams::new("test").with_demo_mode())
+        .await
+        .expect("Demo generation should succeed");
+
+    // Starting a new session lifts the block
+    client.reset_session_spend();
+    client.generate(GenerateParams::new("test")).await.unwrap();
+}
+
+// ============ Error Reporter Tests ============
+
+/// Keeps a description of every error it's given
+#[derive(Debug, Clone, Default)]
+struct RecordingReporter {
+    reports: std::sync::Arc<std::sync::Mutex<Vec<String>>>,
+}
+
+impl ErrorReporter for RecordingReporter {
+    fn report(&self, error: &PeerCatError, context: &ErrorContext<'_>) {
+        self.reports.lock().unwrap().push(format!(
+            "{} {} {:?}",
+            context.method,
+            context.endpoint,
+            error.code()
+        ));
+    }
+}
+
+#[tokio::test]
+async fn test_error_reporter_after_final_failure() {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("GET"))
+        .and(path("/v1/balance"))
+        .respond_with(
+            ResponseTemplate::new(429)
+                .insert_header("Retry-After", "0")
+                .set_body_json(serde_json::json!({
+                    "error": {
+                        "type": "rate_limit_error",
+                        "code": "rate_limit_exceeded",
+                        "message": "Too many requests"
+                    }
+                })),
+        )
+        .expect(3)
+        .mount(&mock_server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/v1/models"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "models": []
+        })))
+        .mount(&mock_server)
+        .await;
+
+    let reporter = RecordingReporter::default();
+    let client = PeerCat::with_config(
+        PeerCatConfig::new("test_api_key")
+            .with_base_url(mock_server.uri())
+            .with_max_retries(2)
+            .with_error_reporter(reporter.clone()),
+    )
+    .unwrap();
+
+    client.get_balance().await.unwrap_err();
+    client.get_models().await.unwrap();
+
+    // Reported once, after the retries ran out; successes aren't reported
+    assert_eq!(
+        *reporter.reports.lock().unwrap(),
+        ["GET /v1/balance Some(\"rate_limit_exceeded\")"]
+    );
+}
+
+#[cfg(feature = "sentry")]
+#[test]
+fn test_sentry_reporter() {
+    use peercat::SentryReporter;
+
+    let events = sentry_core::test::with_captured_events(|| {
+        tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap()
+            .block_on(async {
+                let mock_server = MockServer::start().await;
+                Mock::given(method("GET"))
+                    .and(path("/v1/history/use_123"))
+                    .respond_with(ResponseTemplate::new(404).set_body_json(serde_json::json!({
+                        "error": {
+                            "type": "not_found",
+                            "code": "not_found",
+                            "message": "No such item"
+                        }
+                    })))
+                    .mount(&mock_server)
+                    .await;
+
+                let client = PeerCat::with_config(
+                    PeerCatConfig::new("test_api_key")
+                        .with_base_url(mock_server.uri())
+                        .with_max_retries(0)
+                        .with_error_reporter(SentryReporter),
+                )
+                .unwrap();
+                client.get_history_item("use_123").await.unwrap_err();
+            });
+    });
+
+    assert_eq!(events.len(), 1);
+    let tags = &events[0].tags;
+    assert_eq!(tags["peercat.method"], "GET");
+    assert_eq!(tags["peercat.endpoint"], "/v1/history/use_123");
+    assert_eq!(tags["peercat.error_code"], "not_found");
+    assert_eq!(
+        events[0].exception.values[0].value.as_deref(),
+        Some("Not found: No such item")
+    );
+}
+
+// ============ Test Client Guardrail Tests ============
+
+#[test]
+fn test_test_client_rejects_live_key() {
+    match PeerCat::test("pcat_live_xxx") {
+        Err(PeerCatError::InvalidConfig(_)) => {}
+        Err(e) => panic!("Expected InvalidConfig error, got {:?}", e),
+        Ok(_) => panic!("Expected error for live key"),
+    }
+
+    assert!(PeerCat::test("pcat_test_xxx").is_ok());
+}
+
+#[tokio::test]
+async fn test_test_client_defaults_to_demo_mode() {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("POST"))
+        .and(path("/v1/generate"))
+        .and(body_json(serde_json::json!({
+            "prompt": "test",
+            "mode": "demo"
+        })))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "id": "demo_123",
+            "imageUrl": "https://cdn.peerc.at/demo/placeholder.png",
+            "ipfsHash": null,
+            "model": "stable-diffusion-xl",
+            "mode": "demo",
+            "usage": { "creditsUsed": 0.0, "balanceRemaining": 10.0 }
+        })))
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+
+    let client = PeerCat::with_config(
+        PeerCatConfig::test("pcat_test_xxx")
+            .with_base_url(mock_server.uri())
+            .with_max_retries(0),
+    )
+    .unwrap();
+
+    client
+        .generate(GenerateParams::new("test"))
+        .await
+        .expect("Generate should succeed");
+}
+
+#[tokio::test]
+async fn test_test_client_refuses_production_operations() {
+    let mock_server = MockServer::start().await;
+
+    let client = PeerCat::with_config(
+        PeerCatConfig::test("pcat_test_xxx")
+            .with_base_url(mock_server.uri())
+            .with_max_retries(0),
+    )
+    .unwrap();
+
+    let error = client.revoke_key("key_123").await.unwrap_err();
+    match error {
+        PeerCatError::OperationNotAllowed { operation } => assert_eq!(operation, "revoke_key"),
+        e => panic!("Expected OperationNotAllowed error, got {:?}", e),
+    }
+    assert!(matches!(
+        client.delete_generation("gen_123", true).await,
+        Err(PeerCatError::OperationNotAllowed { .. })
+    ));
+
+    // No request should have reached the server
+    assert!(mock_server.received_requests().await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn test_test_client_production_operations_override() {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("DELETE"))
+        .and(path("/v1/keys/key_123"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "success": true
+        })))
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+
+    let client = PeerCat::with_config(
+        PeerCatConfig::test("pcat_test_xxx")
+            .allow_production_operations()
+            .with_base_url(mock_server.uri())
+            .with_max_retries(0),
+    )
+    .unwrap();
+
+    client
+        .revoke_key("key_123")
+        .await
+        .expect("Revoke should be allowed after override");
+}
+
+// ============ Offline Demo Tests ============
+
+#[tokio::test]
+async fn test_offline_demo_generates_locally() {
+    let client = PeerCat::offline_demo();
+
+    let params = GenerateParams::new("A sunset")
+        .with_model("imagen-3")
+        .with_option("style", "vivid".into());
+    let first = client.generate(params.clone()).await.unwrap();
+    let second = client.generate(params).await.unwrap();
+    assert_eq!(first.id, second.id);
+    assert_eq!(first.seed, second.seed);
+    assert!(first.id.starts_with("demo_"));
+    assert_eq!(first.model, "imagen-3");
+    assert_eq!(first.mode, peercat::GenerationMode::Demo);
+    assert_eq!(first.usage.credits_used, 0.0);
+    assert_eq!(first.prompt.as_deref(), Some("A sunset"));
+    assert!(first.seed.is_some());
+
+    let other = client
+        .generate(GenerateParams::new("A sunrise"))
+        .await
+        .unwrap();
+    assert_ne!(other.id, first.id);
+
+    let seeded = client
+        .generate(GenerateParams::new("A sunset").with_seed(7))
+        .await
+        .unwrap();
+    assert_eq!(seeded.seed, Some(7));
+
+    let preset = client
+        .generate_from_preset("preset_123", GenerateParams::new("A red sneaker"))
+        .await
+        .unwrap();
+    assert_eq!(preset.mode, peercat::GenerationMode::Demo);
+}
+
+#[tokio::test]
+async fn test_offline_demo_refuses_network_access() {
+    let client = PeerCat::offline_demo();
+
+    match client.get_balance().await.unwrap_err() {
+        PeerCatError::OperationNotAllowed { operation } => {
+            assert_eq!(operation, "network access")
+        }
+        e => panic!("Expected OperationNotAllowed error, got {:?}", e),
+    }
+
+    let result = client
+        .generate(GenerateParams::new("A sunset"))
+        .await
+        .unwrap();
+    assert!(matches!(
+        client.download_image_stream(&result).await,
+        Err(PeerCatError::OperationNotAllowed { .. })
+    ));
+}
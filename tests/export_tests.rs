@@ -0,0 +1,273 @@
+//! Tests for history export (requires the `export` feature)
+
+#![cfg(feature = "export")]
+
+use peercat::export::Format;
+#[cfg(feature = "runtime-tokio")]
+use peercat::export::{ExportOptions, ImageManifest};
+use peercat::{HistoryParams, PeerCat, PeerCatConfig};
+use wiremock::matchers::{method, path, query_param};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+/// Helper to create a client configured for mock server
+fn create_test_client(mock_server: &MockServer) -> PeerCat {
+    PeerCat::with_config(
+        PeerCatConfig::new("test_api_key")
+            .with_base_url(mock_server.uri())
+            .with_max_retries(0),
+    )
+    .expect("Failed to create test client")
+}
+
+async fn mount_two_pages(mock_server: &MockServer) {
+    Mock::given(method("GET"))
+        .and(path("/v1/history"))
+        .and(query_param("offset", "0"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "items": [
+                {
+                    "id": "use_1",
+                    "endpoint": "/v1/generate",
+                    "model": "stable-diffusion-xl",
+                    "creditsUsed": 0.28,
+                    "requestId": "gen_1",
+                    "status": "completed",
+                    "createdAt": "2024-01-15T10:00:00Z",
+                    "completedAt": "2024-01-15T10:00:05Z"
+                }
+            ],
+            "pagination": { "total": 2, "limit": 1, "offset": 0, "hasMore": true }
+        })))
+        .expect(1)
+        .mount(mock_server)
+        .await;
+
+    Mock::given(method("GET"))
+        .and(path("/v1/history"))
+        .and(query_param("offset", "1"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "items": [
+                {
+                    "id": "use_2",
+                    "endpoint": "/v1/generate",
+                    "model": null,
+                    "creditsUsed": 1.5,
+                    "requestId": null,
+                    "status": "refunded",
+                    "createdAt": "2024-01-16T10:00:00Z",
+                    "completedAt": null
+                }
+            ],
+            "pagination": { "total": 2, "limit": 1, "offset": 1, "hasMore": false }
+        })))
+        .expect(1)
+        .mount(mock_server)
+        .await;
+}
+
+#[tokio::test]
+async fn test_export_history_csv() {
+    let mock_server = MockServer::start().await;
+    mount_two_pages(&mock_server).await;
+
+    let client = create_test_client(&mock_server);
+    let mut out = Vec::new();
+    let rows = client
+        .export_history(HistoryParams::new().with_limit(1), Format::Csv, &mut out)
+        .await
+        .expect("Export should succeed");
+
+    assert_eq!(rows, 2);
+    assert_eq!(
+        String::from_utf8(out).unwrap(),
+        "id,endpoint,model,credits_used,request_id,status,created_at,completed_at\n\
+         use_1,/v1/generate,stable-diffusion-xl,0.28,gen_1,completed,2024-01-15T10:00:00Z,2024-01-15T10:00:05Z\n\
+         use_2,/v1/generate,,1.5,,refunded,2024-01-16T10:00:00Z,\n"
+    );
+}
+
+#[tokio::test]
+async fn test_export_history_jsonl() {
+    let mock_server = MockServer::start().await;
+    mount_two_pages(&mock_server).await;
+
+    let client = create_test_client(&mock_server);
+    let mut out = Vec::new();
+    let rows = client
+        .export_history(HistoryParams::new().with_limit(1), Format::Jsonl, &mut out)
+        .await
+        .expect("Export should succeed");
+
+    assert_eq!(rows, 2);
+    let lines: Vec<serde_json::Value> = String::from_utf8(out)
+        .unwrap()
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+    assert_eq!(lines.len(), 2);
+    assert_eq!(lines[0]["id"], "use_1");
+    assert_eq!(lines[1]["status"], "refunded");
+}
+
+#[cfg(feature = "runtime-tokio")]
+#[tokio::test]
+async fn test_export_images() {
+    let mock_server = MockServer::start().await;
+    let item = |id: &str, status: &str, image: Option<&str>| {
+        serde_json::json!({
+            "id": format!("use_{}", id),
+            "endpoint": "/v1/generate",
+            "model": "stable-diffusion-xl",
+            "creditsUsed": 0.28,
+            "requestId": format!("gen_{}", id),
+            "status": status,
+            "createdAt": "2024-01-15T10:00:00Z",
+            "completedAt": null,
+            "imageUrl": image.map(|image| format!("{}/images/{}", mock_server.uri(), image))
+        })
+    };
+
+    Mock::given(method("GET"))
+        .and(path("/v1/history"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "items": [
+                item("1", "completed", Some("gen_1.webp?v=2")),
+                item("2", "completed", Some("gen_2.png")),
+                item("3", "completed", Some("gen_3.png")),
+                item("4", "refunded", None),
+            ],
+            "pagination": { "total": 4, "limit": 100, "offset": 0, "hasMore": false }
+        })))
+        .mount(&mock_server)
+        .await;
+
+    Mock::given(method("GET"))
+        .and(path("/images/gen_1.webp"))
+        .respond_with(ResponseTemplate::new(200).set_body_bytes(b"image one".to_vec()))
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+
+    // Fails once, then succeeds on retry
+    Mock::given(method("GET"))
+        .and(path("/images/gen_2.png"))
+        .respond_with(ResponseTemplate::new(503))
+        .up_to_n_times(1)
+        .mount(&mock_server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/images/gen_2.png"))
+        .respond_with(ResponseTemplate::new(200).set_body_bytes(b"image two".to_vec()))
+        .mount(&mock_server)
+        .await;
+
+    Mock::given(method("GET"))
+        .and(path("/images/gen_3.png"))
+        .respond_with(ResponseTemplate::new(404))
+        .mount(&mock_server)
+        .await;
+
+    let dir = std::env::temp_dir().join(format!("peercat-export-{}", std::process::id()));
+    let client = create_test_client(&mock_server);
+    let options = ExportOptions::new().with_max_retries(1);
+    let manifest = client
+        .export_images(HistoryParams::new(), &dir, options.clone())
+        .await
+        .expect("Export should succeed");
+
+    assert_eq!(manifest.images.len(), 2);
+    assert_eq!(manifest.images["gen_1"], "gen_1.webp");
+    assert_eq!(manifest.images["gen_2"], "gen_2.png");
+    assert!(manifest.failed.contains_key("gen_3"));
+    assert_eq!(std::fs::read(dir.join("gen_1.webp")).unwrap(), b"image one");
+    assert_eq!(std::fs::read(dir.join("gen_2.png")).unwrap(), b"image two");
+    assert!(!dir.join("gen_3.png").exists());
+
+    let written: ImageManifest =
+        serde_json::from_slice(&std::fs::read(dir.join("manifest.json")).unwrap()).unwrap();
+    assert_eq!(written.images, manifest.images);
+
+    // Running again keeps existing files instead of downloading them
+    let rerun = client
+        .export_images(HistoryParams::new(), &dir, options)
+        .await
+        .expect("Export should succeed");
+    assert_eq!(rerun.images, manifest.images);
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[cfg(feature = "runtime-tokio")]
+#[tokio::test]
+async fn test_export_images_keeps_colliding_ids_apart() {
+    let mock_server = MockServer::start().await;
+    // IDs that would share a file if sanitized, or on a case-insensitive file system
+    let ids = ["gen_1", "gen/1", "gen:1", "GEN_1", "manifest", "gen_1"];
+    let items: Vec<_> = ids
+        .iter()
+        .enumerate()
+        .map(|(i, id)| {
+            let extension = if *id == "manifest" { "json" } else { "png" };
+            serde_json::json!({
+                "id": format!("use_{}", i),
+                "endpoint": "/v1/generate",
+                "model": "stable-diffusion-xl",
+                "creditsUsed": 0.28,
+                "requestId": id,
+                "status": "completed",
+                "createdAt": "2024-01-15T10:00:00Z",
+                "completedAt": null,
+                "imageUrl": format!("{}/images/{}.{}", mock_server.uri(), i, extension)
+            })
+        })
+        .collect();
+
+    Mock::given(method("GET"))
+        .and(path("/v1/history"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "items": items,
+            "pagination": { "total": 6, "limit": 100, "offset": 0, "hasMore": false }
+        })))
+        .mount(&mock_server)
+        .await;
+    for i in 0..4 {
+        Mock::given(method("GET"))
+            .and(path(format!("/images/{}.png", i)))
+            .respond_with(ResponseTemplate::new(200).set_body_bytes(format!("image {}", i)))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+    }
+
+    let dir = std::env::temp_dir().join(format!("peercat-export-ids-{}", std::process::id()));
+    let manifest = create_test_client(&mock_server)
+        .export_images(HistoryParams::new(), &dir, ExportOptions::new())
+        .await
+        .expect("Export should succeed");
+
+    // Escaped IDs never overwrite one another
+    assert_eq!(manifest.images.len(), 4);
+    assert_eq!(manifest.images["gen_1"], "gen_1.png");
+    assert_eq!(manifest.images["gen/1"], "gen%2f1.png");
+    assert_eq!(manifest.images["gen:1"], "gen%3a1.png");
+    assert_eq!(manifest.images["GEN_1"], "%47%45%4e_1.png");
+    for (i, id) in ids.iter().enumerate().take(4) {
+        let file = dir.join(&manifest.images[*id]);
+        assert_eq!(
+            std::fs::read(file).unwrap(),
+            format!("image {}", i).as_bytes()
+        );
+    }
+
+    // A name that's already taken isn't downloaded over
+    assert_eq!(
+        manifest.failed["manifest"],
+        "manifest.json is already used by another image"
+    );
+    assert_eq!(
+        manifest.failed["gen_1"],
+        "gen_1.png is already used by another image"
+    );
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
@@ -0,0 +1,64 @@
+//! Tests for the response fixtures (requires the `test-util` feature)
+
+#![cfg(feature = "test-util")]
+
+use peercat::{
+    ApiKey, Balance, GenerateResult, GenerationMetadata, HistoryItem, Model,
+    OnChainGenerationStatus, OnChainStatus, PromptSubmission, WithdrawalResult,
+};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+/// Check that a fixture survives a trip through the wire format unchanged
+fn assert_round_trips<T: Serialize + DeserializeOwned>(fixture: T) {
+    let json = serde_json::to_value(&fixture).unwrap();
+    let parsed: T = serde_json::from_value(json.clone()).unwrap();
+    assert_eq!(serde_json::to_value(parsed).unwrap(), json);
+}
+
+#[test]
+fn test_fixtures_round_trip() {
+    assert_round_trips(GenerateResult::fixture());
+    assert_round_trips(GenerationMetadata::fixture());
+    assert_round_trips(Model::fixture());
+    assert_round_trips(Balance::fixture());
+    assert_round_trips(HistoryItem::fixture());
+    assert_round_trips(WithdrawalResult::fixture());
+    assert_round_trips(ApiKey::fixture());
+    assert_round_trips(PromptSubmission::fixture());
+    assert_round_trips(OnChainGenerationStatus::fixture_processing());
+    assert_round_trips(OnChainGenerationStatus::fixture_completed());
+    assert_round_trips(OnChainGenerationStatus::fixture_failed());
+}
+
+#[test]
+fn test_fixtures_are_consistent() {
+    let result = GenerateResult::fixture();
+    assert_eq!(GenerationMetadata::fixture().id, result.id);
+    assert_eq!(
+        HistoryItem::fixture().request_id.as_deref(),
+        Some(result.id.as_str())
+    );
+    assert_eq!(Model::fixture().price_usd, result.usage.credits_used);
+    assert!(result.reproduce_params().is_some());
+
+    let completed = OnChainGenerationStatus::fixture_completed();
+    assert_eq!(completed.status, OnChainStatus::Completed);
+    assert!(completed.image_url.is_some());
+    let failed = OnChainGenerationStatus::fixture_failed();
+    assert!(failed.status.is_terminal());
+    assert!(failed.error.is_some());
+    assert!(!OnChainGenerationStatus::fixture_processing()
+        .status
+        .is_terminal());
+}
+
+#[test]
+fn test_fixture_struct_update() {
+    let balance = Balance {
+        credits: 0.5,
+        ..Balance::fixture()
+    };
+    assert_eq!(balance.credits, 0.5);
+    assert_eq!(balance.total_generated, 1);
+}
@@ -1,17 +1,21 @@
 //! Integration tests for the PeerCat Rust SDK
 
 use peercat::{
-    CreateKeyParams, GenerateParams, HistoryParams, OnChainStatus, PeerCat, PeerCatConfig,
-    PeerCatError, SubmitPromptParams,
+    AspectRatio, CancellationToken, ControlMode, ControlNetParams, CreateCollectionParams,
+    CreateKeyParams, DepositState, GenerateParams, GenerateResult, HistoryParams, HistoryStatus,
+    HttpVersion, KeyScope, KnownModel, OnChainEventKind, OnChainStatus, PeerCat, PeerCatConfig,
+    PeerCatError, PollOptions, PriceGranularity, Resolution, SafetyLevel, Sampler, SessionSpend,
+    ShareOptions, SubmissionFilter, SubmissionStatus, SubmitPromptParams, ThumbSize, UsageGroupBy,
+    UsageSummaryParams, WarmupOptions, WithdrawParams, WithdrawalStatus,
 };
-use wiremock::matchers::{header, method, path, query_param};
+use wiremock::matchers::{body_json, header, header_exists, method, path, query_param};
 use wiremock::{Mock, MockServer, ResponseTemplate};
 
 /// Helper to create a client configured for mock server
 fn create_test_client(mock_server: &MockServer) -> PeerCat {
     PeerCat::with_config(
         PeerCatConfig::new("test_api_key")
-            .with_base_url(&mock_server.uri())
+            .with_base_url(mock_server.uri())
             .with_max_retries(0),
     )
     .expect("Failed to create test client")
@@ -52,6 +56,62 @@ async fn test_generate_success() {
     assert_eq!(result.ipfs_hash, Some("QmXyz123".to_string()));
     assert_eq!(result.model, "stable-diffusion-xl");
     assert_eq!(result.usage.credits_used, 0.28);
+    assert!(!result.private);
+}
+
+#[tokio::test]
+async fn test_session_spend() {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("POST"))
+        .and(path("/v1/generate"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "id": "gen_123",
+            "imageUrl": "https://cdn.peerc.at/images/gen_123.png",
+            "ipfsHash": "QmXyz123",
+            "model": "stable-diffusion-xl",
+            "mode": "production",
+            "usage": {
+                "creditsUsed": 0.25,
+                "balanceRemaining": 9.75
+            }
+        })))
+        .up_to_n_times(2)
+        .mount(&mock_server)
+        .await;
+    Mock::given(method("POST"))
+        .and(path("/v1/generate"))
+        .respond_with(ResponseTemplate::new(402).set_body_json(serde_json::json!({
+            "error": {
+                "type": "insufficient_credits",
+                "code": "insufficient_credits",
+                "message": "Not enough credits"
+            }
+        })))
+        .mount(&mock_server)
+        .await;
+
+    let client = create_test_client(&mock_server);
+    assert_eq!(client.session_spend(), SessionSpend::default());
+
+    // Clones share the total, and failed generations aren't counted
+    client.generate(GenerateParams::new("One")).await.unwrap();
+    client
+        .clone()
+        .generate(GenerateParams::new("Two"))
+        .await
+        .unwrap();
+    client
+        .generate(GenerateParams::new("Three"))
+        .await
+        .unwrap_err();
+
+    let spend = client.session_spend();
+    assert_eq!(spend.credits, 0.5);
+    assert_eq!(spend.generations, 2);
+
+    assert_eq!(client.reset_session_spend(), spend);
+    assert_eq!(client.session_spend(), SessionSpend::default());
 }
 
 #[tokio::test]
@@ -113,220 +173,1782 @@ async fn test_generate_with_model() {
     assert_eq!(result.model, "imagen-3");
 }
 
-// ============ Get Models Tests ============
-
 #[tokio::test]
-async fn test_get_models() {
+async fn test_generate_with_safety_level() {
     let mock_server = MockServer::start().await;
 
-    Mock::given(method("GET"))
-        .and(path("/v1/models"))
+    Mock::given(method("POST"))
+        .and(path("/v1/generate"))
+        .and(body_json(serde_json::json!({
+            "prompt": "Test",
+            "safetyLevel": "relaxed"
+        })))
         .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
-            "models": [
-                {
-                    "id": "stable-diffusion-xl",
-                    "name": "Stable Diffusion XL",
-                    "description": "High quality image generation",
-                    "provider": "stability",
-                    "maxPromptLength": 2000,
-                    "outputFormat": "png",
-                    "outputResolution": "1024x1024",
-                    "priceUsd": 0.28
-                },
-                {
-                    "id": "imagen-3",
-                    "name": "Imagen 3",
-                    "description": "Google's latest model",
-                    "provider": "google",
-                    "maxPromptLength": 1500,
-                    "outputFormat": "png",
-                    "outputResolution": "1024x1024",
-                    "priceUsd": 1.50
-                }
-            ]
+            "id": "gen_789",
+            "imageUrl": "https://cdn.peerc.at/images/gen_789.png",
+            "ipfsHash": null,
+            "model": "stable-diffusion-xl",
+            "mode": "production",
+            "usage": {
+                "creditsUsed": 0.28,
+                "balanceRemaining": 9.72
+            },
+            "safetyLevel": "relaxed"
         })))
         .mount(&mock_server)
         .await;
 
     let client = create_test_client(&mock_server);
-    let models = client.get_models().await.expect("Get models should succeed");
+    let result = client
+        .generate(GenerateParams::new("Test").with_safety_level(SafetyLevel::Relaxed))
+        .await
+        .expect("Generate should succeed");
 
-    assert_eq!(models.len(), 2);
-    assert_eq!(models[0].id, "stable-diffusion-xl");
-    assert_eq!(models[0].price_usd, 0.28);
-    assert_eq!(models[1].id, "imagen-3");
+    assert_eq!(result.safety_level, Some(SafetyLevel::Relaxed));
 }
 
-// ============ Get Prices Tests ============
-
 #[tokio::test]
-async fn test_get_prices() {
+async fn test_generate_with_loras_sends_adapters() {
     let mock_server = MockServer::start().await;
 
-    Mock::given(method("GET"))
-        .and(path("/v1/price"))
-        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
-            "solPrice": 185.50,
-            "slippageTolerance": 0.05,
-            "updatedAt": "2024-01-15T12:00:00Z",
-            "treasury": "9JKi6Tr7JdsTJw1zNedF5vML9GpPnjHD9DWuZq1oE6nV",
-            "models": [
-                {
-                    "model": "stable-diffusion-xl",
-                    "priceUsd": 0.28,
-                    "priceSol": 0.00151,
-                    "priceSolWithSlippage": 0.00159
-                }
+    Mock::given(method("POST"))
+        .and(path("/v1/generate"))
+        .and(body_json(serde_json::json!({
+            "prompt": "A castle",
+            "adapters": [
+                {"id": "lora_pixel", "weight": 0.8},
+                {"id": "lora_neon", "weight": 0.3}
             ]
         })))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "id": "gen_lora",
+            "imageUrl": "https://cdn.peerc.at/images/gen_lora.png",
+            "ipfsHash": null,
+            "model": "stable-diffusion-xl",
+            "mode": "production",
+            "usage": {
+                "creditsUsed": 0.28,
+                "balanceRemaining": 9.72
+            }
+        })))
         .mount(&mock_server)
         .await;
 
     let client = create_test_client(&mock_server);
-    let prices = client.get_prices().await.expect("Get prices should succeed");
+    let params = GenerateParams::new("A castle")
+        .with_lora("lora_pixel", 0.8)
+        .with_lora("lora_neon", 0.3);
 
-    assert_eq!(prices.sol_price, 185.50);
-    assert_eq!(prices.slippage_tolerance, 0.05);
-    assert_eq!(prices.treasury, "9JKi6Tr7JdsTJw1zNedF5vML9GpPnjHD9DWuZq1oE6nV");
-    assert_eq!(prices.models.len(), 1);
-    assert_eq!(prices.models[0].model, "stable-diffusion-xl");
+    client
+        .generate(params)
+        .await
+        .expect("Generate with LoRAs should succeed");
 }
 
-// ============ Get Balance Tests ============
-
 #[tokio::test]
-async fn test_get_balance() {
+async fn test_generate_with_controlnet_url() {
     let mock_server = MockServer::start().await;
 
-    Mock::given(method("GET"))
-        .and(path("/v1/balance"))
+    Mock::given(method("POST"))
+        .and(path("/v1/generate"))
+        .and(body_json(serde_json::json!({
+            "prompt": "A dancer",
+            "controlNet": {
+                "imageUrl": "https://example.com/pose.png",
+                "mode": "pose",
+                "weight": 0.7
+            }
+        })))
         .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
-            "credits": 10.50,
-            "totalDeposited": 50.00,
-            "totalSpent": 39.50,
-            "totalWithdrawn": 0.00,
-            "totalGenerated": 100
+            "id": "gen_cn",
+            "imageUrl": "https://cdn.peerc.at/images/gen_cn.png",
+            "ipfsHash": null,
+            "model": "stable-diffusion-xl",
+            "mode": "production",
+            "usage": {
+                "creditsUsed": 0.28,
+                "balanceRemaining": 9.72
+            }
         })))
         .mount(&mock_server)
         .await;
 
     let client = create_test_client(&mock_server);
-    let balance = client
-        .get_balance()
-        .await
-        .expect("Get balance should succeed");
+    let params = GenerateParams::new("A dancer").with_controlnet(
+        ControlNetParams::new("https://example.com/pose.png", ControlMode::Pose).with_weight(0.7),
+    );
 
-    assert_eq!(balance.credits, 10.50);
-    assert_eq!(balance.total_deposited, 50.00);
-    assert_eq!(balance.total_spent, 39.50);
-    assert_eq!(balance.total_generated, 100);
+    client
+        .generate(params)
+        .await
+        .expect("Generate with ControlNet should succeed");
 }
 
-// ============ Get History Tests ============
-
 #[tokio::test]
-async fn test_get_history() {
+async fn test_generate_with_controlnet_bytes_uploads_first() {
     let mock_server = MockServer::start().await;
+    let png = b"\x89PNG\r\n\x1a\nfake".to_vec();
 
-    Mock::given(method("GET"))
-        .and(path("/v1/history"))
+    Mock::given(method("POST"))
+        .and(path("/v1/uploads"))
+        .and(body_json(serde_json::json!({
+            "data": "iVBORw0KGgpmYWtl",
+            "contentType": "image/png"
+        })))
         .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
-            "items": [
-                {
-                    "id": "use_123",
-                    "endpoint": "/v1/generate",
-                    "model": "stable-diffusion-xl",
-                    "creditsUsed": 0.28,
-                    "requestId": "gen_123",
-                    "status": "completed",
-                    "createdAt": "2024-01-15T10:00:00Z",
-                    "completedAt": "2024-01-15T10:00:05Z"
-                }
-            ],
-            "pagination": {
-                "total": 100,
-                "limit": 50,
-                "offset": 0,
-                "hasMore": true
+            "id": "upl_123",
+            "url": "https://cdn.peerc.at/uploads/upl_123.png"
+        })))
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+
+    Mock::given(method("POST"))
+        .and(path("/v1/generate"))
+        .and(body_json(serde_json::json!({
+            "prompt": "A house",
+            "controlNet": {
+                "imageUrl": "https://cdn.peerc.at/uploads/upl_123.png",
+                "mode": "canny",
+                "weight": 1.0
+            }
+        })))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "id": "gen_cn",
+            "imageUrl": "https://cdn.peerc.at/images/gen_cn.png",
+            "ipfsHash": null,
+            "model": "stable-diffusion-xl",
+            "mode": "production",
+            "usage": {
+                "creditsUsed": 0.28,
+                "balanceRemaining": 9.72
             }
         })))
         .mount(&mock_server)
         .await;
 
     let client = create_test_client(&mock_server);
-    let history = client
-        .get_history(HistoryParams::new())
-        .await
-        .expect("Get history should succeed");
+    let params = GenerateParams::new("A house")
+        .with_controlnet(ControlNetParams::new(png, ControlMode::Canny));
 
-    assert_eq!(history.items.len(), 1);
-    assert_eq!(history.items[0].id, "use_123");
-    assert_eq!(history.pagination.total, 100);
-    assert!(history.pagination.has_more);
+    client
+        .generate(params)
+        .await
+        .expect("Generate with an uploaded control image should succeed");
 }
 
 #[tokio::test]
-async fn test_get_history_with_pagination() {
+async fn test_generate_with_weighted_prompts() {
     let mock_server = MockServer::start().await;
 
-    Mock::given(method("GET"))
-        .and(path("/v1/history"))
-        .and(query_param("limit", "10"))
-        .and(query_param("offset", "20"))
+    Mock::given(method("POST"))
+        .and(path("/v1/generate"))
+        .and(body_json(serde_json::json!({
+            "prompt": "cyberpunk city, watercolor",
+            "prompts": [
+                {"text": "cyberpunk city", "weight": 0.7},
+                {"text": "watercolor", "weight": 0.3}
+            ]
+        })))
         .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
-            "items": [],
-            "pagination": {
-                "total": 100,
-                "limit": 10,
-                "offset": 20,
-                "hasMore": true
+            "id": "gen_blend",
+            "imageUrl": "https://cdn.peerc.at/images/gen_blend.png",
+            "ipfsHash": null,
+            "model": "stable-diffusion-xl",
+            "mode": "production",
+            "usage": {
+                "creditsUsed": 0.28,
+                "balanceRemaining": 9.72
             }
         })))
         .mount(&mock_server)
         .await;
 
     let client = create_test_client(&mock_server);
-    let history = client
-        .get_history(HistoryParams::new().with_limit(10).with_offset(20))
-        .await
-        .expect("Get history should succeed");
+    let params =
+        GenerateParams::new("").with_prompts([("cyberpunk city", 0.7), ("watercolor", 0.3)]);
 
-    assert_eq!(history.pagination.limit, 10);
-    assert_eq!(history.pagination.offset, 20);
+    client
+        .generate(params)
+        .await
+        .expect("Generate with weighted prompts should succeed");
 }
 
-// ============ API Key Tests ============
-
 #[tokio::test]
-async fn test_list_keys() {
+async fn test_generate_with_auto_translate() {
     let mock_server = MockServer::start().await;
 
-    Mock::given(method("GET"))
-        .and(path("/v1/keys"))
+    Mock::given(method("POST"))
+        .and(path("/v1/generate"))
+        .and(body_json(serde_json::json!({
+            "prompt": "夜の東京",
+            "autoTranslate": true,
+            "sourceLanguage": "ja"
+        })))
         .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
-            "keys": [
-                {
-                    "id": "key_123",
-                    "name": "Production Key",
-                    "keyPrefix": "pcat_live_xx",
-                    "environment": "live",
-                    "rateLimitTier": "standard",
-                    "createdAt": "2024-01-15T10:00:00Z",
-                    "lastUsedAt": "2024-01-15T12:00:00Z",
-                    "revoked": false
-                }
-            ]
+            "id": "gen_ja",
+            "imageUrl": "https://cdn.peerc.at/images/gen_ja.png",
+            "ipfsHash": null,
+            "model": "stable-diffusion-xl",
+            "mode": "production",
+            "usage": {
+                "creditsUsed": 0.28,
+                "balanceRemaining": 9.72
+            },
+            "translatedPrompt": "Tokyo at night"
         })))
         .mount(&mock_server)
         .await;
 
     let client = create_test_client(&mock_server);
-    let keys = client.list_keys().await.expect("List keys should succeed");
+    let params = GenerateParams::new("夜の東京")
+        .with_auto_translate(true)
+        .with_source_language("ja");
 
-    assert_eq!(keys.keys.len(), 1);
-    assert_eq!(keys.keys[0].id, "key_123");
-    assert_eq!(keys.keys[0].name, Some("Production Key".to_string()));
-    assert!(!keys.keys[0].revoked);
+    let result = client
+        .generate(params)
+        .await
+        .expect("Generate with translation should succeed");
+
+    assert_eq!(result.translated_prompt.as_deref(), Some("Tokyo at night"));
+    assert_eq!(result.prompt.as_deref(), Some("夜の東京"));
+}
+
+#[tokio::test]
+async fn test_generate_with_quality() {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("POST"))
+        .and(path("/v1/generate"))
+        .and(body_json(serde_json::json!({
+            "prompt": "Test",
+            "quality": 100
+        })))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "id": "gen_789",
+            "imageUrl": "https://cdn.peerc.at/images/gen_789.jpg",
+            "ipfsHash": null,
+            "model": "stable-diffusion-xl",
+            "mode": "production",
+            "usage": {
+                "creditsUsed": 0.28,
+                "balanceRemaining": 9.72
+            },
+            "expectedFileSize": 412000
+        })))
+        .mount(&mock_server)
+        .await;
+
+    let client = create_test_client(&mock_server);
+    let result = client
+        .generate(GenerateParams::new("Test").with_quality(250))
+        .await
+        .expect("Generate should succeed");
+
+    assert_eq!(result.expected_file_size, Some(412000));
+}
+
+#[tokio::test]
+async fn test_generate_reproduce_params() {
+    let mock_server = MockServer::start().await;
+    let response = serde_json::json!({
+        "id": "gen_123",
+        "imageUrl": "https://cdn.peerc.at/images/gen_123.png",
+        "ipfsHash": null,
+        "model": "stable-diffusion-xl",
+        "mode": "production",
+        "usage": {
+            "creditsUsed": 0.28,
+            "balanceRemaining": 9.72
+        },
+        "seed": 1234567
+    });
+
+    Mock::given(method("POST"))
+        .and(path("/v1/generate"))
+        .and(body_json(serde_json::json!({
+            "prompt": "A paper crane",
+            "options": { "style": "origami" }
+        })))
+        .respond_with(ResponseTemplate::new(200).set_body_json(&response))
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+
+    Mock::given(method("POST"))
+        .and(path("/v1/generate"))
+        .and(body_json(serde_json::json!({
+            "prompt": "A paper crane",
+            "model": "stable-diffusion-xl",
+            "mode": "production",
+            "options": { "style": "origami" },
+            "seed": 1234567
+        })))
+        .respond_with(ResponseTemplate::new(200).set_body_json(&response))
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+
+    let client = create_test_client(&mock_server);
+    let result = client
+        .generate(
+            GenerateParams::new("A paper crane").with_option("style", serde_json::json!("origami")),
+        )
+        .await
+        .expect("Generate should succeed");
+    assert_eq!(result.seed, Some(1234567));
+    assert_eq!(result.prompt.as_deref(), Some("A paper crane"));
+
+    let params = result
+        .reproduce_params()
+        .expect("Seed and prompt are known");
+    client
+        .generate(params)
+        .await
+        .expect("Regenerate should succeed");
+}
+
+#[tokio::test]
+async fn test_generate_reproduce_params_keeps_every_param() {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("POST"))
+        .and(path("/v1/generate"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "id": "gen_123",
+            "imageUrl": "https://cdn.peerc.at/images/gen_123.png",
+            "ipfsHash": null,
+            "model": "stable-diffusion-xl",
+            "mode": "production",
+            "usage": {
+                "creditsUsed": 0.28,
+                "balanceRemaining": 9.72
+            }
+        })))
+        .mount(&mock_server)
+        .await;
+
+    let client = create_test_client(&mock_server);
+    let params = GenerateParams::new("")
+        .with_prompts([("cyberpunk city", 0.7), ("watercolor", 0.3)])
+        .with_model("stable-diffusion-xl")
+        .with_production_mode()
+        .with_seed(7)
+        .with_quality(90)
+        .with_lora("watercolor", 0.8)
+        .with_controlnet(ControlNetParams::new(
+            "https://example.com/pose.png",
+            ControlMode::Pose,
+        ))
+        .with_auto_translate(true)
+        .with_source_language("ja");
+    let result = client
+        .generate(params.clone())
+        .await
+        .expect("Generate should succeed");
+
+    let reproduced = result
+        .reproduce_params()
+        .expect("Seed and prompt are known");
+    assert_eq!(
+        serde_json::to_value(&reproduced).unwrap(),
+        serde_json::to_value(&params).unwrap()
+    );
+}
+
+#[tokio::test]
+async fn test_generate_with_aspect_ratio() {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("GET"))
+        .and(path("/v1/models"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "models": [{
+                "id": "stable-diffusion-xl",
+                "name": "Stable Diffusion XL",
+                "description": "High quality image generation",
+                "provider": "Stability AI",
+                "maxPromptLength": 2000,
+                "outputFormat": "png",
+                "outputResolution": "1024x1024",
+                "priceUsd": 0.28,
+                "capabilities": {
+                    "supportedResolutions": ["1024x1024", "832x1216", "1216x832", "1344x768"]
+                }
+            }]
+        })))
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+
+    Mock::given(method("POST"))
+        .and(path("/v1/generate"))
+        .and(body_json(serde_json::json!({
+            "prompt": "A lighthouse",
+            "options": { "resolution": "832x1216" }
+        })))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "id": "gen_123",
+            "imageUrl": "https://cdn.peerc.at/images/gen_123.png",
+            "ipfsHash": null,
+            "model": "stable-diffusion-xl",
+            "mode": "production",
+            "usage": {
+                "creditsUsed": 0.28,
+                "balanceRemaining": 9.72
+            }
+        })))
+        .mount(&mock_server)
+        .await;
+
+    let client = create_test_client(&mock_server);
+    client
+        .generate(GenerateParams::new("A lighthouse").with_aspect_ratio(AspectRatio::Portrait))
+        .await
+        .expect("Generate should succeed");
+
+    // Served from the catalog cache filled by `generate`
+    let model = client
+        .models_catalog()
+        .find("stable-diffusion-xl")
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(
+        model.resolution_for(AspectRatio::Custom(16, 9)),
+        Some(Resolution::new(1344, 768))
+    );
+    assert_eq!(
+        model.resolution_for(AspectRatio::Square),
+        Some(Resolution::new(1024, 1024))
+    );
+}
+
+#[tokio::test]
+async fn test_generate_validates_option_ranges() {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("GET"))
+        .and(path("/v1/models"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "models": [{
+                "id": "stable-diffusion-xl",
+                "name": "Stable Diffusion XL",
+                "description": "High quality image generation",
+                "provider": "Stability AI",
+                "maxPromptLength": 2000,
+                "outputFormat": "png",
+                "outputResolution": "1024x1024",
+                "priceUsd": 0.28,
+                "capabilities": {
+                    "steps": { "min": 1, "max": 50 },
+                    "guidanceScale": { "min": 1.0, "max": 20.0 }
+                }
+            }]
+        })))
+        .mount(&mock_server)
+        .await;
+
+    let client = create_test_client(&mock_server);
+    let error = client
+        .generate(
+            GenerateParams::new("Test")
+                .with_steps(30)
+                .with_guidance_scale(35.0),
+        )
+        .await
+        .unwrap_err();
+
+    match &error {
+        PeerCatError::Validation { field, message } => {
+            assert_eq!(field, "guidanceScale");
+            assert!(message.contains("between 1 and 20"), "{}", message);
+        }
+        e => panic!("Expected Validation error, got {:?}", e),
+    }
+    assert_eq!(error.param(), Some("guidanceScale"));
+
+    // Nothing but the model lookup was sent
+    let requests = mock_server.received_requests().await.unwrap();
+    assert!(requests.iter().all(|r| r.url.path() == "/v1/models"));
+}
+
+#[tokio::test]
+async fn test_generate_with_sampler_uses_model_option_key() {
+    let mock_server = MockServer::start().await;
+    let response = serde_json::json!({
+        "id": "gen_123",
+        "imageUrl": "https://cdn.peerc.at/images/gen_123.png",
+        "ipfsHash": null,
+        "model": "stable-diffusion-xl",
+        "mode": "production",
+        "usage": {
+            "creditsUsed": 0.28,
+            "balanceRemaining": 9.72
+        }
+    });
+
+    Mock::given(method("POST"))
+        .and(path("/v1/generate"))
+        .and(body_json(serde_json::json!({
+            "prompt": "Test",
+            "options": { "scheduler": "dpmpp_2m" }
+        })))
+        .respond_with(ResponseTemplate::new(200).set_body_json(&response))
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+
+    Mock::given(method("POST"))
+        .and(path("/v1/generate"))
+        .and(body_json(serde_json::json!({
+            "prompt": "Test",
+            "model": "imagen-3",
+            "options": { "sampler": "euler_a" }
+        })))
+        .respond_with(ResponseTemplate::new(200).set_body_json(&response))
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+
+    let client = create_test_client(&mock_server);
+    client
+        .generate(GenerateParams::new("Test").with_sampler(Sampler::DpmPlusPlus2M))
+        .await
+        .expect("Generate should succeed");
+    client
+        .generate(
+            GenerateParams::new("Test")
+                .with_model(KnownModel::Imagen3)
+                .with_sampler(Sampler::EulerA),
+        )
+        .await
+        .expect("Generate should succeed");
+}
+
+// ============ Download Tests ============
+
+fn generate_result(image_url: &str) -> GenerateResult {
+    serde_json::from_value(serde_json::json!({
+        "id": "gen_123",
+        "imageUrl": image_url,
+        "ipfsHash": "QmXyz123",
+        "model": "stable-diffusion-xl",
+        "mode": "production",
+        "usage": {
+            "creditsUsed": 0.28,
+            "balanceRemaining": 9.72
+        }
+    }))
+    .unwrap()
+}
+
+#[tokio::test]
+async fn test_download_image_stream() {
+    let mock_server = MockServer::start().await;
+    let image: Vec<u8> = (0..=255).cycle().take(256 * 1024).collect();
+
+    Mock::given(method("GET"))
+        .and(path("/images/gen_123.png"))
+        .respond_with(ResponseTemplate::new(200).set_body_bytes(image.clone()))
+        .mount(&mock_server)
+        .await;
+
+    let client = create_test_client(&mock_server);
+    let result = generate_result(&format!("{}/images/gen_123.png", mock_server.uri()));
+
+    let mut stream = client.download_image_stream(&result).await.unwrap();
+    assert_eq!(stream.content_length(), Some(image.len() as u64));
+    let mut downloaded = Vec::new();
+    while let Some(chunk) = stream.next().await {
+        downloaded.extend_from_slice(&chunk.unwrap());
+    }
+    assert_eq!(downloaded, image);
+
+    #[cfg(feature = "runtime-tokio")]
+    {
+        let mut copied = Vec::new();
+        let written = client
+            .download_image_stream(&result)
+            .await
+            .unwrap()
+            .copy_to(&mut copied)
+            .await
+            .unwrap();
+        assert_eq!(written, image.len() as u64);
+        assert_eq!(copied, image);
+    }
+}
+
+#[tokio::test]
+async fn test_download_image_stream_cancelled() {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("GET"))
+        .and(path("/images/gen_123.png"))
+        .respond_with(ResponseTemplate::new(200).set_body_bytes(vec![0u8; 64 * 1024]))
+        .mount(&mock_server)
+        .await;
+
+    let client = create_test_client(&mock_server);
+    let result = generate_result(&format!("{}/images/gen_123.png", mock_server.uri()));
+    let token = CancellationToken::new();
+
+    let mut stream = client
+        .download_image_stream(&result)
+        .await
+        .unwrap()
+        .with_cancellation(token.clone());
+    token.cancel();
+
+    assert!(matches!(
+        stream.next().await,
+        Some(Err(PeerCatError::Cancelled))
+    ));
+    assert!(stream.next().await.is_none());
+}
+
+#[tokio::test]
+async fn test_download_thumbnail() {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("GET"))
+        .and(path("/images/gen_123.png"))
+        .and(query_param("width", "256"))
+        .respond_with(ResponseTemplate::new(200).set_body_bytes(b"thumbnail".to_vec()))
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+
+    let client = create_test_client(&mock_server);
+    let result = generate_result(&format!("{}/images/gen_123.png", mock_server.uri()));
+
+    let thumbnail = client
+        .download_thumbnail(&result, ThumbSize::Medium)
+        .await
+        .unwrap();
+    assert_eq!(&thumbnail[..], b"thumbnail");
+}
+
+#[tokio::test]
+async fn test_download_from_ipfs_gateway_with_cdn_fallback() {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("GET"))
+        .and(path("/ipfs/QmXyz123"))
+        .respond_with(ResponseTemplate::new(200).set_body_bytes(b"from ipfs".to_vec()))
+        .up_to_n_times(1)
+        .mount(&mock_server)
+        .await;
+
+    Mock::given(method("GET"))
+        .and(path("/ipfs/QmXyz123"))
+        .respond_with(ResponseTemplate::new(504))
+        .mount(&mock_server)
+        .await;
+
+    Mock::given(method("GET"))
+        .and(path("/images/gen_123.png"))
+        .respond_with(ResponseTemplate::new(200).set_body_bytes(b"from cdn".to_vec()))
+        .mount(&mock_server)
+        .await;
+
+    let client = PeerCat::with_config(
+        PeerCatConfig::new("test_api_key")
+            .with_base_url(mock_server.uri())
+            .with_max_retries(0)
+            .with_ipfs_gateway(format!("{}/", mock_server.uri())),
+    )
+    .unwrap();
+    let result = generate_result(&format!("{}/images/gen_123.png", mock_server.uri()));
+    assert_eq!(
+        client.ipfs_url(&result),
+        format!("{}/ipfs/QmXyz123", mock_server.uri())
+    );
+    assert_eq!(
+        result.ipfs_url().as_deref(),
+        Some("https://ipfs.io/ipfs/QmXyz123")
+    );
+
+    let mut stream = client.download_from_ipfs(&result).await.unwrap();
+    assert_eq!(&stream.next().await.unwrap().unwrap()[..], b"from ipfs");
+
+    // The gateway now fails, so the CDN copy is used
+    let mut stream = client.download_from_ipfs(&result).await.unwrap();
+    assert_eq!(&stream.next().await.unwrap().unwrap()[..], b"from cdn");
+}
+
+#[tokio::test]
+async fn test_get_ipfs_status() {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("GET"))
+        .and(path("/v1/ipfs/gen_123/status"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "hash": "QmXyz123",
+            "pinned": true,
+            "providers": 3,
+            "size": 1048576
+        })))
+        .mount(&mock_server)
+        .await;
+
+    let client = create_test_client(&mock_server);
+    let status = client.get_ipfs_status("gen_123").await.unwrap();
+
+    assert_eq!(status.hash, "QmXyz123");
+    assert!(status.pinned);
+    assert_eq!(status.providers, 3);
+    assert_eq!(status.size, Some(1048576));
+}
+
+#[tokio::test]
+async fn test_get_generation_metadata() {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("GET"))
+        .and(path("/v1/generations/gen_123/metadata"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "id": "gen_123",
+            "model": "stable-diffusion-xl",
+            "prompt": "A koi pond",
+            "seed": 4294967296u64,
+            "width": 1024,
+            "height": 768,
+            "inferenceMs": 2350,
+            "totalMs": 3100,
+            "safety": { "flagged": false },
+            "createdAt": "2024-01-15T10:30:00Z"
+        })))
+        .mount(&mock_server)
+        .await;
+
+    let client = create_test_client(&mock_server);
+    let metadata = client.get_generation_metadata("gen_123").await.unwrap();
+
+    assert_eq!(metadata.seed, 4294967296);
+    assert_eq!((metadata.width, metadata.height), (1024, 768));
+    assert_eq!(
+        metadata.inference_time(),
+        std::time::Duration::from_millis(2350)
+    );
+    assert!(!metadata.safety.flagged);
+    assert!(metadata.safety.categories.is_empty());
+}
+
+#[tokio::test]
+async fn test_create_signed_url() {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("POST"))
+        .and(path("/v1/generations/gen_123/signed-url"))
+        .and(body_json(serde_json::json!({ "ttlSeconds": 900 })))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "url": "https://cdn.peerc.at/images/gen_123.png?sig=abc&exp=1705312800",
+            "expiresAt": "2024-01-15T10:00:00Z"
+        })))
+        .mount(&mock_server)
+        .await;
+
+    let client = create_test_client(&mock_server);
+    let mut result = generate_result("https://cdn.peerc.at/images/gen_123.png");
+    result.private = true;
+
+    let signed = client
+        .create_signed_url(&result.id, std::time::Duration::from_secs(900))
+        .await
+        .unwrap();
+    assert!(signed.url.contains("sig=abc"));
+    assert!(signed.is_expired());
+}
+
+#[tokio::test]
+async fn test_download_image_not_found() {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("GET"))
+        .and(path("/images/missing.png"))
+        .respond_with(ResponseTemplate::new(404))
+        .mount(&mock_server)
+        .await;
+
+    let client = create_test_client(&mock_server);
+    let result = generate_result(&format!("{}/images/missing.png", mock_server.uri()));
+
+    let err = client.download_image_stream(&result).await.unwrap_err();
+    assert!(matches!(err, PeerCatError::NotFound { .. }));
+}
+
+#[tokio::test]
+async fn test_create_share_link() {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("POST"))
+        .and(path("/v1/generations/gen_123/share"))
+        .and(body_json(serde_json::json!({
+            "expiresInSeconds": 3600,
+            "allowDownload": true
+        })))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "id": "shr_1",
+            "url": "https://peerc.at/s/shr_1",
+            "expiresAt": "2024-01-15T11:00:00Z",
+            "allowDownload": true
+        })))
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+
+    let client = create_test_client(&mock_server);
+    let link = client
+        .create_share_link(
+            "gen_123",
+            ShareOptions {
+                expires_in: Some(std::time::Duration::from_secs(3600)),
+                allow_download: true,
+            },
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(link.url, "https://peerc.at/s/shr_1");
+    assert!(link.expires_at.is_some());
+}
+
+#[tokio::test]
+async fn test_delete_generation() {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("DELETE"))
+        .and(path("/v1/generations/gen_123"))
+        .and(query_param("unpinIpfs", "true"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "id": "gen_123",
+            "cdnRemoved": true,
+            "ipfsUnpinned": true,
+            "deletedAt": "2024-01-15T10:00:00Z"
+        })))
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+
+    let client = create_test_client(&mock_server);
+    let deletion = client.delete_generation("gen_123", true).await.unwrap();
+
+    assert_eq!(deletion.id, "gen_123");
+    assert!(deletion.cdn_removed);
+    assert!(deletion.ipfs_unpinned);
+}
+
+// ============ Collections Tests ============
+
+#[tokio::test]
+async fn test_collections() {
+    let mock_server = MockServer::start().await;
+    let collection = serde_json::json!({
+        "id": "col_1",
+        "name": "Landscapes",
+        "description": null,
+        "itemCount": 1,
+        "createdAt": "2024-01-15T10:00:00Z",
+        "updatedAt": "2024-01-15T10:05:00Z"
+    });
+    let item = serde_json::json!({
+        "generationId": "gen_123",
+        "imageUrl": "https://cdn.peerc.at/images/gen_123.png",
+        "model": "stable-diffusion-xl",
+        "addedAt": "2024-01-15T10:05:00Z"
+    });
+
+    Mock::given(method("POST"))
+        .and(path("/v1/collections"))
+        .and(body_json(serde_json::json!({ "name": "Landscapes" })))
+        .respond_with(ResponseTemplate::new(200).set_body_json(&collection))
+        .mount(&mock_server)
+        .await;
+    Mock::given(method("POST"))
+        .and(path("/v1/collections/col_1/items"))
+        .and(body_json(serde_json::json!({ "generationId": "gen_123" })))
+        .respond_with(ResponseTemplate::new(200).set_body_json(&item))
+        .mount(&mock_server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/v1/collections"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .set_body_json(serde_json::json!({ "collections": [&collection] })),
+        )
+        .mount(&mock_server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/v1/collections/col_1/items"))
+        .respond_with(
+            ResponseTemplate::new(200).set_body_json(serde_json::json!({ "items": [&item] })),
+        )
+        .mount(&mock_server)
+        .await;
+
+    let client = create_test_client(&mock_server);
+    let created = client
+        .create_collection(CreateCollectionParams::new("Landscapes"))
+        .await
+        .unwrap();
+    assert_eq!(created.id, "col_1");
+    assert!(created.description.is_none());
+
+    let added = client.add_to_collection("col_1", "gen_123").await.unwrap();
+    assert_eq!(added.generation_id, "gen_123");
+
+    let collections = client.list_collections().await.unwrap();
+    assert_eq!(collections.collections.len(), 1);
+    assert_eq!(collections.collections[0].item_count, 1);
+
+    let items = client.list_collection_items("col_1").await.unwrap();
+    assert_eq!(
+        items.items[0].image_url,
+        "https://cdn.peerc.at/images/gen_123.png"
+    );
+}
+
+// ============ Presets Tests ============
+
+#[tokio::test]
+async fn test_presets() {
+    let mock_server = MockServer::start().await;
+    let preset = serde_json::json!({
+        "id": "preset_1",
+        "name": "product-shot",
+        "params": { "prompt": "A product photo", "model": "stable-diffusion-xl", "seed": 7 },
+        "createdAt": "2024-01-15T10:00:00Z",
+        "updatedAt": "2024-01-15T10:00:00Z"
+    });
+
+    Mock::given(method("POST"))
+        .and(path("/v1/presets"))
+        .and(body_json(serde_json::json!({
+            "name": "product-shot",
+            "params": { "prompt": "A product photo", "model": "stable-diffusion-xl", "seed": 7 }
+        })))
+        .respond_with(ResponseTemplate::new(200).set_body_json(&preset))
+        .mount(&mock_server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/v1/presets"))
+        .respond_with(
+            ResponseTemplate::new(200).set_body_json(serde_json::json!({ "presets": [&preset] })),
+        )
+        .mount(&mock_server)
+        .await;
+    Mock::given(method("POST"))
+        .and(path("/v1/presets/preset_1/generate"))
+        .and(body_json(serde_json::json!({ "seed": 8 })))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "id": "gen_preset",
+            "imageUrl": "https://cdn.peerc.at/images/gen_preset.png",
+            "ipfsHash": null,
+            "model": "stable-diffusion-xl",
+            "mode": "production",
+            "usage": {
+                "creditsUsed": 0.28,
+                "balanceRemaining": 9.72
+            },
+            "prompt": "A product photo",
+            "seed": 8
+        })))
+        .mount(&mock_server)
+        .await;
+    Mock::given(method("DELETE"))
+        .and(path("/v1/presets/preset_1"))
+        .respond_with(
+            ResponseTemplate::new(200).set_body_json(serde_json::json!({ "success": true })),
+        )
+        .mount(&mock_server)
+        .await;
+
+    let client = create_test_client(&mock_server);
+    let params = GenerateParams::new("A product photo")
+        .with_model("stable-diffusion-xl")
+        .with_seed(7);
+    let created = client.create_preset("product-shot", params).await.unwrap();
+    assert_eq!(created.id, "preset_1");
+    assert_eq!(created.params["seed"], 7);
+
+    let presets = client.list_presets().await.unwrap();
+    assert_eq!(presets.presets[0].name, "product-shot");
+
+    let result = client
+        .generate_from_preset("preset_1", GenerateParams::new("").with_seed(8))
+        .await
+        .unwrap();
+    assert_eq!(result.seed, Some(8));
+    assert_eq!(result.prompt.as_deref(), Some("A product photo"));
+
+    client.delete_preset("preset_1").await.unwrap();
+}
+
+// ============ Get Models Tests ============
+
+#[tokio::test]
+async fn test_get_models() {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("GET"))
+        .and(path("/v1/models"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "models": [
+                {
+                    "id": "stable-diffusion-xl",
+                    "name": "Stable Diffusion XL",
+                    "description": "High quality image generation",
+                    "provider": "stability",
+                    "maxPromptLength": 2000,
+                    "outputFormat": "png",
+                    "outputResolution": "1024x1024",
+                    "priceUsd": 0.28
+                },
+                {
+                    "id": "imagen-3",
+                    "name": "Imagen 3",
+                    "description": "Google's latest model",
+                    "provider": "google",
+                    "maxPromptLength": 1500,
+                    "outputFormat": "png",
+                    "outputResolution": "1024x1024",
+                    "priceUsd": 1.50
+                }
+            ]
+        })))
+        .mount(&mock_server)
+        .await;
+
+    let client = create_test_client(&mock_server);
+    let models = client.get_models().await.expect("Get models should succeed");
+
+    assert_eq!(models.len(), 2);
+    assert_eq!(models[0].id, "stable-diffusion-xl");
+    assert_eq!(models[0].price_usd, 0.28);
+    assert_eq!(models[1].id, "imagen-3");
+}
+
+#[tokio::test]
+async fn test_models_catalog_caches_and_refreshes() {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("GET"))
+        .and(path("/v1/models"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "models": [
+                {
+                    "id": "stable-diffusion-xl",
+                    "name": "Stable Diffusion XL",
+                    "description": "High quality image generation",
+                    "provider": "stability",
+                    "maxPromptLength": 2000,
+                    "outputFormat": "png",
+                    "outputResolution": "1024x1024",
+                    "priceUsd": 0.28
+                },
+                {
+                    "id": "imagen-3",
+                    "name": "Imagen 3",
+                    "description": "Google's latest model",
+                    "provider": "google",
+                    "maxPromptLength": 1500,
+                    "outputFormat": "png",
+                    "outputResolution": "1024x1024",
+                    "priceUsd": 1.50
+                }
+            ]
+        })))
+        .expect(2)
+        .mount(&mock_server)
+        .await;
+
+    let client = create_test_client(&mock_server);
+    let catalog = client.models_catalog();
+
+    let model = catalog.find("imagen-3").await.unwrap().unwrap();
+    assert_eq!(model.provider, "google");
+    assert!(catalog.find("unknown").await.unwrap().is_none());
+
+    let cheapest = catalog.cheapest().await.unwrap().unwrap();
+    assert_eq!(cheapest.id, "stable-diffusion-xl");
+
+    // Catalogs from clones share the cache
+    let shared = client.clone().models_catalog();
+    let google = shared.by_provider("Google").await.unwrap();
+    assert_eq!(google.len(), 1);
+
+    let models = catalog.refresh().await.unwrap();
+    assert_eq!(models.len(), 2);
+}
+
+#[tokio::test]
+async fn test_get_model() {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("GET"))
+        .and(path("/v1/models/imagen-3"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "id": "imagen-3",
+            "name": "Imagen 3",
+            "description": "Google's latest model",
+            "provider": "google",
+            "maxPromptLength": 2000,
+            "outputFormat": "png",
+            "outputResolution": "1024x1024",
+            "priceUsd": 1.50
+        })))
+        .mount(&mock_server)
+        .await;
+
+    let client = create_test_client(&mock_server);
+    let model = client
+        .get_model("imagen-3")
+        .await
+        .expect("Get model should succeed");
+
+    assert_eq!(model.id, "imagen-3");
+    assert_eq!(model.provider, "google");
+}
+
+#[tokio::test]
+async fn test_get_model_not_found_sets_param() {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("GET"))
+        .and(path("/v1/models/no-such-model"))
+        .respond_with(ResponseTemplate::new(404).set_body_json(serde_json::json!({
+            "error": {
+                "type": "not_found",
+                "code": "model_not_found",
+                "message": "Model not found"
+            }
+        })))
+        .mount(&mock_server)
+        .await;
+
+    let client = create_test_client(&mock_server);
+    let error = client.get_model("no-such-model").await.unwrap_err();
+
+    assert!(matches!(error, PeerCatError::NotFound { .. }));
+    assert_eq!(error.param(), Some("model"));
+}
+
+#[tokio::test]
+async fn test_get_available_loras() {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("GET"))
+        .and(path("/v1/models/stable-diffusion-xl/loras"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "loras": [{
+                "id": "lora_pixel",
+                "name": "Pixel Art",
+                "description": null,
+                "triggerWords": ["pixel art", "8-bit"],
+                "defaultWeight": 0.8
+            }]
+        })))
+        .mount(&mock_server)
+        .await;
+
+    let client = create_test_client(&mock_server);
+    let loras = client
+        .get_available_loras("stable-diffusion-xl")
+        .await
+        .expect("Get LoRAs should succeed");
+
+    assert_eq!(loras.len(), 1);
+    assert_eq!(loras[0].id, "lora_pixel");
+    assert_eq!(loras[0].trigger_words, vec!["pixel art", "8-bit"]);
+    assert_eq!(loras[0].default_weight, 0.8);
+}
+
+// ============ Get Prices Tests ============
+
+#[tokio::test]
+async fn test_get_prices() {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("GET"))
+        .and(path("/v1/price"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "solPrice": 185.50,
+            "slippageTolerance": 0.05,
+            "updatedAt": "2024-01-15T12:00:00Z",
+            "treasury": "9JKi6Tr7JdsTJw1zNedF5vML9GpPnjHD9DWuZq1oE6nV",
+            "models": [
+                {
+                    "model": "stable-diffusion-xl",
+                    "priceUsd": 0.28,
+                    "priceSol": 0.00151,
+                    "priceSolWithSlippage": 0.00159
+                }
+            ]
+        })))
+        .mount(&mock_server)
+        .await;
+
+    let client = create_test_client(&mock_server);
+    let prices = client.get_prices().await.expect("Get prices should succeed");
+
+    assert_eq!(prices.sol_price, 185.50);
+    assert_eq!(prices.slippage_tolerance, 0.05);
+    assert_eq!(prices.treasury, "9JKi6Tr7JdsTJw1zNedF5vML9GpPnjHD9DWuZq1oE6nV");
+    assert_eq!(prices.models.len(), 1);
+    assert_eq!(prices.models[0].model, "stable-diffusion-xl");
+}
+
+#[tokio::test]
+async fn test_get_price_history() {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("GET"))
+        .and(path("/v1/price/history"))
+        .and(query_param("from", "2024-01-01T00:00:00Z"))
+        .and(query_param("to", "2024-01-03T00:00:00Z"))
+        .and(query_param("granularity", "day"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "granularity": "day",
+            "points": [
+                {
+                    "timestamp": "2024-01-01T00:00:00Z",
+                    "solPrice": 100.0,
+                    "models": [
+                        {
+                            "model": "stable-diffusion-xl",
+                            "priceUsd": 0.28,
+                            "priceSol": 0.0028,
+                            "priceSolWithSlippage": 0.00294
+                        }
+                    ]
+                },
+                {
+                    "timestamp": "2024-01-02T00:00:00Z",
+                    "solPrice": 110.0,
+                    "models": []
+                }
+            ]
+        })))
+        .mount(&mock_server)
+        .await;
+
+    let client = create_test_client(&mock_server);
+    let from = "2024-01-01T00:00:00Z".parse().unwrap();
+    let to = "2024-01-03T00:00:00Z".parse().unwrap();
+    let history = client
+        .get_price_history(from..to, PriceGranularity::Day)
+        .await
+        .expect("Get price history should succeed");
+
+    assert_eq!(history.granularity, PriceGranularity::Day);
+    assert_eq!(history.points.len(), 2);
+    assert_eq!(history.points[0].models[0].price_sol, 0.0028);
+
+    let paid_at = "2024-01-02T15:30:00Z".parse().unwrap();
+    assert_eq!(history.at(&paid_at).unwrap().sol_price, 110.0);
+    let before = "2023-12-31T00:00:00Z".parse().unwrap();
+    assert!(history.at(&before).is_none());
+}
+
+// ============ Get Balance Tests ============
+
+#[tokio::test]
+async fn test_get_balance() {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("GET"))
+        .and(path("/v1/balance"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "credits": 10.50,
+            "totalDeposited": 50.00,
+            "totalSpent": 39.50,
+            "totalWithdrawn": 0.00,
+            "totalGenerated": 100
+        })))
+        .mount(&mock_server)
+        .await;
+
+    let client = create_test_client(&mock_server);
+    let balance = client
+        .get_balance()
+        .await
+        .expect("Get balance should succeed");
+
+    assert_eq!(balance.credits, 10.50);
+    assert_eq!(balance.total_deposited, 50.00);
+    assert_eq!(balance.total_spent, 39.50);
+    assert_eq!(balance.total_generated, 100);
+}
+
+// ============ Deposit Tests ============
+
+#[tokio::test]
+async fn test_create_deposit() {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("POST"))
+        .and(path("/v1/deposits"))
+        .and(body_json(serde_json::json!({ "amountUsd": 25.0 })))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "depositId": "dep_123",
+            "paymentAddress": "TreasuryPDA123",
+            "requiredAmount": {
+                "sol": 0.25,
+                "lamports": 250000000,
+                "usd": 25.0
+            },
+            "memo": "PCAT:dep_123",
+            "expiresAt": "2024-01-15T10:10:00Z"
+        })))
+        .mount(&mock_server)
+        .await;
+
+    let client = create_test_client(&mock_server);
+    let deposit = client
+        .create_deposit(25.0)
+        .await
+        .expect("Create deposit should succeed");
+
+    assert_eq!(deposit.deposit_id, "dep_123");
+    assert_eq!(deposit.payment_address, "TreasuryPDA123");
+    assert_eq!(deposit.required_amount.lamports, 250000000);
+    assert_eq!(deposit.memo, "PCAT:dep_123");
+}
+
+#[tokio::test]
+async fn test_get_deposit_status() {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("GET"))
+        .and(path("/v1/deposits/tx_abc"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "txSignature": "tx_abc",
+            "status": "confirmed",
+            "amountSol": 0.25,
+            "creditsAdded": 25.0,
+            "createdAt": "2024-01-15T10:00:00Z",
+            "confirmedAt": "2024-01-15T10:00:30Z",
+            "error": null
+        })))
+        .mount(&mock_server)
+        .await;
+
+    let client = create_test_client(&mock_server);
+    let status = client
+        .get_deposit_status("tx_abc")
+        .await
+        .expect("Get deposit status should succeed");
+
+    assert_eq!(status.status, DepositState::Confirmed);
+    assert_eq!(status.credits_added, Some(25.0));
+}
+
+// ============ Withdrawal Tests ============
+
+#[tokio::test]
+async fn test_withdraw() {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("POST"))
+        .and(path("/v1/withdrawals"))
+        .and(body_json(serde_json::json!({
+            "amount": 10.0,
+            "destinationWallet": "Wallet123",
+            "message": "Withdraw",
+            "signature": "sig",
+            "publicKey": "Wallet123"
+        })))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "withdrawalId": "wd_123",
+            "status": "pending",
+            "amountUsd": 10.0,
+            "amountSol": null,
+            "destinationWallet": "Wallet123",
+            "txSignature": null,
+            "createdAt": "2024-01-15T10:00:00Z",
+            "completedAt": null,
+            "error": null
+        })))
+        .mount(&mock_server)
+        .await;
+
+    let client = create_test_client(&mock_server);
+    let result = client
+        .withdraw(WithdrawParams {
+            amount: 10.0,
+            destination_wallet: "Wallet123".to_string(),
+            message: "Withdraw".to_string(),
+            signature: "sig".to_string(),
+            public_key: "Wallet123".to_string(),
+        })
+        .await
+        .expect("Withdraw should succeed");
+
+    assert_eq!(result.withdrawal_id, "wd_123");
+    assert_eq!(result.status, WithdrawalStatus::Pending);
+    assert!(result.tx_signature.is_none());
+}
+
+#[tokio::test]
+async fn test_withdraw_is_not_retried() {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("POST"))
+        .and(path("/v1/withdrawals"))
+        .respond_with(ResponseTemplate::new(503))
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+
+    let client = PeerCat::with_config(
+        PeerCatConfig::new("test_api_key")
+            .with_base_url(mock_server.uri())
+            .with_max_retries(3),
+    )
+    .unwrap();
+    let result = client
+        .withdraw(WithdrawParams {
+            amount: 10.0,
+            destination_wallet: "Wallet123".to_string(),
+            message: "Withdraw".to_string(),
+            signature: "sig".to_string(),
+            public_key: "Wallet123".to_string(),
+        })
+        .await;
+
+    assert!(result.is_err(), "A failed withdrawal should not be resent");
+}
+
+#[tokio::test]
+async fn test_get_withdrawal() {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("GET"))
+        .and(path("/v1/withdrawals/wd_123"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "withdrawalId": "wd_123",
+            "status": "completed",
+            "amountUsd": 10.0,
+            "amountSol": 0.1,
+            "destinationWallet": "Wallet123",
+            "txSignature": "payout_tx",
+            "createdAt": "2024-01-15T10:00:00Z",
+            "completedAt": "2024-01-15T10:01:00Z"
+        })))
+        .mount(&mock_server)
+        .await;
+
+    let client = create_test_client(&mock_server);
+    let result = client
+        .get_withdrawal("wd_123")
+        .await
+        .expect("Get withdrawal should succeed");
+
+    assert_eq!(result.status, WithdrawalStatus::Completed);
+    assert_eq!(result.tx_signature, Some("payout_tx".to_string()));
+}
+
+// ============ Get History Tests ============
+
+#[tokio::test]
+async fn test_get_history() {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("GET"))
+        .and(path("/v1/history"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "items": [
+                {
+                    "id": "use_123",
+                    "endpoint": "/v1/generate",
+                    "model": "stable-diffusion-xl",
+                    "creditsUsed": 0.28,
+                    "requestId": "gen_123",
+                    "status": "completed",
+                    "createdAt": "2024-01-15T10:00:00Z",
+                    "completedAt": "2024-01-15T10:00:05Z"
+                }
+            ],
+            "pagination": {
+                "total": 100,
+                "limit": 50,
+                "offset": 0,
+                "hasMore": true
+            }
+        })))
+        .mount(&mock_server)
+        .await;
+
+    let client = create_test_client(&mock_server);
+    let history = client
+        .get_history(HistoryParams::new())
+        .await
+        .expect("Get history should succeed");
+
+    assert_eq!(history.items.len(), 1);
+    assert_eq!(history.items[0].id, "use_123");
+    assert_eq!(history.pagination.total, 100);
+    assert!(history.pagination.has_more);
+}
+
+#[tokio::test]
+async fn test_tag_generation_and_filter_history() {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("POST"))
+        .and(path("/v1/generations/gen_123/tags"))
+        .and(body_json(
+            serde_json::json!({ "tags": ["campaign:spring", "hero"] }),
+        ))
+        .respond_with(
+            ResponseTemplate::new(200).set_body_json(serde_json::json!({ "success": true })),
+        )
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+
+    Mock::given(method("GET"))
+        .and(path("/v1/history"))
+        .and(query_param("tags", "campaign:spring,hero"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "items": [
+                {
+                    "id": "use_123",
+                    "endpoint": "/v1/generate",
+                    "model": "stable-diffusion-xl",
+                    "creditsUsed": 0.28,
+                    "requestId": "gen_123",
+                    "status": "completed",
+                    "createdAt": "2024-01-15T10:00:00Z",
+                    "completedAt": "2024-01-15T10:00:05Z",
+                    "tags": ["campaign:spring", "hero"]
+                }
+            ],
+            "pagination": { "total": 1, "limit": 50, "offset": 0, "hasMore": false }
+        })))
+        .mount(&mock_server)
+        .await;
+
+    let client = create_test_client(&mock_server);
+    client
+        .tag_generation(
+            "gen_123",
+            vec!["campaign:spring".to_string(), "hero".to_string()],
+        )
+        .await
+        .expect("Tagging should succeed");
+
+    let history = client
+        .get_history(
+            HistoryParams::new()
+                .with_tag("campaign:spring")
+                .with_tag("hero"),
+        )
+        .await
+        .expect("Get history should succeed");
+    assert_eq!(history.items[0].tags, ["campaign:spring", "hero"]);
+}
+
+#[tokio::test]
+async fn test_get_history_with_pagination() {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("GET"))
+        .and(path("/v1/history"))
+        .and(query_param("limit", "10"))
+        .and(query_param("offset", "20"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "items": [],
+            "pagination": {
+                "total": 100,
+                "limit": 10,
+                "offset": 20,
+                "hasMore": true
+            }
+        })))
+        .mount(&mock_server)
+        .await;
+
+    let client = create_test_client(&mock_server);
+    let history = client
+        .get_history(HistoryParams::new().with_limit(10).with_offset(20))
+        .await
+        .expect("Get history should succeed");
+
+    assert_eq!(history.pagination.limit, 10);
+    assert_eq!(history.pagination.offset, 20);
+}
+
+#[tokio::test]
+async fn test_get_history_with_filters() {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("GET"))
+        .and(path("/v1/history"))
+        .and(query_param("status", "refunded"))
+        .and(query_param("model", "imagen-3"))
+        .and(query_param("from", "2024-01-01T00:00:00Z"))
+        .and(query_param("to", "2024-02-01T00:00:00Z"))
+        .and(query_param("endpoint", "/v1/generate"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "items": [],
+            "pagination": {
+                "total": 0,
+                "limit": 50,
+                "offset": 0,
+                "hasMore": false
+            }
+        })))
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+
+    let client = create_test_client(&mock_server);
+    let history = client
+        .get_history(
+            HistoryParams::new()
+                .with_status(HistoryStatus::Refunded)
+                .with_model("imagen-3")
+                .with_date_range("2024-01-01T00:00:00Z", "2024-02-01T00:00:00Z")
+                .with_endpoint("/v1/generate"),
+        )
+        .await
+        .expect("Get history should succeed");
+
+    assert!(history.items.is_empty());
+}
+
+#[tokio::test]
+async fn test_get_history_item() {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("GET"))
+        .and(path("/v1/history/use_123"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "id": "use_123",
+            "endpoint": "/v1/generate",
+            "model": "stable-diffusion-xl",
+            "creditsUsed": 0.28,
+            "requestId": "gen_123",
+            "status": "completed",
+            "createdAt": "2024-01-15T10:00:00Z",
+            "completedAt": "2024-01-15T10:00:05Z"
+        })))
+        .mount(&mock_server)
+        .await;
+
+    let client = create_test_client(&mock_server);
+    let item = client
+        .get_history_item("use_123")
+        .await
+        .expect("Get history item should succeed");
+
+    assert_eq!(item.id, "use_123");
+    assert_eq!(item.status, HistoryStatus::Completed);
+    assert_eq!(item.request_id, Some("gen_123".to_string()));
+}
+
+// ============ Usage Analytics Tests ============
+
+#[tokio::test]
+async fn test_get_usage_summary() {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("GET"))
+        .and(path("/v1/analytics/usage"))
+        .and(query_param("groupBy", "model"))
+        .and(query_param("from", "2024-01-01T00:00:00Z"))
+        .and(query_param("to", "2024-02-01T00:00:00Z"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "groupBy": "model",
+            "from": "2024-01-01T00:00:00Z",
+            "to": "2024-02-01T00:00:00Z",
+            "totalCreditsUsed": 4.3,
+            "buckets": [
+                { "key": "stable-diffusion-xl", "creditsUsed": 2.8, "requestCount": 10 },
+                { "key": "imagen-3", "creditsUsed": 1.5, "requestCount": 1 }
+            ]
+        })))
+        .mount(&mock_server)
+        .await;
+
+    let client = create_test_client(&mock_server);
+    let summary = client
+        .get_usage_summary(
+            UsageSummaryParams::new(UsageGroupBy::Model)
+                .with_date_range("2024-01-01T00:00:00Z", "2024-02-01T00:00:00Z"),
+        )
+        .await
+        .expect("Get usage summary should succeed");
+
+    assert_eq!(summary.group_by, UsageGroupBy::Model);
+    assert_eq!(summary.buckets.len(), 2);
+    assert_eq!(summary.buckets[0].key, "stable-diffusion-xl");
+    assert_eq!(summary.buckets[0].request_count, 10);
+    assert_eq!(summary.total_credits_used, 4.3);
+}
+
+// ============ API Key Tests ============
+
+#[tokio::test]
+async fn test_list_keys() {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("GET"))
+        .and(path("/v1/keys"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "keys": [
+                {
+                    "id": "key_123",
+                    "name": "Production Key",
+                    "keyPrefix": "pcat_live_xx",
+                    "environment": "live",
+                    "rateLimitTier": "standard",
+                    "createdAt": "2024-01-15T10:00:00Z",
+                    "lastUsedAt": "2024-01-15T12:00:00Z",
+                    "revoked": false
+                }
+            ]
+        })))
+        .mount(&mock_server)
+        .await;
+
+    let client = create_test_client(&mock_server);
+    let keys = client.list_keys().await.expect("List keys should succeed");
+
+    assert_eq!(keys.keys.len(), 1);
+    assert_eq!(keys.keys[0].id, "key_123");
+    assert_eq!(keys.keys[0].name, Some("Production Key".to_string()));
+    assert!(!keys.keys[0].revoked);
+}
+
+#[tokio::test]
+async fn test_get_key() {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("GET"))
+        .and(path("/v1/keys/key_123"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "id": "key_123",
+            "name": "Worker",
+            "keyPrefix": "pcat_live_xx",
+            "environment": "live",
+            "rateLimitTier": "pro",
+            "createdAt": "2024-01-15T10:00:00Z",
+            "lastUsedAt": "2024-01-15T12:00:00Z",
+            "revoked": false
+        })))
+        .mount(&mock_server)
+        .await;
+
+    let client = create_test_client(&mock_server);
+    let key = client.get_key("key_123").await.expect("Get key should succeed");
+
+    assert_eq!(key.id, "key_123");
+    assert_eq!(key.rate_limit_tier, "pro");
+    assert!(key.last_used_at.is_some());
 }
 
 #[tokio::test]
@@ -349,12 +1971,9 @@ async fn test_create_key() {
 
     let client = create_test_client(&mock_server);
     let result = client
-        .create_key(CreateKeyParams {
-            name: Some("New Key".to_string()),
-            message: "Create API key".to_string(),
-            signature: "sig123".to_string(),
-            public_key: "pubkey123".to_string(),
-        })
+        .create_key(
+            CreateKeyParams::new("Create API key", "sig123", "pubkey123").with_name("New Key"),
+        )
         .await
         .expect("Create key should succeed");
 
@@ -362,6 +1981,72 @@ async fn test_create_key() {
     assert_eq!(result.key, "pcat_live_abc123xyz789");
 }
 
+#[tokio::test]
+async fn test_create_scoped_key() {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("POST"))
+        .and(path("/v1/keys"))
+        .and(body_json(serde_json::json!({
+            "message": "Create API key",
+            "signature": "sig123",
+            "publicKey": "pubkey123",
+            "scopes": ["generate", "read"]
+        })))
+        .respond_with(ResponseTemplate::new(201).set_body_json(serde_json::json!({
+            "id": "key_789",
+            "key": "pcat_live_scoped",
+            "keyPrefix": "pcat_live_sco",
+            "name": null,
+            "environment": "live",
+            "createdAt": "2024-01-15T14:00:00Z",
+            "warning": "Store this key securely. It will not be shown again.",
+            "scopes": ["generate", "read"]
+        })))
+        .mount(&mock_server)
+        .await;
+
+    let client = create_test_client(&mock_server);
+    let result = client
+        .create_key(
+            CreateKeyParams::new("Create API key", "sig123", "pubkey123")
+                .with_scopes([KeyScope::Generate, KeyScope::Read]),
+        )
+        .await
+        .expect("Create key should succeed");
+
+    assert_eq!(result.scopes, vec![KeyScope::Generate, KeyScope::Read]);
+}
+
+#[tokio::test]
+async fn test_update_key_allowlist() {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("PATCH"))
+        .and(path("/v1/keys/key_123"))
+        .and(body_json(serde_json::json!({
+            "ipAllowlist": ["203.0.113.0/24", "2001:db8::/32"]
+        })))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "success": true
+        })))
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+
+    let client = create_test_client(&mock_server);
+    client
+        .update_key_allowlist(
+            "key_123",
+            vec![
+                "203.0.113.0/24".parse().unwrap(),
+                "2001:db8::/32".parse().unwrap(),
+            ],
+        )
+        .await
+        .expect("Update allowlist should succeed");
+}
+
 #[tokio::test]
 async fn test_revoke_key() {
     let mock_server = MockServer::start().await;
@@ -384,28 +2069,259 @@ async fn test_revoke_key() {
 async fn test_update_key_name() {
     let mock_server = MockServer::start().await;
 
-    Mock::given(method("PATCH"))
-        .and(path("/v1/keys/key_123"))
+    Mock::given(method("PATCH"))
+        .and(path("/v1/keys/key_123"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "success": true
+        })))
+        .mount(&mock_server)
+        .await;
+
+    let client = create_test_client(&mock_server);
+    let result = client.update_key_name("key_123", "Updated Name").await;
+
+    assert!(result.is_ok());
+}
+
+// ============ On-Chain Payment Tests ============
+
+#[tokio::test]
+async fn test_submit_prompt() {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("POST"))
+        .and(path("/v1/prompts"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "submissionId": "sub_123",
+            "promptHash": "abc123def456",
+            "paymentAddress": "9JKi6Tr7JdsTJw1zNedF5vML9GpPnjHD9DWuZq1oE6nV",
+            "requiredAmount": {
+                "sol": 0.00151,
+                "lamports": 1510000,
+                "usd": 0.28
+            },
+            "memo": "PCAT:v1:sdxl:abc123def456",
+            "model": "stable-diffusion-xl",
+            "slippageTolerance": 0.05,
+            "expiresAt": "2024-01-15T11:00:00Z",
+            "instructions": {
+                "1": "Send SOL to payment address",
+                "2": "Include memo in transaction"
+            }
+        })))
+        .mount(&mock_server)
+        .await;
+
+    let client = create_test_client(&mock_server);
+    let result = client
+        .submit_prompt(SubmitPromptParams::new("A beautiful sunset"))
+        .await
+        .expect("Submit prompt should succeed");
+
+    assert_eq!(result.submission_id, "sub_123");
+    assert_eq!(result.memo, "PCAT:v1:sdxl:abc123def456");
+    assert_eq!(result.required_amount.sol, 0.00151);
+}
+
+#[tokio::test]
+async fn test_submit_prompt_with_slippage_tolerance() {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("POST"))
+        .and(path("/v1/prompts"))
+        .and(body_json(serde_json::json!({
+            "prompt": "A beautiful sunset",
+            "slippageTolerance": 0.1
+        })))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "submissionId": "sub_123",
+            "promptHash": "abc123def456",
+            "paymentAddress": "9JKi6Tr7JdsTJw1zNedF5vML9GpPnjHD9DWuZq1oE6nV",
+            "requiredAmount": {
+                "sol": 0.00166,
+                "lamports": 1660000,
+                "usd": 0.28
+            },
+            "memo": "PCAT:v1:sdxl:abc123def456",
+            "model": "stable-diffusion-xl",
+            "slippageTolerance": 0.1,
+            "expiresAt": "2024-01-15T11:00:00Z",
+            "instructions": {}
+        })))
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+
+    let client = create_test_client(&mock_server);
+    let result = client
+        .submit_prompt(SubmitPromptParams::new("A beautiful sunset").with_slippage_tolerance(0.1))
+        .await
+        .expect("Submit prompt should succeed");
+    assert_eq!(result.slippage_tolerance, 0.1);
+
+    // Out-of-range values are rejected before sending
+    let err = client
+        .submit_prompt(SubmitPromptParams::new("A beautiful sunset").with_slippage_tolerance(1.5))
+        .await
+        .unwrap_err();
+    match err {
+        PeerCatError::Validation { field, message } => {
+            assert_eq!(field, "slippage_tolerance");
+            assert_eq!(message, "must be between 0 and 0.5, got 1.5");
+        }
+        other => panic!("Expected Validation error, got {:?}", other),
+    }
+}
+
+#[tokio::test]
+async fn test_submit_prompt_with_payment_token() {
+    let mock_server = MockServer::start().await;
+    let usdc = "EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v";
+
+    Mock::given(method("POST"))
+        .and(path("/v1/prompts"))
+        .and(body_json(serde_json::json!({
+            "prompt": "A beautiful sunset",
+            "paymentToken": usdc
+        })))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "submissionId": "sub_123",
+            "promptHash": "abc123def456",
+            "paymentAddress": "9JKi6Tr7JdsTJw1zNedF5vML9GpPnjHD9DWuZq1oE6nV",
+            "requiredAmount": {
+                "sol": 0.00151,
+                "lamports": 1510000,
+                "usd": 0.28
+            },
+            "memo": "PCAT:v1:sdxl:abc123def456",
+            "model": "stable-diffusion-xl",
+            "slippageTolerance": 0.05,
+            "expiresAt": "2024-01-15T11:00:00Z",
+            "instructions": {},
+            "swapRoute": {
+                "inputMint": usdc,
+                "inputAmount": 285000,
+                "inputDecimals": 6,
+                "outputLamports": 1512000,
+                "priceImpactPct": 0.01,
+                "route": ["Orca", "Raydium"],
+                "swapTransaction": "AQAAAA==",
+                "quoteExpiresAt": "2024-01-15T10:01:00Z"
+            }
+        })))
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+
+    let client = create_test_client(&mock_server);
+    let result = client
+        .submit_prompt(SubmitPromptParams::new("A beautiful sunset").with_payment_token(usdc))
+        .await
+        .expect("Submit prompt should succeed");
+
+    let route = result.swap_route.expect("Swap route should be present");
+    assert_eq!(route.input_mint, usdc);
+    assert_eq!(route.input_amount, 285000);
+    assert!(route.output_lamports >= result.required_amount.lamports);
+    assert_eq!(route.route, ["Orca", "Raydium"]);
+}
+
+#[tokio::test]
+async fn test_list_submissions() {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("GET"))
+        .and(path("/v1/prompts"))
+        .and(query_param("status", "open"))
+        .and(query_param("limit", "20"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "submissions": [{
+                "submissionId": "sub_123",
+                "promptHash": "abc123def456",
+                "paymentAddress": "9JKi6Tr7JdsTJw1zNedF5vML9GpPnjHD9DWuZq1oE6nV",
+                "requiredAmount": {
+                    "sol": 0.00151,
+                    "lamports": 1510000,
+                    "usd": 0.28
+                },
+                "memo": "PCAT:v1:sdxl:abc123def456",
+                "model": "stable-diffusion-xl",
+                "slippageTolerance": 0.05,
+                "expiresAt": "2024-01-15T11:00:00Z",
+                "instructions": {},
+                "status": "open"
+            }],
+            "pagination": {
+                "total": 1,
+                "limit": 20,
+                "offset": 0,
+                "hasMore": false
+            }
+        })))
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+
+    let client = create_test_client(&mock_server);
+    let result = client
+        .list_submissions(
+            SubmissionFilter::new()
+                .with_status(SubmissionStatus::Open)
+                .with_limit(20),
+        )
+        .await
+        .expect("List submissions should succeed");
+
+    assert_eq!(result.submissions.len(), 1);
+    assert_eq!(result.submissions[0].submission_id, "sub_123");
+    assert_eq!(result.submissions[0].status, SubmissionStatus::Open);
+    assert_eq!(result.submissions[0].required_amount.lamports, 1510000);
+    assert!(!result.pagination.has_more);
+}
+
+#[tokio::test]
+async fn test_get_submission() {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("GET"))
+        .and(path("/v1/prompts/sub_123"))
         .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
-            "success": true
+            "submissionId": "sub_123",
+            "promptHash": "abc123def456",
+            "paymentAddress": "9JKi6Tr7JdsTJw1zNedF5vML9GpPnjHD9DWuZq1oE6nV",
+            "requiredAmount": {
+                "sol": 0.00151,
+                "lamports": 1510000,
+                "usd": 0.28
+            },
+            "memo": "PCAT:v1:sdxl:abc123def456",
+            "model": "stable-diffusion-xl",
+            "slippageTolerance": 0.05,
+            "expiresAt": "2024-01-15T11:00:00Z",
+            "instructions": {},
+            "status": "paid"
         })))
         .mount(&mock_server)
         .await;
 
     let client = create_test_client(&mock_server);
-    let result = client.update_key_name("key_123", "Updated Name").await;
+    let submission = client
+        .get_submission("sub_123")
+        .await
+        .expect("Get submission should succeed");
 
-    assert!(result.is_ok());
+    assert_eq!(submission.memo, "PCAT:v1:sdxl:abc123def456");
+    assert_eq!(submission.required_amount.lamports, 1510000);
+    assert_eq!(submission.status, SubmissionStatus::Paid);
 }
 
-// ============ On-Chain Payment Tests ============
-
 #[tokio::test]
-async fn test_submit_prompt() {
+async fn test_link_transaction() {
     let mock_server = MockServer::start().await;
 
     Mock::given(method("POST"))
-        .and(path("/v1/prompts"))
+        .and(path("/v1/prompts/sub_123/transactions"))
+        .and(body_json(serde_json::json!({ "txSignature": "5xyz" })))
         .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
             "submissionId": "sub_123",
             "promptHash": "abc123def456",
@@ -419,23 +2335,20 @@ async fn test_submit_prompt() {
             "model": "stable-diffusion-xl",
             "slippageTolerance": 0.05,
             "expiresAt": "2024-01-15T11:00:00Z",
-            "instructions": {
-                "1": "Send SOL to payment address",
-                "2": "Include memo in transaction"
-            }
+            "instructions": {},
+            "status": "paid"
         })))
+        .expect(1)
         .mount(&mock_server)
         .await;
 
     let client = create_test_client(&mock_server);
-    let result = client
-        .submit_prompt(SubmitPromptParams::new("A beautiful sunset"))
+    let submission = client
+        .link_transaction("sub_123", "5xyz")
         .await
-        .expect("Submit prompt should succeed");
+        .expect("Link transaction should succeed");
 
-    assert_eq!(result.submission_id, "sub_123");
-    assert_eq!(result.memo, "PCAT:v1:sdxl:abc123def456");
-    assert_eq!(result.required_amount.sol, 0.00151);
+    assert_eq!(submission.status, SubmissionStatus::Paid);
 }
 
 #[tokio::test]
@@ -495,6 +2408,167 @@ async fn test_get_onchain_status_pending() {
     assert!(status.image_url.is_none());
 }
 
+#[tokio::test]
+async fn test_get_onchain_events() {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("GET"))
+        .and(path("/v1/generate/txSig456/events"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "txSignature": "txSig456",
+            "events": [
+                { "type": "payment_detected", "timestamp": "2024-01-15T10:00:00Z" },
+                {
+                    "type": "amount_verified",
+                    "timestamp": "2024-01-15T10:00:01Z",
+                    "message": "Received 1510000 lamports"
+                },
+                { "type": "queued", "timestamp": "2024-01-15T10:00:01Z" },
+                {
+                    "type": "failed",
+                    "timestamp": "2024-01-15T10:00:20Z",
+                    "message": "Model unavailable"
+                },
+                { "type": "refund_issued", "timestamp": "2024-01-15T10:00:25Z" }
+            ]
+        })))
+        .mount(&mock_server)
+        .await;
+
+    let client = create_test_client(&mock_server);
+    let timeline = client
+        .get_onchain_events("txSig456")
+        .await
+        .expect("Get events should succeed");
+
+    let kinds: Vec<_> = timeline.events.iter().map(|event| event.kind).collect();
+    assert_eq!(
+        kinds,
+        [
+            OnChainEventKind::PaymentDetected,
+            OnChainEventKind::AmountVerified,
+            OnChainEventKind::Queued,
+            OnChainEventKind::Failed,
+            OnChainEventKind::RefundIssued,
+        ]
+    );
+    assert_eq!(
+        timeline.events[3].message.as_deref(),
+        Some("Model unavailable")
+    );
+}
+
+#[tokio::test]
+async fn test_wait_for_onchain_completion() {
+    let mock_server = MockServer::start().await;
+
+    for (status, times) in [("pending", 1), ("processing", 1)] {
+        Mock::given(method("GET"))
+            .and(path("/v1/generate/txSig789"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "txSignature": "txSig789",
+                "status": status
+            })))
+            .up_to_n_times(times)
+            .mount(&mock_server)
+            .await;
+    }
+    Mock::given(method("GET"))
+        .and(path("/v1/generate/txSig789"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "txSignature": "txSig789",
+            "status": "completed",
+            "imageUrl": "https://cdn.peerc.at/images/gen_789.png"
+        })))
+        .mount(&mock_server)
+        .await;
+
+    let client = create_test_client(&mock_server);
+    let options = PollOptions::new().with_interval(std::time::Duration::from_millis(10));
+    let status = client
+        .wait_for_onchain_completion("txSig789", options)
+        .await
+        .expect("Wait should succeed");
+
+    assert_eq!(status.status, OnChainStatus::Completed);
+    assert!(status.image_url.is_some());
+}
+
+#[tokio::test]
+async fn test_wait_for_generation_deadline_exceeded() {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("GET"))
+        .and(path("/v1/history/use_456"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "id": "use_456",
+            "endpoint": "/v1/generate",
+            "model": "stable-diffusion-xl",
+            "creditsUsed": 0.28,
+            "requestId": "gen_456",
+            "status": "pending",
+            "createdAt": "2024-01-15T10:00:00Z",
+            "completedAt": null
+        })))
+        .expect(3)
+        .mount(&mock_server)
+        .await;
+
+    let client = create_test_client(&mock_server);
+    let options = PollOptions::new()
+        .with_interval(std::time::Duration::from_millis(5))
+        .with_max_polls(3);
+    let error = client
+        .wait_for_generation("use_456", options)
+        .await
+        .unwrap_err();
+
+    assert!(matches!(
+        error,
+        PeerCatError::DeadlineExceeded { polls: 3, .. }
+    ));
+}
+
+#[tokio::test]
+async fn test_wait_for_generation_cancelled() {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("GET"))
+        .and(path("/v1/history/use_456"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "id": "use_456",
+            "endpoint": "/v1/generate",
+            "model": "stable-diffusion-xl",
+            "creditsUsed": 0.28,
+            "requestId": "gen_456",
+            "status": "pending",
+            "createdAt": "2024-01-15T10:00:00Z",
+            "completedAt": null
+        })))
+        .mount(&mock_server)
+        .await;
+
+    let client = create_test_client(&mock_server);
+    let token = CancellationToken::new();
+    let options = PollOptions::new()
+        .with_interval(std::time::Duration::from_secs(60))
+        .with_cancellation(token.clone());
+    tokio::spawn(async move {
+        tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+        token.cancel();
+    });
+
+    let started = std::time::Instant::now();
+    let error = client
+        .wait_for_generation("use_456", options)
+        .await
+        .unwrap_err();
+
+    assert!(matches!(error, PeerCatError::Cancelled));
+    assert!(!error.is_retryable());
+    assert!(started.elapsed() < std::time::Duration::from_secs(5));
+}
+
 // ============ Error Handling Tests ============
 
 #[tokio::test]
@@ -714,7 +2788,7 @@ async fn test_custom_base_url() {
 
     let client = PeerCat::with_config(
         PeerCatConfig::new("test_key")
-            .with_base_url(&format!("{}/", mock_server.uri())) // Trailing slash should be stripped
+            .with_base_url(format!("{}/", mock_server.uri())) // Trailing slash should be stripped
             .with_max_retries(0),
     )
     .expect("Failed to create client");
@@ -723,6 +2797,283 @@ async fn test_custom_base_url() {
     assert!(result.is_ok());
 }
 
+#[tokio::test]
+async fn test_connection_pool_settings() {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("GET"))
+        .and(path("/v1/balance"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "credits": 10.0,
+            "totalDeposited": 10.0,
+            "totalSpent": 0.0,
+            "totalWithdrawn": 0.0,
+            "totalGenerated": 0
+        })))
+        .expect(2)
+        .mount(&mock_server)
+        .await;
+
+    let config = PeerCatConfig::new("test_key")
+        .with_base_url(mock_server.uri())
+        .with_max_retries(0)
+        .with_pool_max_idle_per_host(4)
+        .with_pool_idle_timeout(std::time::Duration::from_secs(300))
+        .with_tcp_keepalive(std::time::Duration::from_secs(30));
+    assert_eq!(config.pool_max_idle_per_host, Some(4));
+    assert_eq!(
+        config.tcp_keepalive,
+        Some(std::time::Duration::from_secs(30))
+    );
+
+    let client = PeerCat::with_config(config).expect("Failed to create client");
+    for _ in 0..2 {
+        client.get_balance().await.expect("Request should succeed");
+    }
+}
+
+#[tokio::test]
+async fn test_http_version_settings() {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("GET"))
+        .and(path("/v1/balance"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "credits": 10.0,
+            "totalDeposited": 10.0,
+            "totalSpent": 0.0,
+            "totalWithdrawn": 0.0,
+            "totalGenerated": 0
+        })))
+        .expect(2)
+        .mount(&mock_server)
+        .await;
+
+    for version in [HttpVersion::Http1Only, HttpVersion::Http2PriorKnowledge] {
+        let client = PeerCat::with_config(
+            PeerCatConfig::new("test_key")
+                .with_base_url(mock_server.uri())
+                .with_max_retries(0)
+                .with_http_version(version)
+                .with_http2_adaptive_window(),
+        )
+        .expect("Failed to create client");
+
+        client
+            .get_balance()
+            .await
+            .unwrap_or_else(|e| panic!("{:?} request failed: {}", version, e));
+    }
+}
+
+#[tokio::test]
+async fn test_resolve_override() {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("GET"))
+        .and(path("/v1/balance"))
+        .and(header("host", "api.peercat.invalid"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "credits": 10.0,
+            "totalDeposited": 10.0,
+            "totalSpent": 0.0,
+            "totalWithdrawn": 0.0,
+            "totalGenerated": 0
+        })))
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+
+    // The .invalid TLD never resolves, so the request only lands if the override is used
+    let client = PeerCat::with_config(
+        PeerCatConfig::new("test_key")
+            .with_base_url("http://api.peercat.invalid")
+            .with_max_retries(0)
+            .with_resolve("api.peercat.invalid", *mock_server.address()),
+    )
+    .expect("Failed to create client");
+
+    client
+        .get_balance()
+        .await
+        .expect("Request should go to the pinned address");
+}
+
+fn create_conditional_client(mock_server: &MockServer) -> PeerCat {
+    PeerCat::with_config(
+        PeerCatConfig::new("test_key")
+            .with_base_url(mock_server.uri())
+            .with_max_retries(0)
+            .with_conditional_requests(),
+    )
+    .expect("Failed to create client")
+}
+
+#[tokio::test]
+async fn test_conditional_get_returns_cached_value_on_304() {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("GET"))
+        .and(path("/v1/balance"))
+        .and(header("if-none-match", "\"v1\""))
+        .respond_with(ResponseTemplate::new(304))
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/v1/balance"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .insert_header("ETag", "\"v1\"")
+                .set_body_json(serde_json::json!({
+                    "credits": 10.0,
+                    "totalDeposited": 10.0,
+                    "totalSpent": 0.0,
+                    "totalWithdrawn": 0.0,
+                    "totalGenerated": 0
+                })),
+        )
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+
+    let client = create_conditional_client(&mock_server);
+    let first = client
+        .get_balance()
+        .await
+        .expect("First request should succeed");
+    let second = client
+        .get_balance()
+        .await
+        .expect("Revalidated request should succeed");
+
+    assert_eq!(first.credits, 10.0);
+    assert_eq!(second.credits, 10.0);
+}
+
+#[tokio::test]
+async fn test_conditional_get_uses_last_modified() {
+    let mock_server = MockServer::start().await;
+    let last_modified = "Mon, 15 Jan 2024 12:00:00 GMT";
+
+    Mock::given(method("GET"))
+        .and(path("/v1/models"))
+        .and(header_exists("if-modified-since"))
+        .respond_with(ResponseTemplate::new(304))
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/v1/models"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .insert_header("Last-Modified", last_modified)
+                .set_body_json(serde_json::json!({ "models": [] })),
+        )
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+
+    let client = create_conditional_client(&mock_server);
+    for _ in 0..2 {
+        let models = client.get_models().await.expect("Request should succeed");
+        assert!(models.is_empty());
+    }
+}
+
+#[tokio::test]
+async fn test_conditional_requests_off_by_default() {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("GET"))
+        .and(path("/v1/balance"))
+        .and(header("if-none-match", "\"v1\""))
+        .respond_with(ResponseTemplate::new(304))
+        .expect(0)
+        .mount(&mock_server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/v1/balance"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .insert_header("ETag", "\"v1\"")
+                .set_body_json(serde_json::json!({
+                    "credits": 10.0,
+                    "totalDeposited": 10.0,
+                    "totalSpent": 0.0,
+                    "totalWithdrawn": 0.0,
+                    "totalGenerated": 0
+                })),
+        )
+        .expect(2)
+        .mount(&mock_server)
+        .await;
+
+    let client = create_test_client(&mock_server);
+    for _ in 0..2 {
+        client.get_balance().await.expect("Request should succeed");
+    }
+}
+
+#[tokio::test]
+async fn test_warmup_opens_connection() {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("HEAD"))
+        .respond_with(ResponseTemplate::new(404))
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+
+    let client = create_test_client(&mock_server);
+    client
+        .warmup(WarmupOptions::new())
+        .await
+        .expect("Warmup should ignore the response status");
+}
+
+#[tokio::test]
+async fn test_warmup_primes_model_catalog() {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("GET"))
+        .and(path("/v1/models"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "models": [{
+                "id": "stable-diffusion-xl",
+                "name": "Stable Diffusion XL",
+                "description": "High-quality image generation",
+                "provider": "stability",
+                "maxPromptLength": 2000,
+                "outputFormat": "png",
+                "outputResolution": "1024x1024",
+                "priceUsd": 0.28
+            }]
+        })))
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+    Mock::given(method("HEAD"))
+        .respond_with(ResponseTemplate::new(200))
+        .expect(0)
+        .mount(&mock_server)
+        .await;
+
+    let client = create_test_client(&mock_server);
+    client
+        .warmup(WarmupOptions::new().with_models())
+        .await
+        .expect("Warmup should succeed");
+
+    // Served from the primed cache
+    let model = client
+        .models_catalog()
+        .find("stable-diffusion-xl")
+        .await
+        .unwrap();
+    assert!(model.is_some());
+}
+
 #[tokio::test]
 async fn test_error_code_accessor() {
     let error = PeerCatError::Authentication {
@@ -0,0 +1,53 @@
+//! Contract tests against the live test environment (requires the `live-tests` feature)
+//!
+//! Set `PEERCAT_API_KEY` to a `pcat_test_` key to run them; without it they
+//! pass without doing anything.
+
+#![cfg(feature = "live-tests")]
+
+use peercat::live;
+
+#[tokio::test]
+async fn test_live_contract() {
+    let Some(client) = live::client_from_env().expect("Failed to create live client") else {
+        eprintln!(
+            "{} not set, skipping live contract tests",
+            live::API_KEY_VAR
+        );
+        return;
+    };
+
+    let report = live::run_contract_checks(&client).await;
+    eprintln!("{}", report);
+    report.assert_ok();
+}
+
+#[cfg(feature = "testing")]
+#[tokio::test]
+async fn test_contract_checks_against_fake() {
+    use peercat::live::CheckOutcome;
+    use peercat::testing::FakePeerCat;
+
+    let fake = FakePeerCat::start().await;
+    let client = fake.client().unwrap();
+
+    let report = live::run_contract_checks(&client).await;
+    report.assert_ok();
+    let passed = |name| {
+        report
+            .checks
+            .iter()
+            .any(|check| check.name == name && matches!(check.outcome, CheckOutcome::Passed))
+    };
+    assert!(passed("generate"));
+    assert!(passed("list_collection_items"));
+    assert!(passed("delete_preset"));
+    assert!(passed("delete_generation"));
+    assert!(
+        report
+            .checks
+            .iter()
+            .any(|check| check.name == "withdraw"
+                && matches!(check.outcome, CheckOutcome::Skipped(_)))
+    );
+}
@@ -0,0 +1,183 @@
+//! Tests for the pluggable metrics sink
+
+use std::sync::{Arc, Mutex};
+
+use peercat::{GenerateParams, MetricEvent, PeerCat, PeerCatConfig, PeerCatMetrics};
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+/// Keeps a description of every event it's given
+#[derive(Debug, Clone, Default)]
+struct Recorder {
+    events: Arc<Mutex<Vec<String>>>,
+}
+
+impl Recorder {
+    fn events(&self) -> Vec<String> {
+        self.events.lock().unwrap().clone()
+    }
+}
+
+impl PeerCatMetrics for Recorder {
+    fn record(&self, event: MetricEvent<'_>) {
+        let event = match event {
+            MetricEvent::Request {
+                method,
+                endpoint,
+                error,
+                ..
+            } => format!("request {} {} ok={}", method, endpoint, error.is_none()),
+            MetricEvent::Retry {
+                endpoint, attempt, ..
+            } => format!("retry {} {}", endpoint, attempt),
+            MetricEvent::RateLimited {
+                endpoint,
+                retry_after,
+                ..
+            } => format!("rate_limited {} {:?}", endpoint, retry_after),
+            MetricEvent::CreditsSpent { endpoint, credits } => {
+                format!("credits {} {}", endpoint, credits)
+            }
+            _ => "other".to_string(),
+        };
+        self.events.lock().unwrap().push(event);
+    }
+}
+
+fn create_test_client(mock_server: &MockServer, max_retries: u32, recorder: &Recorder) -> PeerCat {
+    PeerCat::with_config(
+        PeerCatConfig::new("test_api_key")
+            .with_base_url(mock_server.uri())
+            .with_max_retries(max_retries)
+            .with_metrics(recorder.clone()),
+    )
+    .expect("Failed to create test client")
+}
+
+fn generate_body() -> serde_json::Value {
+    serde_json::json!({
+        "id": "gen_123",
+        "imageUrl": "https://cdn.peerc.at/images/gen_123.png",
+        "ipfsHash": "QmXyz123",
+        "model": "stable-diffusion-xl",
+        "mode": "production",
+        "usage": {
+            "creditsUsed": 0.28,
+            "balanceRemaining": 9.72
+        }
+    })
+}
+
+#[tokio::test]
+async fn test_metrics_request_and_credits() {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("POST"))
+        .and(path("/v1/generate"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(generate_body()))
+        .mount(&mock_server)
+        .await;
+
+    let recorder = Recorder::default();
+    let client = create_test_client(&mock_server, 0, &recorder);
+    client
+        .generate(GenerateParams::new("A sunset"))
+        .await
+        .expect("Generate should succeed");
+
+    assert_eq!(
+        recorder.events(),
+        [
+            "credits /v1/generate 0.28",
+            "request POST /v1/generate ok=true"
+        ]
+    );
+}
+
+#[tokio::test]
+async fn test_metrics_rate_limit_and_retry() {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("GET"))
+        .and(path("/v1/balance"))
+        .respond_with(
+            ResponseTemplate::new(429)
+                .insert_header("Retry-After", "0")
+                .set_body_json(serde_json::json!({
+                    "error": {
+                        "type": "rate_limit_error",
+                        "code": "rate_limit_exceeded",
+                        "message": "Too many requests"
+                    }
+                })),
+        )
+        .mount(&mock_server)
+        .await;
+
+    let recorder = Recorder::default();
+    let client = create_test_client(&mock_server, 1, &recorder);
+    client.get_balance().await.unwrap_err();
+
+    assert_eq!(
+        recorder.events(),
+        [
+            "rate_limited /v1/balance Some(0)",
+            "retry /v1/balance 1",
+            "rate_limited /v1/balance Some(0)",
+            "request GET /v1/balance ok=false"
+        ]
+    );
+}
+
+#[cfg(feature = "prometheus")]
+#[tokio::test]
+async fn test_prometheus_metrics() {
+    use peercat::PrometheusMetrics;
+
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("POST"))
+        .and(path("/v1/generate"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(generate_body()))
+        .mount(&mock_server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/v1/history/use_123"))
+        .respond_with(ResponseTemplate::new(404).set_body_json(serde_json::json!({
+            "error": {
+                "type": "not_found",
+                "code": "not_found",
+                "message": "No such item"
+            }
+        })))
+        .mount(&mock_server)
+        .await;
+
+    let registry = prometheus::Registry::new();
+    let client = PeerCat::with_config(
+        PeerCatConfig::new("test_api_key")
+            .with_base_url(mock_server.uri())
+            .with_max_retries(0)
+            .with_metrics(PrometheusMetrics::new(&registry).unwrap()),
+    )
+    .unwrap();
+    client
+        .generate(GenerateParams::new("A sunset"))
+        .await
+        .unwrap();
+    client.get_history_item("use_123").await.unwrap_err();
+
+    let output = prometheus::TextEncoder::new()
+        .encode_to_string(&registry.gather())
+        .unwrap();
+    assert!(output.contains(
+        r#"peercat_requests_total{method="POST",outcome="success",route="/v1/generate"} 1"#
+    ));
+    assert!(output.contains(
+        r#"peercat_requests_total{method="GET",outcome="not_found",route="/v1/history/{id}"} 1"#
+    ));
+    assert!(output.contains(r#"peercat_credits_spent_total{route="/v1/generate"} 0.28"#));
+
+    // Registering the same metrics twice fails
+    assert!(PrometheusMetrics::new(&registry).is_err());
+}
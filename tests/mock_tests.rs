@@ -0,0 +1,168 @@
+//! Tests for the PeerCatApi trait and MockPeerCat (requires the `mock` feature)
+
+#![cfg(feature = "mock")]
+
+use std::sync::Arc;
+
+use peercat::mock::MockPeerCat;
+use peercat::{
+    Balance, DynPeerCat, GenerateParams, GenerateResult, GenerateUsage, GenerationMode, PeerCat,
+    PeerCatApi, PeerCatConfig, PeerCatError,
+};
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+/// Application code written against the trait
+async fn generate_if_affordable<A: PeerCatApi>(
+    api: &A,
+    prompt: &str,
+) -> peercat::Result<Option<GenerateResult>> {
+    if api.get_balance().await?.credits < 0.28 {
+        return Ok(None);
+    }
+    api.generate(GenerateParams::new(prompt)).await.map(Some)
+}
+
+fn balance(credits: f64) -> Balance {
+    Balance {
+        credits,
+        total_deposited: 10.0,
+        total_spent: 10.0 - credits,
+        total_withdrawn: 0.0,
+        total_generated: 10,
+    }
+}
+
+#[tokio::test]
+async fn test_mock_programmable_responses() {
+    let api = MockPeerCat::new()
+        .on_get_balance(|| Ok(balance(5.0)))
+        .on_generate(|params| {
+            Ok(GenerateResult {
+                id: "gen_mock".to_string(),
+                image_url: format!("https://example.com/{}.png", params.prompt.len()),
+                ipfs_hash: None,
+                model: "stable-diffusion-xl".to_string(),
+                mode: GenerationMode::Demo,
+                usage: GenerateUsage {
+                    credits_used: 0.0,
+                    balance_remaining: 5.0,
+                },
+                private: false,
+                safety_level: None,
+                seed: Some(42),
+                prompt: Some(params.prompt),
+                options: None,
+                prompts: Vec::new(),
+                quality: None,
+                adapters: Vec::new(),
+                controlnet: None,
+                auto_translate: None,
+                source_language: None,
+                expected_file_size: None,
+                translated_prompt: None,
+            })
+        });
+
+    let result = generate_if_affordable(&api, "A cat")
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(result.id, "gen_mock");
+    assert_eq!(result.image_url, "https://example.com/5.png");
+}
+
+#[tokio::test]
+async fn test_mock_returns_programmed_errors() {
+    let api = MockPeerCat::new()
+        .on_get_balance(|| Ok(balance(0.1)))
+        .on_get_key(|key_id| {
+            Err(PeerCatError::NotFound {
+                message: format!("Key {} not found", key_id),
+                code: "resource_not_found".to_string(),
+                param: None,
+            })
+        });
+
+    // Balance too low: generate is never called
+    assert!(generate_if_affordable(&api, "A cat")
+        .await
+        .unwrap()
+        .is_none());
+
+    let err = api.get_key("key_missing").await.unwrap_err();
+    assert_eq!(err.to_string(), "Not found: Key key_missing not found");
+}
+
+#[tokio::test]
+#[should_panic(expected = "no handler set for get_models")]
+async fn test_mock_panics_without_handler() {
+    let _ = MockPeerCat::new().get_models().await;
+}
+
+#[tokio::test]
+async fn test_client_implements_trait() {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("GET"))
+        .and(path("/v1/balance"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "credits": 0.10,
+            "totalDeposited": 10.00,
+            "totalSpent": 9.90,
+            "totalWithdrawn": 0.00,
+            "totalGenerated": 35
+        })))
+        .mount(&mock_server)
+        .await;
+
+    let client = PeerCat::with_config(
+        PeerCatConfig::new("test_api_key")
+            .with_base_url(mock_server.uri())
+            .with_max_retries(0),
+    )
+    .unwrap();
+
+    assert!(generate_if_affordable(&client, "A cat")
+        .await
+        .unwrap()
+        .is_none());
+}
+
+#[tokio::test]
+async fn test_dyn_client() {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("GET"))
+        .and(path("/v1/balance"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "credits": 0.10,
+            "totalDeposited": 10.00,
+            "totalSpent": 9.90,
+            "totalWithdrawn": 0.00,
+            "totalGenerated": 35
+        })))
+        .mount(&mock_server)
+        .await;
+
+    let client = PeerCat::with_config(
+        PeerCatConfig::new("test_api_key")
+            .with_base_url(mock_server.uri())
+            .with_max_retries(0),
+    )
+    .unwrap();
+
+    // Real and mock clients can be held behind the same type
+    let clients: Vec<DynPeerCat> = vec![
+        Arc::new(client),
+        Arc::new(MockPeerCat::new().on_get_balance(|| Ok(balance(0.1)))),
+    ];
+    for api in &clients {
+        assert_eq!(api.get_balance().await.unwrap().credits, 0.1);
+        // And passed to code written against the generic trait
+        assert!(generate_if_affordable(api, "A cat")
+            .await
+            .unwrap()
+            .is_none());
+    }
+}
@@ -0,0 +1,241 @@
+//! Conformance tests between the SDK types and the OpenAPI specification
+//!
+//! The spec is read from `tests/openapi.json`, or from the path in
+//! `PEERCAT_OPENAPI_SPEC`. The committed file was written by hand from the API
+//! reference; when the `PEERCAT_OPENAPI_URL` repository variable is set, the
+//! `openapi` CI job downloads the published spec from it, runs these tests
+//! against it, and fails if the committed file differs. Copy the published
+//! spec over `tests/openapi.json` to update it. For every schema these tests
+//! assert that:
+//! 1. A payload with every property deserializes and serializes back to the
+//!    same property names
+//! 2. Required properties cannot be omitted, and optional ones can
+//! 3. Nullable properties accept `null` and required non-nullable ones
+//!    reject it
+//! 4. Every enum value round-trips through the matching Rust enum
+
+use peercat::{
+    ApiKey, Balance, Collection, CollectionItem, CollectionItemsResponse, CollectionsResponse,
+    CreateKeyResult, DepositInstructions, DepositState, DepositStatus, GenerateResult,
+    GenerateUsage, GenerationDeletion, GenerationMetadata, GenerationMode, HistoryItem,
+    HistoryResponse, HistoryStatus, IpfsStatus, KeyEnvironment, KeyScope, KeysResponse, Lora,
+    LorasResponse, Model, ModelCapabilities, ModelPrice, ModelsResponse, OnChainEvent,
+    OnChainEventKind, OnChainEvents, OnChainGenerationStatus, OnChainStatus, Pagination,
+    ParamRange, Preset, PresetsResponse, PriceGranularity, PriceHistory, PricePoint, PriceResponse,
+    PromptSubmission, RequiredAmount, SafetyCheck, SafetyLevel, ShareLink, SignedUrl,
+    SubmissionStatus, SubmissionsResponse, SwapRoute, UploadedImage, UsageBucket, UsageGroupBy,
+    UsageSummary, WithdrawalResult, WithdrawalStatus,
+};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::{Map, Value};
+use std::collections::BTreeSet;
+use std::path::PathBuf;
+
+// ============ Spec Loading ============
+
+fn load_spec() -> Value {
+    let path = std::env::var_os("PEERCAT_OPENAPI_SPEC")
+        .map(PathBuf::from)
+        .unwrap_or_else(|| PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/openapi.json"));
+    let contents = std::fs::read_to_string(&path)
+        .unwrap_or_else(|e| panic!("failed to read {}: {}", path.display(), e));
+    serde_json::from_str(&contents).expect("spec should be valid JSON")
+}
+
+fn schemas(spec: &Value) -> &Map<String, Value> {
+    spec["components"]["schemas"]
+        .as_object()
+        .expect("spec should define components.schemas")
+}
+
+fn resolve<'a>(spec: &'a Value, schema: &'a Value) -> &'a Value {
+    match schema["$ref"].as_str() {
+        Some(reference) => {
+            let name = reference.trim_start_matches("#/components/schemas/");
+            &schemas(spec)[name]
+        }
+        None => schema,
+    }
+}
+
+/// Build an example value for a schema, filling in every property
+fn sample(spec: &Value, schema: &Value) -> Value {
+    let schema = resolve(spec, schema);
+    if let Some(example) = schema.get("example") {
+        return example.clone();
+    }
+    if let Some(values) = schema["enum"].as_array() {
+        return values[0].clone();
+    }
+
+    match schema["type"].as_str() {
+        Some("object") => {
+            let mut object = Map::new();
+            if let Some(properties) = schema["properties"].as_object() {
+                for (name, property) in properties {
+                    object.insert(name.clone(), sample(spec, property));
+                }
+            } else if let Some(values) = schema.get("additionalProperties") {
+                object.insert("key".to_string(), sample(spec, values));
+            }
+            Value::Object(object)
+        }
+        Some("array") => Value::Array(vec![sample(spec, &schema["items"])]),
+        Some("string") if schema["format"] == "date-time" => "2024-01-15T10:30:00Z".into(),
+        Some("string") => "value".into(),
+        Some("integer") => 1.into(),
+        Some("number") => 1.5.into(),
+        Some("boolean") => true.into(),
+        // An empty schema allows any value
+        None if schema.as_object().is_some_and(Map::is_empty) => "value".into(),
+        other => panic!("unsupported schema type {:?}", other),
+    }
+}
+
+// ============ Conformance Checks ============
+
+fn check_object<T: Serialize + DeserializeOwned>(spec: &Value, name: &str) {
+    let schema = &schemas(spec)[name];
+    let properties = schema["properties"]
+        .as_object()
+        .unwrap_or_else(|| panic!("{} should be an object schema", name));
+    let required: BTreeSet<&str> = schema["required"]
+        .as_array()
+        .map(|names| names.iter().filter_map(Value::as_str).collect())
+        .unwrap_or_default();
+
+    let full = sample(spec, schema);
+    let parsed: T = serde_json::from_value(full.clone())
+        .unwrap_or_else(|e| panic!("{}: full payload should deserialize: {}", name, e));
+    let serialized = serde_json::to_value(&parsed).unwrap();
+    let spec_fields: BTreeSet<&str> = properties.keys().map(String::as_str).collect();
+    let rust_fields: BTreeSet<&str> = serialized
+        .as_object()
+        .unwrap_or_else(|| panic!("{} should serialize to an object", name))
+        .keys()
+        .map(String::as_str)
+        .collect();
+    assert_eq!(
+        rust_fields, spec_fields,
+        "{}: field names differ from spec",
+        name
+    );
+
+    for (field, property) in properties {
+        let mut without = full.clone();
+        without.as_object_mut().unwrap().remove(field);
+        let omitted = serde_json::from_value::<T>(without).is_ok();
+        if required.contains(field.as_str()) && property["nullable"] != true {
+            assert!(
+                !omitted,
+                "{}.{} is required but may be omitted",
+                name, field
+            );
+        } else if !required.contains(field.as_str()) {
+            assert!(
+                omitted,
+                "{}.{} is optional but cannot be omitted",
+                name, field
+            );
+        }
+
+        let mut null = full.clone();
+        null[field.as_str()] = Value::Null;
+        let nullable = serde_json::from_value::<T>(null).is_ok();
+        if property["nullable"] == true {
+            assert!(nullable, "{}.{} is nullable but rejects null", name, field);
+        } else if required.contains(field.as_str()) {
+            assert!(
+                !nullable,
+                "{}.{} is not nullable but accepts null",
+                name, field
+            );
+        }
+    }
+}
+
+fn check_enum<T: Serialize + DeserializeOwned>(spec: &Value, name: &str) {
+    let values = schemas(spec)[name]["enum"]
+        .as_array()
+        .unwrap_or_else(|| panic!("{} should be an enum schema", name));
+
+    for value in values {
+        let parsed: T = serde_json::from_value(value.clone())
+            .unwrap_or_else(|e| panic!("{}: {} should deserialize: {}", name, value, e));
+        assert_eq!(&serde_json::to_value(&parsed).unwrap(), value, "{}", name);
+    }
+    assert!(
+        serde_json::from_value::<T>("not-a-variant".into()).is_err(),
+        "{} accepts values outside the spec",
+        name
+    );
+}
+
+/// Check each listed schema, and that the list covers every schema in the spec
+macro_rules! conformance {
+    ($spec:expr, objects: [$($object:ident),* $(,)?], enums: [$($enum:ident),* $(,)?]) => {{
+        let spec = &$spec;
+        $(check_object::<$object>(spec, stringify!($object));)*
+        $(check_enum::<$enum>(spec, stringify!($enum));)*
+
+        let covered: BTreeSet<&str> =
+            [$(stringify!($object),)* $(stringify!($enum),)*].into_iter().collect();
+        let missing: Vec<&String> = schemas(spec)
+            .keys()
+            .filter(|name| !covered.contains(name.as_str()))
+            .collect();
+        assert!(missing.is_empty(), "spec schemas without a checked type: {:?}", missing);
+    }};
+}
+
+#[test]
+fn test_types_conform_to_openapi_spec() {
+    conformance!(
+        load_spec(),
+        objects: [
+            Model, ModelCapabilities, ModelsResponse, ModelPrice, PriceResponse, PricePoint,
+            PriceHistory, GenerateUsage, GenerateResult, IpfsStatus, SafetyCheck,
+            GenerationMetadata, Balance, RequiredAmount, DepositInstructions, DepositStatus,
+            WithdrawalResult, HistoryItem, Pagination, HistoryResponse, UsageBucket, UsageSummary,
+            ApiKey, CreateKeyResult, KeysResponse, PromptSubmission, OnChainGenerationStatus,
+            SignedUrl, Collection, CollectionItem, CollectionsResponse, CollectionItemsResponse,
+            GenerationDeletion, ShareLink, ParamRange, Lora, LorasResponse, UploadedImage, Preset,
+            PresetsResponse, SubmissionsResponse, OnChainEvents, OnChainEvent, SwapRoute,
+        ],
+        enums: [
+            PriceGranularity, GenerationMode, DepositState, WithdrawalStatus, HistoryStatus,
+            UsageGroupBy, KeyScope, KeyEnvironment, OnChainStatus, SafetyLevel, SubmissionStatus,
+            OnChainEventKind,
+        ]
+    );
+}
+
+#[test]
+fn test_spec_records_its_version() {
+    let spec = load_spec();
+    let version = spec["info"]["version"].as_str().unwrap_or_default();
+    assert!(!version.is_empty(), "spec should record info.version");
+}
+
+#[test]
+fn test_spec_responses_reference_known_schemas() {
+    let spec = load_spec();
+    let known = schemas(&spec);
+
+    for (path, operations) in spec["paths"].as_object().unwrap() {
+        for (method, operation) in operations.as_object().unwrap() {
+            let schema = &operation["responses"]["200"]["content"]["application/json"]["schema"];
+            if let Some(reference) = schema["$ref"].as_str() {
+                let name = reference.trim_start_matches("#/components/schemas/");
+                assert!(
+                    known.contains_key(name),
+                    "{} {} references unknown schema {}",
+                    method,
+                    path,
+                    name
+                );
+            }
+        }
+    }
+}
@@ -0,0 +1,63 @@
+//! Property tests over the SDK's `proptest` strategies (requires the `proptest` feature)
+
+#![cfg(feature = "proptest")]
+
+use peercat::{
+    ApiKey, Balance, DepositStatus, GenerateParams, GenerateResult, HistoryItem, HistoryParams,
+    OnChainEvent, OnChainGenerationStatus, WithdrawalResult,
+};
+use proptest::prelude::*;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+/// Check that a value survives a trip through the wire format unchanged
+fn round_trips<T: Serialize + DeserializeOwned>(value: &T) -> bool {
+    let json = serde_json::to_value(value).unwrap();
+    let parsed: T = serde_json::from_value(json.clone()).unwrap();
+    serde_json::to_value(parsed).unwrap() == json
+}
+
+proptest! {
+    #[test]
+    fn test_generate_params_round_trip(params in any::<GenerateParams>()) {
+        let json = serde_json::to_string(&params).unwrap();
+        let parsed: GenerateParams = serde_json::from_str(&json).unwrap();
+        prop_assert_eq!(parsed, params);
+    }
+
+    #[test]
+    fn test_history_params_round_trip(params in any::<HistoryParams>()) {
+        let json = serde_json::to_string(&params).unwrap();
+        let parsed: HistoryParams = serde_json::from_str(&json).unwrap();
+        prop_assert_eq!(parsed, params);
+    }
+
+    #[test]
+    fn test_responses_round_trip(
+        result in any::<GenerateResult>(),
+        balance in any::<Balance>(),
+        item in any::<HistoryItem>(),
+        deposit in any::<DepositStatus>(),
+        withdrawal in any::<WithdrawalResult>(),
+        key in any::<ApiKey>(),
+        status in any::<OnChainGenerationStatus>(),
+        event in any::<OnChainEvent>(),
+    ) {
+        prop_assert!(round_trips(&result));
+        prop_assert!(round_trips(&balance));
+        prop_assert!(round_trips(&item));
+        prop_assert!(round_trips(&deposit));
+        prop_assert!(round_trips(&withdrawal));
+        prop_assert!(round_trips(&key));
+        prop_assert!(round_trips(&status));
+        prop_assert!(round_trips(&event));
+    }
+
+    #[test]
+    fn test_reproduce_params_keeps_seed(result in any::<GenerateResult>()) {
+        match result.reproduce_params() {
+            Some(params) => prop_assert_eq!(params.seed, result.seed),
+            None => prop_assert!(result.seed.is_none() || result.prompt.is_none()),
+        }
+    }
+}
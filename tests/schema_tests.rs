@@ -7,9 +7,10 @@
 //! 4. Nullable fields are correctly represented as Option<T>
 
 use peercat::{
-    Balance, GenerateResult, GenerateUsage, GenerationMode, HistoryItem, HistoryStatus,
+    AspectRatio, Balance, CanonicalJson, ControlMode, ControlNetParams, GenerateParams,
+    GenerateResult, GenerateUsage, GenerationMode, HistoryItem, HistoryParams, HistoryStatus,
     KeyEnvironment, Model, ModelPrice, OnChainGenerationStatus, OnChainStatus, Pagination,
-    PriceResponse, RequiredAmount,
+    PriceResponse, RequiredAmount, Resolution, SafetyLevel, Sampler, SubmitPromptParams,
 };
 use serde_json::json;
 
@@ -41,6 +42,69 @@ fn test_model_deserialization() {
     assert_eq!(model.price_usd, 0.28);
 }
 
+#[test]
+fn test_model_capabilities_deserialization() {
+    let json = json!({
+        "id": "stable-diffusion-xl",
+        "name": "Stable Diffusion XL",
+        "description": "High quality image generation",
+        "provider": "stability",
+        "maxPromptLength": 2000,
+        "outputFormat": "png",
+        "outputResolution": "1024x1024",
+        "priceUsd": 0.28,
+        "capabilities": {
+            "supportedResolutions": ["1024x1024", "1152x896"],
+            "supportsNegativePrompt": true,
+            "supportsSeed": true,
+            "maxImagesPerRequest": 4
+        }
+    });
+
+    let model: Model = serde_json::from_value(json).expect("Should deserialize Model");
+    let capabilities = model.capabilities.expect("capabilities should be present");
+
+    assert_eq!(
+        capabilities.supported_resolutions,
+        vec![Resolution::new(1024, 1024), Resolution::new(1152, 896)]
+    );
+    assert!(capabilities.supports_negative_prompt);
+    assert!(capabilities.supports_seed);
+    assert_eq!(capabilities.max_images_per_request, 4);
+}
+
+#[test]
+fn test_model_without_capabilities() {
+    let json = json!({
+        "id": "stable-diffusion-xl",
+        "name": "Stable Diffusion XL",
+        "description": "High quality image generation",
+        "provider": "stability",
+        "maxPromptLength": 2000,
+        "outputFormat": "png",
+        "outputResolution": "1024x1024",
+        "priceUsd": 0.28
+    });
+
+    let model: Model = serde_json::from_value(json).expect("Should deserialize Model");
+
+    assert!(model.capabilities.is_none());
+}
+
+#[test]
+fn test_resolution_round_trip() {
+    let resolution: Resolution = "2048x1536".parse().unwrap();
+
+    assert_eq!(resolution, Resolution::new(2048, 1536));
+    assert_eq!(resolution.to_string(), "2048x1536");
+    assert_eq!(
+        serde_json::to_value(resolution).unwrap(),
+        json!("2048x1536")
+    );
+    assert!("2048".parse::<Resolution>().is_err());
+    assert!(serde_json::from_value::<Resolution>(json!("axb")).is_err());
+}
+
 #[test]
 fn test_balance_deserialization() {
     let json = json!({
@@ -156,6 +220,45 @@ fn test_history_item_complete() {
     assert!(item.completed_at.is_some());
 }
 
+#[cfg(feature = "chrono")]
+#[test]
+fn test_history_item_typed_timestamps() {
+    let json = json!({
+        "id": "use_123",
+        "endpoint": "/v1/generate",
+        "model": "stable-diffusion-xl",
+        "creditsUsed": 0.28,
+        "requestId": "gen_123",
+        "status": "completed",
+        "createdAt": "2024-01-15T10:00:00Z",
+        "completedAt": "2024-01-15T10:00:05.250+00:00"
+    });
+
+    let item: HistoryItem = serde_json::from_value(json).expect("Should deserialize HistoryItem");
+
+    let completed_at = item.completed_at.expect("completedAt should be present");
+    assert_eq!(item.created_at.timestamp(), 1705312800);
+    assert_eq!((completed_at - item.created_at).num_milliseconds(), 5250);
+}
+
+#[test]
+fn test_invalid_timestamp_rejected() {
+    let json = json!({
+        "id": "use_123",
+        "endpoint": "/v1/generate",
+        "model": null,
+        "creditsUsed": 0.28,
+        "requestId": null,
+        "status": "completed",
+        "createdAt": "not a timestamp",
+        "completedAt": null
+    });
+
+    let result: Result<HistoryItem, _> = serde_json::from_value(json);
+
+    assert_eq!(result.is_err(), cfg!(feature = "chrono"));
+}
+
 #[test]
 fn test_history_item_pending_null_fields() {
     let json = json!({
@@ -299,6 +402,205 @@ fn test_pagination_serialization_roundtrip() {
     assert_eq!(pagination.has_more, deserialized.has_more);
 }
 
+#[test]
+fn test_generate_params_roundtrip() {
+    let params = GenerateParams::new("A lighthouse")
+        .with_prompts([("A lighthouse", 0.7), ("watercolor", 0.3)])
+        .with_model("stable-diffusion-xl")
+        .with_demo_mode()
+        .with_seed(42)
+        .with_steps(30)
+        .with_safety_level(SafetyLevel::Relaxed)
+        .with_aspect_ratio(AspectRatio::Custom(16, 9))
+        .with_quality(80)
+        .with_sampler(Sampler::DpmPlusPlus2MKarras)
+        .with_lora("lora_pixel", 0.8)
+        .with_controlnet(ControlNetParams::new(
+            "https://example.com/edges.png",
+            ControlMode::Canny,
+        ))
+        .with_auto_translate(true)
+        .with_source_language("ja");
+
+    let json = serde_json::to_string(&params).expect("Should serialize GenerateParams");
+    let deserialized: GenerateParams =
+        serde_json::from_str(&json).expect("Should deserialize GenerateParams");
+
+    assert_eq!(params, deserialized);
+}
+
+#[test]
+fn test_generate_params_aspect_ratio_and_sampler_roundtrip() {
+    let aspect_ratios = [
+        AspectRatio::Square,
+        AspectRatio::Portrait,
+        AspectRatio::Landscape,
+        AspectRatio::Custom(21, 9),
+    ];
+    let samplers = [
+        Sampler::Euler,
+        Sampler::EulerA,
+        Sampler::DpmPlusPlus2M,
+        Sampler::DpmPlusPlus2MKarras,
+        Sampler::Ddim,
+        Sampler::Lms,
+        Sampler::Other("heun".to_string()),
+    ];
+
+    for aspect_ratio in aspect_ratios {
+        let params = GenerateParams::new("A lighthouse").with_aspect_ratio(aspect_ratio);
+        let json = serde_json::to_value(&params).expect("Should serialize GenerateParams");
+        let deserialized: GenerateParams =
+            serde_json::from_value(json).expect("Should deserialize GenerateParams");
+        assert_eq!(deserialized.aspect_ratio, Some(aspect_ratio));
+    }
+    for sampler in samplers {
+        let params = GenerateParams::new("A lighthouse").with_sampler(sampler.clone());
+        let json = serde_json::to_value(&params).expect("Should serialize GenerateParams");
+        assert_eq!(json["sampler"], sampler.as_str());
+        let deserialized: GenerateParams =
+            serde_json::from_value(json).expect("Should deserialize GenerateParams");
+        assert_eq!(deserialized.sampler, Some(sampler));
+    }
+}
+
+#[test]
+fn test_controlnet_bytes_roundtrip() {
+    let controlnet =
+        ControlNetParams::new(vec![0x89, b'P', b'N', b'G'], ControlMode::Depth).with_weight(0.5);
+
+    let json = serde_json::to_value(&controlnet).expect("Should serialize ControlNetParams");
+    assert_eq!(json["imageData"], "iVBORw==");
+    assert!(json.get("imageUrl").is_none());
+    let deserialized: ControlNetParams =
+        serde_json::from_value(json).expect("Should deserialize ControlNetParams");
+
+    assert_eq!(controlnet, deserialized);
+}
+
+#[test]
+fn test_generate_params_from_minimal_config() {
+    let params: GenerateParams =
+        serde_json::from_value(json!({ "prompt": "A lighthouse", "safetyLevel": "strict" }))
+            .expect("Should deserialize GenerateParams");
+
+    assert_eq!(
+        params,
+        GenerateParams::new("A lighthouse").with_safety_level(SafetyLevel::Strict)
+    );
+}
+
+#[test]
+fn test_history_params_tags_roundtrip() {
+    let params = HistoryParams::new()
+        .with_limit(10)
+        .with_tag("hero")
+        .with_tag("draft");
+
+    let json = serde_json::to_value(&params).expect("Should serialize HistoryParams");
+    assert_eq!(json["tags"], "hero,draft");
+    let deserialized: HistoryParams =
+        serde_json::from_value(json).expect("Should deserialize HistoryParams");
+    assert_eq!(params, deserialized);
+
+    // An empty tag list is sent as an empty string and comes back empty
+    let empty = HistoryParams {
+        tags: Some(vec![]),
+        ..HistoryParams::default()
+    };
+    let json = serde_json::to_value(&empty).expect("Should serialize HistoryParams");
+    assert_eq!(json["tags"], "");
+    let deserialized: HistoryParams =
+        serde_json::from_value(json).expect("Should deserialize HistoryParams");
+    assert_eq!(empty, deserialized);
+
+    // Config files can list tags as an array too
+    let from_list: HistoryParams = serde_json::from_value(json!({ "tags": ["hero", "draft"] }))
+        .expect("Should deserialize tag list");
+    assert_eq!(from_list.tags, params.tags);
+}
+
+#[test]
+fn test_submit_prompt_params_roundtrip() {
+    let params = SubmitPromptParams::new("A lighthouse").with_model("stable-diffusion-xl");
+
+    let json = serde_json::to_value(&params).expect("Should serialize SubmitPromptParams");
+    let deserialized: SubmitPromptParams =
+        serde_json::from_value(json).expect("Should deserialize SubmitPromptParams");
+
+    assert_eq!(params, deserialized);
+}
+
+// ============ Canonical JSON Tests ============
+
+const GENERATE_RESULT_JSON: &str = r#"{
+    "id": "gen_123",
+    "imageUrl": "https://cdn.peerc.at/images/gen_123.png",
+    "ipfsHash": null,
+    "model": "stable-diffusion-xl",
+    "mode": "production",
+    "usage": { "creditsUsed": 0.28, "balanceRemaining": 9.72 },
+    "seed": 42,
+    "prompt": "A sunset",
+    "options": { "steps": 30, "cfg_scale": 7, "sampler": "euler" }
+}"#;
+
+#[test]
+fn test_canonical_json_ignores_key_order() {
+    let result: GenerateResult = serde_json::from_str(GENERATE_RESULT_JSON).unwrap();
+    let reordered: GenerateResult = serde_json::from_str(
+        r#"{
+            "options": { "sampler": "euler", "cfg_scale": 7, "steps": 30 },
+            "usage": { "balanceRemaining": 9.72, "creditsUsed": 0.28 },
+            "prompt": "A sunset",
+            "seed": 42,
+            "mode": "production",
+            "model": "stable-diffusion-xl",
+            "ipfsHash": null,
+            "imageUrl": "https://cdn.peerc.at/images/gen_123.png",
+            "id": "gen_123"
+        }"#,
+    )
+    .unwrap();
+
+    assert_eq!(
+        result.to_canonical_json().unwrap(),
+        reordered.to_canonical_json().unwrap()
+    );
+}
+
+#[test]
+fn test_canonical_json_output() {
+    let result: GenerateResult = serde_json::from_str(GENERATE_RESULT_JSON).unwrap();
+
+    // Changing this output breaks downstream snapshots, so only do it on purpose
+    assert_eq!(
+        result.to_canonical_json().unwrap(),
+        r#"{
+  "expectedFileSize": null,
+  "id": "gen_123",
+  "imageUrl": "https://cdn.peerc.at/images/gen_123.png",
+  "ipfsHash": null,
+  "mode": "production",
+  "model": "stable-diffusion-xl",
+  "options": {
+    "cfg_scale": 7,
+    "sampler": "euler",
+    "steps": 30
+  },
+  "private": false,
+  "prompt": "A sunset",
+  "safetyLevel": null,
+  "seed": 42,
+  "translatedPrompt": null,
+  "usage": {
+    "balanceRemaining": 9.72,
+    "creditsUsed": 0.28
+  }
+}"#
+    );
+}
+
 // ============ Contract Tests ============
 // These tests verify type structure matches OpenAPI at compile time
 
@@ -308,7 +610,7 @@ fn test_contract_price_response_has_treasury() {
     let response = PriceResponse {
         sol_price: 185.50,
         slippage_tolerance: 0.05,
-        updated_at: "2024-01-15T12:00:00Z".to_string(),
+        updated_at: "2024-01-15T12:00:00Z".parse().unwrap(),
         treasury: "9JKi6Tr7JdsTJw1zNedF5vML9GpPnjHD9DWuZq1oE6nV".to_string(),
         models: vec![],
     };
@@ -367,3 +669,95 @@ fn test_invalid_on_chain_status_fails() {
     let result: Result<OnChainStatus, _> = serde_json::from_str("\"cancelled\"");
     assert!(result.is_err(), "Invalid on-chain status should fail to deserialize");
 }
+
+// ============ Params Validation Tests ============
+
+fn model_with_capabilities() -> Model {
+    serde_json::from_value(json!({
+        "id": "stable-diffusion-xl",
+        "name": "Stable Diffusion XL",
+        "description": "High quality image generation",
+        "provider": "stability",
+        "maxPromptLength": 20,
+        "outputFormat": "png",
+        "outputResolution": "1024x1024",
+        "priceUsd": 0.28,
+        "capabilities": {
+            "supportedResolutions": ["1024x1024"],
+            "supportsNegativePrompt": true,
+            "supportsSeed": false,
+            "steps": { "min": 1, "max": 50 }
+        }
+    }))
+    .expect("Should deserialize Model")
+}
+
+#[test]
+fn test_validate_accepts_valid_params() {
+    let params = GenerateParams::new("A red fox")
+        .with_steps(30)
+        .with_option("resolution", "1024x1024".into())
+        .with_option("negativePrompt", "blurry".into())
+        .with_option("scheduler", "euler".into())
+        .with_option("style", "vivid".into());
+
+    assert_eq!(params.validate(&model_with_capabilities()), []);
+}
+
+#[test]
+fn test_validate_reports_options_the_capabilities_rule_out() {
+    let mut model = model_with_capabilities();
+    model.capabilities.as_mut().unwrap().supports_negative_prompt = false;
+    let params = GenerateParams::new("A red fox")
+        .with_guidance_scale(7.5)
+        .with_option("negativePrompt", "blurry".into());
+
+    let issues = params.validate(&model);
+    assert_eq!(issues.len(), 1);
+    assert_eq!(
+        issues[0].to_string(),
+        "negativePrompt: isn't an option stable-diffusion-xl accepts"
+    );
+}
+
+#[test]
+fn test_validate_reports_every_issue() {
+    let params = GenerateParams::new("A red fox in a snowy forest at dawn")
+        .with_demo_mode()
+        .with_seed(7)
+        .with_steps(80)
+        .with_guidance_scale(7.5)
+        .with_option("resolution", "640x480".into())
+        .with_lora("watercolor", 0.8)
+        .with_auto_translate(false)
+        .with_source_language("ja");
+
+    let fields: Vec<_> = params
+        .validate(&model_with_capabilities())
+        .into_iter()
+        .map(|issue| issue.field)
+        .collect();
+    assert_eq!(
+        fields,
+        [
+            "prompt",
+            "steps",
+            "seed",
+            "resolution",
+            "options",
+            "adapters",
+            "source_language",
+        ]
+    );
+}
+
+#[test]
+fn test_validate_without_capabilities_checks_prompt_only() {
+    let mut model = model_with_capabilities();
+    model.capabilities = None;
+    let params = GenerateParams::new(" ").with_option("style", "vivid".into());
+
+    let issues = params.validate(&model);
+    assert_eq!(issues.len(), 1);
+    assert_eq!(issues[0].to_string(), "prompt: is required");
+}
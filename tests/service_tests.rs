@@ -0,0 +1,142 @@
+//! Tests for the tower transport (requires the `tower` feature)
+
+#![cfg(feature = "tower")]
+
+use peercat::service::{AuthLayer, HttpRequest, HttpService, RetryLayer};
+use peercat::{PeerCat, PeerCatConfig, PeerCatError};
+use tower::{Service, ServiceBuilder, ServiceExt};
+use wiremock::matchers::{header, method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+fn balance_json() -> serde_json::Value {
+    serde_json::json!({
+        "credits": 10.50,
+        "totalDeposited": 50.00,
+        "totalSpent": 39.50,
+        "totalWithdrawn": 0.00,
+        "totalGenerated": 100
+    })
+}
+
+#[tokio::test]
+async fn test_client_sends_through_custom_layers() {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("GET"))
+        .and(path("/v1/balance"))
+        .and(header("Authorization", "Bearer pcat_test_xxx"))
+        .and(header("X-Trace-Id", "trace-123"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(balance_json()))
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+
+    let transport = ServiceBuilder::new()
+        .map_request(|mut request: HttpRequest| {
+            request
+                .headers_mut()
+                .insert("X-Trace-Id", "trace-123".parse().unwrap());
+            request
+        })
+        .service(HttpService::new());
+
+    let client = PeerCat::with_config(
+        PeerCatConfig::new("pcat_test_xxx")
+            .with_base_url(mock_server.uri())
+            .with_max_retries(0)
+            .with_transport(transport),
+    )
+    .unwrap();
+
+    let balance = client.get_balance().await.unwrap();
+    assert_eq!(balance.credits, 10.50);
+}
+
+#[tokio::test]
+async fn test_client_maps_service_errors() {
+    let transport = tower::service_fn(|_request: HttpRequest| async {
+        Err::<peercat::service::HttpResponse, _>("load shed")
+    });
+
+    let client = PeerCat::with_config(
+        PeerCatConfig::new("pcat_test_xxx")
+            .with_max_retries(0)
+            .with_transport(transport),
+    )
+    .unwrap();
+
+    let err = client.get_balance().await.unwrap_err();
+    assert!(matches!(err, PeerCatError::Transport(_)));
+    assert!(err.is_retryable());
+}
+
+#[tokio::test]
+async fn test_auth_and_retry_layers() {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("GET"))
+        .and(path("/v1/balance"))
+        .respond_with(ResponseTemplate::new(503))
+        .up_to_n_times(1)
+        .mount(&mock_server)
+        .await;
+
+    Mock::given(method("GET"))
+        .and(path("/v1/balance"))
+        .and(header("Authorization", "Bearer pcat_test_xxx"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(balance_json()))
+        .mount(&mock_server)
+        .await;
+
+    let mut service = ServiceBuilder::new()
+        .layer(RetryLayer::new(1))
+        .layer(AuthLayer::new("pcat_test_xxx"))
+        .service(HttpService::new());
+
+    let request = http::Request::get(format!("{}/v1/balance", mock_server.uri()))
+        .body(Vec::new())
+        .unwrap();
+    let response = service.ready().await.unwrap().call(request).await.unwrap();
+
+    assert_eq!(response.status(), 200);
+    let balance: peercat::Balance = serde_json::from_slice(response.body()).unwrap();
+    assert_eq!(balance.total_generated, 100);
+}
+
+#[tokio::test]
+async fn test_authorization_header_is_sensitive() {
+    async fn check(request: HttpRequest) -> Result<peercat::service::HttpResponse, &'static str> {
+        match request.headers().get(http::header::AUTHORIZATION) {
+            Some(value) if value.is_sensitive() => Ok(http::Response::builder()
+                .status(200)
+                .body(serde_json::to_vec(&balance_json()).unwrap())
+                .unwrap()),
+            _ => Err("authorization header not sensitive"),
+        }
+    }
+
+    let client = PeerCat::with_config(
+        PeerCatConfig::new("pcat_test_xxx")
+            .with_max_retries(0)
+            .with_transport(tower::service_fn(check)),
+    )
+    .unwrap();
+    client
+        .get_balance()
+        .await
+        .expect("Client should mark its key sensitive");
+
+    let mut service = ServiceBuilder::new()
+        .layer(AuthLayer::new("pcat_test_xxx"))
+        .service(tower::service_fn(check));
+    let request = http::Request::get("https://api.peerc.at/v1/balance")
+        .body(Vec::new())
+        .unwrap();
+    service
+        .ready()
+        .await
+        .unwrap()
+        .call(request)
+        .await
+        .expect("AuthLayer should mark its key sensitive");
+}
@@ -0,0 +1,80 @@
+//! Tests for wallet signing (requires the `signer` feature)
+
+#![cfg(feature = "signer")]
+
+use ed25519_dalek::{Signature, Verifier, VerifyingKey};
+use peercat::signer::{create_key_message, WalletSigner};
+use peercat::PeerCatError;
+
+fn verify(public_key: &str, message: &str, signature: &str) -> bool {
+    let public_key: [u8; 32] = bs58::decode(public_key)
+        .into_vec()
+        .unwrap()
+        .try_into()
+        .unwrap();
+    let signature: [u8; 64] = bs58::decode(signature)
+        .into_vec()
+        .unwrap()
+        .try_into()
+        .unwrap();
+
+    VerifyingKey::from_bytes(&public_key)
+        .unwrap()
+        .verify(message.as_bytes(), &Signature::from_bytes(&signature))
+        .is_ok()
+}
+
+#[test]
+fn test_create_key_params_are_signed() {
+    let signer = WalletSigner::from_secret_key_bytes(&[7u8; 32]);
+    let params = signer.create_key_params(Some("CI".to_string()));
+
+    assert_eq!(params.name, Some("CI".to_string()));
+    assert_eq!(params.public_key, signer.public_key());
+    let timestamp: u64 = params
+        .message
+        .rsplit("Timestamp: ")
+        .next()
+        .and_then(|t| t.parse().ok())
+        .expect("Message should end with a timestamp");
+    assert_eq!(
+        params.message,
+        create_key_message(&params.public_key, timestamp)
+    );
+    assert!(verify(
+        &params.public_key,
+        &params.message,
+        &params.signature
+    ));
+}
+
+#[test]
+fn test_from_base58_keypair() {
+    let secret = [3u8; 32];
+    let signer = WalletSigner::from_secret_key_bytes(&secret);
+
+    let mut keypair = secret.to_vec();
+    keypair.extend(bs58::decode(signer.public_key()).into_vec().unwrap());
+    let encoded = bs58::encode(keypair).into_string();
+
+    let decoded = WalletSigner::from_base58(&encoded).expect("Should decode keypair");
+    assert_eq!(decoded.public_key(), signer.public_key());
+}
+
+#[test]
+fn test_from_base58_rejects_wrong_length() {
+    let encoded = bs58::encode([1u8; 32]).into_string();
+
+    match WalletSigner::from_base58(&encoded) {
+        Err(PeerCatError::Signer(_)) => {}
+        other => panic!("Expected Signer error, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_from_keypair_bytes_rejects_mismatched_public_key() {
+    let mut keypair = [5u8; 64];
+    keypair[32..].copy_from_slice(&[9u8; 32]);
+
+    assert!(WalletSigner::from_keypair_bytes(&keypair).is_err());
+}
@@ -0,0 +1,403 @@
+//! Tests for the Solana payment helper (requires the `solana` feature)
+
+#![cfg(feature = "solana")]
+
+use ed25519_dalek::{Signature, Verifier, VerifyingKey};
+use peercat::signer::WalletSigner;
+use peercat::solana::{
+    payment_instructions, AccountMeta, Commitment, PaymentOptions, PaymentVerification,
+    SolanaPayer, SolanaRpc,
+};
+use peercat::{PeerCatError, PromptSubmission};
+use wiremock::matchers::{body_partial_json, method};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+const TREASURY: &str = "9JKi6Tr7JdsTJw1zNedF5vML9GpPnjHD9DWuZq1oE6nV";
+const BLOCKHASH: &str = "EkSnNWid2cvwEVnVx9aBqawnmiCNiDgp3gUdkDPTKN1N";
+
+fn submission() -> PromptSubmission {
+    serde_json::from_value(serde_json::json!({
+        "submissionId": "sub_123",
+        "promptHash": "abc123",
+        "paymentAddress": TREASURY,
+        "requiredAmount": { "sol": 0.00151, "lamports": 1510000, "usd": 0.28 },
+        "memo": "PCAT:abc123",
+        "model": "stable-diffusion-xl",
+        "slippageTolerance": 0.05,
+        "expiresAt": "2024-01-15T10:10:00Z",
+        "instructions": {}
+    }))
+    .unwrap()
+}
+
+fn payer(rpc_url: String) -> SolanaPayer {
+    SolanaPayer::new(rpc_url, WalletSigner::from_secret_key_bytes(&[7u8; 32]))
+}
+
+fn rpc_result(result: serde_json::Value) -> ResponseTemplate {
+    ResponseTemplate::new(200).set_body_json(serde_json::json!({
+        "jsonrpc": "2.0",
+        "id": 1,
+        "result": result
+    }))
+}
+
+/// Split a single-signature transaction into its signature and message
+fn split_transaction(transaction: &[u8]) -> ([u8; 64], &[u8]) {
+    assert_eq!(transaction[0], 1, "expected one signature");
+    (transaction[1..65].try_into().unwrap(), &transaction[65..])
+}
+
+#[test]
+fn test_payment_transaction_includes_priority_fee() {
+    let payer = payer("http://localhost".to_string());
+    let options = PaymentOptions::new()
+        .with_priority_fee(50_000)
+        .with_compute_unit_limit(20_000);
+
+    let transaction = payer
+        .payment_transaction(&submission(), &options, BLOCKHASH)
+        .unwrap();
+    let (signature, message) = split_transaction(&transaction);
+
+    // Signed by the payer
+    let public_key: [u8; 32] = bs58::decode(payer.public_key())
+        .into_vec()
+        .unwrap()
+        .try_into()
+        .unwrap();
+    assert!(VerifyingKey::from_bytes(&public_key)
+        .unwrap()
+        .verify(message, &Signature::from_bytes(&signature))
+        .is_ok());
+
+    // One writable signer, five accounts, three of them read-only programs
+    assert_eq!(&message[..4], &[1, 0, 3, 5]);
+    let keys: Vec<&[u8]> = message[4..4 + 5 * 32].chunks(32).collect();
+    assert_eq!(keys[0], public_key);
+    assert_eq!(keys[1], bs58::decode(TREASURY).into_vec().unwrap());
+
+    let instructions = &message[4 + 5 * 32 + 32..];
+    assert_eq!(instructions[0], 4, "compute limit, price, transfer, memo");
+    // SetComputeUnitLimit, then SetComputeUnitPrice, on the compute budget program
+    assert_eq!(&instructions[1..4], &[4, 0, 5]);
+    assert_eq!(
+        &instructions[4..9],
+        &[&[2u8][..], &20_000u32.to_le_bytes()].concat()[..]
+    );
+    assert_eq!(&instructions[9..12], &[4, 0, 9]);
+    assert_eq!(
+        &instructions[12..21],
+        &[&[3u8][..], &50_000u64.to_le_bytes()].concat()[..]
+    );
+    // Transfer of the required lamports from the payer to the treasury
+    assert_eq!(&instructions[21..26], &[2, 2, 0, 1, 12]);
+    assert_eq!(&instructions[30..38], &1_510_000u64.to_le_bytes());
+    // Memo signed by the payer
+    assert_eq!(&instructions[38..42], &[3, 1, 0, 11]);
+    assert_eq!(&instructions[42..], b"PCAT:abc123");
+}
+
+#[test]
+fn test_payment_transaction_without_priority_fee_skips_compute_budget() {
+    let payer = payer("http://localhost".to_string());
+
+    let transaction = payer
+        .payment_transaction(&submission(), &PaymentOptions::new(), BLOCKHASH)
+        .unwrap();
+    let (_, message) = split_transaction(&transaction);
+
+    assert_eq!(&message[..4], &[1, 0, 2, 4]);
+    assert_eq!(message[4 + 4 * 32 + 32], 2, "transfer and memo only");
+}
+
+#[test]
+fn test_payment_instructions_for_program_payer() {
+    let pda = "4wBqpZM9xaSheZzJSMawUHDgZ7miWfSsxmfVF5jJpYP";
+    let pda_key: [u8; 32] = bs58::decode(pda).into_vec().unwrap().try_into().unwrap();
+
+    let instructions = payment_instructions(&submission(), pda).unwrap();
+    assert_eq!(instructions.len(), 2);
+
+    let transfer = &instructions[0];
+    assert_eq!(transfer.program_id, [0u8; 32], "system program");
+    assert_eq!(
+        transfer.accounts,
+        [
+            AccountMeta {
+                pubkey: pda_key,
+                is_signer: true,
+                is_writable: true
+            },
+            AccountMeta {
+                pubkey: bs58::decode(TREASURY)
+                    .into_vec()
+                    .unwrap()
+                    .try_into()
+                    .unwrap(),
+                is_signer: false,
+                is_writable: true
+            },
+        ]
+    );
+    assert_eq!(
+        transfer.data,
+        [&2u32.to_le_bytes()[..], &1_510_000u64.to_le_bytes()].concat()
+    );
+
+    let memo = &instructions[1];
+    assert_eq!(
+        memo.program_id[..],
+        bs58::decode("MemoSq4gqABAXKb96qnH8TysNcWxMyWCqXgDLGmfcHr")
+            .into_vec()
+            .unwrap()
+    );
+    assert_eq!(memo.accounts.len(), 1);
+    assert!(memo.accounts[0].is_signer);
+    assert_eq!(memo.data, b"PCAT:abc123");
+
+    assert!(matches!(
+        payment_instructions(&submission(), "not a key"),
+        Err(PeerCatError::Solana(_))
+    ));
+}
+
+#[tokio::test]
+async fn test_pay_sends_and_waits_for_commitment() {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("POST"))
+        .and(body_partial_json(serde_json::json!({
+            "method": "getLatestBlockhash",
+            "params": [{ "commitment": "finalized" }]
+        })))
+        .respond_with(rpc_result(serde_json::json!({
+            "context": { "slot": 1 },
+            "value": { "blockhash": BLOCKHASH, "lastValidBlockHeight": 100 }
+        })))
+        .mount(&mock_server)
+        .await;
+    Mock::given(method("POST"))
+        .and(body_partial_json(
+            serde_json::json!({ "method": "sendTransaction" }),
+        ))
+        .respond_with(rpc_result(serde_json::json!("5sigABC")))
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+    Mock::given(method("POST"))
+        .and(body_partial_json(
+            serde_json::json!({ "method": "getSignatureStatuses" }),
+        ))
+        .respond_with(rpc_result(serde_json::json!({
+            "context": { "slot": 2 },
+            "value": [{
+                "slot": 2,
+                "confirmations": null,
+                "err": null,
+                "confirmationStatus": "confirmed"
+            }]
+        })))
+        .up_to_n_times(1)
+        .mount(&mock_server)
+        .await;
+    Mock::given(method("POST"))
+        .and(body_partial_json(
+            serde_json::json!({ "method": "getSignatureStatuses" }),
+        ))
+        .respond_with(rpc_result(serde_json::json!({
+            "context": { "slot": 3 },
+            "value": [{
+                "slot": 2,
+                "confirmations": null,
+                "err": null,
+                "confirmationStatus": "finalized"
+            }]
+        })))
+        .mount(&mock_server)
+        .await;
+
+    let options = PaymentOptions::new().with_commitment(Commitment::Finalized);
+    let signature = payer(mock_server.uri())
+        .pay(&submission(), &options)
+        .await
+        .expect("Payment should be confirmed");
+
+    assert_eq!(signature, "5sigABC");
+}
+
+#[tokio::test]
+async fn test_pay_reports_failed_transaction() {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("POST"))
+        .and(body_partial_json(
+            serde_json::json!({ "method": "getLatestBlockhash" }),
+        ))
+        .respond_with(rpc_result(serde_json::json!({
+            "context": { "slot": 1 },
+            "value": { "blockhash": BLOCKHASH, "lastValidBlockHeight": 100 }
+        })))
+        .mount(&mock_server)
+        .await;
+    Mock::given(method("POST"))
+        .and(body_partial_json(
+            serde_json::json!({ "method": "sendTransaction" }),
+        ))
+        .respond_with(rpc_result(serde_json::json!("5sigABC")))
+        .mount(&mock_server)
+        .await;
+    Mock::given(method("POST"))
+        .and(body_partial_json(
+            serde_json::json!({ "method": "getSignatureStatuses" }),
+        ))
+        .respond_with(rpc_result(serde_json::json!({
+            "context": { "slot": 2 },
+            "value": [{
+                "slot": 2,
+                "confirmations": 0,
+                "err": { "InstructionError": [2, { "Custom": 1 }] },
+                "confirmationStatus": "processed"
+            }]
+        })))
+        .mount(&mock_server)
+        .await;
+
+    let error = payer(mock_server.uri())
+        .pay(&submission(), &PaymentOptions::new())
+        .await
+        .unwrap_err();
+
+    assert!(matches!(error, PeerCatError::Solana(ref message) if message.contains("failed")));
+}
+
+fn transaction_result(
+    destination: &str,
+    lamports: u64,
+    memo: &str,
+    err: serde_json::Value,
+) -> serde_json::Value {
+    serde_json::json!({
+        "slot": 250,
+        "meta": { "err": err, "fee": 5000 },
+        "transaction": {
+            "signatures": ["5sigABC"],
+            "message": {
+                "instructions": [
+                    {
+                        "program": "system",
+                        "programId": "11111111111111111111111111111111",
+                        "parsed": {
+                            "type": "transfer",
+                            "info": {
+                                "source": "payer",
+                                "destination": destination,
+                                "lamports": lamports
+                            }
+                        }
+                    },
+                    {
+                        "program": "spl-memo",
+                        "programId": "MemoSq4gqABAXKb96qnH8TysNcWxMyWCqXgDLGmfcHr",
+                        "parsed": memo
+                    }
+                ]
+            }
+        }
+    })
+}
+
+async fn verify(result: serde_json::Value) -> PaymentVerification {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("POST"))
+        .and(body_partial_json(serde_json::json!({
+            "method": "getTransaction",
+            "params": ["5sigABC", { "encoding": "jsonParsed", "commitment": "confirmed" }]
+        })))
+        .respond_with(rpc_result(result))
+        .mount(&mock_server)
+        .await;
+
+    SolanaRpc::new(mock_server.uri())
+        .verify_on_chain("5sigABC", &submission(), Commitment::Confirmed)
+        .await
+        .expect("Verification should succeed")
+}
+
+#[tokio::test]
+async fn test_verify_on_chain_matching_payment() {
+    let verification = verify(transaction_result(
+        TREASURY,
+        1_510_000,
+        "PCAT:abc123",
+        serde_json::Value::Null,
+    ))
+    .await;
+
+    assert_eq!(
+        verification,
+        PaymentVerification::Verified {
+            slot: 250,
+            lamports: 1_510_000
+        }
+    );
+}
+
+#[tokio::test]
+async fn test_verify_on_chain_not_found() {
+    let verification = verify(serde_json::Value::Null).await;
+
+    assert_eq!(verification, PaymentVerification::NotFound);
+}
+
+#[tokio::test]
+async fn test_verify_on_chain_mismatches() {
+    let underpaid = verify(transaction_result(
+        TREASURY,
+        1_000_000,
+        "PCAT:abc123",
+        serde_json::Value::Null,
+    ))
+    .await;
+    assert_eq!(
+        underpaid,
+        PaymentVerification::Mismatch {
+            lamports: 1_000_000,
+            memo_matches: true
+        }
+    );
+
+    let wrong_destination = verify(transaction_result(
+        "11111111111111111111111111111112",
+        1_510_000,
+        "PCAT:abc123",
+        serde_json::Value::Null,
+    ))
+    .await;
+    assert_eq!(
+        wrong_destination,
+        PaymentVerification::Mismatch {
+            lamports: 0,
+            memo_matches: true
+        }
+    );
+
+    let wrong_memo = verify(transaction_result(
+        TREASURY,
+        1_510_000,
+        "PCAT:other",
+        serde_json::Value::Null,
+    ))
+    .await;
+    assert!(!wrong_memo.is_verified());
+
+    let failed = verify(transaction_result(
+        TREASURY,
+        1_510_000,
+        "PCAT:abc123",
+        serde_json::json!({ "InstructionError": [0, "InsufficientFunds"] }),
+    ))
+    .await;
+    assert!(matches!(failed, PaymentVerification::Failed { .. }));
+}
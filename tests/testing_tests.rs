@@ -0,0 +1,242 @@
+//! Tests for the in-process fake API (requires the `testing` feature)
+
+#![cfg(feature = "testing")]
+
+use std::time::Duration;
+
+use peercat::testing::{FakeError, FakePeerCat};
+use peercat::{
+    CreateCollectionParams, CreateKeyParams, GenerateParams, HistoryParams, PeerCat, PeerCatError,
+    PriceGranularity, ShareOptions, SubmitPromptParams, UsageGroupBy, UsageSummaryParams,
+    WithdrawParams,
+};
+
+/// Helper to create a client for the fake, without retries
+fn create_test_client(fake: &FakePeerCat) -> PeerCat {
+    PeerCat::with_config(fake.config().with_max_retries(0)).expect("Failed to create test client")
+}
+
+#[tokio::test]
+async fn test_fake_generate_charges_balance_and_records_history() {
+    let fake = FakePeerCat::start().await;
+    let client = create_test_client(&fake);
+
+    let result = client
+        .generate(GenerateParams::new("A sunset").with_seed(42))
+        .await
+        .unwrap();
+    assert_eq!(result.model, "stable-diffusion-xl");
+    assert_eq!(result.seed, Some(42));
+    assert_eq!(result.usage.credits_used, 0.28);
+    assert_eq!(result.usage.balance_remaining, 9.72);
+
+    let balance = client.get_balance().await.unwrap();
+    assert_eq!(balance.credits, 9.72);
+    assert_eq!(balance.total_spent, 0.28);
+    assert_eq!(balance.total_generated, 1);
+    assert_eq!(fake.balance(), 9.72);
+
+    // Demo generations are free
+    let demo = client
+        .generate(GenerateParams::new("A sunset").with_demo_mode())
+        .await
+        .unwrap();
+    assert_eq!(demo.usage.credits_used, 0.0);
+    assert!(demo.ipfs_hash.is_none());
+
+    let history = client.get_history(HistoryParams::new()).await.unwrap();
+    assert_eq!(history.items.len(), 2);
+    assert_eq!(
+        history.items[1].request_id.as_deref(),
+        Some(result.id.as_str())
+    );
+    let item = client.get_history_item(&history.items[1].id).await.unwrap();
+    assert_eq!(item.credits_used, 0.28);
+
+    let metadata = client.get_generation_metadata(&result.id).await.unwrap();
+    assert_eq!(metadata.prompt, "A sunset");
+    assert_eq!(metadata.seed, 42);
+
+    // Images are served by the fake too
+    let mut image = client.download_image_stream(&result).await.unwrap();
+    let mut bytes = Vec::new();
+    while let Some(chunk) = image.next().await {
+        bytes.extend_from_slice(&chunk.unwrap());
+    }
+    assert!(bytes.starts_with(b"\x89PNG"));
+
+    client.delete_generation(&result.id, true).await.unwrap();
+    assert!(matches!(
+        client.get_generation_metadata(&result.id).await,
+        Err(PeerCatError::NotFound { .. })
+    ));
+}
+
+#[tokio::test]
+async fn test_fake_set_balance() {
+    let fake = FakePeerCat::start().await;
+    let client = create_test_client(&fake);
+
+    fake.set_balance(0.1);
+    let error = client
+        .generate(GenerateParams::new("A sunset"))
+        .await
+        .unwrap_err();
+    assert!(matches!(error, PeerCatError::InsufficientCredits { .. }));
+    assert_eq!(client.get_balance().await.unwrap().credits, 0.1);
+}
+
+#[tokio::test]
+async fn test_fake_fail_next() {
+    let fake = FakePeerCat::start().await;
+    let client = PeerCat::with_config(fake.config().with_max_retries(2)).unwrap();
+
+    // Retries use up queued failures, then succeed
+    fake.fail_next(2, FakeError::RateLimited { retry_after: 0 });
+    client.get_balance().await.unwrap();
+
+    fake.fail_next(1, FakeError::Unauthorized);
+    assert!(matches!(
+        client.get_balance().await,
+        Err(PeerCatError::Authentication { .. })
+    ));
+    client.get_balance().await.unwrap();
+
+    assert_eq!(fake.requests(), ["GET /v1/balance"; 5]);
+}
+
+#[tokio::test]
+async fn test_fake_set_response() {
+    let fake = FakePeerCat::start().await;
+    let client = create_test_client(&fake);
+
+    fake.set_response(
+        "get",
+        "/v1/balance",
+        serde_json::json!({
+            "credits": 123.0,
+            "totalDeposited": 200.0,
+            "totalSpent": 77.0,
+            "totalWithdrawn": 0.0,
+            "totalGenerated": 275
+        }),
+    );
+    assert_eq!(client.get_balance().await.unwrap().credits, 123.0);
+
+    fake.clear_responses();
+    assert_eq!(client.get_balance().await.unwrap().credits, 10.0);
+}
+
+#[tokio::test]
+async fn test_fake_serves_every_endpoint() {
+    let fake = FakePeerCat::start().await;
+    let client = create_test_client(&fake);
+
+    let generation = client
+        .generate(GenerateParams::new("A koi pond"))
+        .await
+        .unwrap();
+    let id = generation.id.as_str();
+
+    client.upload_image(b"\x89PNG fake".to_vec()).await.unwrap();
+    client.get_ipfs_status(id).await.unwrap();
+    client
+        .create_signed_url(id, Duration::from_secs(900))
+        .await
+        .unwrap();
+    let share = client
+        .create_share_link(id, ShareOptions::new().with_download())
+        .await
+        .unwrap();
+    assert!(share.allow_download);
+    client
+        .tag_generation(id, vec!["hero".to_string()])
+        .await
+        .unwrap();
+
+    let collection = client
+        .create_collection(CreateCollectionParams::new("Landscapes"))
+        .await
+        .unwrap();
+    assert_eq!(collection.name, "Landscapes");
+    client.add_to_collection(&collection.id, id).await.unwrap();
+    client.list_collections().await.unwrap();
+    client.list_collection_items(&collection.id).await.unwrap();
+
+    let preset = client
+        .create_preset("product-shot", GenerateParams::new("A product photo"))
+        .await
+        .unwrap();
+    client.list_presets().await.unwrap();
+    client
+        .generate_from_preset(&preset.id, GenerateParams::new("A red sneaker"))
+        .await
+        .unwrap();
+    client.delete_preset(&preset.id).await.unwrap();
+
+    assert_eq!(client.get_models().await.unwrap().len(), 2);
+    client.get_model("imagen-3").await.unwrap();
+    client
+        .get_available_loras("stable-diffusion-xl")
+        .await
+        .unwrap();
+    client.get_prices().await.unwrap();
+    client
+        .get_price_history(
+            "2024-01-01T00:00:00Z".parse().unwrap().."2024-01-02T00:00:00Z".parse().unwrap(),
+            PriceGranularity::Day,
+        )
+        .await
+        .unwrap();
+
+    client.create_deposit(25.0).await.unwrap();
+    client.get_deposit_status("tx_abc").await.unwrap();
+    let withdrawal = client
+        .withdraw(WithdrawParams {
+            amount: 1.0,
+            destination_wallet: "Wallet123".to_string(),
+            message: "Withdraw".to_string(),
+            signature: "sig".to_string(),
+            public_key: "Wallet123".to_string(),
+        })
+        .await
+        .unwrap();
+    client
+        .get_withdrawal(&withdrawal.withdrawal_id)
+        .await
+        .unwrap();
+    let usage = client
+        .get_usage_summary(UsageSummaryParams::new(UsageGroupBy::Model))
+        .await
+        .unwrap();
+    assert_eq!(usage.buckets.len(), 1);
+
+    let key = client
+        .create_key(CreateKeyParams::new("msg", "sig", "pubkey"))
+        .await
+        .unwrap();
+    client.list_keys().await.unwrap();
+    client.get_key(&key.id).await.unwrap();
+    client.update_key_name(&key.id, "Worker").await.unwrap();
+    client
+        .update_key_allowlist(&key.id, vec!["203.0.113.0/24".parse().unwrap()])
+        .await
+        .unwrap();
+    client.revoke_key(&key.id).await.unwrap();
+
+    let submission = client
+        .submit_prompt(SubmitPromptParams::new("A koi pond"))
+        .await
+        .unwrap();
+    client.list_submissions(Default::default()).await.unwrap();
+    client
+        .get_submission(&submission.submission_id)
+        .await
+        .unwrap();
+    client
+        .link_transaction(&submission.submission_id, "txSig123")
+        .await
+        .unwrap();
+    client.get_onchain_status("txSig123").await.unwrap();
+    client.get_onchain_events("txSig123").await.unwrap();
+}
@@ -0,0 +1,174 @@
+//! Tests for request tracing spans (requires the `tracing` feature)
+
+#![cfg(feature = "tracing")]
+
+use std::collections::HashMap;
+use std::fmt::Debug;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+
+use peercat::{GenerateParams, PeerCat, PeerCatConfig};
+use tracing::field::{Field, Visit};
+use tracing::span::{Attributes, Id, Record};
+use tracing::{Event, Metadata, Subscriber};
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+/// A span as seen by the collector, with its fields rendered as strings
+#[derive(Debug, Clone, Default)]
+struct RecordedSpan {
+    name: &'static str,
+    parent: Option<u64>,
+    fields: HashMap<String, String>,
+}
+
+impl Visit for RecordedSpan {
+    fn record_debug(&mut self, field: &Field, value: &dyn Debug) {
+        self.fields
+            .insert(field.name().to_string(), format!("{:?}", value));
+    }
+
+    fn record_str(&mut self, field: &Field, value: &str) {
+        self.fields
+            .insert(field.name().to_string(), value.to_string());
+    }
+}
+
+/// Collects every span created while it's the default subscriber
+#[derive(Clone, Default)]
+struct Collector {
+    next_id: Arc<AtomicU64>,
+    spans: Arc<Mutex<HashMap<u64, RecordedSpan>>>,
+}
+
+impl Collector {
+    fn spans(&self, name: &str) -> Vec<(u64, RecordedSpan)> {
+        let mut spans: Vec<_> = self
+            .spans
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|(_, span)| span.name == name)
+            .map(|(id, span)| (*id, span.clone()))
+            .collect();
+        spans.sort_by_key(|(id, _)| *id);
+        spans
+    }
+}
+
+impl Subscriber for Collector {
+    fn enabled(&self, _: &Metadata<'_>) -> bool {
+        true
+    }
+
+    fn new_span(&self, attrs: &Attributes<'_>) -> Id {
+        let id = self.next_id.fetch_add(1, Ordering::SeqCst) + 1;
+        let mut span = RecordedSpan {
+            name: attrs.metadata().name(),
+            parent: attrs.parent().map(Id::into_u64),
+            ..Default::default()
+        };
+        attrs.record(&mut span);
+        self.spans.lock().unwrap().insert(id, span);
+        Id::from_u64(id)
+    }
+
+    fn record(&self, id: &Id, values: &Record<'_>) {
+        if let Some(span) = self.spans.lock().unwrap().get_mut(&id.into_u64()) {
+            values.record(span);
+        }
+    }
+
+    fn record_follows_from(&self, _: &Id, _: &Id) {}
+    fn event(&self, _: &Event<'_>) {}
+    fn enter(&self, _: &Id) {}
+    fn exit(&self, _: &Id) {}
+}
+
+fn create_test_client(mock_server: &MockServer, max_retries: u32) -> PeerCat {
+    PeerCat::with_config(
+        PeerCatConfig::new("test_api_key")
+            .with_base_url(mock_server.uri())
+            .with_max_retries(max_retries),
+    )
+    .expect("Failed to create test client")
+}
+
+#[tokio::test]
+async fn test_request_span_with_credits_used() {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("POST"))
+        .and(path("/v1/generate"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "id": "gen_123",
+            "imageUrl": "https://cdn.peerc.at/images/gen_123.png",
+            "ipfsHash": "QmXyz123",
+            "model": "stable-diffusion-xl",
+            "mode": "production",
+            "usage": {
+                "creditsUsed": 0.28,
+                "balanceRemaining": 9.72
+            }
+        })))
+        .mount(&mock_server)
+        .await;
+
+    let collector = Collector::default();
+    let _guard = tracing::subscriber::set_default(collector.clone());
+    let client = create_test_client(&mock_server, 0);
+    client
+        .generate(GenerateParams::new("A sunset"))
+        .await
+        .expect("Generate should succeed");
+
+    let requests = collector.spans("peercat.request");
+    assert_eq!(requests.len(), 1);
+    let (request_id, request) = &requests[0];
+    assert_eq!(request.fields["http.method"], "POST");
+    assert_eq!(request.fields["peercat.endpoint"], "/v1/generate");
+    assert_eq!(request.fields["http.status_code"], "200");
+    assert_eq!(request.fields["retry.count"], "0");
+    assert_eq!(request.fields["peercat.credits_used"], "0.28");
+    assert!(!request.fields.contains_key("error"));
+
+    let attempts = collector.spans("peercat.attempt");
+    assert_eq!(attempts.len(), 1);
+    assert_eq!(attempts[0].1.parent, Some(*request_id));
+    assert_eq!(attempts[0].1.fields["retry.attempt"], "0");
+    assert_eq!(attempts[0].1.fields["http.status_code"], "200");
+}
+
+#[tokio::test]
+async fn test_attempt_spans_for_retries() {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("GET"))
+        .and(path("/v1/balance"))
+        .respond_with(ResponseTemplate::new(503))
+        .mount(&mock_server)
+        .await;
+
+    let collector = Collector::default();
+    let _guard = tracing::subscriber::set_default(collector.clone());
+    let client = create_test_client(&mock_server, 1);
+    client.get_balance().await.unwrap_err();
+
+    let requests = collector.spans("peercat.request");
+    assert_eq!(requests.len(), 1);
+    let (request_id, request) = &requests[0];
+    assert_eq!(request.fields["http.method"], "GET");
+    assert_eq!(request.fields["peercat.endpoint"], "/v1/balance");
+    assert_eq!(request.fields["http.status_code"], "503");
+    assert_eq!(request.fields["retry.count"], "1");
+    assert!(request.fields.contains_key("error"));
+    assert!(!request.fields.contains_key("peercat.credits_used"));
+
+    let attempts = collector.spans("peercat.attempt");
+    assert_eq!(attempts.len(), 2);
+    for (n, (_, attempt)) in attempts.iter().enumerate() {
+        assert_eq!(attempt.parent, Some(*request_id));
+        assert_eq!(attempt.fields["retry.attempt"], n.to_string());
+        assert_eq!(attempt.fields["http.status_code"], "503");
+    }
+}
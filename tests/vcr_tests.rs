@@ -0,0 +1,239 @@
+//! Tests for record/replay (requires the `vcr` feature)
+
+#![cfg(feature = "vcr")]
+
+use std::path::PathBuf;
+
+use peercat::vcr::VcrMode;
+use peercat::{GenerateParams, PeerCat, PeerCatConfig, PeerCatError};
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+/// A cassette path unique to this test run
+fn cassette_path(name: &str) -> PathBuf {
+    std::env::temp_dir()
+        .join(format!("peercat-vcr-{}", std::process::id()))
+        .join(format!("{}.json", name))
+}
+
+#[tokio::test]
+async fn test_record_then_replay() {
+    let mock_server = MockServer::start().await;
+    let cassette = cassette_path("record_then_replay");
+
+    Mock::given(method("GET"))
+        .and(path("/v1/balance"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "credits": 10.50,
+            "totalDeposited": 50.00,
+            "totalSpent": 39.50,
+            "totalWithdrawn": 0.00,
+            "totalGenerated": 100
+        })))
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+
+    let recorder = PeerCat::with_config(
+        PeerCatConfig::new("pcat_test_secret")
+            .with_base_url(mock_server.uri())
+            .with_max_retries(0)
+            .with_vcr(VcrMode::Record(cassette.clone())),
+    )
+    .unwrap();
+    let recorded = recorder.get_balance().await.unwrap();
+
+    // Interactions are buffered until the cassette is saved
+    assert!(!cassette.exists());
+    recorder.save_cassette().unwrap();
+    let contents = std::fs::read_to_string(&cassette).unwrap();
+    assert!(contents.contains("/v1/balance"));
+    assert!(!contents.contains("pcat_test_secret"));
+
+    // Replay never touches the network
+    let player = PeerCat::with_config(
+        PeerCatConfig::new("pcat_test_other")
+            .with_base_url("http://127.0.0.1:9")
+            .with_max_retries(0)
+            .with_vcr(VcrMode::Replay(cassette.clone())),
+    )
+    .unwrap();
+    let replayed = player.get_balance().await.unwrap();
+    assert_eq!(replayed.credits, recorded.credits);
+
+    // Each interaction is served once
+    let err = player.get_balance().await.unwrap_err();
+    assert!(matches!(err, PeerCatError::InvalidConfig(_)));
+}
+
+#[tokio::test]
+async fn test_record_redacts_created_keys_and_replays_errors() {
+    let mock_server = MockServer::start().await;
+    let cassette = cassette_path("redacts_created_keys");
+
+    Mock::given(method("POST"))
+        .and(path("/v1/keys"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "id": "key_new",
+            "key": "pcat_live_brandnewsecret",
+            "keyPrefix": "pcat_live_bra",
+            "name": "CI",
+            "environment": "live",
+            "createdAt": "2024-01-15T10:30:00Z",
+            "warning": "Store this key securely"
+        })))
+        .mount(&mock_server)
+        .await;
+
+    Mock::given(method("GET"))
+        .and(path("/v1/keys/key_missing"))
+        .respond_with(ResponseTemplate::new(404).set_body_json(serde_json::json!({
+            "error": {
+                "type": "not_found",
+                "code": "resource_not_found",
+                "message": "Key not found"
+            }
+        })))
+        .mount(&mock_server)
+        .await;
+
+    let recorder = PeerCat::with_config(
+        PeerCatConfig::new("pcat_test_secret")
+            .with_base_url(mock_server.uri())
+            .with_max_retries(0)
+            .with_vcr(VcrMode::Record(cassette.clone())),
+    )
+    .unwrap();
+    recorder
+        .create_key(peercat::CreateKeyParams::new("msg", "sig", "pubkey"))
+        .await
+        .unwrap();
+    recorder.get_key("key_missing").await.unwrap_err();
+
+    // Dropping the client saves the cassette
+    drop(recorder);
+    let contents = std::fs::read_to_string(&cassette).unwrap();
+    assert!(!contents.contains("brandnewsecret"));
+
+    let player = PeerCat::with_config(
+        PeerCatConfig::new("pcat_test_secret")
+            .with_base_url("http://127.0.0.1:9")
+            .with_vcr(VcrMode::Replay(cassette)),
+    )
+    .unwrap();
+    let err = player.get_key("key_missing").await.unwrap_err();
+    assert!(matches!(err, PeerCatError::NotFound { .. }));
+}
+
+#[tokio::test]
+async fn test_record_redacts_configured_fields() {
+    let mock_server = MockServer::start().await;
+    let cassette = cassette_path("redacts_configured_fields");
+
+    Mock::given(method("POST"))
+        .and(path("/v1/generate"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "id": "gen_123",
+            "imageUrl": "https://cdn.peerc.at/images/gen_123.png",
+            "ipfsHash": "QmXyz123",
+            "model": "stable-diffusion-xl",
+            "mode": "production",
+            "prompt": "Jane Doe at 12 Elm St",
+            "usage": {
+                "creditsUsed": 0.28,
+                "balanceRemaining": 9.72
+            }
+        })))
+        .mount(&mock_server)
+        .await;
+
+    let recorder = PeerCat::with_config(
+        PeerCatConfig::new("pcat_test_secret")
+            .with_base_url(mock_server.uri())
+            .with_max_retries(0)
+            .with_redacted_field("prompt")
+            .with_vcr(VcrMode::Record(cassette.clone())),
+    )
+    .unwrap();
+    recorder
+        .generate(GenerateParams::new("Jane Doe at 12 Elm St"))
+        .await
+        .unwrap();
+
+    drop(recorder);
+    let contents = std::fs::read_to_string(&cassette).unwrap();
+    assert!(!contents.contains("Jane Doe"));
+    assert!(contents.contains("[REDACTED]"));
+
+    // Replay matches on the redacted body
+    let player = PeerCat::with_config(
+        PeerCatConfig::new("pcat_test_secret")
+            .with_base_url("http://127.0.0.1:9")
+            .with_redacted_field("prompt")
+            .with_vcr(VcrMode::Replay(cassette)),
+    )
+    .unwrap();
+    let result = player
+        .generate(GenerateParams::new("Jane Doe at 12 Elm St"))
+        .await
+        .unwrap();
+    assert_eq!(result.id, "gen_123");
+}
+
+#[tokio::test]
+async fn test_replay_matches_requests_containing_the_key() {
+    let mock_server = MockServer::start().await;
+    let cassette = cassette_path("replay_redacted_requests");
+
+    Mock::given(method("GET"))
+        .and(path("/v1/keys/pcat_test_secret"))
+        .respond_with(ResponseTemplate::new(404).set_body_json(serde_json::json!({
+            "error": {
+                "type": "not_found",
+                "code": "resource_not_found",
+                "message": "Key not found"
+            }
+        })))
+        .mount(&mock_server)
+        .await;
+
+    Mock::given(method("POST"))
+        .and(path("/v1/generate"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "id": "gen_123",
+            "imageUrl": "https://cdn.peerc.at/images/gen_123.png",
+            "ipfsHash": null,
+            "model": "stable-diffusion-xl",
+            "mode": "demo",
+            "usage": { "creditsUsed": 0.0, "balanceRemaining": 10.0 }
+        })))
+        .mount(&mock_server)
+        .await;
+
+    let config = |mode: VcrMode| {
+        PeerCatConfig::new("pcat_test_secret")
+            .with_max_retries(0)
+            .with_vcr(mode)
+    };
+    let params = GenerateParams::new("A sign reading pcat_test_secret").with_demo_mode();
+
+    let recorder = PeerCat::with_config(
+        config(VcrMode::Record(cassette.clone())).with_base_url(mock_server.uri()),
+    )
+    .unwrap();
+    recorder.get_key("pcat_test_secret").await.unwrap_err();
+    recorder.generate(params.clone()).await.unwrap();
+
+    drop(recorder);
+    let contents = std::fs::read_to_string(&cassette).unwrap();
+    assert!(!contents.contains("pcat_test_secret"));
+
+    // The key in the path and body is redacted before matching, as when recorded
+    let player =
+        PeerCat::with_config(config(VcrMode::Replay(cassette)).with_base_url("http://127.0.0.1:9"))
+            .unwrap();
+    let err = player.get_key("pcat_test_secret").await.unwrap_err();
+    assert!(matches!(err, PeerCatError::NotFound { .. }));
+    let result = player.generate(params).await.unwrap();
+    assert_eq!(result.id, "gen_123");
+}
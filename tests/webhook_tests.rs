@@ -0,0 +1,120 @@
+//! Tests for webhook verification (requires the `webhook` feature)
+
+#![cfg(feature = "webhook")]
+
+use hmac::{Hmac, Mac};
+use peercat::webhook::{parse_event, verify_signature};
+use peercat::PeerCatError;
+use sha2::Sha256;
+
+const SECRET: &[u8] = b"whsec_test";
+
+fn payload() -> Vec<u8> {
+    serde_json::to_vec(&serde_json::json!({
+        "id": "evt_123",
+        "type": "generation.completed",
+        "createdAt": "2024-01-15T10:30:00Z",
+        "data": {
+            "txSignature": "txSig123",
+            "status": "completed",
+            "imageUrl": "https://cdn.peerc.at/images/gen_123.png"
+        }
+    }))
+    .unwrap()
+}
+
+fn sign(secret: &[u8], payload: &[u8]) -> String {
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret).unwrap();
+    mac.update(payload);
+    format!("sha256={}", hex::encode(mac.finalize().into_bytes()))
+}
+
+#[test]
+fn test_parse_event_with_valid_signature() {
+    let payload = payload();
+    let event = parse_event(SECRET, &payload, &sign(SECRET, &payload)).unwrap();
+
+    assert_eq!(event.id, "evt_123");
+    assert_eq!(event.event_type, "generation.completed");
+
+    let status: peercat::OnChainGenerationStatus = event.data_as().unwrap();
+    assert_eq!(status.status, peercat::OnChainStatus::Completed);
+}
+
+#[test]
+fn test_verify_signature_accepts_bare_hex() {
+    let payload = payload();
+    let signature = sign(SECRET, &payload);
+    let bare = signature.trim_start_matches("sha256=");
+
+    assert!(verify_signature(SECRET, &payload, bare).is_ok());
+}
+
+#[test]
+fn test_verify_signature_rejects_tampering() {
+    let payload = payload();
+    let signature = sign(SECRET, &payload);
+
+    let mut tampered = payload.clone();
+    tampered[10] ^= 1;
+    assert!(matches!(
+        verify_signature(SECRET, &tampered, &signature),
+        Err(PeerCatError::InvalidSignature)
+    ));
+    assert!(matches!(
+        verify_signature(b"wrong_secret", &payload, &signature),
+        Err(PeerCatError::InvalidSignature)
+    ));
+    assert!(matches!(
+        verify_signature(SECRET, &payload, "not-hex"),
+        Err(PeerCatError::InvalidSignature)
+    ));
+}
+
+#[cfg(feature = "axum")]
+mod axum_extractor {
+    use super::*;
+    use axum::body::Body;
+    use axum::extract::FromRequest;
+    use axum::http::{Request, StatusCode};
+    use axum::response::IntoResponse;
+    use peercat::webhook::axum::{VerifiedWebhook, WebhookSecret};
+    use peercat::webhook::SIGNATURE_HEADER;
+
+    fn request(payload: Vec<u8>, signature: Option<String>) -> Request<Body> {
+        let mut builder = Request::post("/webhooks/peercat");
+        if let Some(signature) = signature {
+            builder = builder.header(SIGNATURE_HEADER, signature);
+        }
+        builder.body(Body::from(payload)).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_extractor_accepts_signed_request() {
+        let payload = payload();
+        let signature = sign(SECRET, &payload);
+        let state = WebhookSecret::new(SECRET);
+
+        let VerifiedWebhook(event) =
+            VerifiedWebhook::from_request(request(payload, Some(signature)), &state)
+                .await
+                .unwrap();
+        assert_eq!(event.id, "evt_123");
+    }
+
+    #[tokio::test]
+    async fn test_extractor_rejects_bad_signature() {
+        let state = WebhookSecret::new(SECRET);
+
+        let rejection = VerifiedWebhook::from_request(request(payload(), None), &state)
+            .await
+            .unwrap_err();
+        assert_eq!(rejection.into_response().status(), StatusCode::UNAUTHORIZED);
+
+        let signature = sign(b"wrong_secret", &payload());
+        let rejection = VerifiedWebhook::from_request(request(payload(), Some(signature)), &state)
+            .await
+            .unwrap_err();
+        assert_eq!(rejection.into_response().status(), StatusCode::UNAUTHORIZED);
+    }
+}